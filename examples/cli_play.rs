@@ -0,0 +1,161 @@
+//! Play chess against a simple greedy engine in the terminal
+//!
+//! Run with ``cargo run --example cli_play`` (add ``black`` to play for black). Moves are
+//! accepted in SAN ("Nf3", "exd5", "O-O") or UCI ("g1f3", "e7e8q") notation. Other commands:
+//! "moves", "undo", "flip", "pgn", "help" and "quit"
+
+use libchess::{
+    Action, BoardMove, BoardStatus, ChessBoard, Color, Game, GameStatus, MovePropertiesOnBoard,
+    Valuation,
+};
+use rand::seq::SliceRandom;
+use std::io::{self, BufRead, Write};
+
+const MATE_SCORE: i32 = 100_000;
+
+const HELP: &str = "\
+Enter the move in SAN (Nf3, exd5, O-O) or UCI (g1f3, e7e8q) notation, or the command:
+    moves - list the legal moves
+    undo  - take back the last move of each side
+    flip  - flip the board
+    pgn   - print the game in PGN
+    help  - print this message
+    quit  - exit";
+
+/// Returns the SAN of the move made on the board
+fn to_san(board: &ChessBoard, board_move: &BoardMove) -> String {
+    board_move.to_string(MovePropertiesOnBoard::new(board_move, board).unwrap())
+}
+
+/// Parses the move in SAN or UCI notation
+fn parse_move(board: &ChessBoard, input: &str) -> Option<BoardMove> {
+    board
+        .parse_san(input)
+        .or_else(|_| board.parse_uci(input))
+        .ok()
+        .filter(|board_move| board.is_legal_move(board_move))
+}
+
+/// Scores the position from the perspective of the side which has just moved
+fn score_after_move(board: &ChessBoard, valuation: &Valuation, mover: Color) -> i32 {
+    match board.get_status() {
+        BoardStatus::CheckMated(_) => MATE_SCORE,
+        BoardStatus::Ongoing => {
+            let balance = valuation.get_material_balance(board);
+            match mover {
+                Color::White => balance,
+                Color::Black => -balance,
+            }
+        }
+        _ => 0,
+    }
+}
+
+/// Chooses the move which keeps the most material after the best reply of the opponent (the
+/// search depth is 2 plies). Equal moves are chosen randomly
+fn choose_engine_move(board: &ChessBoard, valuation: &Valuation) -> BoardMove {
+    let mover = board.get_side_to_move();
+    let mut moves = board.get_legal_moves();
+    moves.shuffle(&mut rand::thread_rng());
+
+    let evaluate = |board_move: &BoardMove| {
+        let next = board.make_move(board_move).unwrap();
+        if next.is_terminal() {
+            return score_after_move(&next, valuation, mover);
+        }
+        next.get_legal_moves()
+            .iter()
+            .map(|reply| {
+                let after_reply = next.make_move(reply).unwrap();
+                -score_after_move(&after_reply, valuation, !mover)
+            })
+            .min()
+            .unwrap()
+    };
+
+    *moves.iter().max_by_key(|m| evaluate(m)).unwrap()
+}
+
+/// Takes back the last ``plies`` moves by replaying the game history
+fn undo(game: &Game, plies: usize) -> Game {
+    let history = game.get_action_history();
+    let mut result = Game::from_board(history.get_positions()[0]);
+    let moves = history.get_moves();
+    for board_move in moves[..moves.len().saturating_sub(plies)].iter() {
+        result.make_move(&Action::MakeMove(*board_move)).unwrap();
+    }
+    result
+}
+
+fn is_finished(game: &Game) -> bool {
+    !matches!(
+        game.get_game_status(),
+        GameStatus::Ongoing | GameStatus::DrawOffered(_)
+    )
+}
+
+fn main() {
+    let player = match std::env::args().nth(1).as_deref() {
+        Some("black") => Color::Black,
+        _ => Color::White,
+    };
+    let valuation = Valuation::default();
+    let mut game = Game::default();
+    let mut flipped = player == Color::Black;
+    let mut lines = io::stdin().lock().lines();
+
+    println!("{HELP}\n");
+    loop {
+        let board = game.get_position();
+        if board.get_side_to_move() != player && !is_finished(&game) {
+            let engine_move = choose_engine_move(&board, &valuation);
+            println!("Engine plays {}", to_san(&board, &engine_move));
+            game.make_move(&Action::MakeMove(engine_move)).unwrap();
+            continue;
+        }
+
+        println!(
+            "{}",
+            game.get_position()
+                .render_localized(&Default::default(), flipped)
+        );
+        if is_finished(&game) {
+            println!("Game over: {}", game.get_game_status());
+            println!("{}", game.as_pgn());
+            break;
+        }
+
+        print!("{player}> ");
+        io::stdout().flush().unwrap();
+        let input = match lines.next() {
+            Some(Ok(line)) => line.trim().to_string(),
+            _ => break,
+        };
+        match input.as_str() {
+            "" => {}
+            "quit" | "exit" => break,
+            "help" => println!("{HELP}"),
+            "flip" => flipped = !flipped,
+            "pgn" => println!("{}\n", game.as_pgn()),
+            "moves" => {
+                let moves: Vec<String> = board
+                    .get_legal_moves()
+                    .iter()
+                    .map(|m| to_san(&board, m))
+                    .collect();
+                println!("{}", moves.join(" "));
+            }
+            "undo" => match game.get_action_history().get_moves().len() {
+                // the engine's reply is taken back too
+                plies if plies >= 2 => game = undo(&game, 2),
+                _ => println!("Nothing to take back"),
+            },
+            _ => match parse_move(&board, &input) {
+                Some(board_move) => {
+                    game.make_move(&Action::MakeMove(board_move)).unwrap();
+                }
+                None => println!("Illegal or unrecognized move: {input} (type \"help\")"),
+            },
+        }
+    }
+}
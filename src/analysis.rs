@@ -0,0 +1,153 @@
+//! Engine analysis data model
+//!
+//! This module defines the structures which are used by engines to report the results of the
+//! position analysis back to the game: evaluations and multiple ranked lines (MultiPV)
+
+use crate::errors::LibChessError as Error;
+use crate::{BoardMove, ChessBoard};
+use std::fmt;
+
+/// Represents the evaluation of the position from white's perspective
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Evaluation {
+    /// Advantage in centipawns (positive values are good for white)
+    Centipawns(i32),
+    /// Forced mate in specified number of moves (negative values mean that black mates)
+    Mate(i32),
+}
+
+impl fmt::Display for Evaluation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Evaluation::Centipawns(cp) => {
+                let sign = if cp < 0 { "-" } else { "+" };
+                write!(f, "{sign}{}.{:02}", cp.abs() / 100, cp.abs() % 100)
+            }
+            Evaluation::Mate(n) => write!(f, "#{n}"),
+        }
+    }
+}
+
+/// Represents a single line (principal variation) reported by the engine
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnalysisLine {
+    evaluation: Evaluation,
+    moves:      Vec<BoardMove>,
+}
+
+impl AnalysisLine {
+    pub fn new(evaluation: Evaluation, moves: Vec<BoardMove>) -> Self { Self { evaluation, moves } }
+
+    #[inline]
+    pub fn get_evaluation(&self) -> Evaluation { self.evaluation }
+
+    #[inline]
+    pub fn get_moves(&self) -> &Vec<BoardMove> { &self.moves }
+
+    #[inline]
+    pub fn get_first_move(&self) -> Option<BoardMove> { self.moves.first().copied() }
+
+    /// Checks that the line is not empty and consists of legal moves only (if played from the
+    /// specified position)
+    pub fn validate(&self, position: &ChessBoard) -> Result<(), Error> {
+        if self.moves.is_empty() {
+            return Err(Error::InvalidAnalysisLine);
+        }
+
+        let mut board = *position;
+        for m in self.moves.iter() {
+            board
+                .make_move_mut(m)
+                .map_err(|_| Error::InvalidAnalysisLine)?;
+        }
+        Ok(())
+    }
+}
+
+/// Multiple ranked lines reported by the engine for a single position (the first line is the best)
+///
+/// # Examples
+/// ```
+/// use libchess::{mv, squares::*, BoardMove, PieceMove, PieceType::*};
+/// use libchess::{AnalysisLine, Evaluation, MultiPv};
+///
+/// let mut analysis = MultiPv::default();
+/// analysis
+///     .push(AnalysisLine::new(
+///         Evaluation::Centipawns(30),
+///         vec![mv!(Pawn, E2, E4)],
+///     ))
+///     .push(AnalysisLine::new(
+///         Evaluation::Centipawns(25),
+///         vec![mv!(Pawn, D2, D4)],
+///     ));
+/// assert_eq!(
+///     analysis.get_best_line().unwrap().get_evaluation(),
+///     Evaluation::Centipawns(30)
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MultiPv {
+    lines: Vec<AnalysisLine>,
+}
+
+impl MultiPv {
+    pub fn new(lines: Vec<AnalysisLine>) -> Self { Self { lines } }
+
+    /// Adds the next line (with lower rank than all previously added lines)
+    pub fn push(&mut self, line: AnalysisLine) -> &mut Self {
+        self.lines.push(line);
+        self
+    }
+
+    #[inline]
+    pub fn get_lines(&self) -> &Vec<AnalysisLine> { &self.lines }
+
+    #[inline]
+    pub fn get_best_line(&self) -> Option<&AnalysisLine> { self.lines.first() }
+
+    #[inline]
+    pub fn len(&self) -> usize { self.lines.len() }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.lines.is_empty() }
+
+    /// Checks that every line is legal for the specified position
+    pub fn validate(&self, position: &ChessBoard) -> Result<(), Error> {
+        self.lines
+            .iter()
+            .try_for_each(|line| line.validate(position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mv, squares::*, PieceMove, PieceType::*};
+
+    #[test]
+    fn evaluation_representation() {
+        assert_eq!(format!("{}", Evaluation::Centipawns(30)), "+0.30");
+        assert_eq!(format!("{}", Evaluation::Centipawns(-125)), "-1.25");
+        assert_eq!(format!("{}", Evaluation::Centipawns(0)), "+0.00");
+        assert_eq!(format!("{}", Evaluation::Mate(3)), "#3");
+        assert_eq!(format!("{}", Evaluation::Mate(-2)), "#-2");
+    }
+
+    #[test]
+    fn lines_validation() {
+        let board = ChessBoard::default();
+        let line = AnalysisLine::new(
+            Evaluation::Centipawns(30),
+            vec![mv!(Pawn, E2, E4), mv!(Pawn, E7, E5)],
+        );
+        assert!(line.validate(&board).is_ok());
+
+        let line = AnalysisLine::new(Evaluation::Centipawns(30), vec![mv!(Pawn, E2, E5)]);
+        assert!(line.validate(&board).is_err());
+
+        let line = AnalysisLine::new(Evaluation::Centipawns(30), vec![]);
+        assert!(line.validate(&board).is_err());
+    }
+}
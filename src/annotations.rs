@@ -0,0 +1,251 @@
+//! Graphical annotations
+//!
+//! This module defines the arrows, square highlights and text labels which can be attached to
+//! the moves of the game (see ``GameHistory::set_annotations``) and drawn by the renderers.
+//! Arrows and highlights are exported to PGN as ``[%cal Ge2e4]`` and ``[%csl Rd5]`` comment
+//! commands (the convention used by lichess and ChessBase). PGN has no commands for text labels,
+//! so they are not exported
+
+use crate::errors::LibChessError as Error;
+use crate::Square;
+use std::fmt;
+use std::str::FromStr;
+
+/// Color of the arrow or the square highlight (the set of colors supported by PGN commands)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AnnotationColor {
+    #[default]
+    Green,
+    Red,
+    Yellow,
+    Blue,
+}
+
+impl AnnotationColor {
+    /// Returns the letter of the color used in PGN commands
+    pub fn to_pgn_letter(&self) -> char {
+        match self {
+            AnnotationColor::Green => 'G',
+            AnnotationColor::Red => 'R',
+            AnnotationColor::Yellow => 'Y',
+            AnnotationColor::Blue => 'B',
+        }
+    }
+
+    pub fn from_pgn_letter(letter: char) -> Option<Self> {
+        match letter {
+            'G' => Some(AnnotationColor::Green),
+            'R' => Some(AnnotationColor::Red),
+            'Y' => Some(AnnotationColor::Yellow),
+            'B' => Some(AnnotationColor::Blue),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AnnotationColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            AnnotationColor::Green => "green",
+            AnnotationColor::Red => "red",
+            AnnotationColor::Yellow => "yellow",
+            AnnotationColor::Blue => "blue",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Arrow {
+    pub source:      Square,
+    pub destination: Square,
+    pub color:       AnnotationColor,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SquareHighlight {
+    pub square: Square,
+    pub color:  AnnotationColor,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TextLabel {
+    pub square: Square,
+    pub text:   String,
+}
+
+/// Set of graphical annotations of the position
+///
+/// # Examples
+/// ```
+/// use libchess::{squares::*, AnnotationColor::*, Annotations, ChessBoard};
+/// let mut annotations = Annotations::new();
+/// annotations
+///     .add_arrow(E2, E4, Green)
+///     .add_highlight(D5, Red)
+///     .add_label(F7, "weak");
+/// assert_eq!(annotations.get_highlight(D5), Some(Red));
+/// println!(
+///     "{}",
+///     ChessBoard::default().render_annotated(&annotations, false)
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Annotations {
+    arrows:     Vec<Arrow>,
+    highlights: Vec<SquareHighlight>,
+    labels:     Vec<TextLabel>,
+}
+
+impl Annotations {
+    pub fn new() -> Self { Self::default() }
+
+    #[inline]
+    pub fn get_arrows(&self) -> &Vec<Arrow> { &self.arrows }
+
+    #[inline]
+    pub fn get_highlights(&self) -> &Vec<SquareHighlight> { &self.highlights }
+
+    #[inline]
+    pub fn get_labels(&self) -> &Vec<TextLabel> { &self.labels }
+
+    /// Returns the color of the square highlight (the last one if the square is highlighted
+    /// several times)
+    pub fn get_highlight(&self, square: Square) -> Option<AnnotationColor> {
+        self.highlights
+            .iter()
+            .rev()
+            .find(|highlight| highlight.square == square)
+            .map(|highlight| highlight.color)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.arrows.is_empty() & self.highlights.is_empty() & self.labels.is_empty()
+    }
+
+    pub fn add_arrow(
+        &mut self,
+        source: Square,
+        destination: Square,
+        color: AnnotationColor,
+    ) -> &mut Self {
+        self.arrows.push(Arrow {
+            source,
+            destination,
+            color,
+        });
+        self
+    }
+
+    pub fn add_highlight(&mut self, square: Square, color: AnnotationColor) -> &mut Self {
+        self.highlights.push(SquareHighlight { square, color });
+        self
+    }
+
+    pub fn add_label(&mut self, square: Square, text: impl Into<String>) -> &mut Self {
+        self.labels.push(TextLabel {
+            square,
+            text: text.into(),
+        });
+        self
+    }
+
+    /// Renders the arrows and highlights as PGN comment commands (``[%csl ...] [%cal ...]``).
+    /// Returns ``None`` if there is nothing to export
+    pub(crate) fn to_pgn_commands(&self) -> Option<String> {
+        let mut commands = vec![];
+        if !self.highlights.is_empty() {
+            let squares: Vec<String> = self
+                .highlights
+                .iter()
+                .map(|h| format!("{}{}", h.color.to_pgn_letter(), h.square))
+                .collect();
+            commands.push(format!("[%csl {}]", squares.join(",")));
+        }
+        if !self.arrows.is_empty() {
+            let arrows: Vec<String> = self
+                .arrows
+                .iter()
+                .map(|a| format!("{}{}{}", a.color.to_pgn_letter(), a.source, a.destination))
+                .collect();
+            commands.push(format!("[%cal {}]", arrows.join(",")));
+        }
+
+        match commands.is_empty() {
+            true => None,
+            false => Some(commands.join(" ")),
+        }
+    }
+
+    /// Adds the arrows or highlights from the PGN command ("cal" or "csl") with its arguments
+    /// (e.g. "Ge2e4,Rd7d5")
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidAnnotation`` if the command is unknown or arguments are invalid
+    pub(crate) fn add_pgn_command(
+        &mut self,
+        command: &str,
+        args: &str,
+    ) -> Result<&mut Self, Error> {
+        let invalid = || Error::InvalidAnnotation {
+            s: format!("[%{command} {args}]"),
+        };
+        for item in args.split(',').map(|item| item.trim()) {
+            if !item.is_ascii() | item.is_empty() {
+                return Err(invalid());
+            }
+            let color = AnnotationColor::from_pgn_letter(item.chars().next().unwrap())
+                .ok_or_else(invalid)?;
+            let square = |range: std::ops::Range<usize>| {
+                item.get(range)
+                    .and_then(|s| Square::from_str(s).ok())
+                    .ok_or_else(invalid)
+            };
+            match (command, item.len()) {
+                ("csl", 3) => self.add_highlight(square(1..3)?, color),
+                ("cal", 5) => self.add_arrow(square(1..3)?, square(3..5)?, color),
+                _ => return Err(invalid()),
+            };
+        }
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::squares::*;
+    use AnnotationColor::*;
+
+    #[test]
+    fn pgn_commands() {
+        let mut annotations = Annotations::new();
+        assert_eq!(annotations.to_pgn_commands(), None);
+
+        annotations
+            .add_arrow(E2, E4, Green)
+            .add_arrow(G8, F6, Blue)
+            .add_highlight(D5, Red)
+            .add_label(E4, "center");
+        assert_eq!(
+            annotations.to_pgn_commands(),
+            Some("[%csl Rd5] [%cal Ge2e4,Bg8f6]".to_string())
+        );
+
+        let mut restored = Annotations::new();
+        restored
+            .add_pgn_command("cal", "Ge2e4,Bg8f6")
+            .unwrap()
+            .add_pgn_command("csl", "Rd5")
+            .unwrap();
+        assert_eq!(restored.get_arrows(), annotations.get_arrows());
+        assert_eq!(restored.get_highlight(D5), Some(Red));
+        assert!(restored.get_labels().is_empty());
+
+        assert!(restored.add_pgn_command("cal", "Ge2").is_err());
+        assert!(restored.add_pgn_command("csl", "Xd5").is_err());
+        assert!(restored.add_pgn_command("csl", "Rz5").is_err());
+        assert!(restored.add_pgn_command("clk", "Rd5").is_err());
+    }
+}
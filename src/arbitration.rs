@@ -0,0 +1,52 @@
+//! Arbitration of illegal move attempts
+//!
+//! By default ``Game`` simply rejects illegal moves. Servers which need to enforce the rules
+//! against players can enable an ``IllegalMovePolicy``: every illegal move attempt is then
+//! recorded as a strike of the player and the player may forfeit the game after the specified
+//! number of attempts
+
+use crate::{BoardMove, Color, Ply};
+
+/// Policy of handling illegal move attempts in ``Game``
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IllegalMovePolicy {
+    /// The attempt is rejected with ``LibChessError::IllegalActionDetected`` and is not recorded
+    #[default]
+    Reject,
+    /// The attempt is recorded and rejected with ``LibChessError::IllegalMoveStrike``
+    Warn,
+    /// The same as ``Warn``, but the player forfeits the game on the N-th attempt
+    ForfeitAfter(usize),
+}
+
+/// Record of the illegal move attempt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IllegalMoveAttempt {
+    pub color:      Color,
+    pub board_move: BoardMove,
+    pub ply:        Ply,
+    pub strike:     usize,
+}
+
+impl IllegalMovePolicy {
+    /// Checks if the player with specified number of strikes forfeits the game
+    pub(crate) fn is_forfeit(&self, strikes: usize) -> bool {
+        match self {
+            IllegalMovePolicy::ForfeitAfter(limit) => strikes >= *limit,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forfeit_limits() {
+        assert!(!IllegalMovePolicy::default().is_forfeit(100));
+        assert!(!IllegalMovePolicy::Warn.is_forfeit(100));
+        assert!(!IllegalMovePolicy::ForfeitAfter(3).is_forfeit(2));
+        assert!(IllegalMovePolicy::ForfeitAfter(3).is_forfeit(3));
+    }
+}
@@ -1,4 +1,5 @@
-use super::{File, Rank, Square, FILES, RANKS};
+use super::{File, Rank, Square, SquareColor, FILES, RANKS};
+use crate::errors::LibChessError as Error;
 use std::fmt;
 use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Mul, Not};
 
@@ -6,6 +7,12 @@ use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, M
 pub struct BitBoard(u64);
 
 pub const BLANK: BitBoard = BitBoard(0);
+pub const LIGHT_SQUARES: BitBoard = BitBoard(0x55AA_55AA_55AA_55AA);
+pub const DARK_SQUARES: BitBoard = BitBoard(0xAA55_AA55_AA55_AA55);
+/// The squares d4, e4, d5 and e5
+pub const CENTER_SQUARES: BitBoard = BitBoard(0x0000_0018_1800_0000);
+/// The squares from c3 to f6 (including the center)
+pub const EXTENDED_CENTER_SQUARES: BitBoard = BitBoard(0x0000_3C3C_3C3C_0000);
 
 impl BitAnd for BitBoard {
     type Output = BitBoard;
@@ -97,7 +104,7 @@ impl fmt::Display for BitBoard {
 
 impl BitBoard {
     #[inline]
-    pub fn new(b: u64) -> BitBoard { BitBoard(b) }
+    pub const fn new(b: u64) -> BitBoard { BitBoard(b) }
 
     pub fn bits(&self) -> u64 { self.0 }
 
@@ -125,6 +132,15 @@ impl BitBoard {
         result
     }
 
+    /// Returns the mask of all light or dark squares
+    #[inline]
+    pub fn from_square_color(square_color: SquareColor) -> Self {
+        match square_color {
+            SquareColor::Light => LIGHT_SQUARES,
+            SquareColor::Dark => DARK_SQUARES,
+        }
+    }
+
     #[inline]
     pub fn count_ones(&self) -> u32 { self.0.count_ones() }
 
@@ -149,6 +165,41 @@ impl BitBoard {
         }
         Some(Square::new(self.0.trailing_zeros() as u8).unwrap())
     }
+
+    /// Returns the comma-separated list of squares of the mask in ascending order ("e4, d5")
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{squares::*, BitBoard, BLANK};
+    /// let mask = BitBoard::from_square(D5) | BitBoard::from_square(E4);
+    /// assert_eq!(mask.to_notation_list(), "e4, d5");
+    /// assert_eq!(BLANK.to_notation_list(), "");
+    /// ```
+    pub fn to_notation_list(&self) -> String {
+        self.map(|square| square.to_string())
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// Parses the comma-separated list of squares into the mask (see
+    /// ``Square::from_notation_list``)
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidSquareRepresentation`` if any item of the list is not a square
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{squares::*, BitBoard};
+    /// assert_eq!(
+    ///     BitBoard::from_notation_list("d5,e4").unwrap(),
+    ///     BitBoard::from_square(D5) | BitBoard::from_square(E4)
+    /// );
+    /// ```
+    pub fn from_notation_list(s: &str) -> Result<Self, Error> {
+        Ok(Square::from_notation_list(s)?
+            .into_iter()
+            .fold(BLANK, |mask, square| mask | Self::from_square(square)))
+    }
 }
 
 #[rustfmt::skip]
@@ -186,4 +237,19 @@ mod tests {
         let result = 0xffffffffefffffffu64;
         assert_eq!(bit_board.0, result);
     }
+
+    #[test]
+    fn notation_lists() {
+        let mask = BitBoard::from_file(File::A)
+            & (BitBoard::from_rank(Rank::First) | BitBoard::from_rank(Rank::Eighth));
+        assert_eq!(mask.to_notation_list(), "a1, a8");
+        assert_eq!(BitBoard::from_notation_list(" a8 ,a1 ").unwrap(), mask);
+        assert_eq!(BitBoard::from_notation_list("a1, a1, a8").unwrap(), mask);
+        assert_eq!(BitBoard::from_notation_list(&(!BLANK).to_notation_list()).unwrap(), !BLANK);
+        assert_eq!(Square::all_from_bitboard(!BLANK).len(), 64);
+        assert!(Square::all_from_bitboard(BLANK).is_empty());
+        for invalid in ["a9", "e4 d5", "e4,", "E4"] {
+            assert!(BitBoard::from_notation_list(invalid).is_err(), "{}", invalid);
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use super::{ChessBoard, File, Rank, Square, FILES, RANKS, SQUARES_NUMBER};
+use super::{ChessBoard, File, Rank, Square, FILES, FILES_NUMBER, RANKS, SQUARES_NUMBER};
 use crate::errors::LibChessError as Error;
 use crate::{CastlingRights, Color, Piece, PieceType, COLORS_NUMBER};
 use std::fmt;
@@ -6,6 +6,37 @@ use std::ops::{Index, IndexMut};
 use std::str;
 use std::str::FromStr;
 
+/// The FEN of the standard starting position
+pub const STARTING_POSITION_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// The types of the pieces on the back ranks of the standard starting position (from A to H file)
+pub const INITIAL_BACK_RANK: [PieceType; FILES_NUMBER] = [
+    PieceType::Rook,
+    PieceType::Knight,
+    PieceType::Bishop,
+    PieceType::Queen,
+    PieceType::King,
+    PieceType::Bishop,
+    PieceType::Knight,
+    PieceType::Rook,
+];
+
+/// The pieces on each square of the standard starting position (indexed by ``Square::to_index``)
+pub const INITIAL_PIECE_PLACEMENT: [Option<Piece>; SQUARES_NUMBER] = {
+    let mut pieces = [None; SQUARES_NUMBER];
+    let mut file = 0;
+    while file < FILES_NUMBER {
+        pieces[file] = Some(Piece(INITIAL_BACK_RANK[file], Color::White));
+        pieces[FILES_NUMBER + file] = Some(Piece(PieceType::Pawn, Color::White));
+        pieces[SQUARES_NUMBER - 2 * FILES_NUMBER + file] =
+            Some(Piece(PieceType::Pawn, Color::Black));
+        pieces[SQUARES_NUMBER - FILES_NUMBER + file] =
+            Some(Piece(INITIAL_BACK_RANK[file], Color::Black));
+        file += 1;
+    }
+    pieces
+};
+
 /// The board builder is used for initializing the ChessBoard without position checks
 ///
 /// It does not check the sanity of position, moves ordering etc.
@@ -29,25 +60,33 @@ use std::str::FromStr;
 pub struct BoardBuilder {
     pieces: [Option<Piece>; SQUARES_NUMBER],
     side_to_move: Color,
-    castle_rights: [CastlingRights; COLORS_NUMBER],
+    /// Files of the rooks which carry the castling rights (king side, queen side) for each color
+    castle_rook_files: [[Option<File>; 2]; COLORS_NUMBER],
     en_passant: Option<Square>,
     moves_since_capture_or_pawn_move: usize,
     move_number: usize,
+    /// Drop the inconsistent castling rights when the ChessBoard is built instead of rejecting
+    /// the position
+    lenient_castling: bool,
 }
 
 impl From<ChessBoard> for BoardBuilder {
     fn from(board: ChessBoard) -> Self {
-        let mut pieces = vec![];
+        // the pieces are collected on the stack, so the conversion doesn't allocate
+        let mut pieces =
+            [(Square::default(), Piece(PieceType::Pawn, Color::White)); SQUARES_NUMBER];
+        let mut pieces_number = 0;
         for i in 0..SQUARES_NUMBER {
             let square = Square::new(i as u8).unwrap();
             if let Some(piece_type) = board.get_piece_type_on(square) {
                 let color = board.get_piece_color_on(square).unwrap();
-                pieces.push((square, Piece(piece_type, color)));
+                pieces[pieces_number] = (square, Piece(piece_type, color));
+                pieces_number += 1;
             }
         }
 
         BoardBuilder::setup(
-            &pieces,
+            &pieces[..pieces_number],
             board.get_side_to_move(),
             board.get_castle_rights(Color::White),
             board.get_castle_rights(Color::Black),
@@ -71,9 +110,7 @@ impl IndexMut<Square> for BoardBuilder {
 }
 
 impl Default for BoardBuilder {
-    fn default() -> BoardBuilder {
-        BoardBuilder::from_str("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap()
-    }
+    fn default() -> BoardBuilder { BoardBuilder::startpos() }
 }
 
 impl FromStr for BoardBuilder {
@@ -92,16 +129,17 @@ impl FromStr for BoardBuilder {
         let side = tokens[1];
         let castles = tokens[2];
         let en_passant = tokens[3];
-        fen.set_moves_since_capture_or_pawn_move(match usize::from_str(tokens[4]) {
-            Ok(c) => c,
+        // the counters of ``ChessBoard`` are stored as u32
+        fen.set_moves_since_capture_or_pawn_move(match u32::from_str(tokens[4]) {
+            Ok(c) => c as usize,
             Err(_) => {
                 return Err(Error::InvalidFENString {
                     s: value.to_string(),
                 })
             }
         });
-        fen.set_move_number(match usize::from_str(tokens[5]) {
-            Ok(c) => c,
+        fen.set_move_number(match u32::from_str(tokens[5]) {
+            Ok(c) => c as usize,
             Err(_) => {
                 return Err(Error::InvalidFENString {
                     s: value.to_string(),
@@ -109,57 +147,10 @@ impl FromStr for BoardBuilder {
             }
         });
 
-        let mut current_rank = Rank::Eighth;
-        let mut current_file = File::A;
-        for c in pieces.chars() {
-            match c {
-                '/' => {
-                    match current_rank.down() {
-                        Ok(r) => current_rank = r,
-                        Err(_) => {
-                            return Err(Error::InvalidFENString {
-                                s: value.to_string(),
-                            });
-                        }
-                    };
-                    current_file = File::A;
-                }
-                '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' => {
-                    if let Ok(f) =
-                        File::from_index(current_file.to_index() + (c as usize) - ('0' as usize))
-                    {
-                        current_file = f
-                    }
-                }
-                'r' | 'R' | 'n' | 'N' | 'b' | 'B' | 'q' | 'Q' | 'k' | 'K' | 'p' | 'P' => {
-                    let color = {
-                        if c.is_uppercase() {
-                            Color::White
-                        } else {
-                            Color::Black
-                        }
-                    };
-                    let piece_type = match PieceType::from_str(c.to_string().as_str()) {
-                        Ok(t) => t,
-                        Err(_) => {
-                            return Err(Error::InvalidFENString {
-                                s: value.to_string(),
-                            });
-                        }
-                    };
-                    fen[Square::from_rank_file(current_rank, current_file)] =
-                        Some(Piece(piece_type, color));
-                    if let Ok(f) = current_file.right() {
-                        current_file = f
-                    }
-                }
-                _ => {
-                    return Err(Error::InvalidFENString {
-                        s: value.to_string(),
-                    })
-                }
-            }
-        }
+        fen.set_placement(pieces)
+            .map_err(|_| Error::InvalidFENString {
+                s: value.to_string(),
+            })?;
 
         match side {
             "w" | "W" => fen = *fen.set_side_to_move(Color::White),
@@ -171,25 +162,10 @@ impl FromStr for BoardBuilder {
             }
         }
 
-        if castles.contains('K') && castles.contains('Q') {
-            fen.set_castling_rights(Color::White, CastlingRights::BothSides);
-        } else if castles.contains('K') {
-            fen.set_castling_rights(Color::White, CastlingRights::KingSide);
-        } else if castles.contains('Q') {
-            fen.set_castling_rights(Color::White, CastlingRights::QueenSide);
-        } else {
-            fen.set_castling_rights(Color::White, CastlingRights::Neither);
-        }
-
-        if castles.contains('k') && castles.contains('q') {
-            fen.set_castling_rights(Color::Black, CastlingRights::BothSides);
-        } else if castles.contains('k') {
-            fen.set_castling_rights(Color::Black, CastlingRights::KingSide);
-        } else if castles.contains('q') {
-            fen.set_castling_rights(Color::Black, CastlingRights::QueenSide);
-        } else {
-            fen.set_castling_rights(Color::Black, CastlingRights::Neither);
-        }
+        fen.set_castling_tokens(castles)
+            .map_err(|_| Error::InvalidFENString {
+                s: value.to_string(),
+            })?;
 
         if let Ok(sq) = Square::from_str(en_passant) {
             fen.set_en_passant(Some(sq));
@@ -200,80 +176,100 @@ impl FromStr for BoardBuilder {
 }
 
 impl fmt::Display for BoardBuilder {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut pieces_string = String::new();
-        let mut empty_squares: usize = 0;
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { self.write_fen(f) }
+}
 
+impl BoardBuilder {
+    /// Writes the FEN-string of the position into the writer without intermediate allocations
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{BoardBuilder, ChessBoard};
+    /// let mut fen = String::new();
+    /// BoardBuilder::from(ChessBoard::default())
+    ///     .write_fen(&mut fen)
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     fen,
+    ///     "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+    /// );
+    /// ```
+    pub fn write_fen(&self, w: &mut impl fmt::Write) -> fmt::Result {
         for rank in RANKS.iter().rev() {
             if *rank != Rank::Eighth {
-                pieces_string.push('/');
+                w.write_char('/')?;
             }
+            let mut empty_squares: usize = 0;
             for file in FILES.iter() {
                 match self[Square::from_rank_file(*rank, *file)] {
-                    Some(p) => {
+                    Some(piece) => {
                         if empty_squares != 0 {
-                            pieces_string += format!("{empty_squares}").as_str();
+                            write!(w, "{empty_squares}")?;
                             empty_squares = 0;
                         }
-                        let mut s = format!("{}", p.0);
-                        match p.1 {
-                            Color::White => s = s.to_uppercase(),
-                            Color::Black => s = s.to_lowercase(),
-                        }
-                        pieces_string.push_str(&s);
+                        w.write_char(piece.to_char())?;
                     }
                     None => empty_squares += 1,
                 }
             }
             if empty_squares != 0 {
-                pieces_string += format!("{empty_squares}").as_str();
-                empty_squares = 0;
+                write!(w, "{empty_squares}")?;
             }
         }
 
-        let castles_string = match self.castle_rights {
-            [CastlingRights::Neither, CastlingRights::Neither] => "-".to_string(),
-            _ => {
-                format!(
-                    "{}{}",
-                    format!("{}", self.castle_rights[0]).to_uppercase(),
-                    self.castle_rights[1]
-                )
-            }
-        };
-
+        write!(w, " {:#} ", self.get_side_to_move())?;
+        let mut has_castling_rights = false;
+        for token in self.get_castling_tokens() {
+            w.write_char(token)?;
+            has_castling_rights = true;
+        }
+        if !has_castling_rights {
+            w.write_char('-')?;
+        }
+        match self.en_passant {
+            Some(square) => write!(w, " {square}")?,
+            None => w.write_str(" -")?,
+        }
         write!(
-            f,
-            "{} {} {} {} {} {}",
-            pieces_string,
-            match self.get_side_to_move() {
-                Color::White => "w",
-                Color::Black => "b",
-            },
-            castles_string,
-            match self.en_passant {
-                Some(value) => format!("{value}"),
-                None => "-".to_string(),
-            },
+            w,
+            " {} {}",
             self.get_moves_since_capture_or_pawn_move(),
             self.get_move_number(),
         )
     }
-}
 
-impl BoardBuilder {
     /// Creates empty object of BoardBuilder
     pub fn new() -> BoardBuilder {
         BoardBuilder {
             pieces: [None; 64],
             side_to_move: Color::White,
-            castle_rights: [CastlingRights::Neither, CastlingRights::Neither],
+            castle_rook_files: [[None; 2]; COLORS_NUMBER],
             en_passant: None,
             moves_since_capture_or_pawn_move: 0,
             move_number: 0,
+            lenient_castling: false,
         }
     }
 
+    /// Creates the builder of the standard starting position without parsing the FEN
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{BoardBuilder, STARTING_POSITION_FEN};
+    /// assert_eq!(BoardBuilder::startpos().to_string(), STARTING_POSITION_FEN);
+    /// ```
+    pub fn startpos() -> BoardBuilder {
+        let mut result = BoardBuilder {
+            pieces: INITIAL_PIECE_PLACEMENT,
+            move_number: 1,
+            ..BoardBuilder::new()
+        };
+        result
+            .set_castling_rights(Color::White, CastlingRights::BothSides)
+            .set_castling_rights(Color::Black, CastlingRights::BothSides);
+        result
+    }
+
     /// Creates new BoardBuilder and allows to setup any properties of the position
     pub fn setup<'a>(
         pieces: impl IntoIterator<Item = &'a (Square, Piece)>,
@@ -284,7 +280,7 @@ impl BoardBuilder {
         moves_since_capture_or_pawn_move: usize,
         move_number: usize,
     ) -> BoardBuilder {
-        BoardBuilder {
+        let mut result = BoardBuilder {
             pieces: {
                 let mut squares_pieces = [None; SQUARES_NUMBER];
                 pieces.into_iter().for_each(|(s, p)| {
@@ -293,11 +289,16 @@ impl BoardBuilder {
                 squares_pieces
             },
             side_to_move,
-            castle_rights: [white_castle_rights, black_castle_rights],
+            castle_rook_files: [[None; 2]; COLORS_NUMBER],
             en_passant,
             moves_since_capture_or_pawn_move,
             move_number,
-        }
+            lenient_castling: false,
+        };
+        result
+            .set_castling_rights(Color::White, white_castle_rights)
+            .set_castling_rights(Color::Black, black_castle_rights);
+        result
     }
 
     /// Returns presence or absence of pieces on each square of the chess board
@@ -317,7 +318,65 @@ impl BoardBuilder {
     /// Returns castling rights for specified color
     #[inline]
     pub fn get_castle_rights(&self, color: Color) -> CastlingRights {
-        self.castle_rights[color.to_index()]
+        let [king_side, queen_side] = self.castle_rook_files[color.to_index()];
+        match (king_side.is_some(), queen_side.is_some()) {
+            (false, false) => CastlingRights::Neither,
+            (true, false) => CastlingRights::KingSide,
+            (false, true) => CastlingRights::QueenSide,
+            (true, true) => CastlingRights::BothSides,
+        }
+    }
+
+    /// Returns the file of the rook which carries the castling right on specified side
+    /// (``KingSide`` or ``QueenSide``). In the standard chess these are the H and A files. Other
+    /// files are only kept to read and write X-FEN and Shredder-FEN strings: castling with such
+    /// rooks is not supported, so ``ChessBoard`` can't be built from this builder (see
+    /// ``BoardBuilder::has_standard_castling``)
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{BoardBuilder, CastlingRights::*, Color::*, File};
+    /// use std::str::FromStr;
+    /// let builder = BoardBuilder::from_str("1r2kr2/8/8/8/8/8/8/1R2KR2 w BFbf - 0 1").unwrap();
+    /// assert_eq!(builder.get_castle_rook_file(White, KingSide), Some(File::F));
+    /// assert_eq!(
+    ///     builder.get_castle_rook_file(Black, QueenSide),
+    ///     Some(File::B)
+    /// );
+    /// assert_eq!(builder.get_castle_rights(Black), BothSides);
+    /// assert!(!builder.has_standard_castling());
+    /// ```
+    #[inline]
+    pub fn get_castle_rook_file(&self, color: Color, side: CastlingRights) -> Option<File> {
+        match side {
+            CastlingRights::KingSide => self.castle_rook_files[color.to_index()][0],
+            CastlingRights::QueenSide => self.castle_rook_files[color.to_index()][1],
+            _ => None,
+        }
+    }
+
+    /// Returns true if all castling rights are carried by the rooks on the A and H files. Only such
+    /// builders can be converted into ``ChessBoard``
+    pub fn has_standard_castling(&self) -> bool {
+        Color::iter().all(|color| self.find_non_standard_rook_file(color).is_none())
+    }
+
+    /// Returns the first side of castling rights of specified color which is carried by the rook
+    /// outside of the A and H files (with this rook's file)
+    pub(crate) fn find_non_standard_rook_file(
+        &self,
+        color: Color,
+    ) -> Option<(CastlingRights, File)> {
+        [
+            (CastlingRights::KingSide, File::H),
+            (CastlingRights::QueenSide, File::A),
+        ]
+        .into_iter()
+        .find_map(|(side, standard_file)| {
+            self.get_castle_rook_file(color, side)
+                .filter(|file| *file != standard_file)
+                .map(|file| (side, file))
+        })
     }
 
     /// Returns color of side to move
@@ -328,6 +387,11 @@ impl BoardBuilder {
     #[inline]
     pub fn get_en_passant(&self) -> Option<Square> { self.en_passant }
 
+    /// Returns true if the castling rights are downgraded when the ChessBoard is built (see
+    /// ``BoardBuilder::set_lenient_castling``)
+    #[inline]
+    pub fn is_lenient_castling(&self) -> bool { self.lenient_castling }
+
     /// Allows to set move number manually
     #[inline]
     pub fn set_move_number(&mut self, counter: usize) -> &mut Self {
@@ -352,7 +416,94 @@ impl BoardBuilder {
     /// Allows to set castling rights manually
     #[inline]
     pub fn set_castling_rights(&mut self, color: Color, rights: CastlingRights) -> &mut Self {
-        self.castle_rights[color.to_index()] = rights;
+        self.castle_rook_files[color.to_index()] = [
+            rights.has_kingside().then_some(File::H),
+            rights.has_queenside().then_some(File::A),
+        ];
+        self
+    }
+
+    /// Sets the file of the rook which carries the castling right on specified side
+    /// (``KingSide`` or ``QueenSide``, other values are ignored). ``None`` removes the right. The
+    /// files other than H and A are not supported by ``ChessBoard`` (see
+    /// ``BoardBuilder::get_castle_rook_file``)
+    pub fn set_castle_rook_file(
+        &mut self,
+        color: Color,
+        side: CastlingRights,
+        file: Option<File>,
+    ) -> &mut Self {
+        match side {
+            CastlingRights::KingSide => self.castle_rook_files[color.to_index()][0] = file,
+            CastlingRights::QueenSide => self.castle_rook_files[color.to_index()][1] = file,
+            _ => {}
+        }
+        self
+    }
+
+    /// Drops castling rights which are not supported by the king and rooks placement (the king
+    /// or the rook has left its initial square)
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{BoardBuilder, CastlingRights, Color::*};
+    /// use std::str::FromStr;
+    /// let mut builder = BoardBuilder::from_str("r3k3/8/8/8/8/8/8/4K2R w KQkq - 0 1").unwrap();
+    /// builder.downgrade_castling_rights();
+    /// assert_eq!(builder.get_castle_rights(White), CastlingRights::KingSide);
+    /// assert_eq!(builder.get_castle_rights(Black), CastlingRights::QueenSide);
+    /// ```
+    pub fn downgrade_castling_rights(&mut self) -> &mut Self {
+        for color in Color::iter() {
+            if self.find_non_standard_rook_file(color).is_none() {
+                while let Some((side, _)) = self
+                    .get_castle_rights(color)
+                    .find_inconsistency(color, |square| self[square])
+                {
+                    self.set_castle_rook_file(color, side, None);
+                }
+                continue;
+            }
+
+            let king_file = self.get_king_file(color);
+            for side in [CastlingRights::KingSide, CastlingRights::QueenSide] {
+                let rook_file = match self.get_castle_rook_file(color, side) {
+                    Some(file) => file,
+                    None => continue,
+                };
+                let rook_square = Square::from_rank_file(color.get_back_rank(), rook_file);
+                let is_consistent = king_file.is_some_and(|king_file| {
+                    (self[rook_square] == Some(Piece(PieceType::Rook, color)))
+                        & ((rook_file.to_index() > king_file.to_index())
+                            == (side == CastlingRights::KingSide))
+                });
+                if !is_consistent {
+                    self.set_castle_rook_file(color, side, None);
+                }
+            }
+        }
+        self
+    }
+
+    /// Defines if the castling rights which are inconsistent with the king and rooks placement
+    /// are silently dropped (see ``BoardBuilder::downgrade_castling_rights``) when the ChessBoard
+    /// is built from the builder. Otherwise such position is rejected with
+    /// ``LibChessError::InvalidBoardInconsistentCastlingRights``. Board editors usually enable
+    /// it, so the rights follow the pieces moved by the user
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{BoardBuilder, CastlingRights, ChessBoard, Color::*};
+    /// use std::str::FromStr;
+    /// let mut builder = BoardBuilder::from_str("r3k3/8/8/8/8/8/8/4K2R w KQkq - 0 1").unwrap();
+    /// assert!(ChessBoard::try_from(&builder).is_err());
+    ///
+    /// let board = ChessBoard::try_from(builder.set_lenient_castling(true)).unwrap();
+    /// assert_eq!(board.get_castle_rights(White), CastlingRights::KingSide);
+    /// assert_eq!(builder.get_castle_rights(White), CastlingRights::BothSides);
+    /// ```
+    pub fn set_lenient_castling(&mut self, lenient: bool) -> &mut Self {
+        self.lenient_castling = lenient;
         self
     }
 
@@ -367,6 +518,214 @@ impl BoardBuilder {
         self[square] = piece;
         self
     }
+
+    /// Creates BoardBuilder with pieces defined by the piece placement field of FEN-string (the
+    /// first one). All other properties are set the same as for ``BoardBuilder::new()``
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{squares::*, BoardBuilder, Color::*, Piece, PieceType::*};
+    /// let builder = BoardBuilder::from_placement("4k3/8/8/8/8/8/4P3/4K3").unwrap();
+    /// assert_eq!(builder[E2], Some(Piece(Pawn, White)));
+    /// ```
+    pub fn from_placement(placement: &str) -> Result<BoardBuilder, Error> {
+        let mut result = BoardBuilder::new();
+        result.set_placement(placement)?;
+        Ok(result)
+    }
+
+    /// Places the piece on specified square (replacing the piece which was standing there)
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{squares::*, BoardBuilder, ChessBoard, Color::*, Piece, PieceType::*};
+    /// let board: ChessBoard = BoardBuilder::new()
+    ///     .place(Piece(King, White), E1)
+    ///     .place(Piece(King, Black), E8)
+    ///     .place_many([(Piece(Pawn, White), E2), (Piece(Pawn, Black), E7)])
+    ///     .with_side(Black)
+    ///     .with_move_number(1)
+    ///     .try_into()
+    ///     .unwrap();
+    /// assert_eq!(board.as_fen(), "4k3/4p3/8/8/8/8/4P3/4K3 b - - 0 1");
+    /// ```
+    #[inline]
+    pub fn place(&mut self, piece: Piece, square: Square) -> &mut Self {
+        self.put_piece_on_square(square, Some(piece))
+    }
+
+    /// Places all pieces from iterable container of pairs Piece + Square
+    pub fn place_many(&mut self, pieces: impl IntoIterator<Item = (Piece, Square)>) -> &mut Self {
+        pieces.into_iter().for_each(|(piece, square)| {
+            self.place(piece, square);
+        });
+        self
+    }
+
+    /// Removes the piece from specified square
+    #[inline]
+    pub fn remove(&mut self, square: Square) -> &mut Self { self.put_piece_on_square(square, None) }
+
+    /// Removes all pieces from the board
+    pub fn clear(&mut self) -> &mut Self {
+        self.pieces = [None; SQUARES_NUMBER];
+        self
+    }
+
+    /// The same as ``BoardBuilder::set_side_to_move``
+    #[inline]
+    pub fn with_side(&mut self, color: Color) -> &mut Self { self.set_side_to_move(color) }
+
+    /// The same as ``BoardBuilder::set_castling_rights``
+    #[inline]
+    pub fn with_castling(&mut self, color: Color, rights: CastlingRights) -> &mut Self {
+        self.set_castling_rights(color, rights)
+    }
+
+    /// The same as ``BoardBuilder::set_en_passant``
+    #[inline]
+    pub fn with_en_passant(&mut self, square: Option<Square>) -> &mut Self {
+        self.set_en_passant(square)
+    }
+
+    /// The same as ``BoardBuilder::set_move_number``
+    #[inline]
+    pub fn with_move_number(&mut self, counter: usize) -> &mut Self {
+        self.set_move_number(counter)
+    }
+
+    /// Returns the file of the king of specified color if it stands on its back rank
+    fn get_king_file(&self, color: Color) -> Option<File> {
+        FILES.into_iter().find(|file| {
+            self[Square::from_rank_file(color.get_back_rank(), *file)]
+                == Some(Piece(PieceType::King, color))
+        })
+    }
+
+    /// Returns the outermost rook file on specified side of the king (the rook which "K" and "Q"
+    /// castling tokens of X-FEN refer to)
+    fn get_outermost_rook_file(&self, color: Color, side: CastlingRights) -> Option<File> {
+        let king_file = self.get_king_file(color)?;
+        let is_rook = |file: &File| {
+            self[Square::from_rank_file(color.get_back_rank(), *file)]
+                == Some(Piece(PieceType::Rook, color))
+        };
+        match side {
+            CastlingRights::KingSide => FILES
+                .into_iter()
+                .rev()
+                .take_while(|file| file.to_index() > king_file.to_index())
+                .find(is_rook),
+            _ => FILES
+                .into_iter()
+                .take_while(|file| file.to_index() < king_file.to_index())
+                .find(is_rook),
+        }
+    }
+
+    /// Parses the castling field of FEN-string. Besides the standard "KQkq" tokens it accepts the
+    /// rook files (X-FEN and Shredder-FEN, e.g. "HAha" or "BFbf"). "K" and "Q" refer to the
+    /// outermost rook on the side of the king (or the H and A files if there is no such rook)
+    fn set_castling_tokens(&mut self, castles: &str) -> Result<&mut Self, Error> {
+        self.castle_rook_files = [[None; 2]; COLORS_NUMBER];
+        if castles == "-" {
+            return Ok(self);
+        }
+
+        for c in castles.chars() {
+            let color = match c.is_uppercase() {
+                true => Color::White,
+                false => Color::Black,
+            };
+            let (side, file) = match c.to_ascii_lowercase() {
+                'k' => {
+                    let side = CastlingRights::KingSide;
+                    let file = self.get_outermost_rook_file(color, side);
+                    (side, file.unwrap_or(File::H))
+                }
+                'q' => {
+                    let side = CastlingRights::QueenSide;
+                    let file = self.get_outermost_rook_file(color, side);
+                    (side, file.unwrap_or(File::A))
+                }
+                letter @ 'a'..='h' => {
+                    let file = File::from_str(&letter.to_string())?;
+                    let king_file = self.get_king_file(color).unwrap_or(File::E);
+                    match file.to_index() > king_file.to_index() {
+                        true => (CastlingRights::KingSide, file),
+                        false => (CastlingRights::QueenSide, file),
+                    }
+                }
+                _ => return Err(Error::InvalidCastlingIndexRepresentation),
+            };
+            self.set_castle_rook_file(color, side, Some(file));
+        }
+        Ok(self)
+    }
+
+    /// Renders the castling field of FEN-string. The rights are written as "KQkq" if they are
+    /// carried by the outermost rooks and as the rook files otherwise (X-FEN)
+    fn get_castling_tokens(&self) -> impl Iterator<Item = char> + '_ {
+        Color::iter().flat_map(move |color| {
+            [
+                (CastlingRights::KingSide, 'k'),
+                (CastlingRights::QueenSide, 'q'),
+            ]
+            .into_iter()
+            .filter_map(move |(side, letter)| {
+                let file = self.get_castle_rook_file(color, side)?;
+                let is_outermost = match self.get_outermost_rook_file(color, side) {
+                    Some(outermost) => outermost == file,
+                    None => file == [File::H, File::A][(letter == 'q') as usize],
+                };
+                let token = match is_outermost {
+                    true => letter,
+                    false => (b'a' + file.to_index() as u8) as char,
+                };
+                Some(match color {
+                    Color::White => token.to_ascii_uppercase(),
+                    Color::Black => token,
+                })
+            })
+        })
+    }
+
+    /// Replaces all pieces on the board with the ones defined by the piece placement field of
+    /// FEN-string
+    fn set_placement(&mut self, placement: &str) -> Result<&mut Self, Error> {
+        let error = || Error::InvalidFENString {
+            s: placement.to_string(),
+        };
+
+        self.clear();
+        let mut current_rank = Rank::Eighth;
+        let mut current_file = File::A;
+        for c in placement.chars() {
+            match c {
+                '/' => {
+                    current_rank = current_rank.down().map_err(|_| error())?;
+                    current_file = File::A;
+                }
+                '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' => {
+                    if let Ok(f) =
+                        File::from_index(current_file.to_index() + (c as usize) - ('0' as usize))
+                    {
+                        current_file = f
+                    }
+                }
+                'r' | 'R' | 'n' | 'N' | 'b' | 'B' | 'q' | 'Q' | 'k' | 'K' | 'p' | 'P' => {
+                    self[Square::from_rank_file(current_rank, current_file)] =
+                        Some(Piece::from_char(c).map_err(|_| error())?);
+                    if let Ok(f) = current_file.right() {
+                        current_file = f
+                    }
+                }
+                _ => return Err(error()),
+            }
+        }
+
+        Ok(self)
+    }
 }
 
 #[cfg(test)]
@@ -389,4 +748,104 @@ mod tests {
         let fen = "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 1";
         assert_eq!(format!("{}", BoardBuilder::from_str(fen).unwrap()), fen);
     }
+
+    #[test]
+    fn starting_position() {
+        let builder = BoardBuilder::startpos();
+        assert_eq!(builder.to_string(), STARTING_POSITION_FEN);
+        assert_eq!(
+            builder.get_pieces(),
+            BoardBuilder::from_str(STARTING_POSITION_FEN)
+                .unwrap()
+                .get_pieces()
+        );
+        assert!(builder.has_standard_castling());
+
+        let board = ChessBoard::try_from(builder).unwrap();
+        assert!(board.is_startpos());
+        assert_eq!(board, ChessBoard::from_str(STARTING_POSITION_FEN).unwrap());
+        assert!(
+            ChessBoard::from_str("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 7 12")
+                .unwrap()
+                .is_startpos()
+        );
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w Kkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBK1BNR w kq - 0 1",
+        ] {
+            assert!(!ChessBoard::from_str(fen).unwrap().is_startpos(), "{fen}");
+        }
+    }
+
+    #[test]
+    fn fluent_placement() {
+        use crate::squares::*;
+        use crate::{Color::*, PieceType::*};
+
+        let mut builder = BoardBuilder::from_placement("4k3/8/8/8/8/8/4P3/4K3").unwrap();
+        builder
+            .remove(E2)
+            .place(Piece(Rook, White), H1)
+            .with_castling(White, CastlingRights::KingSide)
+            .with_move_number(1);
+        assert_eq!(format!("{}", builder), "4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+
+        assert!(BoardBuilder::from_placement("4k3/8/8/8/8/8/4X3/4K3").is_err());
+        assert!(BoardBuilder::from_placement("4k3/8/8/8/8/8/4P3/4K3/8").is_err());
+    }
+
+    #[test]
+    fn castling_rook_files() {
+        use crate::{squares::*, CastlingRights::*, ChessBoard, Color::*};
+
+        // standard rights are carried by the rooks on A and H files
+        let builder = BoardBuilder::default();
+        assert_eq!(builder.get_castle_rook_file(White, KingSide), Some(File::H));
+        assert_eq!(
+            builder.get_castle_rook_file(Black, QueenSide),
+            Some(File::A)
+        );
+        assert_eq!(builder.get_castle_rook_file(White, BothSides), None);
+        assert!(builder.has_standard_castling());
+
+        // Shredder-FEN and X-FEN notations of the same Chess960 position
+        let xfen = "rk2r3/8/8/8/8/8/8/RK2R3 w KQkq - 0 1";
+        let mut shredder = BoardBuilder::from_str("rk2r3/8/8/8/8/8/8/RK2R3 w EAea - 0 1").unwrap();
+        assert_eq!(format!("{shredder}"), xfen);
+        assert_eq!(
+            shredder.get_castle_rook_file(White, KingSide),
+            Some(File::E)
+        );
+        assert!(!shredder.has_standard_castling());
+        assert!(matches!(
+            ChessBoard::try_from(&shredder),
+            Err(Error::InvalidBoardInconsistentCastlingRights {
+                rook_square: E1,
+                ..
+            })
+        ));
+
+        // the inner rook is written by its file
+        let fen = "2r1kr1r/8/8/8/8/8/8/2R1KR1R w Ff - 0 1";
+        let builder = BoardBuilder::from_str(fen).unwrap();
+        assert_eq!(format!("{builder}"), fen);
+        assert_eq!(builder.get_castle_rights(White), KingSide);
+
+        let mut builder = BoardBuilder::from_str("1r2kr2/8/8/8/8/8/8/R3K3 w BFbf - 0 1").unwrap();
+        builder.downgrade_castling_rights();
+        assert_eq!(builder.get_castle_rights(White), Neither);
+        assert_eq!(builder.get_castle_rights(Black), BothSides);
+
+        // the lenient builder drops the rights of the missing rooks, but the Chess960 castling
+        // is still rejected
+        let mut builder = BoardBuilder::from_str("r3k3/8/8/8/8/8/8/R3K3 w KQkq - 0 1").unwrap();
+        assert!(!builder.is_lenient_castling());
+        let board = ChessBoard::try_from(builder.set_lenient_castling(true)).unwrap();
+        assert_eq!(board.as_fen(), "r3k3/8/8/8/8/8/8/R3K3 w Qq - 0 1");
+        assert_eq!(format!("{builder}"), "r3k3/8/8/8/8/8/8/R3K3 w KQkq - 0 1");
+        assert!(ChessBoard::try_from(shredder.set_lenient_castling(true)).is_err());
+
+        assert!(BoardBuilder::from_str("4k3/8/8/8/8/8/8/4K3 w X - 0 1").is_err());
+    }
 }
@@ -0,0 +1,216 @@
+//! Board editors
+//!
+//! This module implements the undoable layer over ``BoardBuilder`` for the board editors: the
+//! pieces are placed, removed and moved by the commands, and only the changed squares are kept
+//! in the undo/redo history (so the builder is not copied on each edit). Every command reports
+//! the changed squares, so the editor redraws only them
+
+use crate::{BitBoard, BoardBuilder, Piece, Square, BLANK};
+
+/// The edit of the pieces placement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BoardEdit {
+    /// Places the piece on the square (replacing the piece which was standing there)
+    Place(Piece, Square),
+    /// Removes the piece from the square
+    Remove(Square),
+    /// Moves the piece from the first square to the second one (replacing the piece which was
+    /// standing there). Moving from the empty square does nothing
+    Move(Square, Square),
+}
+
+/// The change of one square: its contents before and after the edit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SquareChange {
+    square: Square,
+    before: Option<Piece>,
+    after:  Option<Piece>,
+}
+
+/// The changes of the squares made by one edit (an edit changes at most two squares)
+type EditRecord = [Option<SquareChange>; 2];
+
+/// Board builder with the undo/redo history of the edits
+///
+/// # Examples
+/// ```
+/// use libchess::{squares::*, BitBoard, BoardBuilder, BoardEdit, BoardEditor};
+/// use libchess::{Color::*, Piece, PieceType::*};
+///
+/// let mut editor = BoardEditor::new(BoardBuilder::default());
+/// let changed = editor.apply(BoardEdit::Move(E2, E4));
+/// assert_eq!(
+///     changed,
+///     BitBoard::from_square(E2) | BitBoard::from_square(E4)
+/// );
+/// editor.apply(BoardEdit::Remove(D7));
+/// assert_eq!(editor.get_builder()[D7], None);
+///
+/// assert_eq!(editor.undo(), Some(BitBoard::from_square(D7)));
+/// assert_eq!(editor.get_builder()[D7], Some(Piece(Pawn, Black)));
+/// assert!(editor.redo().is_some());
+/// assert!(editor.redo().is_none());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BoardEditor {
+    builder:    BoardBuilder,
+    undo_stack: Vec<EditRecord>,
+    redo_stack: Vec<EditRecord>,
+}
+
+impl From<BoardBuilder> for BoardEditor {
+    fn from(builder: BoardBuilder) -> Self { Self::new(builder) }
+}
+
+impl BoardEditor {
+    /// Creates the editor of the builder with the empty history
+    pub fn new(builder: BoardBuilder) -> Self {
+        Self {
+            builder,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn get_builder(&self) -> &BoardBuilder { &self.builder }
+
+    /// Returns the edited builder dropping the history
+    #[inline]
+    pub fn into_builder(self) -> BoardBuilder { self.builder }
+
+    #[inline]
+    pub fn can_undo(&self) -> bool { !self.undo_stack.is_empty() }
+
+    #[inline]
+    pub fn can_redo(&self) -> bool { !self.redo_stack.is_empty() }
+
+    /// Removes all edits from the history
+    pub fn clear_history(&mut self) -> &mut Self {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self
+    }
+
+    /// Applies the edit and returns the mask of the changed squares. The edit which changes
+    /// nothing (e.g. removing from the empty square) is not added to the history, otherwise the
+    /// edits which were undone can't be redone anymore
+    pub fn apply(&mut self, edit: BoardEdit) -> BitBoard {
+        let (first, second) = match edit {
+            BoardEdit::Place(piece, square) => ((square, Some(piece)), None),
+            BoardEdit::Remove(square) => ((square, None), None),
+            BoardEdit::Move(from, to) => match self.builder[from] {
+                Some(piece) if from != to => ((from, None), Some((to, Some(piece)))),
+                _ => return BLANK,
+            },
+        };
+
+        let mut record: EditRecord = [None; 2];
+        for (change, (square, after)) in record
+            .iter_mut()
+            .zip([Some(first), second].into_iter().flatten())
+        {
+            let before = self.builder[square];
+            if before != after {
+                *change = Some(SquareChange {
+                    square,
+                    before,
+                    after,
+                });
+            }
+        }
+        let changed = Self::apply_record(&mut self.builder, &record, false);
+        if !changed.is_blank() {
+            self.undo_stack.push(record);
+            self.redo_stack.clear();
+        }
+        changed
+    }
+
+    /// Undoes the last edit and returns the mask of the changed squares (None if there is
+    /// nothing to undo)
+    pub fn undo(&mut self) -> Option<BitBoard> {
+        let record = self.undo_stack.pop()?;
+        let changed = Self::apply_record(&mut self.builder, &record, true);
+        self.redo_stack.push(record);
+        Some(changed)
+    }
+
+    /// Redoes the last undone edit and returns the mask of the changed squares (None if there
+    /// is nothing to redo)
+    pub fn redo(&mut self) -> Option<BitBoard> {
+        let record = self.redo_stack.pop()?;
+        let changed = Self::apply_record(&mut self.builder, &record, false);
+        self.undo_stack.push(record);
+        Some(changed)
+    }
+
+    /// Sets the contents of the squares after the edit (or before it if ``backwards``) and
+    /// returns the mask of the changed squares
+    fn apply_record(builder: &mut BoardBuilder, record: &EditRecord, backwards: bool) -> BitBoard {
+        let mut changed = BLANK;
+        for change in record.iter().flatten() {
+            let piece = if backwards { change.before } else { change.after };
+            builder.put_piece_on_square(change.square, piece);
+            changed |= BitBoard::from_square(change.square);
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{squares::*, Color::*, PieceType::*};
+
+    #[test]
+    fn board_editing() {
+        let initial = BoardBuilder::default();
+        let mut editor = BoardEditor::from(initial);
+        assert!(!editor.can_undo() & !editor.can_redo());
+
+        // the edits which change nothing are not recorded
+        assert_eq!(editor.apply(BoardEdit::Remove(E4)), BLANK);
+        assert_eq!(editor.apply(BoardEdit::Move(E4, E5)), BLANK);
+        assert_eq!(editor.apply(BoardEdit::Move(E2, E2)), BLANK);
+        assert_eq!(
+            editor.apply(BoardEdit::Place(Piece(King, White), E1)),
+            BLANK
+        );
+        assert!(!editor.can_undo());
+
+        // the knight captures the pawn, the queen replaces the rook
+        assert_eq!(
+            editor.apply(BoardEdit::Move(B1, D2)),
+            BitBoard::from_square(B1) | BitBoard::from_square(D2)
+        );
+        assert_eq!(
+            editor.apply(BoardEdit::Place(Piece(Queen, Black), A1)),
+            BitBoard::from_square(A1)
+        );
+        assert_eq!(editor.get_builder()[D2], Some(Piece(Knight, White)));
+        assert_eq!(editor.get_builder()[A1], Some(Piece(Queen, Black)));
+
+        assert_eq!(editor.undo(), Some(BitBoard::from_square(A1)));
+        assert_eq!(
+            editor.undo(),
+            Some(BitBoard::from_square(B1) | BitBoard::from_square(D2))
+        );
+        assert_eq!(editor.undo(), None);
+        assert_eq!(editor.get_builder().to_string(), initial.to_string());
+
+        assert!(editor.redo().is_some());
+        assert_eq!(editor.get_builder()[D2], Some(Piece(Knight, White)));
+        // a new edit drops the undone ones
+        editor.apply(BoardEdit::Remove(H8));
+        assert!(!editor.can_redo());
+        assert_eq!(editor.redo(), None);
+        assert_eq!(
+            editor.clone().into_builder().to_string(),
+            "rnbqkbn1/pppppppp/8/8/8/8/PPPNPPPP/R1BQKBNR w KQkq - 0 1"
+        );
+
+        editor.clear_history();
+        assert!(!editor.can_undo());
+    }
+}
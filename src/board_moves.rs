@@ -1,5 +1,8 @@
 use crate::errors::LibChessError as Error;
-use crate::{BitBoard, ChessBoard, PieceType, Square};
+use crate::{
+    BitBoard, BoardStatus, CastlingRights, ChessBoard, File, NotationLocale, Piece, PieceType,
+    PositionHashValueType, Square, PIECE_TYPES_NUMBER, SQUARES_NUMBER,
+};
 use std::fmt;
 use std::str::FromStr;
 
@@ -14,6 +17,8 @@ pub enum DisplayAmbiguityType {
 pub struct MovePropertiesOnBoard {
     pub is_check:       bool,
     pub is_checkmate:   bool,
+    /// The opponent has no legal moves and is not in check after the move
+    pub is_stalemate:   bool,
     pub is_capture:     bool,
     pub ambiguity_type: DisplayAmbiguityType,
 }
@@ -23,6 +28,7 @@ impl MovePropertiesOnBoard {
         let board_after_move = board.make_move(board_move)?;
         let is_check = board_after_move.get_check_mask().count_ones() > 0;
         let is_checkmate = board_after_move.is_terminal() & is_check;
+        let is_stalemate = board_after_move.is_terminal() & !is_check;
         let is_capture = match board_move {
             BoardMove::MovePiece(m) => m.is_capture_on_board(board),
             BoardMove::CastleKingSide => false,
@@ -40,12 +46,124 @@ impl MovePropertiesOnBoard {
         Ok(Self {
             is_check,
             is_checkmate,
+            is_stalemate,
             is_capture,
             ambiguity_type,
         })
     }
 }
 
+/// Cheap flags of the move describing its kind on the specific board (unlike
+/// ``MovePropertiesOnBoard`` they don't require making the move)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MoveFlags {
+    pub is_capture:     bool,
+    pub is_double_push: bool,
+    pub is_en_passant:  bool,
+    pub is_promotion:   bool,
+    pub is_castling:    bool,
+}
+
+impl MoveFlags {
+    pub fn new(board_move: &BoardMove, board: &ChessBoard) -> Self {
+        match board_move {
+            BoardMove::MovePiece(m) => Self {
+                is_capture:     m.is_capture_on_board(board),
+                is_double_push: m.is_double_push(),
+                is_en_passant:  m.is_en_passant_move(board),
+                is_promotion:   m.get_promotion().is_some(),
+                is_castling:    false,
+            },
+            BoardMove::CastleKingSide | BoardMove::CastleQueenSide => Self {
+                is_castling: true,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Returns true if the move is neither capture nor promotion
+    #[inline]
+    pub fn is_quiet(&self) -> bool { !self.is_capture & !self.is_promotion }
+}
+
+/// Describes what happened on the board after the move was made (see
+/// ``ChessBoard::make_move_with_outcome``)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveOutcome {
+    /// The captured piece and its square (differs from the destination square of en passant)
+    pub captured:      Option<(Piece, Square)>,
+    pub is_en_passant: bool,
+    /// The side of castling (``KingSide`` or ``QueenSide``) if the move was castling
+    pub castling:      Option<CastlingRights>,
+    pub promotion:     Option<PieceType>,
+    /// The status of the board after the move
+    pub status:        BoardStatus,
+    /// The hash of the board after the move
+    pub hash:          PositionHashValueType,
+}
+
+/// Movement of a single piece which is animated in GUI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PieceAnimation {
+    pub square_from: Square,
+    pub square_to:   Square,
+    /// The piece displayed on the source square before the animation
+    pub piece_from:  Piece,
+    /// The piece displayed on the destination square after the animation (differs from
+    /// ``piece_from`` when the promotion is taken back)
+    pub piece_to:    Piece,
+}
+
+/// Describes how to animate taking the move back: which pieces go back and which captured piece
+/// appears on the board again
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Takeback {
+    pub movements: Vec<PieceAnimation>,
+    /// The captured piece and its square (differs from the destination square of en-passant)
+    pub restored:  Option<(Piece, Square)>,
+}
+
+/// Defines how the parsers handle pawn moves to the last rank written without the promotion
+/// piece ("e8", "e7e8")
+#[derive(Debug, Clone, Copy, Default)]
+pub enum PromotionPolicy {
+    /// Such moves are rejected
+    #[default]
+    Strict,
+    /// The pawn is promoted to queen
+    AutoQueen,
+    /// The callback chooses the promotion piece for the move from the source to the destination
+    /// square (the move is rejected if it returns ``None``)
+    Prompt(fn(&ChessBoard, Square, Square) -> Option<PieceType>),
+}
+
+/// What kind of move the client expects to make. Is used to detect the desynchronization of
+/// the client's and the server's positions (see ``ChessBoard::make_move_with_intent``)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MoveIntent {
+    /// Non-capturing move (including non-capturing promotions)
+    Normal,
+    /// Capture of the piece on the destination square (including capturing promotions)
+    Capture,
+    EnPassant,
+    /// The pawn's two-square advance
+    DoublePush,
+    Castle,
+}
+
+impl fmt::Display for MoveIntent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            MoveIntent::Normal => "normal move",
+            MoveIntent::Capture => "capture",
+            MoveIntent::EnPassant => "en-passant capture",
+            MoveIntent::DoublePush => "double pawn push",
+            MoveIntent::Castle => "castling",
+        };
+        write!(f, "{s}")
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct PieceMove {
     piece_type:  PieceType,
@@ -82,6 +200,9 @@ impl FromStr for PieceMove {
         let tokens: Vec<&str> = value.split('=').collect();
         let piece_str = tokens[0];
         let len = piece_str.len();
+        if !(4..=5).contains(&len) | !piece_str.is_ascii() | (tokens.len() > 2) {
+            return Err(Error::InvalidBoardMoveRepresentation);
+        }
 
         let piece_type = if len == 4 {
             PieceType::Pawn
@@ -112,7 +233,10 @@ impl FromStr for PieceMove {
             source_square,
             destination_square,
             if tokens.len() > 1 {
-                Some(PieceType::from_str(tokens[1]).unwrap())
+                match PieceType::from_str(tokens[1]) {
+                    Ok(p) => Some(p),
+                    Err(_) => return Err(Error::InvalidBoardMoveRepresentation),
+                }
             } else {
                 None
             },
@@ -152,9 +276,17 @@ impl PieceMove {
 
     #[inline]
     pub fn is_en_passant_move(&self, board: &ChessBoard) -> bool {
-        board.get_en_passant().map_or(false, |ep| {
-            (self.piece_type == PieceType::Pawn) & (self.square_to == ep)
-        })
+        board
+            .get_en_passant()
+            .is_some_and(|ep| (self.piece_type == PieceType::Pawn) & (self.square_to == ep))
+    }
+
+    /// Returns true if the move is the pawn's two-square advance
+    #[inline]
+    pub fn is_double_push(&self) -> bool {
+        let source_rank = self.square_from.get_rank().to_index();
+        let destination_rank = self.square_to.get_rank().to_index();
+        (self.piece_type == PieceType::Pawn) & (source_rank.abs_diff(destination_rank) == 2)
     }
 
     pub fn is_capture_on_board(&self, board: &ChessBoard) -> bool {
@@ -176,8 +308,8 @@ impl FromStr for BoardMove {
 
     fn from_str(value: &str) -> Result<Self, Self::Err> {
         match value {
-            "O-O-O" => Ok(Self::CastleQueenSide),
-            "O-O" => Ok(Self::CastleKingSide),
+            "O-O-O" | "0-0-0" => Ok(Self::CastleQueenSide),
+            "O-O" | "0-0" => Ok(Self::CastleKingSide),
             s => Ok(Self::MovePiece(PieceMove::from_str(s)?)),
         }
     }
@@ -203,48 +335,189 @@ impl BoardMove {
         }
     }
 
+    /// Returns the key of the canonical order of moves which is used by
+    /// ``ChessBoard::get_legal_moves``: the piece moves are ordered by the piece type (pawns
+    /// first, the king last), then by the source and destination squares (``Square::to_index``)
+    /// and by the promotion piece type (knight first), and the castling moves (king side first)
+    /// are placed after all other moves. Sorting by the key restores the canonical order of the
+    /// moves collected in any other way
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{BoardMove, ChessBoard};
+    /// let mut moves = ChessBoard::default().get_legal_moves();
+    /// let generated = moves.clone();
+    /// moves.reverse();
+    /// moves.sort_by_key(BoardMove::sort_key);
+    /// assert_eq!(moves, generated);
+    /// assert_eq!(format!("{}", moves[0]), "a2a3");
+    /// ```
+    pub fn sort_key(&self) -> u32 {
+        const SQUARES: u32 = SQUARES_NUMBER as u32;
+        const PIECE_MOVES: u32 =
+            PIECE_TYPES_NUMBER as u32 * SQUARES * SQUARES * PIECE_TYPES_NUMBER as u32;
+
+        match self {
+            BoardMove::MovePiece(m) => {
+                let key = m.get_piece_type().to_index() as u32;
+                let key = key * SQUARES + m.get_source_square().to_index() as u32;
+                let key = key * SQUARES + m.get_destination_square().to_index() as u32;
+                key * PIECE_TYPES_NUMBER as u32
+                    + m.get_promotion().map_or(0, |p| p.to_index() as u32)
+            }
+            BoardMove::CastleKingSide => PIECE_MOVES,
+            BoardMove::CastleQueenSide => PIECE_MOVES + 1,
+        }
+    }
+
+    /// Returns the kind of this move in the position on the board. The king moves which stand
+    /// for the castling (see ``ChessBoard::normalize_castling``) are the castling
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{mv, squares::*, BoardMove, ChessBoard, MoveIntent, PieceMove, PieceType::*};
+    /// let board = ChessBoard::default();
+    /// assert_eq!(mv!(Pawn, E2, E4).get_intent(&board), MoveIntent::DoublePush);
+    /// assert_eq!(mv!(Knight, G1, F3).get_intent(&board), MoveIntent::Normal);
+    /// ```
+    pub fn get_intent(&self, board: &ChessBoard) -> MoveIntent {
+        match board.normalize_castling(self) {
+            BoardMove::CastleKingSide | BoardMove::CastleQueenSide => MoveIntent::Castle,
+            BoardMove::MovePiece(m) if m.is_en_passant_move(board) => MoveIntent::EnPassant,
+            BoardMove::MovePiece(m) if m.is_capture_on_board(board) => MoveIntent::Capture,
+            BoardMove::MovePiece(m) if m.is_double_push() => MoveIntent::DoublePush,
+            BoardMove::MovePiece(_) => MoveIntent::Normal,
+        }
+    }
+
+    /// Checks that the move is of the kind the caller expects (see ``BoardMove::get_intent``).
+    /// Illegal moves pass the check so they are reported (and counted) by the move makers
+    pub(crate) fn check_intent(&self, board: &ChessBoard, intent: MoveIntent) -> Result<(), Error> {
+        if !board.is_legal_move(&board.normalize_castling(self)) {
+            return Ok(());
+        }
+        let actual = self.get_intent(board);
+        match actual == intent {
+            true => Ok(()),
+            false => Err(Error::IntentMismatch {
+                expected: intent,
+                actual,
+            }),
+        }
+    }
+
     pub fn to_string(&self, properties: MovePropertiesOnBoard) -> String {
-        let check_string = if properties.is_checkmate {
-            "#"
-        } else if properties.is_check {
-            "+"
-        } else {
-            ""
-        };
+        let mut san = String::new();
+        self.write_san(properties, &mut san).unwrap();
+        san
+    }
+
+    /// Returns SAN-representation of the move with the piece letters of specified locale
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{mv, squares::*, BoardMove, ChessBoard};
+    /// use libchess::{MovePropertiesOnBoard, NotationLocale};
+    /// use libchess::{PieceMove, PieceType::*};
+    /// let board = ChessBoard::default();
+    /// let m = mv!(Knight, G1, F3);
+    /// let properties = MovePropertiesOnBoard::new(&m, &board).unwrap();
+    /// assert_eq!(
+    ///     m.to_localized_string(properties, &NotationLocale::german()),
+    ///     "Sf3"
+    /// );
+    /// assert_eq!(
+    ///     m.to_localized_string(properties, &NotationLocale::figurine()),
+    ///     "♘f3"
+    /// );
+    /// ```
+    pub fn to_localized_string(
+        &self,
+        properties: MovePropertiesOnBoard,
+        locale: &NotationLocale,
+    ) -> String {
+        let mut san = String::new();
+        self.write_localized_san(properties, locale, &mut san)
+            .unwrap();
+        san
+    }
+
+    /// Writes SAN-representation of the move into the writer without intermediate allocations.
+    /// Is used for the bulk export of games
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{mv, squares::*, BoardMove, ChessBoard, MovePropertiesOnBoard};
+    /// use libchess::{PieceMove, PieceType::*};
+    /// let board = ChessBoard::default();
+    /// let mut movetext = String::from("1.");
+    /// for m in [mv!(Knight, G1, F3), mv!(Pawn, E2, E4)] {
+    ///     let properties = MovePropertiesOnBoard::new(&m, &board).unwrap();
+    ///     m.write_san(properties, &mut movetext).unwrap();
+    ///     movetext.push(' ');
+    /// }
+    /// assert_eq!(movetext, "1.Nf3 e4 ");
+    /// ```
+    pub fn write_san(
+        &self,
+        properties: MovePropertiesOnBoard,
+        w: &mut impl fmt::Write,
+    ) -> fmt::Result {
+        self.write_san_with(properties, w, |w, piece_type| write!(w, "{piece_type}"))
+    }
+
+    /// Writes SAN-representation of the move with the piece letters of specified locale into
+    /// the writer (see ``BoardMove::write_san``)
+    pub fn write_localized_san(
+        &self,
+        properties: MovePropertiesOnBoard,
+        locale: &NotationLocale,
+        w: &mut impl fmt::Write,
+    ) -> fmt::Result {
+        self.write_san_with(properties, w, |w, piece_type| {
+            w.write_str(locale.get_piece_letter(piece_type))
+        })
+    }
 
+    fn write_san_with<W: fmt::Write>(
+        &self,
+        properties: MovePropertiesOnBoard,
+        w: &mut W,
+        write_letter: impl Fn(&mut W, PieceType) -> fmt::Result,
+    ) -> fmt::Result {
         match self {
             BoardMove::MovePiece(m) => {
-                let piece_type_string = match m.get_piece_type() {
-                    PieceType::Pawn => String::new(),
-                    p => format!("{p}"),
-                };
-                let ambiguity_resolve_string = match properties.ambiguity_type {
+                if m.get_piece_type() != PieceType::Pawn {
+                    write_letter(w, m.get_piece_type())?;
+                }
+                match properties.ambiguity_type {
                     DisplayAmbiguityType::ExtraFile => {
-                        format!("{}", m.get_source_square().get_file())
+                        write!(w, "{}", m.get_source_square().get_file())?
                     }
                     DisplayAmbiguityType::ExtraRank => {
-                        format!("{}", m.get_source_square().get_rank())
+                        write!(w, "{}", m.get_source_square().get_rank())?
                     }
-                    DisplayAmbiguityType::Neither => String::new(),
-                };
-                let capture_string = if properties.is_capture { "x" } else { "" };
-                let promotion_string = match m.get_promotion() {
-                    Some(piece_type) => format!("={piece_type}"),
-                    None => String::new(),
-                };
-
-                format!(
-                    "{}{}{}{}{}{}",
-                    piece_type_string,
-                    ambiguity_resolve_string,
-                    capture_string,
-                    m.get_destination_square(),
-                    promotion_string,
-                    check_string,
-                )
+                    DisplayAmbiguityType::Neither => {}
+                }
+                if properties.is_capture {
+                    w.write_char('x')?;
+                }
+                write!(w, "{}", m.get_destination_square())?;
+                if let Some(piece_type) = m.get_promotion() {
+                    w.write_char('=')?;
+                    write_letter(w, piece_type)?;
+                }
             }
-            BoardMove::CastleKingSide => format!("O-O{check_string}"),
-            BoardMove::CastleQueenSide => format!("O-O-O{check_string}"),
+            BoardMove::CastleKingSide => w.write_str("O-O")?,
+            BoardMove::CastleQueenSide => w.write_str("O-O-O")?,
+        }
+
+        if properties.is_checkmate {
+            w.write_char('#')
+        } else if properties.is_check {
+            w.write_char('+')
+        } else {
+            Ok(())
         }
     }
 
@@ -254,6 +527,109 @@ impl BoardMove {
             _ => false,
         }
     }
+
+    #[inline]
+    pub fn is_castling(&self) -> bool {
+        matches!(self, BoardMove::CastleKingSide | BoardMove::CastleQueenSide)
+    }
+
+    pub fn is_en_passant_move(&self, board: &ChessBoard) -> bool {
+        match self {
+            BoardMove::MovePiece(m) => m.is_en_passant_move(board),
+            _ => false,
+        }
+    }
+
+    /// Returns the instruction for the animation of taking this move back. The board is the
+    /// position before the move
+    ///
+    /// # Errors
+    /// ``LibChessError::IllegalMoveDetected`` if the move is illegal on this board
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::PieceType::*;
+    /// use libchess::{mv, squares::*, BoardMove, ChessBoard, Color::*, Piece, PieceMove};
+    /// use std::str::FromStr;
+    ///
+    /// let board = ChessBoard::from_str("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    /// let takeback = mv!(Pawn, A7, B8, Queen).get_takeback(&board).unwrap();
+    /// assert_eq!(takeback.movements[0].square_from, B8);
+    /// assert_eq!(takeback.movements[0].piece_from, Piece(Queen, White));
+    /// assert_eq!(takeback.movements[0].piece_to, Piece(Pawn, White));
+    /// assert_eq!(takeback.restored, Some((Piece(Knight, Black), B8)));
+    /// ```
+    pub fn get_takeback(&self, board: &ChessBoard) -> Result<Takeback, Error> {
+        board.make_move(self)?;
+        let color = board.get_side_to_move();
+        let back_rank = color.get_back_rank();
+        let on_back_rank = |file| Square::from_rank_file(back_rank, file);
+        let animation = |piece_type, square_from, square_to| PieceAnimation {
+            square_from,
+            square_to,
+            piece_from: Piece(piece_type, color),
+            piece_to: Piece(piece_type, color),
+        };
+
+        let m = match board.normalize_castling(self) {
+            BoardMove::CastleKingSide => {
+                return Ok(Takeback {
+                    movements: vec![
+                        animation(
+                            PieceType::King,
+                            on_back_rank(File::G),
+                            on_back_rank(File::E),
+                        ),
+                        animation(
+                            PieceType::Rook,
+                            on_back_rank(File::F),
+                            on_back_rank(File::H),
+                        ),
+                    ],
+                    restored:  None,
+                })
+            }
+            BoardMove::CastleQueenSide => {
+                return Ok(Takeback {
+                    movements: vec![
+                        animation(
+                            PieceType::King,
+                            on_back_rank(File::C),
+                            on_back_rank(File::E),
+                        ),
+                        animation(
+                            PieceType::Rook,
+                            on_back_rank(File::D),
+                            on_back_rank(File::A),
+                        ),
+                    ],
+                    restored:  None,
+                })
+            }
+            BoardMove::MovePiece(m) => m,
+        };
+
+        let destination = m.get_destination_square();
+        let restored = if m.is_en_passant_move(board) {
+            let square =
+                Square::from_rank_file(m.get_source_square().get_rank(), destination.get_file());
+            Some((Piece(PieceType::Pawn, !color), square))
+        } else {
+            board
+                .get_piece_on(destination)
+                .map(|piece| (piece, destination))
+        };
+
+        Ok(Takeback {
+            movements: vec![PieceAnimation {
+                square_from: destination,
+                square_to:   m.get_source_square(),
+                piece_from:  Piece(m.get_promotion().unwrap_or(m.get_piece_type()), color),
+                piece_to:    Piece(m.get_piece_type(), color),
+            }],
+            restored,
+        })
+    }
 }
 
 #[macro_export]
@@ -293,7 +669,7 @@ macro_rules! castle_queen_side {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::squares::*;
+    use crate::{squares::*, Color};
     use std::str::FromStr;
     use PieceType::*;
 
@@ -317,11 +693,11 @@ mod tests {
         let board = ChessBoard::from_str("k7/1q6/8/8/8/8/6Q1/5K2 w - - 0 1").unwrap();
         let board_move = mv!(Queen, G2, B7);
         let metadata = MovePropertiesOnBoard::new(&board_move, &board).unwrap();
-        assert_eq!(metadata.is_capture, true);
+        assert!(metadata.is_capture);
 
         let board_move = mv!(Queen, G2, C6);
         let metadata = MovePropertiesOnBoard::new(&board_move, &board).unwrap();
-        assert_eq!(metadata.is_capture, false);
+        assert!(!metadata.is_capture);
 
         let board = ChessBoard::from_str(
             "r2q1rk1/1b3pbn/pp1p2pp/2pP4/PP1NPp2/2PB3P/3N2P1/R2Q1RK1 w - c6 0 18",
@@ -329,7 +705,45 @@ mod tests {
         .unwrap();
         let board_move = mv!(Pawn, D5, C6);
         let metadata = MovePropertiesOnBoard::new(&board_move, &board).unwrap();
-        assert_eq!(metadata.is_capture, true);
+        assert!(metadata.is_capture);
+    }
+
+    #[test]
+    fn stalemate() {
+        let board = ChessBoard::from_str("7k/8/6K1/8/8/8/8/5Q2 w - - 0 1").unwrap();
+        let metadata = MovePropertiesOnBoard::new(&mv!(Queen, F1, F7), &board).unwrap();
+        assert!(metadata.is_stalemate & !metadata.is_check);
+        let metadata = MovePropertiesOnBoard::new(&mv!(Queen, F1, F8), &board).unwrap();
+        assert!(!metadata.is_stalemate & metadata.is_checkmate);
+        let metadata = MovePropertiesOnBoard::new(&mv!(Queen, F1, F6), &board).unwrap();
+        assert!(!metadata.is_stalemate);
+    }
+
+    #[test]
+    fn san_writing() {
+        let board = ChessBoard::from_str("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let mut buffer = String::new();
+        for (m, expected) in [
+            (mv!(Pawn, B7, A8, Knight), "bxa8=N"),
+            (mv!(Rook, A1, A8), "Rxa8+"),
+            (castle_king_side!(), "O-O"),
+            (mv!(Rook, H1, H8), "Rxh8+"),
+        ] {
+            let properties = MovePropertiesOnBoard::new(&m, &board).unwrap();
+            buffer.clear();
+            m.write_san(properties, &mut buffer).unwrap();
+            assert_eq!(buffer, expected);
+            assert_eq!(m.to_string(properties), expected);
+            assert_eq!(
+                m.to_localized_string(properties, &NotationLocale::default()),
+                expected
+            );
+        }
+
+        let mut fen = String::new();
+        board.write_fen(&mut fen).unwrap();
+        assert_eq!(fen, board.as_fen());
+        assert_eq!(ChessBoard::from_str(&fen).unwrap(), board);
     }
 
     #[test]
@@ -361,6 +775,16 @@ mod tests {
             BoardMove::CastleKingSide
         );
 
+        assert_eq!(
+            BoardMove::from_str("0-0-0").unwrap(),
+            BoardMove::CastleQueenSide
+        );
+
+        assert_eq!(
+            BoardMove::from_str("0-0").unwrap(),
+            BoardMove::CastleKingSide
+        );
+
         assert_eq!(
             BoardMove::from_str("bc1h6").unwrap(),
             BoardMove::MovePiece(PieceMove::new(Bishop, C1, H6, None).unwrap())
@@ -369,12 +793,152 @@ mod tests {
         assert!(BoardMove::from_str("gc1h6").is_err());
         assert!(BoardMove::from_str("Bz1h6").is_err());
         assert!(BoardMove::from_str("Bc1h61").is_err());
+        assert!(BoardMove::from_str("Kg1").is_err());
+        assert!(BoardMove::from_str("e7e8=X").is_err());
     }
 
     #[test]
     fn en_passant_check() {
         let board = ChessBoard::from_str("8/2p5/3p4/KP5r/1R2Pp1k/8/6P1/8 b - e3 0 1").unwrap();
         let pm = PieceMove::new(Pawn, F4, E3, None).unwrap();
-        assert_eq!(pm.is_en_passant_move(&board), true);
+        assert!(pm.is_en_passant_move(&board));
+    }
+
+    #[test]
+    fn move_outcomes() {
+        let board = ChessBoard::from_str("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let (_, outcome) = board.make_move_with_outcome(&mv!(King, E1, G1)).unwrap();
+        assert_eq!(outcome.castling, Some(CastlingRights::KingSide));
+        assert_eq!(outcome.captured, None);
+
+        let (next_board, outcome) = board
+            .make_move_with_outcome(&mv!(Pawn, B7, A8, Queen))
+            .unwrap();
+        assert_eq!(outcome.captured, Some((Piece(Rook, Color::Black), A8)));
+        assert_eq!(outcome.promotion, Some(Queen));
+        assert!(!outcome.is_en_passant & outcome.castling.is_none());
+        assert_eq!(outcome.hash, next_board.get_hash());
+
+        let board = ChessBoard::from_str("7k/8/5KQ1/8/8/8/8/8 w - - 0 1").unwrap();
+        let (_, outcome) = board.make_move_with_outcome(&mv!(Queen, G6, G7)).unwrap();
+        assert_eq!(outcome.status, BoardStatus::CheckMated(Color::Black));
+        assert!(board.make_move_with_outcome(&mv!(Queen, G6, A6)).is_err());
+    }
+
+    #[test]
+    fn move_intents() {
+        use MoveIntent::*;
+        let board = ChessBoard::from_str("r3k2r/8/8/3pP3/8/8/1P6/R3K2R w KQkq d6 0 1").unwrap();
+        assert_eq!(castle_king_side!().get_intent(&board), Castle);
+        assert_eq!(mv!(Pawn, E5, D6).get_intent(&board), EnPassant);
+        assert_eq!(mv!(Pawn, B2, B4).get_intent(&board), DoublePush);
+        assert_eq!(mv!(Pawn, B2, B3).get_intent(&board), Normal);
+        assert_eq!(mv!(Rook, A1, A8).get_intent(&board), Capture);
+        // the king moves standing for the castling
+        assert_eq!(mv!(King, E1, G1).get_intent(&board), Castle);
+        assert_eq!(mv!(King, E1, A1).get_intent(&board), Castle);
+        assert_eq!(mv!(King, E1, F1).get_intent(&board), Normal);
+        assert!(board
+            .make_move_with_intent(&mv!(King, E1, G1), Castle)
+            .is_ok());
+        assert!(board
+            .make_move_with_intent(&mv!(King, E1, G1), Normal)
+            .is_err());
+
+        assert!(board
+            .make_move_with_intent(&mv!(Rook, A1, A8), Capture)
+            .is_ok());
+        // the client missed that the pawn has just moved by two squares
+        assert!(matches!(
+            board.make_move_with_intent(&mv!(Pawn, E5, D6), Normal),
+            Err(Error::IntentMismatch {
+                expected: Normal,
+                actual:   EnPassant,
+            })
+        ));
+        // the intent matches, but the move is still illegal
+        assert!(matches!(
+            board.make_move_with_intent(&mv!(Knight, B1, C3), Normal),
+            Err(Error::IllegalMoveDetected)
+        ));
+        assert!(matches!(
+            board.make_move_with_intent(&mv!(Knight, B1, C3), Capture),
+            Err(Error::IllegalMoveDetected)
+        ));
+    }
+
+    #[test]
+    fn takeback() {
+        let board = ChessBoard::from_str("r3k3/8/8/3pP3/8/8/8/4K2R w Kq d6 0 1").unwrap();
+        let takeback = mv!(King, E1, G1).get_takeback(&board).unwrap();
+        assert_eq!(takeback, castle_king_side!().get_takeback(&board).unwrap());
+        assert_eq!(
+            takeback.movements[1],
+            PieceAnimation {
+                square_from: F1,
+                square_to:   H1,
+                piece_from:  Piece(Rook, Color::White),
+                piece_to:    Piece(Rook, Color::White),
+            }
+        );
+        assert_eq!(takeback.restored, None);
+
+        let takeback = mv!(Pawn, E5, D6).get_takeback(&board).unwrap();
+        assert_eq!(
+            (
+                takeback.movements[0].square_from,
+                takeback.movements[0].square_to
+            ),
+            (D6, E5)
+        );
+        assert_eq!(takeback.restored, Some((Piece(Pawn, Color::Black), D5)));
+
+        let board = board.make_move(&mv!(Pawn, E5, E6)).unwrap();
+        let takeback = castle_queen_side!().get_takeback(&board).unwrap();
+        assert_eq!(
+            (
+                takeback.movements[0].square_from,
+                takeback.movements[0].square_to
+            ),
+            (C8, E8)
+        );
+        assert_eq!(
+            (
+                takeback.movements[1].square_from,
+                takeback.movements[1].square_to
+            ),
+            (D8, A8)
+        );
+
+        assert!(mv!(King, E8, D7).get_takeback(&board).is_err());
+    }
+
+    #[test]
+    fn move_flags() {
+        let board = ChessBoard::from_str("r3k3/1P6/8/3pP3/8/8/4P3/4K2R w Kq d6 0 1").unwrap();
+        let flags = |m: BoardMove| MoveFlags::new(&m, &board);
+
+        let en_passant = flags(BoardMove::MovePiece(
+            PieceMove::new(Pawn, E5, D6, None).unwrap(),
+        ));
+        assert!(en_passant.is_capture & en_passant.is_en_passant & !en_passant.is_quiet());
+
+        let double_push = flags(BoardMove::MovePiece(
+            PieceMove::new(Pawn, E2, E4, None).unwrap(),
+        ));
+        assert!(double_push.is_double_push & double_push.is_quiet());
+
+        let promotion = PieceMove::new(Pawn, B7, A8, Some(Queen)).unwrap();
+        let promotion = flags(BoardMove::MovePiece(promotion));
+        assert!(promotion.is_capture & promotion.is_promotion & !promotion.is_en_passant);
+
+        let castling = flags(BoardMove::CastleKingSide);
+        assert!(castling.is_castling & castling.is_quiet());
+        assert_eq!(
+            flags(BoardMove::MovePiece(
+                PieceMove::new(King, E1, F1, None).unwrap()
+            )),
+            MoveFlags::default()
+        );
     }
 }
@@ -0,0 +1,126 @@
+use crate::errors::LibChessError as Error;
+use crate::{Color, File, Rank, Square, FILES, FILES_NUMBER, RANKS, RANKS_NUMBER};
+use std::ops::Not;
+
+/// Orientation of the board on the screen. Maps the cells of the screen grid (the row 0 is the
+/// top one, the column 0 is the left one) to the squares and back, so the renderers and GUIs
+/// share the same flipped-view logic
+///
+/// # Examples
+/// ```
+/// use libchess::{squares::*, BoardOrientation};
+/// let orientation = BoardOrientation::BlackBottom;
+/// assert_eq!(orientation.to_square(0, 0).unwrap(), H1);
+/// assert_eq!(orientation.to_grid(E2), (1, 3));
+/// assert_eq!((!orientation).to_grid(E2), (6, 4));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BoardOrientation {
+    /// White's pieces are at the bottom: A8 is the top-left corner
+    #[default]
+    WhiteBottom,
+    /// Black's pieces are at the bottom (the flipped board): H1 is the top-left corner
+    BlackBottom,
+}
+
+impl Not for BoardOrientation {
+    type Output = BoardOrientation;
+
+    fn not(self) -> BoardOrientation {
+        match self {
+            BoardOrientation::WhiteBottom => BoardOrientation::BlackBottom,
+            BoardOrientation::BlackBottom => BoardOrientation::WhiteBottom,
+        }
+    }
+}
+
+impl BoardOrientation {
+    /// Returns the orientation with pieces of specified color at the bottom
+    #[inline]
+    pub fn from_color(color: Color) -> Self {
+        match color {
+            Color::White => BoardOrientation::WhiteBottom,
+            Color::Black => BoardOrientation::BlackBottom,
+        }
+    }
+
+    /// Returns the color of pieces at the bottom of the board
+    #[inline]
+    pub fn get_bottom_color(&self) -> Color {
+        match self {
+            BoardOrientation::WhiteBottom => Color::White,
+            BoardOrientation::BlackBottom => Color::Black,
+        }
+    }
+
+    /// Returns the square displayed in the cell of the screen grid
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidSquareRepresentation`` if the row or the column is out of the board
+    pub fn to_square(&self, row: usize, column: usize) -> Result<Square, Error> {
+        if (row >= RANKS_NUMBER) | (column >= FILES_NUMBER) {
+            return Err(Error::InvalidSquareRepresentation);
+        }
+        Ok(Square::from_rank_file(
+            self.get_ranks()[row],
+            self.get_files()[column],
+        ))
+    }
+
+    /// Returns the cell of the screen grid (row, column) where the square is displayed
+    pub fn to_grid(&self, square: Square) -> (usize, usize) {
+        let (rank, file) = (square.get_rank().to_index(), square.get_file().to_index());
+        match self {
+            BoardOrientation::WhiteBottom => (RANKS_NUMBER - 1 - rank, file),
+            BoardOrientation::BlackBottom => (rank, FILES_NUMBER - 1 - file),
+        }
+    }
+
+    /// Returns the ranks in the order of displaying from top to bottom
+    pub fn get_ranks(&self) -> [Rank; RANKS_NUMBER] {
+        let mut ranks = RANKS;
+        if *self == BoardOrientation::WhiteBottom {
+            ranks.reverse();
+        }
+        ranks
+    }
+
+    /// Returns the files in the order of displaying from left to right
+    pub fn get_files(&self) -> [File; FILES_NUMBER] {
+        let mut files = FILES;
+        if *self == BoardOrientation::BlackBottom {
+            files.reverse();
+        }
+        files
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::squares::*;
+
+    #[test]
+    fn grid_mapping() {
+        use BoardOrientation::*;
+        assert_eq!(WhiteBottom.to_square(0, 0).unwrap(), A8);
+        assert_eq!(WhiteBottom.to_square(7, 7).unwrap(), H1);
+        assert_eq!(BlackBottom.to_square(7, 7).unwrap(), A8);
+        assert!(WhiteBottom.to_square(8, 0).is_err());
+        assert!(BlackBottom.to_square(0, 8).is_err());
+
+        for orientation in [WhiteBottom, BlackBottom] {
+            for row in 0..RANKS_NUMBER {
+                for column in 0..FILES_NUMBER {
+                    let square = orientation.to_square(row, column).unwrap();
+                    assert_eq!(orientation.to_grid(square), (row, column));
+                }
+            }
+        }
+
+        assert_eq!(BoardOrientation::from_color(Color::Black), BlackBottom);
+        assert_eq!((!BlackBottom).get_bottom_color(), Color::White);
+        assert_eq!(BlackBottom.get_files()[0], File::H);
+        assert_eq!(WhiteBottom.get_ranks()[0], Rank::Eighth);
+    }
+}
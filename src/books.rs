@@ -0,0 +1,388 @@
+//! Opening books
+//!
+//! This module implements building the opening books from PGN databases: the moves played in
+//! the first plies of the games are collected for every position and weighted by their
+//! frequency, results or recency. The book can be exported to the Polyglot ".bin" format
+
+use crate::db::PgnReader;
+use crate::errors::LibChessError as Error;
+use crate::{BoardMove, ChessBoard, Color, File, Game, GameStatus, PieceType};
+use crate::{PositionHashValueType, Square};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, Write};
+
+/// Strategy of weighting the book moves
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum BookWeighting {
+    /// The number of games with the move
+    #[default]
+    Frequency,
+    /// The share of points scored with the move by the side which made it (a draw is a half
+    /// point). The share is smoothed as if one won and one lost game were added, so rare moves
+    /// don't get extreme weights
+    WinRate,
+    /// The number of games where each game is discounted by its age: the game played
+    /// ``half_life_years`` before the newest game of the database counts as a half. Games
+    /// without the year in the "Date" tag are considered as old as the oldest dated game. The
+    /// half-life must be positive
+    RecencyDecay { half_life_years: f64 },
+}
+
+/// The move of the book and its statistics
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookMove {
+    pub board_move: BoardMove,
+    /// Number of games with the move
+    pub games:      usize,
+    /// Points scored with the move by the side which made it
+    pub points:     f64,
+    /// Weight of the move according to the ``BookWeighting`` of the builder
+    pub weight:     f64,
+    /// Numbers of games with the move by year (games without the year are not included)
+    years:          BTreeMap<u16, usize>,
+}
+
+/// The book positions with the moves played in them
+#[derive(Debug, Clone, Default)]
+pub struct OpeningBook {
+    positions: HashMap<PositionHashValueType, (ChessBoard, Vec<BookMove>)>,
+}
+
+/// Builds the opening book from the PGN database
+///
+/// # Examples
+/// ```
+/// use libchess::books::{BookWeighting, OpeningBookBuilder};
+/// use libchess::ChessBoard;
+/// let database = "[Date \"2020.01.01\"]\n\n1. e4 e5 1-0\n\n\
+///                 [Date \"2023.01.01\"]\n\n1. d4 d5 0-1\n\n\
+///                 [Date \"2023.05.01\"]\n\n1. d4 Nf6 1/2-1/2\n";
+/// let book = OpeningBookBuilder::new()
+///     .set_max_plies(2)
+///     .set_weighting(BookWeighting::RecencyDecay {
+///         half_life_years: 1.0,
+///     })
+///     .build(database.as_bytes())
+///     .unwrap();
+///
+/// let moves = book.get_moves(&ChessBoard::default());
+/// assert_eq!(moves.len(), 2);
+/// assert_eq!(moves[0].games, 2); // 1. d4 is the most recent and the most frequent move
+/// assert_eq!(moves[1].weight, 0.125); // 1. e4 was played three years before
+/// ```
+#[derive(Debug, Clone)]
+pub struct OpeningBookBuilder {
+    max_plies: usize,
+    min_games: usize,
+    weighting: BookWeighting,
+}
+
+const DEFAULT_BOOK_MAX_PLIES: usize = 16;
+
+impl Default for OpeningBookBuilder {
+    fn default() -> Self {
+        Self {
+            max_plies: DEFAULT_BOOK_MAX_PLIES,
+            min_games: 1,
+            weighting: BookWeighting::default(),
+        }
+    }
+}
+
+impl OpeningBookBuilder {
+    #[inline]
+    pub fn new() -> Self { Self::default() }
+
+    /// Sets the number of first plies of every game which are added to the book
+    #[inline]
+    pub fn set_max_plies(&mut self, max_plies: usize) -> &mut Self {
+        self.max_plies = max_plies;
+        self
+    }
+
+    /// Sets the minimal number of games with the move to keep it in the book
+    #[inline]
+    pub fn set_min_games(&mut self, min_games: usize) -> &mut Self {
+        self.min_games = min_games;
+        self
+    }
+
+    #[inline]
+    pub fn set_weighting(&mut self, weighting: BookWeighting) -> &mut Self {
+        self.weighting = weighting;
+        self
+    }
+
+    /// Reads all games from the PGN-stream and builds the book
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidBookWeighting`` if the half-life of ``BookWeighting::RecencyDecay``
+    /// is not a positive number
+    ///
+    /// ``LibChessError::InvalidPGNStream`` if the stream can't be read. Games which can't be
+    /// parsed are skipped
+    pub fn build(&self, reader: impl BufRead) -> Result<OpeningBook, Error> {
+        if let BookWeighting::RecencyDecay { half_life_years } = self.weighting {
+            if half_life_years.is_nan() | (half_life_years <= 0.0) {
+                return Err(Error::InvalidBookWeighting {
+                    description: format!("non-positive half-life {half_life_years}"),
+                });
+            }
+        }
+
+        let mut book = OpeningBook::default();
+        for pgn in PgnReader::new(reader) {
+            if let Ok(game) = Game::from_pgn(&pgn?) {
+                book.add_game(&game, self.max_plies);
+            }
+        }
+
+        let years = book
+            .positions
+            .values()
+            .flat_map(|(_, moves)| moves.iter())
+            .flat_map(|m| m.years.keys().copied());
+        let (oldest, newest) = years.fold((u16::MAX, u16::MIN), |(oldest, newest), year| {
+            (oldest.min(year), newest.max(year))
+        });
+
+        for (_, moves) in book.positions.values_mut() {
+            moves.retain(|m| m.games >= self.min_games);
+            for m in moves.iter_mut() {
+                m.weight = self.get_weight(m, oldest, newest);
+            }
+            moves.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+        }
+        book.positions.retain(|_, (_, moves)| !moves.is_empty());
+        Ok(book)
+    }
+
+    fn get_weight(&self, m: &BookMove, oldest: u16, newest: u16) -> f64 {
+        match self.weighting {
+            BookWeighting::Frequency => m.games as f64,
+            BookWeighting::WinRate => (m.points + 1.0) / (m.games as f64 + 2.0),
+            BookWeighting::RecencyDecay { half_life_years } => {
+                let decay = |year: u16| 0.5f64.powf((newest - year) as f64 / half_life_years);
+                let undated = m.games - m.years.values().sum::<usize>();
+                let dated: f64 = m
+                    .years
+                    .iter()
+                    .map(|(&year, &games)| games as f64 * decay(year))
+                    .sum();
+                dated + undated as f64 * decay(oldest.min(newest))
+            }
+        }
+    }
+}
+
+impl OpeningBook {
+    fn add_game(&mut self, game: &Game, max_plies: usize) {
+        let year = game
+            .get_metadata()
+            .get_date()
+            .and_then(|date| date.get(..4))
+            .and_then(|year| year.parse().ok());
+        let history = game.get_action_history();
+        let positions = history.get_positions();
+
+        for (ply, board_move) in history.get_moves().iter().take(max_plies).enumerate() {
+            let board = positions[ply];
+            let (_, moves) = self
+                .positions
+                .entry(board.get_hash())
+                .or_insert_with(|| (board, Vec::new()));
+            let index = match moves.iter().position(|m| m.board_move == *board_move) {
+                Some(index) => index,
+                None => {
+                    moves.push(BookMove {
+                        board_move: *board_move,
+                        games:      0,
+                        points:     0.0,
+                        weight:     0.0,
+                        years:      BTreeMap::new(),
+                    });
+                    moves.len() - 1
+                }
+            };
+            let m = &mut moves[index];
+            m.games += 1;
+            m.points += get_points(game.get_game_status(), board.get_side_to_move());
+            if let Some(year) = year {
+                *m.years.entry(year).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// Returns the book moves in the position sorted by their weights (the heaviest first)
+    pub fn get_moves(&self, board: &ChessBoard) -> &[BookMove] {
+        self.positions
+            .get(&board.get_hash())
+            .map_or(&[], |(_, moves)| moves.as_slice())
+    }
+
+    /// Returns the number of positions in the book
+    #[inline]
+    pub fn len(&self) -> usize { self.positions.len() }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.positions.is_empty() }
+
+    /// Writes the book in the Polyglot ".bin" format: 16-byte big-endian entries (key, move,
+    /// weight, learn) sorted by the key. The weights are scaled so the heaviest move of each
+    /// position has the weight 65535
+    ///
+    /// The positions are keyed by ``board.get_polyglot_hash()``, so the file can be read by
+    /// other programs which support the format
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidBookStream`` if the book can't be written
+    pub fn write_polyglot(&self, writer: &mut impl Write) -> Result<(), Error> {
+        let mut entries = Vec::new();
+        for (board, moves) in self.positions.values() {
+            let key = board.get_polyglot_hash();
+            let max_weight = moves.iter().map(|m| m.weight).fold(0.0, f64::max);
+            for m in moves {
+                let weight = match max_weight > 0.0 {
+                    true => (m.weight / max_weight * u16::MAX as f64).round() as u16,
+                    false => 0,
+                };
+                entries.push((key, encode_polyglot_move(board, &m.board_move), weight));
+            }
+        }
+        entries.sort_by(|a, b| a.0.cmp(&b.0).then(b.2.cmp(&a.2)));
+
+        for (key, polyglot_move, weight) in entries {
+            let mut entry = [0u8; 16];
+            entry[..8].copy_from_slice(&key.to_be_bytes());
+            entry[8..10].copy_from_slice(&polyglot_move.to_be_bytes());
+            entry[10..12].copy_from_slice(&weight.to_be_bytes());
+            writer
+                .write_all(&entry)
+                .map_err(|e| Error::InvalidBookStream {
+                    description: e.to_string(),
+                })?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the points of the game for the player of specified color (unfinished games count as
+/// draws)
+fn get_points(status: GameStatus, color: Color) -> f64 {
+    use GameStatus::*;
+    match status {
+        CheckMated(loser) | Resigned(loser) | Forfeited(loser) => match loser == color {
+            true => 0.0,
+            false => 1.0,
+        },
+        _ => 0.5,
+    }
+}
+
+/// Encodes the move in Polyglot format: destination square in bits 0-5, source square in bits
+/// 6-11, promotion piece in bits 12-14. Castling is encoded as the king's move to its rook
+fn encode_polyglot_move(board: &ChessBoard, board_move: &BoardMove) -> u16 {
+    let back_rank = board.get_side_to_move().get_back_rank();
+    let king_square = Square::from_rank_file(back_rank, File::E);
+    let (source, destination, promotion) = match board_move {
+        BoardMove::MovePiece(m) => (
+            m.get_source_square(),
+            m.get_destination_square(),
+            m.get_promotion(),
+        ),
+        BoardMove::CastleKingSide => (
+            king_square,
+            Square::from_rank_file(back_rank, File::H),
+            None,
+        ),
+        BoardMove::CastleQueenSide => (
+            king_square,
+            Square::from_rank_file(back_rank, File::A),
+            None,
+        ),
+    };
+    let promotion = match promotion {
+        Some(PieceType::Knight) => 1,
+        Some(PieceType::Bishop) => 2,
+        Some(PieceType::Rook) => 3,
+        Some(PieceType::Queen) => 4,
+        _ => 0,
+    };
+    (destination.to_index() as u16) | ((source.to_index() as u16) << 6) | (promotion << 12)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{castle_king_side, mv, squares::*, PieceMove, PieceType::*};
+
+    #[test]
+    fn book_weights() {
+        let database = "[Event \"1\"]\n\n1. e4 e5 2. Nf3 1-0\n\n\
+                        [Event \"2\"]\n\n1. e4 c5 0-1\n\n\
+                        [Event \"3\"]\n\n1. e4 c5 1-0\n\n\
+                        [Event \"4\"]\n\n1. d4 d5 1-0\n";
+        let board = ChessBoard::default();
+
+        let book = OpeningBookBuilder::new()
+            .build(database.as_bytes())
+            .unwrap();
+        let moves = book.get_moves(&board);
+        assert_eq!(moves[0].board_move, mv!(Pawn, E2, E4));
+        assert_eq!((moves[0].games, moves[0].points), (3, 2.0));
+        assert_eq!(moves[0].weight, 3.0);
+
+        let book = OpeningBookBuilder::new()
+            .set_weighting(BookWeighting::WinRate)
+            .build(database.as_bytes())
+            .unwrap();
+        let moves = book.get_moves(&board);
+        assert_eq!(moves[0].board_move, mv!(Pawn, D2, D4)); // (1 + 1) / (1 + 2)
+        assert_eq!(moves[1].weight, 0.6); // (2 + 1) / (3 + 2)
+
+        let book = OpeningBookBuilder::new()
+            .set_min_games(2)
+            .build(database.as_bytes())
+            .unwrap();
+        assert_eq!(book.get_moves(&board).len(), 1);
+        let after_e4 = board.make_move(&mv!(Pawn, E2, E4)).unwrap();
+        assert_eq!(book.get_moves(&after_e4)[0].board_move, mv!(Pawn, C7, C5));
+        assert_eq!(book.len(), 2);
+
+        for half_life_years in [0.0, -1.0, f64::NAN] {
+            assert!(OpeningBookBuilder::new()
+                .set_weighting(BookWeighting::RecencyDecay { half_life_years })
+                .build(database.as_bytes())
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn polyglot_export() {
+        let database = "[Event \"1\"]\n\n1. e4 e5 1-0\n\n[Event \"2\"]\n\n1. e4 c5 1-0\n\n\
+                        [Event \"3\"]\n\n1. d4 d5 1-0\n";
+        let book = OpeningBookBuilder::new()
+            .set_max_plies(1)
+            .build(database.as_bytes())
+            .unwrap();
+        let mut bin = Vec::new();
+        book.write_polyglot(&mut bin).unwrap();
+
+        assert_eq!(bin.len(), 32);
+        let key = 0x463b96181691fc9cu64.to_be_bytes();
+        assert_eq!(bin[..8], key);
+        assert_eq!(bin[8..10], (28u16 | (12 << 6)).to_be_bytes()); // e2e4
+        assert_eq!(bin[10..12], u16::MAX.to_be_bytes());
+        assert_eq!(bin[26..28], (u16::MAX / 2 + 1).to_be_bytes());
+
+        let board = ChessBoard::from_fen("4k3/1P6/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        assert_eq!(
+            encode_polyglot_move(&board, &castle_king_side!()),
+            7 | (4 << 6)
+        );
+        assert_eq!(
+            encode_polyglot_move(&board, &mv!(Pawn, B7, B8, Queen)),
+            57 | (49 << 6) | (4 << 12)
+        );
+    }
+}
@@ -0,0 +1,122 @@
+//! Canonical forms of positions
+//!
+//! This module defines the options of bringing positions to the canonical form (see
+//! ``ChessBoard::canonicalize``) and the deduplication of FEN corpora by this form. The
+//! symmetric duplicates are also found cheaply by the hashes of the transformed positions (see
+//! ``BoardSymmetry``)
+
+use crate::errors::LibChessError as Error;
+use crate::{ChessBoard, Square, FILES_NUMBER, RANKS_NUMBER};
+use std::collections::HashSet;
+use std::str::FromStr;
+
+/// Options of the canonical form of the position. The en passant square is always removed if
+/// there is no legal en passant capture
+///
+/// # Examples
+/// ```
+/// use libchess::{CanonicalForm, ChessBoard};
+/// let form = CanonicalForm {
+///     mirror_to_white: true,
+///     strip_counters:  true,
+/// };
+/// let board = ChessBoard::from_fen("4k3/4p3/8/8/8/8/8/4K3 b - - 5 40").unwrap();
+/// assert_eq!(
+///     board.get_canonical_fen(&form),
+///     "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CanonicalForm {
+    /// Positions with black to move are mirrored (the board is flipped vertically and the
+    /// colors are swapped), so white is always to move
+    pub mirror_to_white: bool,
+    /// The move number and the number of moves since the last capture or pawn move are reset
+    pub strip_counters:  bool,
+}
+
+/// The symmetry of the board which keeps the chess rules (see ``ChessBoard::get_symmetric_hash``)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BoardSymmetry {
+    /// The board is flipped vertically, the colors of pieces, the castling rights and the side
+    /// to move are swapped (see ``ChessBoard::get_mirrored``)
+    ColorMirror,
+    /// The board is flipped horizontally (the files from A to H are reversed). Is valid only
+    /// without castling rights
+    HorizontalFlip,
+    /// The board is rotated by 180 degrees and the colors are swapped (both transformations
+    /// above). Is valid only without castling rights
+    Rotation,
+}
+
+impl BoardSymmetry {
+    /// Returns true if the symmetry flips the files
+    #[inline]
+    pub(crate) fn flips_files(&self) -> bool { *self != BoardSymmetry::ColorMirror }
+
+    /// Returns true if the symmetry flips the ranks and swaps the colors
+    #[inline]
+    pub(crate) fn swaps_colors(&self) -> bool { *self != BoardSymmetry::HorizontalFlip }
+
+    /// Returns the image of the square
+    pub fn transform_square(&self, square: Square) -> Square {
+        let mut index = square.to_int();
+        if self.flips_files() {
+            index ^= (FILES_NUMBER - 1) as u8;
+        }
+        if self.swaps_colors() {
+            index ^= ((RANKS_NUMBER - 1) * FILES_NUMBER) as u8;
+        }
+        Square::new(index).unwrap()
+    }
+}
+
+impl Default for CanonicalForm {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            mirror_to_white: false,
+            strip_counters:  true,
+        }
+    }
+}
+
+impl CanonicalForm {
+    /// Returns the canonical FEN-strings of the positions without duplicates, in the order of
+    /// their first occurrence
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidFENString`` and other position errors for the first invalid
+    /// FEN-string
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::CanonicalForm;
+    /// let fens = [
+    ///     "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+    ///     "4k3/8/8/8/8/8/4P3/4K3 w - - 12 30",
+    ///     "4k3/4p3/8/8/8/8/8/4K3 b - - 0 1",
+    /// ];
+    /// let form = CanonicalForm::default();
+    /// assert_eq!(form.dedup_fens(fens).unwrap().len(), 2);
+    /// let form = CanonicalForm {
+    ///     mirror_to_white: true,
+    ///     ..form
+    /// };
+    /// assert_eq!(form.dedup_fens(fens).unwrap().len(), 1);
+    /// ```
+    pub fn dedup_fens<'a>(
+        &self,
+        fens: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Vec<String>, Error> {
+        let mut seen = HashSet::new();
+        let mut result = Vec::new();
+        for fen in fens {
+            let canonical = ChessBoard::from_str(fen)?.get_canonical_fen(self);
+            if seen.insert(canonical.clone()) {
+                result.push(canonical);
+            }
+        }
+        Ok(result)
+    }
+}
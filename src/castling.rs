@@ -1,4 +1,5 @@
 use crate::errors::LibChessError as Error;
+use crate::{Color, File, Piece, PieceType, Square, COLORS_NUMBER, SQUARES_NUMBER};
 use std::fmt;
 use std::ops::{Add, AddAssign, Sub, SubAssign};
 
@@ -12,6 +13,21 @@ pub enum CastlingRights {
 
 pub const CASTLING_RIGHTS_NUMBER: usize = 4;
 
+/// Castling rights which each color loses when any move starts or ends on the square: the king's
+/// and rooks' initial squares (the rook may be moved away or captured there)
+const CASTLING_RIGHTS_LOSS: [[CastlingRights; SQUARES_NUMBER]; COLORS_NUMBER] = {
+    let mut table = [[CastlingRights::Neither; SQUARES_NUMBER]; COLORS_NUMBER];
+    let back_rank_offsets = [0, SQUARES_NUMBER - 8];
+    let mut i = 0;
+    while i < COLORS_NUMBER {
+        table[i][back_rank_offsets[i]] = CastlingRights::QueenSide;
+        table[i][back_rank_offsets[i] + 4] = CastlingRights::BothSides;
+        table[i][back_rank_offsets[i] + 7] = CastlingRights::KingSide;
+        i += 1;
+    }
+    table
+};
+
 impl Add for CastlingRights {
     type Output = CastlingRights;
 
@@ -94,6 +110,37 @@ impl CastlingRights {
 
     #[inline]
     pub fn has_any(&self) -> bool { self != &CastlingRights::Neither }
+
+    /// Returns the castling rights which specified color loses when any move starts or ends on
+    /// the square
+    #[inline]
+    pub(crate) fn get_lost_on(color: Color, square: Square) -> CastlingRights {
+        CASTLING_RIGHTS_LOSS[color.to_index()][square.to_index()]
+    }
+
+    /// Finds the first side of these rights which is not supported by the king and rook
+    /// placement of specified color. Returns this side (``KingSide`` or ``QueenSide``) with the
+    /// square where the rook is expected
+    pub(crate) fn find_inconsistency(
+        self,
+        color: Color,
+        piece_on: impl Fn(Square) -> Option<Piece>,
+    ) -> Option<(CastlingRights, Square)> {
+        let rank = color.get_back_rank();
+        let king_is_home =
+            piece_on(Square::from_rank_file(rank, File::E)) == Some(Piece(PieceType::King, color));
+
+        [
+            (self.has_kingside(), CastlingRights::KingSide, File::H),
+            (self.has_queenside(), CastlingRights::QueenSide, File::A),
+        ]
+        .into_iter()
+        .filter(|(has_side, ..)| *has_side)
+        .map(|(_, side, file)| (side, Square::from_rank_file(rank, file)))
+        .find(|(_, rook_square)| {
+            !king_is_home | (piece_on(*rook_square) != Some(Piece(PieceType::Rook, color)))
+        })
+    }
 }
 
 #[cfg(test)]
@@ -102,14 +149,14 @@ mod tests {
 
     #[test]
     fn availability() {
-        assert_eq!(CastlingRights::BothSides.has_kingside(), true);
-        assert_eq!(CastlingRights::BothSides.has_queenside(), true);
-        assert_eq!(CastlingRights::Neither.has_kingside(), false);
-        assert_eq!(CastlingRights::Neither.has_queenside(), false);
-        assert_eq!(CastlingRights::KingSide.has_kingside(), true);
-        assert_eq!(CastlingRights::KingSide.has_queenside(), false);
-        assert_eq!(CastlingRights::QueenSide.has_kingside(), false);
-        assert_eq!(CastlingRights::QueenSide.has_queenside(), true);
+        assert!(CastlingRights::BothSides.has_kingside());
+        assert!(CastlingRights::BothSides.has_queenside());
+        assert!(!CastlingRights::Neither.has_kingside());
+        assert!(!CastlingRights::Neither.has_queenside());
+        assert!(CastlingRights::KingSide.has_kingside());
+        assert!(!CastlingRights::KingSide.has_queenside());
+        assert!(!CastlingRights::QueenSide.has_kingside());
+        assert!(CastlingRights::QueenSide.has_queenside());
     }
 
     #[test]
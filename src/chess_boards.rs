@@ -4,23 +4,58 @@
 //! calculation). Implements the logics of moving pieces and inferring the board status
 
 use crate::errors::LibChessError as Error;
+use crate::games::strip_pgn_comments;
+use crate::kpk::KPK_BITBASE;
 use crate::move_masks::{
-    BETWEEN_TABLE as BETWEEN, BISHOP_TABLE as BISHOP, KING_TABLE as KING, KNIGHT_TABLE as KNIGHT,
-    PAWN_TABLE as PAWN, QUEEN_TABLE as QUEEN, RAYS_TABLE as RAYS, ROOK_TABLE as ROOK,
+    castling_empty_path, castling_king_path, BETWEEN_TABLE as BETWEEN, BISHOP_TABLE as BISHOP,
+    KING_TABLE as KING, KNIGHT_TABLE as KNIGHT, PAWN_TABLE as PAWN, QUEEN_TABLE as QUEEN,
+    RAYS_TABLE as RAYS, ROOK_TABLE as ROOK,
 };
+use crate::polyglot_keys::calculate_polyglot_hash;
 use crate::{
-    castle_king_side, castle_queen_side, mv, squares, BitBoard, BoardBuilder, BoardMove,
-    CastlingRights, Color, DisplayAmbiguityType, File, Piece, PieceMove, PieceType,
-    PositionHashValueType, Rank, Square, BLANK, COLORS_NUMBER, FILES, PIECE_TYPES_NUMBER, RANKS,
-    SQUARES_NUMBER, ZOBRIST_TABLES as ZOBRIST,
+    castle_king_side, castle_queen_side, mv, AnnotationColor, Annotations, BitBoard, BoardBuilder,
+    BoardMove, BoardOrientation, BoardSymmetry, CanonicalForm, CastlingRights, Color,
+    DisplayAmbiguityType, EndgameClass, EvaluatorBackend, File, GamePhase, GameStatus, MoveFlags,
+    MoveIntent, MoveOutcome, MovePropertiesOnBoard, NotationLocale, PerftStats, Piece, PieceMove,
+    PieceType, PieceVisibility, PlayoutPolicy, PositionCore, PositionHashValueType,
+    PromotionPolicy, Rank, Square, SquareColor, Stipulation, WinDrawLoss, BLANK, CENTER_SQUARES,
+    COLORS_NUMBER, ENDGAME_MAX_PIECES, EXTENDED_CENTER_SQUARES, FILES, FILES_NUMBER,
+    OPENING_MIN_BACK_RANK_PIECES, OPENING_MIN_PIECES, PIECE_TYPES_NUMBER, POLICY_SIZE, RANKS,
+    RANKS_NUMBER, SQUARES_NUMBER, ZOBRIST_TABLES as ZOBRIST,
 };
+use crate::{perfts, policy_encodings, stipulations};
 use crate::{CastlingRights::*, Color::*, PieceType::*};
 use colored::Colorize;
+use lazy_static::lazy_static;
+use rand::Rng;
+use std::collections::HashMap;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
 use std::str::FromStr;
 
 pub type LegalMoves = Vec<BoardMove>;
 
+/// The maximal number of legal moves in a single position. The vectors of legal moves are
+/// pre-allocated with this capacity, so they are never reallocated during the generation
+pub const MAX_LEGAL_MOVES: usize = 218;
+
+lazy_static! {
+    /// The standard starting position (is built once, see ``ChessBoard::is_startpos``)
+    static ref STARTING_POSITION: ChessBoard =
+        ChessBoard::try_from(BoardBuilder::startpos()).unwrap();
+}
+
+/// Width of the rendered board in characters (without the colors escape sequences)
+const BOARD_RENDER_WIDTH: usize = 29;
+
+/// Numbers of plies without captures and pawn moves for the 50-move and 75-move rules
+const FIFTY_MOVES_PLIES: usize = 100;
+const SEVENTY_FIVE_MOVES_PLIES: usize = 150;
+
+/// Number of repetitions of the position which finishes the random playout
+const PLAYOUT_REPETITIONS: usize = 3;
+
 /// Represents the board status
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BoardStatus {
@@ -31,6 +66,156 @@ pub enum BoardStatus {
     Stalemate,
 }
 
+/// Structured report of the draw conditions in the position. Repetitions are not included
+/// because they depend on the game history (see ``Game``)
+///
+/// # Examples
+/// ```
+/// use libchess::ChessBoard;
+/// let report = ChessBoard::from_fen("8/8/4k3/8/8/3K4/8/8 w - - 120 90")
+///     .unwrap()
+///     .draw_report();
+/// assert!(report.fifty_move_claimable & !report.seventy_five_forced);
+/// assert!(report.insufficient_material & !report.stalemate);
+/// println!("{}", report);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DrawReport {
+    /// At least 50 moves were made by each side since the last capture or pawn move, so the
+    /// player may claim a draw
+    pub fifty_move_claimable: bool,
+    /// At least 75 moves were made by each side since the last capture or pawn move, so the draw
+    /// is declared automatically
+    pub seventy_five_forced: bool,
+    /// Neither side has enough material to checkmate
+    pub insufficient_material: bool,
+    /// The side to move has no legal moves and is not in check
+    pub stalemate: bool,
+}
+
+impl DrawReport {
+    /// Returns true if any of the draw conditions is met
+    #[inline]
+    pub fn is_any(&self) -> bool {
+        self.fifty_move_claimable
+            | self.seventy_five_forced
+            | self.insufficient_material
+            | self.stalemate
+    }
+}
+
+impl fmt::Display for DrawReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut reasons = vec![];
+        if self.stalemate {
+            reasons.push("stalemate");
+        }
+        if self.insufficient_material {
+            reasons.push("insufficient material to checkmate");
+        }
+        if self.seventy_five_forced {
+            reasons.push("75 moves without captures and pawn moves (forced draw)");
+        } else if self.fifty_move_claimable {
+            reasons.push("50 moves without captures and pawn moves (draw may be claimed)");
+        }
+
+        match reasons.is_empty() {
+            true => write!(f, "no draw conditions"),
+            false => write!(f, "{}", reasons.join(", ")),
+        }
+    }
+}
+
+/// Center control of one side (see ``ChessBoard::center_control``). The extended center
+/// includes the center
+///
+/// # Examples
+/// ```
+/// use libchess::{ChessBoard, Color::*};
+/// let board = ChessBoard::default().apply_san_line("e4 e5 Nf3").unwrap();
+/// let control = board.center_control(White);
+/// assert_eq!((control.center_attacked, control.center_occupied), (3, 1));
+/// assert_eq!(control.extended_center_occupied, 2);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CenterControl {
+    /// The number of center squares attacked (or defended) by the side
+    pub center_attacked:          u32,
+    /// The number of center squares occupied by pieces of the side
+    pub center_occupied:          u32,
+    pub extended_center_attacked: u32,
+    pub extended_center_occupied: u32,
+}
+
+/// The reason why the en-passant capture is not legal (see ``ChessBoard::check_en_passant``)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnPassantIllegality {
+    /// The last move was not a double pawn push
+    NoEnPassantSquare,
+    /// There is no pawn of the side to move on the specified square which attacks the
+    /// en-passant square
+    NoCapturingPawn,
+    /// The king is in check from the specified piece and the capture doesn't resolve it
+    UnresolvedCheck { attacker: Square },
+    /// The capturing pawn is pinned to the king by the specified piece
+    PinnedPawn { attacker: Square },
+    /// The king, the capturing pawn and the captured pawn stand on the same rank: both pawns
+    /// leave it at once and expose the king to the specified rook or queen. This is the only
+    /// case when the move removes two blockers from the same line, so the usual pin detection
+    /// doesn't see it
+    HorizontalDiscovery { attacker: Square },
+    /// The captured pawn shields the king of the side to move: removing it from the board
+    /// exposes the king to the specified bishop or queen
+    CapturedPawnDiscovery { attacker: Square },
+}
+
+impl fmt::Display for EnPassantIllegality {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use EnPassantIllegality::*;
+        match self {
+            NoEnPassantSquare => write!(f, "there is no en-passant square"),
+            NoCapturingPawn => write!(f, "there is no pawn to capture en-passant"),
+            UnresolvedCheck { attacker } => {
+                write!(f, "the king remains in check from {attacker}")
+            }
+            PinnedPawn { attacker } => {
+                write!(f, "the capturing pawn is pinned by {attacker}")
+            }
+            HorizontalDiscovery { attacker } => write!(
+                f,
+                "both pawns leave the king's rank and expose the king to {attacker}"
+            ),
+            CapturedPawnDiscovery { attacker } => write!(
+                f,
+                "the captured pawn shields the king and its removal exposes the king to {attacker}"
+            ),
+        }
+    }
+}
+
+/// How the en passant field of the FEN-string is treated (see ``FenParseOptions``)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum EnPassantPolicy {
+    /// The square is kept as it is. The FEN-string is rejected only if there is no pawn which
+    /// has just made the double push
+    #[default]
+    Keep,
+    /// The square is dropped if there is no legal en passant capture (many FEN sources set it
+    /// after every double push) or if it is inconsistent with the pawns placement
+    Strip,
+    /// The FEN-string is rejected if the square is set but there is no legal en passant capture
+    Strict,
+}
+
+/// Options of parsing FEN-strings from lax sources (see ``ChessBoard::from_fen_with_options``)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FenParseOptions {
+    pub en_passant:       EnPassantPolicy,
+    /// Drop castling rights which are inconsistent with the king and rooks placement (see
+    /// ``ChessBoard::from_fen_lenient``)
+    pub lenient_castling: bool,
+}
+
 /// The Chess board representation
 ///
 /// Represents any available board position. Can be initialized by the FEN-string (most recommended)
@@ -55,7 +240,24 @@ pub enum BoardStatus {
 /// println!("{}", board.as_fen());
 /// println!("{}", board.make_move(&mv!(King, F4, G5)).unwrap());
 /// ```
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+///
+/// Boards are compared by the position only (pieces placement, side to move, castling rights and
+/// capturable en-passant square), so the move counters are ignored. It's consistent with the
+/// ``Hash`` implementation which uses the Zobrist hash, so boards can be used as keys of
+/// transposition tables or opening books:
+/// ```
+/// use libchess::ChessBoard;
+/// use std::collections::HashMap;
+/// use std::str::FromStr;
+///
+/// let mut visits = HashMap::new();
+/// *visits.entry(ChessBoard::default()).or_insert(0) += 1;
+/// let board =
+///     ChessBoard::from_str("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 4 3").unwrap();
+/// *visits.entry(board).or_insert(0) += 1;
+/// assert_eq!(visits[&ChessBoard::default()], 2);
+/// ```
+#[derive(Debug, Copy, Clone)]
 pub struct ChessBoard {
     pieces_mask: [BitBoard; PIECE_TYPES_NUMBER],
     colors_mask: [BitBoard; COLORS_NUMBER],
@@ -66,8 +268,8 @@ pub struct ChessBoard {
     pinned: BitBoard,
     checks: BitBoard,
     is_terminal_position: bool,
-    moves_since_capture_or_pawn_move: usize,
-    move_number: usize,
+    moves_since_capture_or_pawn_move: u32,
+    move_number: u32,
     hash: PositionHashValueType,
 }
 
@@ -75,12 +277,41 @@ impl TryFrom<&BoardBuilder> for ChessBoard {
     type Error = Error;
 
     fn try_from(builder: &BoardBuilder) -> Result<Self, Self::Error> {
+        if builder.is_lenient_castling() {
+            let mut builder = *builder;
+            builder
+                .set_lenient_castling(false)
+                .downgrade_castling_rights();
+            return ChessBoard::try_from(&builder);
+        }
+
+        // castling with the rooks outside of the A and H files (Chess960) is not supported
+        for color in Color::iter() {
+            if let Some((side, file)) = builder.find_non_standard_rook_file(color) {
+                return Err(Error::InvalidBoardInconsistentCastlingRights {
+                    color,
+                    side,
+                    rook_square: Square::from_rank_file(color.get_back_rank(), file),
+                });
+            }
+        }
+
+        // the counters are stored as u32 and must leave room for the next move
+        let counter = |value: usize| match u32::try_from(value) {
+            Ok(value) if value < u32::MAX => Ok(value),
+            _ => Err(Error::WrongMoveNumber),
+        };
+        // the fullmove counter 0 (which is accepted in FEN) is treated as the first move
+        let move_number = counter(builder.get_move_number())?.max(1);
+        let moves_since_capture_or_pawn_move =
+            counter(builder.get_moves_since_capture_or_pawn_move())?;
+
         let mut board = ChessBoard::new();
 
         for i in 0..SQUARES_NUMBER {
             let square = Square::new(i as u8).unwrap();
             if let Some(piece) = builder[square] {
-                board.put_piece(piece, square);
+                board.put_piece(piece, square, &mut ());
             }
         }
 
@@ -89,8 +320,8 @@ impl TryFrom<&BoardBuilder> for ChessBoard {
             .set_en_passant(builder.get_en_passant())
             .set_castling_rights(White, builder.get_castle_rights(White))
             .set_castling_rights(Black, builder.get_castle_rights(Black))
-            .set_move_number(builder.get_move_number())
-            .set_moves_since_capture_or_pawn_move(builder.get_moves_since_capture_or_pawn_move())
+            .set_move_number(move_number)
+            .set_moves_since_capture_or_pawn_move(moves_since_capture_or_pawn_move)
             .update_pins_and_checks()
             .update_terminal_status();
 
@@ -115,6 +346,48 @@ impl TryFrom<BoardBuilder> for ChessBoard {
     fn try_from(fen: BoardBuilder) -> Result<Self, Self::Error> { (&fen).try_into() }
 }
 
+impl From<&ChessBoard> for PositionCore {
+    fn from(board: &ChessBoard) -> Self {
+        Self {
+            pieces_mask: board.pieces_mask,
+            colors_mask: board.colors_mask,
+            hash: board.hash,
+            moves_since_capture_or_pawn_move: board.moves_since_capture_or_pawn_move,
+            move_number: board.move_number,
+            side_to_move: board.side_to_move,
+            castle_rights: board.castle_rights,
+            en_passant: board.en_passant,
+        }
+    }
+}
+
+impl From<ChessBoard> for PositionCore {
+    fn from(board: ChessBoard) -> Self { (&board).into() }
+}
+
+impl From<&PositionCore> for ChessBoard {
+    fn from(core: &PositionCore) -> Self {
+        let mut board = ChessBoard {
+            pieces_mask: core.pieces_mask,
+            colors_mask: core.colors_mask,
+            combined_mask: core.colors_mask[White.to_index()] | core.colors_mask[Black.to_index()],
+            side_to_move: core.side_to_move,
+            castle_rights: core.castle_rights,
+            en_passant: core.en_passant,
+            moves_since_capture_or_pawn_move: core.moves_since_capture_or_pawn_move,
+            move_number: core.move_number,
+            hash: core.hash,
+            ..ChessBoard::new()
+        };
+        board.update_pins_and_checks().update_terminal_status();
+        board
+    }
+}
+
+impl From<PositionCore> for ChessBoard {
+    fn from(core: PositionCore) -> Self { (&core).into() }
+}
+
 impl FromStr for ChessBoard {
     type Err = Error;
 
@@ -127,11 +400,32 @@ impl fmt::Display for ChessBoard {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{}", self.render_straight()) }
 }
 
+impl PartialEq for ChessBoard {
+    fn eq(&self, other: &Self) -> bool {
+        let en_passant = |board: &Self| {
+            board
+                .en_passant
+                .filter(|_| board.is_en_passant_capturable())
+        };
+        (self.hash == other.hash)
+            & (self.combined_mask == other.combined_mask)
+            & (self.pieces_mask == other.pieces_mask)
+            & (self.colors_mask == other.colors_mask)
+            & (self.side_to_move == other.side_to_move)
+            & (self.castle_rights == other.castle_rights)
+            & (en_passant(self) == en_passant(other))
+    }
+}
+
+impl Eq for ChessBoard {}
+
+impl Hash for ChessBoard {
+    fn hash<H: Hasher>(&self, state: &mut H) { self.hash.hash(state); }
+}
+
 impl Default for ChessBoard {
     #[inline]
-    fn default() -> ChessBoard {
-        ChessBoard::from_str("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap()
-    }
+    fn default() -> ChessBoard { *STARTING_POSITION }
 }
 
 impl ChessBoard {
@@ -171,7 +465,8 @@ impl ChessBoard {
     /// passant square
     ///
     /// ``LibChessError::InvalidBoardInconsistentCastlingRights`` if there is any incompatible
-    /// conditions of king an rooks positions and castling rights for any of color
+    /// conditions of king an rooks positions and castling rights for any of color. The error
+    /// contains the color, the castling side and the square where the rook is expected
     ///
     /// # Examples
     /// ```
@@ -237,7 +532,8 @@ impl ChessBoard {
     /// passant square
     ///
     /// ``LibChessError::InvalidBoardInconsistentCastlingRights`` if there is any incompatible
-    /// conditions of king an rooks positions and castling rights for any of color
+    /// conditions of king an rooks positions and castling rights for any of color. The error
+    /// contains the color, the castling side and the square where the rook is expected
     ///
     /// # Examples
     /// ```
@@ -248,10 +544,155 @@ impl ChessBoard {
     /// ```
     pub fn from_fen(fen: &str) -> Result<Self, Error> { Self::from_str(fen) }
 
+    /// Initializes the ChessBoard structure by a FEN-string like ``ChessBoard::from_fen()``, but
+    /// silently drops castling rights which are inconsistent with the king and rooks placement
+    /// (as most chess GUIs do) instead of returning an error
+    ///
+    /// # Errors
+    /// The same as ``ChessBoard::from_fen()`` except
+    /// ``LibChessError::InvalidBoardInconsistentCastlingRights``
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{CastlingRights, ChessBoard, Color::*};
+    /// let fen = "4k3/8/8/8/8/8/8/4K2R w KQ - 0 1";
+    /// assert!(ChessBoard::from_fen(fen).is_err());
+    ///
+    /// let board = ChessBoard::from_fen_lenient(fen).unwrap();
+    /// assert_eq!(board.get_castle_rights(White), CastlingRights::KingSide);
+    /// ```
+    pub fn from_fen_lenient(fen: &str) -> Result<Self, Error> {
+        Self::from_fen_with_options(
+            fen,
+            &FenParseOptions {
+                lenient_castling: true,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Initializes the ChessBoard structure by a FEN-string like ``ChessBoard::from_fen()``, but
+    /// normalizes the en passant square and the castling rights according to the options
+    ///
+    /// # Errors
+    /// The same as ``ChessBoard::from_fen()``. ``LibChessError::InvalidBoardInconsistentEnPassant``
+    /// is returned with ``EnPassantPolicy::Strict`` also if there is no legal en passant capture
+    /// and is never returned with ``EnPassantPolicy::Strip``
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{squares::*, ChessBoard, EnPassantPolicy, FenParseOptions};
+    /// let fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
+    /// let parse = |en_passant| {
+    ///     let options = FenParseOptions {
+    ///         en_passant,
+    ///         ..Default::default()
+    ///     };
+    ///     ChessBoard::from_fen_with_options(fen, &options)
+    /// };
+    /// assert_eq!(
+    ///     parse(EnPassantPolicy::Keep).unwrap().get_en_passant(),
+    ///     Some(E3)
+    /// );
+    /// assert_eq!(
+    ///     parse(EnPassantPolicy::Strip).unwrap().get_en_passant(),
+    ///     None
+    /// );
+    /// assert!(parse(EnPassantPolicy::Strict).is_err());
+    /// ```
+    pub fn from_fen_with_options(fen: &str, options: &FenParseOptions) -> Result<Self, Error> {
+        let mut builder = BoardBuilder::from_str(fen)?;
+        builder.set_lenient_castling(options.lenient_castling);
+
+        let board = ChessBoard::try_from(&builder);
+        match (options.en_passant, board) {
+            (EnPassantPolicy::Keep, board) => board,
+            (_, Ok(board)) if board.en_passant.is_none() | board.is_en_passant_legal() => Ok(board),
+            (EnPassantPolicy::Strip, Ok(_) | Err(Error::InvalidBoardInconsistentEnPassant)) => {
+                ChessBoard::try_from(&*builder.set_en_passant(None))
+            }
+            (EnPassantPolicy::Strict, Ok(_)) => Err(Error::InvalidBoardInconsistentEnPassant),
+            (_, Err(e)) => Err(e),
+        }
+    }
+
+    /// Initializes the ChessBoard structure by the 8x8 grid of piece letters where the empty
+    /// squares are dots (the format of ``ChessBoard::render_small``). The first line is the 8th
+    /// rank. Blank lines and spaces between the squares are ignored. There is no en passant
+    /// square and the counters are the initial ones
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidBoardGrid`` if the grid has not 8 lines of 8 squares or contains
+    /// unknown letters
+    ///
+    /// The position errors as for ``ChessBoard::from_fen()``
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{CastlingRights::*, ChessBoard, Color::*};
+    /// let board = ChessBoard::from_ascii_grid(
+    ///     "
+    ///     r . . . k . . .
+    ///     . . . . . . . .
+    ///     . . . . . . . .
+    ///     . . . . . . . .
+    ///     . . . . . . . .
+    ///     . . . . . . . .
+    ///     . . . . P . . .
+    ///     . . . . K . . R
+    ///     ",
+    ///     Black,
+    ///     KingSide,
+    ///     QueenSide,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(board.as_fen(), "r3k3/8/8/8/8/8/4P3/4K2R b Kq - 0 1");
+    /// ```
+    pub fn from_ascii_grid(
+        grid: &str,
+        side_to_move: Color,
+        white_castle_rights: CastlingRights,
+        black_castle_rights: CastlingRights,
+    ) -> Result<Self, Error> {
+        let error = || Error::InvalidBoardGrid {
+            s: grid.to_string(),
+        };
+        let lines: Vec<String> = grid
+            .lines()
+            .map(|line| line.chars().filter(|c| !c.is_whitespace()).collect())
+            .filter(|line: &String| !line.is_empty())
+            .collect();
+        if lines.len() != RANKS_NUMBER {
+            return Err(error());
+        }
+
+        let mut pieces = Vec::new();
+        for (line, rank) in lines.iter().zip(RANKS.iter().rev()) {
+            if line.chars().count() != FILES_NUMBER {
+                return Err(error());
+            }
+            for (c, file) in line.chars().zip(FILES.iter()) {
+                if c == '.' {
+                    continue;
+                }
+                let piece = Piece::from_char(c).map_err(|_| error())?;
+                pieces.push((Square::from_rank_file(*rank, *file), piece));
+            }
+        }
+
+        ChessBoard::try_from(BoardBuilder::setup(
+            pieces.iter(),
+            side_to_move,
+            white_castle_rights,
+            black_castle_rights,
+            None,
+            0,
+            1,
+        ))
+    }
+
     /// Validates the position on the board
     fn validate(&self) -> Option<Error> {
-        use squares::*;
-
         // make sure that is no color overlapping
         if !(self.get_color_mask(White) & self.get_color_mask(Black)).is_blank() {
             return Some(Error::InvalidPositionColorsOverlap);
@@ -311,64 +752,61 @@ impl ChessBoard {
         }
 
         // validate castling rights
-        let white_rook_mask = self.get_piece_type_mask(Rook) & self.get_color_mask(White);
-        if self.get_king_square(White) == E1 {
-            let validation_mask = match self.get_castle_rights(White) {
-                Neither => BLANK,
-                QueenSide => BitBoard::from_square(A1),
-                KingSide => BitBoard::from_square(H1),
-                BothSides => BitBoard::from_square(A1) | BitBoard::from_square(H1),
-            };
-            if (white_rook_mask & validation_mask).count_ones() != validation_mask.count_ones() {
-                return Some(Error::InvalidBoardInconsistentCastlingRights);
-            }
-        } else if self.get_castle_rights(White) != Neither {
-            return Some(Error::InvalidBoardInconsistentCastlingRights);
-        }
-
-        let black_rook_mask = self.get_piece_type_mask(Rook) & self.get_color_mask(Black);
-        if self.get_king_square(Black) == E8 {
-            let validation_mask = match self.get_castle_rights(Black) {
-                Neither => BLANK,
-                QueenSide => BitBoard::from_square(A8),
-                KingSide => BitBoard::from_square(H8),
-                BothSides => BitBoard::from_square(A8) | BitBoard::from_square(H8),
-            };
-            if (black_rook_mask & validation_mask).count_ones() != validation_mask.count_ones() {
-                return Some(Error::InvalidBoardInconsistentCastlingRights);
+        for color in Color::iter() {
+            if let Some((side, rook_square)) = self
+                .get_castle_rights(color)
+                .find_inconsistency(color, |square| self.get_piece_on(square))
+            {
+                return Some(Error::InvalidBoardInconsistentCastlingRights {
+                    color,
+                    side,
+                    rook_square,
+                });
             }
-        } else if self.get_castle_rights(Black) != Neither {
-            return Some(Error::InvalidBoardInconsistentCastlingRights);
         }
 
         None
     }
 
     /// Unified (from white's and black's perspective) method for rendering ChessBoard to terminal
-    fn render<'a>(
+    pub(crate) fn render(
         &self,
-        ranks: impl Iterator<Item = &'a Rank>,
-        files: impl Iterator<Item = &'a File> + Clone,
-        footer: &str,
+        orientation: BoardOrientation,
+        highlighted: BitBoard,
+        locale: &NotationLocale,
+        annotations: Option<&Annotations>,
     ) -> String {
         let mut field_string = String::new();
-        for rank in ranks {
-            field_string = format!("{field_string}{}  ║", (rank).to_index() + 1);
-            for file in files.clone() {
-                let square = Square::from_rank_file(*rank, *file);
-                field_string = if self.is_empty_square(square) {
+        for rank in orientation.get_ranks() {
+            field_string = format!("{field_string}{}  ║", rank.to_index() + 1);
+            for file in orientation.get_files() {
+                let square = Square::from_rank_file(rank, file);
+                let highlight = match (highlighted & BitBoard::from_square(square)).is_blank() {
+                    false => Some(AnnotationColor::Yellow),
+                    true => annotations.and_then(|a| a.get_highlight(square)),
+                };
+                field_string = if let Some(color) = highlight {
+                    let square_str = match self.get_piece_on(square) {
+                        None => "   ".to_string(),
+                        Some(piece) => format!("{:^3}", locale.get_piece_symbol(piece)),
+                    }
+                    .black();
+                    let square_str = match color {
+                        AnnotationColor::Green => square_str.on_green(),
+                        AnnotationColor::Red => square_str.on_red(),
+                        AnnotationColor::Yellow => square_str.on_yellow(),
+                        AnnotationColor::Blue => square_str.on_blue(),
+                    };
+                    format!("{field_string}{square_str}")
+                } else if self.is_empty_square(square) {
                     if square.is_light() {
                         format!("{field_string}{}", "   ".on_white())
                     } else {
                         format!("{field_string}{}", "   ")
                     }
                 } else {
-                    let mut piece_type_str =
-                        format!(" {} ", self.get_piece_type_on(square).unwrap());
-                    piece_type_str = match self.get_piece_color_on(square).unwrap() {
-                        White => piece_type_str.to_uppercase(),
-                        Black => piece_type_str.to_lowercase(),
-                    };
+                    let piece = self.get_piece_on(square).unwrap();
+                    let piece_type_str = format!("{:^3}", locale.get_piece_symbol(piece));
 
                     if square.is_light() {
                         format!("{field_string}{}", piece_type_str.black().on_white())
@@ -380,7 +818,13 @@ impl ChessBoard {
             field_string = format!("{field_string}║\n");
         }
 
-        let board_string = format!(
+        let footer: Vec<String> = orientation
+            .get_files()
+            .iter()
+            .map(|file| file.to_string())
+            .collect();
+        let footer = format!("     {}", footer.join("  "));
+        let mut board_string = format!(
             "   {}  {}{}\n{}\n{}{}\n{}\n",
             self.get_side_to_move(),
             format!("{}", self.get_castle_rights(White)).to_uppercase(),
@@ -390,6 +834,18 @@ impl ChessBoard {
             "   ╚════════════════════════╝",
             footer,
         );
+        // arrows and labels can't be drawn in the terminal, so they are listed under the board
+        if let Some(annotations) = annotations {
+            for arrow in annotations.get_arrows() {
+                board_string += &format!(
+                    "   {} -> {} ({})\n",
+                    arrow.source, arrow.destination, arrow.color
+                );
+            }
+            for label in annotations.get_labels() {
+                board_string += &format!("   {}: {}\n", label.square, label.text);
+            }
+        }
         board_string
     }
 
@@ -402,8 +858,12 @@ impl ChessBoard {
     /// println!("{}", ChessBoard::default().render_straight()); // will print the same
     /// ```
     pub fn render_straight(&self) -> String {
-        let footer = "     a  b  c  d  e  f  g  h";
-        self.render(RANKS.iter().rev(), FILES.iter(), footer)
+        self.render(
+            BoardOrientation::WhiteBottom,
+            BLANK,
+            &NotationLocale::default(),
+            None,
+        )
     }
 
     /// Returns ASCII-representation of the flipped board as a String
@@ -415,138 +875,615 @@ impl ChessBoard {
     /// println!("{}", ChessBoard::default().render_flipped()); // will print flipped board
     /// ```
     pub fn render_flipped(&self) -> String {
-        let footer = "     h  g  f  e  d  c  b  a";
-        self.render(RANKS.iter(), FILES.iter().rev(), footer)
+        self.render(
+            BoardOrientation::BlackBottom,
+            BLANK,
+            &NotationLocale::default(),
+            None,
+        )
     }
 
-    /// Returns a FEN string of current position
-    ///
-    /// [FEN-string](https://en.wikipedia.org/wiki/Forsyth%E2%80%93Edwards_Notation)
-    /// (Forsyth–Edwards Notation) - standard notation for describing a particular board position
-    /// of a chess game. This method transforms ChessBoard to a FEN-string
+    /// Returns ASCII-representation of the board with the piece symbols of specified locale
     ///
     /// # Examples
     /// ```
-    /// use libchess::ChessBoard;
-    /// let initial_position_fen =
-    ///     String::from("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
-    /// assert_eq!(ChessBoard::default().as_fen(), initial_position_fen);
+    /// use libchess::{ChessBoard, NotationLocale};
+    /// println!(
+    ///     "{}",
+    ///     ChessBoard::default().render_localized(&NotationLocale::figurine(), false)
+    /// );
+    /// println!(
+    ///     "{}",
+    ///     ChessBoard::default().render_localized(&NotationLocale::russian(), true)
+    /// );
     /// ```
-    #[inline]
-    pub fn as_fen(&self) -> String { format!("{}", BoardBuilder::from(*self)) }
+    pub fn render_localized(&self, locale: &NotationLocale, flipped: bool) -> String {
+        let orientation = match flipped {
+            true => BoardOrientation::BlackBottom,
+            false => BoardOrientation::WhiteBottom,
+        };
+        self.render(orientation, BLANK, locale, None)
+    }
 
-    /// Returns a Bitboard mask of same-color pieces
+    /// Returns ASCII-representation of the board (from white's perspective) with specified
+    /// squares highlighted
     ///
-    /// This method is used to locate all pieces of particular color. Typically is used in
-    /// combination with ``.get_combined_mask()`` which returns mask of all pieces on the board
+    /// # Examples
+    /// ```
+    /// use libchess::{squares::*, BitBoard, ChessBoard};
+    /// let mask = BitBoard::from_square(E2) | BitBoard::from_square(E4);
+    /// println!("{}", ChessBoard::default().render_highlighted(mask));
+    /// ```
+    pub fn render_highlighted(&self, squares: BitBoard) -> String {
+        self.render(
+            BoardOrientation::WhiteBottom,
+            squares,
+            &NotationLocale::default(),
+            None,
+        )
+    }
+
+    /// Returns ASCII-representation of the board with the square highlights of the annotations.
+    /// Arrows and text labels are listed under the board
     ///
     /// # Examples
     /// ```
-    /// use libchess::{ChessBoard, Color::*};
-    /// println! {"{}", ChessBoard::default().get_color_mask(White)};
-    /// println! {"{}", ChessBoard::default().get_color_mask(Black)};
+    /// use libchess::{squares::*, AnnotationColor::*, Annotations, ChessBoard};
+    /// let mut annotations = Annotations::new();
+    /// annotations.add_arrow(G1, F3, Green).add_label(F7, "weak");
+    /// let rendered = ChessBoard::default().render_annotated(&annotations, true);
+    /// assert!(rendered.contains("g1 -> f3 (green)"));
+    /// assert!(rendered.contains("f7: weak"));
     /// ```
-    #[inline]
-    pub fn get_color_mask(&self, color: Color) -> BitBoard { self.colors_mask[color.to_index()] }
+    pub fn render_annotated(&self, annotations: &Annotations, flipped: bool) -> String {
+        let orientation = match flipped {
+            true => BoardOrientation::BlackBottom,
+            false => BoardOrientation::WhiteBottom,
+        };
+        let locale = NotationLocale::default();
+        self.render(orientation, BLANK, &locale, Some(annotations))
+    }
 
-    /// Returns a Bitboard mask for all pieces on the board
+    /// Returns the one-line representation of the board: the pieces placement, the side to move
+    /// and the castling rights as in FEN. Is convenient for logs
     ///
     /// # Examples
     /// ```
     /// use libchess::ChessBoard;
-    /// println! {"{}", ChessBoard::default().get_combined_mask()};
+    /// assert_eq!(
+    ///     ChessBoard::default().render_compact(),
+    ///     "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq"
+    /// );
     /// ```
-    #[inline]
-    pub fn get_combined_mask(&self) -> BitBoard { self.combined_mask }
+    pub fn render_compact(&self) -> String {
+        self.as_fen()
+            .split_whitespace()
+            .take(3)
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
 
-    /// Returns a square for king-piece of specified color
+    /// Returns the small 8x8 grid of piece letters without frames and colors (empty squares
+    /// are dots). Is convenient for debugging output
     ///
     /// # Examples
     /// ```
-    /// use libchess::{ChessBoard, Color::*};
-    /// println! {"{}", ChessBoard::default().get_king_square(White)};
-    /// println! {"{}", ChessBoard::default().get_king_square(Black)};
+    /// use libchess::{BoardOrientation, ChessBoard};
+    /// let board = ChessBoard::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+    /// assert_eq!(
+    ///     board.render_small(BoardOrientation::WhiteBottom),
+    ///     "....k...\n........\n........\n........\n........\n........\n....P...\n....K...\n"
+    /// );
     /// ```
-    #[inline]
-    pub fn get_king_square(&self, color: Color) -> Square {
-        (self.get_piece_type_mask(King) & self.get_color_mask(color)).to_square()
+    pub fn render_small(&self, orientation: BoardOrientation) -> String {
+        let locale = NotationLocale::default();
+        let mut board_string = String::new();
+        for rank in orientation.get_ranks() {
+            for file in orientation.get_files() {
+                match self.get_piece_on(Square::from_rank_file(rank, file)) {
+                    Some(piece) => board_string.push_str(locale.get_piece_symbol(piece)),
+                    None => board_string.push('.'),
+                }
+            }
+            board_string.push('\n');
+        }
+        board_string
     }
 
-    /// Returns a Bitboard mask for all pieces of the same specified type
+    /// Returns ASCII-representation of the board where only the pieces allowed by the
+    /// visibility are shown. Is used for blindfold training and pawn structure studies
     ///
     /// # Examples
     /// ```
-    /// use libchess::{ChessBoard, PieceType::*};
-    /// println! {"{}", ChessBoard::default().get_piece_type_mask(Rook)};
+    /// use libchess::{BoardOrientation, ChessBoard, PieceVisibility};
+    /// let board = ChessBoard::default();
+    /// println!(
+    ///     "{}",
+    ///     board.render_partial(
+    ///         &PieceVisibility::pawn_structure(),
+    ///         BoardOrientation::WhiteBottom
+    ///     )
+    /// );
+    /// assert!(!board
+    ///     .render_partial(&PieceVisibility::blindfold(), BoardOrientation::BlackBottom)
+    ///     .contains('N'));
     /// ```
-    #[inline]
-    pub fn get_piece_type_mask(&self, piece_type: PieceType) -> BitBoard {
-        self.pieces_mask[piece_type.to_index()]
+    pub fn render_partial(
+        &self,
+        visibility: &PieceVisibility,
+        orientation: BoardOrientation,
+    ) -> String {
+        self.get_visible_board(visibility).render(
+            orientation,
+            BLANK,
+            &NotationLocale::default(),
+            None,
+        )
     }
 
-    /// Returns a Bitboard mask for all pieces which pins the king with
-    /// color defined by ``board.get_side_to_move()``
+    /// Returns the copy of the board without the pieces hidden by the visibility. The result
+    /// is not a valid position (e.g. the kings may be removed), so it is used for rendering only
+    pub(crate) fn get_visible_board(&self, visibility: &PieceVisibility) -> Self {
+        let mut board = *self;
+        for piece_type in PieceType::iter() {
+            for color in Color::iter() {
+                if visibility.is_visible(Piece(piece_type, color)) {
+                    continue;
+                }
+                let mask = self.get_piece_type_mask(piece_type) & self.get_color_mask(color);
+                board.pieces_mask[piece_type.to_index()] ^= mask;
+                board.colors_mask[color.to_index()] ^= mask;
+                board.combined_mask ^= mask;
+            }
+        }
+        board
+    }
+
+    /// Returns the mask of squares which content differs on two boards
     ///
     /// # Examples
     /// ```
-    /// use libchess::{ChessBoard, PieceType::*};
-    /// let board = ChessBoard::from_fen("7k/6r1/8/8/3Q4/4K3/8/6R1 b - - 0 1").unwrap();
-    /// println! {"{}", board};
-    /// println! {"{}", board.get_pin_mask()};
+    /// use libchess::{squares::*, BitBoard, ChessBoard};
+    /// let board = ChessBoard::default();
+    /// let next_board = board.apply_san_line("e4").unwrap();
+    /// assert_eq!(
+    ///     board.get_diff_mask(&next_board),
+    ///     BitBoard::from_square(E2) | BitBoard::from_square(E4)
+    /// );
     /// ```
-    #[inline]
-    pub fn get_pin_mask(&self) -> BitBoard { self.pinned }
+    pub fn get_diff_mask(&self, other: &ChessBoard) -> BitBoard {
+        let pieces_diff = (0..PIECE_TYPES_NUMBER).fold(BLANK, |diff, i| {
+            diff | (self.pieces_mask[i] ^ other.pieces_mask[i])
+        });
+        let colors_diff = (0..COLORS_NUMBER).fold(BLANK, |diff, i| {
+            diff | (self.colors_mask[i] ^ other.colors_mask[i])
+        });
+        pieces_diff | colors_diff
+    }
 
-    /// Returns the castling rights (not the availability of castling) for specified color
-    ///
-    /// The presence of castling rights does not mean that king can castle at
-    /// this move (checks, extra pieces on backrank, etc.).
+    /// Returns ASCII-representation of two boards side by side (from white's perspective) with
+    /// the changed squares highlighted on both of them. Useful to show the position before and
+    /// after the move or the puzzle solution
     ///
     /// # Examples
     /// ```
-    /// use libchess::{CastlingRights::*, ChessBoard, Color::*};
-    /// let board =
-    ///     ChessBoard::from_fen("r1bqk1nr/pppp1ppp/2n5/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 0 1")
-    ///         .unwrap();
-    /// assert_eq!(board.get_castle_rights(White), BothSides); /* Rooks or King not moved, so
-    /// castling is legal for both sides but available only to the King side */
-    /// assert_eq!(board.castling_is_available_on_board(None), KingSide);
+    /// use libchess::ChessBoard;
+    /// let board = ChessBoard::default();
+    /// println!(
+    ///     "{}",
+    ///     board.render_diff(&board.apply_san_line("e4 e5 Nf3").unwrap())
+    /// );
     /// ```
-    #[inline]
-    pub fn get_castle_rights(&self, color: Color) -> CastlingRights {
-        self.castle_rights[color.to_index()]
+    pub fn render_diff(&self, other: &ChessBoard) -> String {
+        let diff = self.get_diff_mask(other);
+        let left = self.render_highlighted(diff);
+        let right = other.render_highlighted(diff);
+
+        let mut result = String::new();
+        for (left_line, right_line) in left.lines().zip(right.lines()) {
+            let padding = " ".repeat(BOARD_RENDER_WIDTH.saturating_sub(visible_width(left_line)));
+            result = format!("{result}{left_line}{padding}    {right_line}\n");
+        }
+        result
     }
 
-    /// Shows which side has move now
+    /// Returns true if the board is the standard starting position. The move counters are
+    /// ignored (as by comparing the boards), so a position reached after the knights return to
+    /// their squares is the starting position too
     ///
     /// # Examples
     /// ```
-    /// use libchess::{ChessBoard, Color::*};
-    /// assert_eq!(ChessBoard::default().get_side_to_move(), White);
+    /// use libchess::ChessBoard;
+    /// assert!(ChessBoard::default().is_startpos());
+    /// let board = ChessBoard::default()
+    ///     .apply_san_line("Nf3 Nf6 Ng1 Ng8")
+    ///     .unwrap();
+    /// assert!(board.is_startpos());
+    /// assert!(!board.apply_san_line("e4").unwrap().is_startpos());
     /// ```
     #[inline]
-    pub fn get_side_to_move(&self) -> Color { self.side_to_move }
+    pub fn is_startpos(&self) -> bool { *self == *STARTING_POSITION }
 
-    /// Returns en-passant square if it exists after the last move
+    /// Returns the mirrored board: the board is flipped vertically, the colors of pieces, the
+    /// castling rights and the side to move are swapped. The counters are kept
     ///
     /// # Examples
     /// ```
-    /// use libchess::{mv, squares::*, BoardMove, ChessBoard, PieceMove, PieceType::*};
-    /// let board = ChessBoard::default().make_move(&mv!(Pawn, E2, E4)).unwrap();
-    /// assert_eq!(board.get_en_passant(), Some(E3));
+    /// use libchess::ChessBoard;
+    /// let board = ChessBoard::default().apply_san_line("e4").unwrap();
+    /// assert_eq!(
+    ///     board.get_mirrored().as_fen(),
+    ///     "rnbqkbnr/pppp1ppp/8/4p3/8/8/PPPPPPPP/RNBQKBNR w KQkq e6 0 1"
+    /// );
     /// ```
-    #[inline]
-    pub fn get_en_passant(&self) -> Option<Square> { self.en_passant }
+    pub fn get_mirrored(&self) -> Self {
+        let mirror = |square: Square| {
+            Square::from_rank_file(
+                Rank::from_index(RANKS_NUMBER - 1 - square.get_rank().to_index()).unwrap(),
+                square.get_file(),
+            )
+        };
+        let pieces: Vec<(Square, Piece)> = self
+            .combined_mask
+            .map(|square| {
+                let Piece(piece_type, color) = self.get_piece_on(square).unwrap();
+                (mirror(square), Piece(piece_type, !color))
+            })
+            .collect();
+        let builder = BoardBuilder::setup(
+            pieces.iter(),
+            !self.side_to_move,
+            self.get_castle_rights(Black),
+            self.get_castle_rights(White),
+            self.en_passant.map(mirror),
+            self.get_moves_since_capture_or_pawn_move(),
+            self.get_move_number(),
+        );
+        ChessBoard::try_from(builder).expect("the mirrored position is valid")
+    }
 
-    /// Returns a move number (increments every time after black makes move)
+    /// Returns the hash of the board transformed by the symmetry without building it, so the
+    /// symmetric duplicates are detected by comparing the hashes. Returns None if the symmetry is
+    /// not valid for the position (the files are flipped while there are castling rights)
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{BoardSymmetry::*, ChessBoard};
+    /// let board = ChessBoard::default().apply_san_line("e4").unwrap();
+    /// assert_eq!(
+    ///     board.get_symmetric_hash(ColorMirror),
+    ///     Some(board.get_mirrored().get_hash())
+    /// );
+    /// assert_eq!(board.get_symmetric_hash(HorizontalFlip), None);
+    ///
+    /// let board = ChessBoard::from_fen("4k3/8/8/8/8/8/1P6/4K3 w - - 0 1").unwrap();
+    /// let flipped = ChessBoard::from_fen("3k4/8/8/8/8/8/6P1/3K4 w - - 0 1").unwrap();
+    /// assert_eq!(
+    ///     board.get_symmetric_hash(HorizontalFlip),
+    ///     Some(flipped.get_hash())
+    /// );
+    /// ```
+    #[inline]
+    pub fn get_symmetric_hash(&self, symmetry: BoardSymmetry) -> Option<PositionHashValueType> {
+        ZOBRIST.calculate_symmetric_hash(self, symmetry)
+    }
+
+    /// Returns the hash of the position used by the Polyglot opening books. It is calculated with
+    /// the standard Polyglot table and differs from ``board.get_hash()``
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::ChessBoard;
+    /// use std::str::FromStr;
+    ///
+    /// assert_eq!(
+    ///     ChessBoard::default().get_polyglot_hash(),
+    ///     0x463b96181691fc9c
+    /// );
+    ///
+    /// let board = ChessBoard::from_str("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1")
+    ///     .unwrap();
+    /// assert_eq!(board.get_polyglot_hash(), 0x823c9b50fd114196);
+    /// ```
+    #[inline]
+    pub fn get_polyglot_hash(&self) -> u64 { calculate_polyglot_hash(self) }
+
+    /// Returns the board in the canonical form (see ``CanonicalForm``) which is used to find the
+    /// same positions in datasets
+    pub fn canonicalize(&self, form: &CanonicalForm) -> Self {
+        let board = match form.mirror_to_white & (self.side_to_move == Black) {
+            true => self.get_mirrored(),
+            false => *self,
+        };
+        let mut builder = BoardBuilder::from(board);
+        if form.strip_counters {
+            builder
+                .set_move_number(1)
+                .set_moves_since_capture_or_pawn_move(0);
+        }
+        if !board.is_en_passant_legal() {
+            builder.set_en_passant(None);
+        }
+        ChessBoard::try_from(builder).expect("the canonical position is valid")
+    }
+
+    /// Returns the FEN-string of the board in the canonical form. Is used as the key of the
+    /// position in datasets
+    #[inline]
+    pub fn get_canonical_fen(&self, form: &CanonicalForm) -> String {
+        self.canonicalize(form).as_fen()
+    }
+
+    /// Returns a FEN string of current position
+    ///
+    /// [FEN-string](https://en.wikipedia.org/wiki/Forsyth%E2%80%93Edwards_Notation)
+    /// (Forsyth–Edwards Notation) - standard notation for describing a particular board position
+    /// of a chess game. This method transforms ChessBoard to a FEN-string
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::ChessBoard;
+    /// let initial_position_fen =
+    ///     String::from("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    /// assert_eq!(ChessBoard::default().as_fen(), initial_position_fen);
+    /// ```
+    #[inline]
+    pub fn as_fen(&self) -> String {
+        let mut fen = String::new();
+        self.write_fen(&mut fen).unwrap();
+        fen
+    }
+
+    /// Writes the FEN-string of the position into the writer without intermediate allocations.
+    /// Is used for the bulk export of positions
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::ChessBoard;
+    /// use std::fmt::Write;
+    /// let mut fens = String::new();
+    /// for board in [
+    ///     ChessBoard::default(),
+    ///     ChessBoard::default().apply_san_line("e4").unwrap(),
+    /// ] {
+    ///     board.write_fen(&mut fens).unwrap();
+    ///     fens.write_char('\n').unwrap();
+    /// }
+    /// assert_eq!(
+    ///     fens.lines().nth(1),
+    ///     Some(
+    ///         ChessBoard::default()
+    ///             .apply_san_line("e4")
+    ///             .unwrap()
+    ///             .as_fen()
+    ///             .as_str()
+    ///     )
+    /// );
+    /// ```
+    #[inline]
+    pub fn write_fen(&self, w: &mut impl fmt::Write) -> fmt::Result {
+        BoardBuilder::from(*self).write_fen(w)
+    }
+
+    /// Returns a Bitboard mask of same-color pieces
+    ///
+    /// This method is used to locate all pieces of particular color. Typically is used in
+    /// combination with ``.get_combined_mask()`` which returns mask of all pieces on the board
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{ChessBoard, Color::*};
+    /// println! {"{}", ChessBoard::default().get_color_mask(White)};
+    /// println! {"{}", ChessBoard::default().get_color_mask(Black)};
+    /// ```
+    #[inline]
+    pub fn get_color_mask(&self, color: Color) -> BitBoard { self.colors_mask[color.to_index()] }
+
+    /// Returns a Bitboard mask for all pieces on the board
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::ChessBoard;
+    /// println! {"{}", ChessBoard::default().get_combined_mask()};
+    /// ```
+    #[inline]
+    pub fn get_combined_mask(&self) -> BitBoard { self.combined_mask }
+
+    /// Returns a square for king-piece of specified color
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{ChessBoard, Color::*};
+    /// println! {"{}", ChessBoard::default().get_king_square(White)};
+    /// println! {"{}", ChessBoard::default().get_king_square(Black)};
+    /// ```
+    #[inline]
+    pub fn get_king_square(&self, color: Color) -> Square {
+        (self.get_piece_type_mask(King) & self.get_color_mask(color)).to_square()
+    }
+
+    /// Returns a Bitboard mask for all pieces of the same specified type
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{ChessBoard, PieceType::*};
+    /// println! {"{}", ChessBoard::default().get_piece_type_mask(Rook)};
+    /// ```
+    #[inline]
+    pub fn get_piece_type_mask(&self, piece_type: PieceType) -> BitBoard {
+        self.pieces_mask[piece_type.to_index()]
+    }
+
+    /// Returns a Bitboard mask for all pieces which pins the king with
+    /// color defined by ``board.get_side_to_move()``
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{ChessBoard, PieceType::*};
+    /// let board = ChessBoard::from_fen("7k/6r1/8/8/3Q4/4K3/8/6R1 b - - 0 1").unwrap();
+    /// println! {"{}", board};
+    /// println! {"{}", board.get_pin_mask()};
+    /// ```
+    #[inline]
+    pub fn get_pin_mask(&self) -> BitBoard { self.pinned }
+
+    /// Returns the castling rights (not the availability of castling) for specified color
+    ///
+    /// The presence of castling rights does not mean that king can castle at
+    /// this move (checks, extra pieces on backrank, etc.).
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{CastlingRights::*, ChessBoard, Color::*};
+    /// let board =
+    ///     ChessBoard::from_fen("r1bqk1nr/pppp1ppp/2n5/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 0 1")
+    ///         .unwrap();
+    /// assert_eq!(board.get_castle_rights(White), BothSides); /* Rooks or King not moved, so
+    /// castling is legal for both sides but available only to the King side */
+    /// assert_eq!(board.castling_is_available_on_board(None), KingSide);
+    /// ```
+    #[inline]
+    pub fn get_castle_rights(&self, color: Color) -> CastlingRights {
+        self.castle_rights[color.to_index()]
+    }
+
+    /// Shows which side has move now
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{ChessBoard, Color::*};
+    /// assert_eq!(ChessBoard::default().get_side_to_move(), White);
+    /// ```
+    #[inline]
+    pub fn get_side_to_move(&self) -> Color { self.side_to_move }
+
+    /// Returns en-passant square if it exists after the last move
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{mv, squares::*, BoardMove, ChessBoard, PieceMove, PieceType::*};
+    /// let board = ChessBoard::default().make_move(&mv!(Pawn, E2, E4)).unwrap();
+    /// assert_eq!(board.get_en_passant(), Some(E3));
+    /// ```
+    #[inline]
+    pub fn get_en_passant(&self) -> Option<Square> { self.en_passant }
+
+    /// Returns true if there is en-passant square and some pawn of the side to move attacks it.
+    /// Only in this case the en-passant square is included into the position hash, so positions
+    /// which differ by an uncapturable en-passant square are treated as the same (following the
+    /// Polyglot convention)
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::ChessBoard;
+    /// use std::str::FromStr;
+    /// let board = ChessBoard::from_str("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1").unwrap();
+    /// assert!(!board.is_en_passant_capturable());
+    ///
+    /// let board = ChessBoard::from_str("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").unwrap();
+    /// assert!(board.is_en_passant_capturable());
+    /// ```
+    pub fn is_en_passant_capturable(&self) -> bool {
+        self.en_passant.is_some_and(|square| {
+            !(PAWN.get_captures(square, !self.side_to_move)
+                & self.get_piece_type_mask(Pawn)
+                & self.get_color_mask(self.side_to_move))
+            .is_blank()
+        })
+    }
+
+    /// Returns true if the side to move has at least one legal en-passant capture. Unlike
+    /// ``board.is_en_passant_capturable()`` this also checks that the capture doesn't leave the
+    /// king in check
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::ChessBoard;
+    /// let board = ChessBoard::from_fen("8/8/8/1Pp5/8/8/8/K6k w - c6 0 1").unwrap();
+    /// assert!(board.is_en_passant_legal());
+    ///
+    /// // after bxc6 both pawns leave the 5th rank and the king is attacked by the rook
+    /// let board = ChessBoard::from_fen("8/8/8/KPp4r/8/8/8/7k w - c6 0 1").unwrap();
+    /// assert!(board.is_en_passant_capturable() & !board.is_en_passant_legal());
+    /// ```
+    pub fn is_en_passant_legal(&self) -> bool {
+        self.en_passant.is_some_and(|square| {
+            (PAWN.get_captures(square, !self.side_to_move)
+                & self.get_piece_type_mask(Pawn)
+                & self.get_color_mask(self.side_to_move))
+            .into_iter()
+            .any(|source| self.check_en_passant(source).is_ok())
+        })
+    }
+
+    /// Checks if the pawn on the ``source`` square can capture en-passant and explains why it
+    /// can't. Besides the usual pins and checks this detects the subtle case of the horizontal
+    /// discovery, when the king, the capturing pawn and the captured pawn stand on the same rank
+    /// with the opponent's rook or queen: both pawns leave the rank at once, so neither of them
+    /// is pinned on its own, but the capture exposes the king
+    ///
+    /// # Errors
+    /// ``EnPassantIllegality`` describing the reason why the capture is not legal
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{squares::*, ChessBoard, EnPassantIllegality};
+    /// let board = ChessBoard::from_fen("8/8/8/KPp4r/8/8/8/7k w - c6 0 1").unwrap();
+    /// assert_eq!(
+    ///     board.check_en_passant(B5),
+    ///     Err(EnPassantIllegality::HorizontalDiscovery { attacker: H5 })
+    /// );
+    /// assert_eq!(
+    ///     board.check_en_passant(A5),
+    ///     Err(EnPassantIllegality::NoCapturingPawn)
+    /// );
+    /// ```
+    pub fn check_en_passant(&self, source: Square) -> Result<(), EnPassantIllegality> {
+        use EnPassantIllegality::*;
+        let ep_square = self.en_passant.ok_or(NoEnPassantSquare)?;
+        if (PAWN.get_captures(ep_square, !self.side_to_move)
+            & self.get_piece_type_mask(Pawn)
+            & self.get_color_mask(self.side_to_move)
+            & BitBoard::from_square(source))
+        .is_blank()
+        {
+            return Err(NoCapturingPawn);
+        }
+
+        let checks = self.get_check_mask_after_piece_move(
+            &PieceMove::new(Pawn, source, ep_square, None).unwrap(),
+        );
+        if checks.is_blank() {
+            return Ok(());
+        }
+
+        let remaining_checks = checks & self.checks;
+        if !remaining_checks.is_blank() {
+            return Err(UnresolvedCheck {
+                attacker: remaining_checks.to_square(),
+            });
+        }
+        let attacker = checks.to_square();
+        if !(BitBoard::from_square(source) & self.pinned).is_blank() {
+            return Err(PinnedPawn { attacker });
+        }
+        let king_rank = self.get_king_square(self.side_to_move).get_rank();
+        match (source.get_rank() == king_rank) & (attacker.get_rank() == king_rank) {
+            true => Err(HorizontalDiscovery { attacker }),
+            false => Err(CapturedPawnDiscovery { attacker }),
+        }
+    }
+
+    /// Returns a move number (increments every time after black makes move). The move counters
+    /// stop at ``u32::MAX``
     #[inline]
-    pub fn get_move_number(&self) -> usize { self.move_number }
+    pub fn get_move_number(&self) -> usize { self.move_number as usize }
 
     /// Returns a number of moves since last capture or pawn move (is used  to determine the game
     /// termination by the 50-move rule)
     #[inline]
     pub fn get_moves_since_capture_or_pawn_move(&self) -> usize {
-        self.moves_since_capture_or_pawn_move
+        self.moves_since_capture_or_pawn_move as usize
     }
 
     /// Returns a Bitboard mask for all pieces attacking the king with color defined by
@@ -554,6 +1491,42 @@ impl ChessBoard {
     #[inline]
     pub fn get_check_mask(&self) -> BitBoard { self.checks }
 
+    /// Returns the masks of pinned pieces and checking pieces for the king of specified color.
+    /// Unlike ``get_pin_mask`` and ``get_check_mask`` works for both sides regardless of the
+    /// side to move
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{squares::*, BitBoard, ChessBoard, Color::*};
+    /// let board = ChessBoard::from_fen("4k3/4r3/8/8/8/8/4B3/4K3 b - - 0 1").unwrap();
+    /// let (pinned, checks) = board.pins_and_checks_for(White);
+    /// assert_eq!(pinned, BitBoard::from_square(E2));
+    /// assert!(checks.is_blank());
+    /// ```
+    #[inline]
+    pub fn pins_and_checks_for(&self, color: Color) -> (BitBoard, BitBoard) {
+        self.get_pins_and_checks(self.get_king_square(color), color)
+    }
+
+    /// Returns the masks of pieces of specified color pinned to the square and pieces of
+    /// opposite color attacking the square (as if the king of specified color was on it)
+    #[inline]
+    pub fn pins_and_checks_at(&self, color: Color, square: Square) -> (BitBoard, BitBoard) {
+        self.get_pins_and_checks(square, color)
+    }
+
+    /// Returns the mask of pieces giving check to the king of specified color
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{squares::*, BitBoard, ChessBoard, Color::*};
+    /// let board = ChessBoard::from_fen("4k3/8/8/8/8/8/8/4K1r1 w - - 0 1").unwrap();
+    /// assert_eq!(board.checkers_of(White), BitBoard::from_square(G1));
+    /// assert!(board.checkers_of(Black).is_blank());
+    /// ```
+    #[inline]
+    pub fn checkers_of(&self, color: Color) -> BitBoard { self.pins_and_checks_for(color).1 }
+
     /// Checks if specified square is not taken by any piece
     #[inline]
     pub fn is_empty_square(&self, square: Square) -> bool {
@@ -573,46 +1546,28 @@ impl ChessBoard {
     /// castle to both sides, for this position allows to castle only to king side */
     /// ```
     pub fn castling_is_available_on_board(&self, check_mask: Option<BitBoard>) -> CastlingRights {
-        use squares::*;
-
         let mut result = Neither;
         let checks = check_mask.unwrap_or(self.get_check_mask());
         if !checks.is_blank() {
             return result;
         }
 
-        // check castling king side
-        if self.get_castle_rights(self.side_to_move).has_kingside() {
-            let (square_1, square_2) = match self.side_to_move {
-                White => (F1, G1),
-                Black => (F8, G8),
-            };
-            let is_king_side_not_attacked =
-                !self.is_under_attack(square_1) & !self.is_under_attack(square_2);
-            let is_empty_king_side = ((BitBoard::from_square(square_1)
-                ^ BitBoard::from_square(square_2))
-                & self.get_combined_mask())
-            .is_blank();
-            if is_king_side_not_attacked & is_empty_king_side {
-                result += KingSide;
+        let rights = self.get_castle_rights(self.side_to_move);
+        for (side, has_right) in [
+            (KingSide, rights.has_kingside()),
+            (QueenSide, rights.has_queenside()),
+        ] {
+            if !has_right {
+                continue;
             }
-        }
-
-        // check castling queen side
-        if self.get_castle_rights(self.side_to_move).has_queenside() {
-            let (square_1, square_2, square_3) = match self.side_to_move {
-                White => (D1, C1, B1),
-                Black => (D8, C8, B8),
-            };
-            let is_queen_side_not_attacked =
-                !self.is_under_attack(square_1) & !self.is_under_attack(square_2);
-            let is_empty_queen_side = ((BitBoard::from_square(square_1)
-                ^ BitBoard::from_square(square_2)
-                ^ BitBoard::from_square(square_3))
+            let is_empty_path = (castling_empty_path(self.side_to_move, side)
                 & self.get_combined_mask())
             .is_blank();
-            if is_queen_side_not_attacked & is_empty_queen_side {
-                result += QueenSide;
+            if is_empty_path
+                && castling_king_path(self.side_to_move, side)
+                    .all(|square| !self.is_under_attack(square))
+            {
+                result += side;
             }
         }
 
@@ -684,12 +1639,8 @@ impl ChessBoard {
                     return false;
                 }
 
-                /* Check if promotion option contain any piece. If yes, we need to ensure that this
-                is a Pawn move and the pawn is moving to opposite side's back-rank */
-                if (m.get_promotion().is_some())
-                    & (m.get_piece_type() != Pawn)
-                    & (destination.get_rank() != self.side_to_move.get_back_rank())
-                {
+                // Pawn moves to the last rank (and only them) must contain the promotion piece
+                if self.validate_promotion(m).is_err() {
                     return false;
                 }
 
@@ -714,731 +1665,3266 @@ impl ChessBoard {
         true
     }
 
-    /// Returns true if current side has at least one legal move
-    #[inline]
-    pub fn is_terminal(&self) -> bool { self.is_terminal_position }
-
-    /// Returns a Vec of all legal moves for current board
-    pub fn get_legal_moves(&self) -> LegalMoves {
-        let mut moves = Vec::with_capacity(218); /* maximum possible number of legal
-                                                 moves in a single position (just to avoid memory reallocations) */
-        let color_mask = self.get_color_mask(self.side_to_move);
-        let check_mask = self.get_check_mask();
+    /// Checks that the promotion piece is specified for the pawn moves to the last rank and is not
+    /// specified for any other moves
+    fn validate_promotion(&self, m: &PieceMove) -> Result<(), Error> {
+        let is_promotion_move = (m.get_piece_type() == Pawn)
+            & (m.get_destination_square().get_rank() == self.side_to_move.get_promotion_rank());
+        match (is_promotion_move, m.get_promotion().is_some()) {
+            (true, false) => Err(Error::MissingPromotionPiece),
+            (false, true) => Err(Error::UnexpectedPromotionPiece),
+            _ => Ok(()),
+        }
+    }
 
-        for piece_type in PieceType::iter() {
-            for square in color_mask & self.get_piece_type_mask(piece_type) {
-                let piece_moves = self
-                    .get_piece_moves_mask(piece_type, square)
-                    .map(|s| PieceMove::new(piece_type, square, s, None).unwrap())
-                    .filter(|pm| {
-                        if !check_mask.is_blank()
-                            | (piece_type == King)
-                            | pm.is_en_passant_move(self)
-                            | !(BitBoard::from_square(pm.get_source_square()) & self.pinned)
-                                .is_blank()
-                        {
-                            return self.get_check_mask_after_piece_move(pm).is_blank();
-                        }
-                        true
-                    });
-
-                if piece_type == Pawn {
-                    piece_moves.for_each(|m| {
-                        let destination = m.get_destination_square();
-                        let promotion_rank = self.side_to_move.get_promotion_rank();
-                        if destination.get_rank() == promotion_rank {
-                            // Generate promotion moves
-                            let (s, d) = (m.get_source_square(), destination);
-                            moves.extend_from_slice(&[
-                                mv!(Pawn, s, d, Knight),
-                                mv!(Pawn, s, d, Bishop),
-                                mv!(Pawn, s, d, Rook),
-                                mv!(Pawn, s, d, Queen),
-                            ]);
-                        } else {
-                            moves.push(BoardMove::MovePiece(m));
-                        }
-                    })
-                } else {
-                    moves.extend(piece_moves.map(|m| BoardMove::MovePiece(m)));
-                }
-            }
+    /// Checks that the move is legal and has the correct promotion piece (see
+    /// ``ChessBoard::make_move_mut``) and returns it with the castling normalized
+    fn validate_move(&self, next_move: &BoardMove) -> Result<BoardMove, Error> {
+        let next_move = self.normalize_castling(next_move);
+        if let BoardMove::MovePiece(m) = &next_move {
+            self.validate_promotion(m)?;
         }
+        match self.is_legal_move(&next_move) {
+            true => Ok(next_move),
+            false => Err(Error::IllegalMoveDetected),
+        }
+    }
 
-        // Check if castling is legal
-        moves.extend_from_slice(
-            match self.castling_is_available_on_board(Some(check_mask)) {
-                QueenSide => &[castle_queen_side!()],
-                KingSide => &[castle_king_side!()],
-                BothSides => &[castle_king_side!(), castle_queen_side!()],
-                Neither => &[],
-            },
-        );
+    /// Converts the alternative castling notations into ``BoardMove::CastleKingSide`` or
+    /// ``BoardMove::CastleQueenSide``. Other moves are returned unchanged
+    ///
+    /// Castling could be written as a king move by two squares (UCI-style "e1g1") or as a king
+    /// capturing its own rook (used in some Chess960 PGNs, "e1h1"). Such a move is treated as
+    /// castling only if the side to move still has the corresponding castling rights
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{mv, squares::*, BoardMove, ChessBoard, PieceMove, PieceType::*};
+    /// let board = ChessBoard::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    /// assert_eq!(
+    ///     board.normalize_castling(&mv!(King, E1, G1)),
+    ///     BoardMove::CastleKingSide
+    /// );
+    /// assert_eq!(
+    ///     board.normalize_castling(&mv!(King, E1, A1)),
+    ///     BoardMove::CastleQueenSide
+    /// );
+    /// assert_eq!(
+    ///     board.normalize_castling(&mv!(King, E1, F1)),
+    ///     mv!(King, E1, F1)
+    /// );
+    /// ```
+    pub fn normalize_castling(&self, board_move: &BoardMove) -> BoardMove {
+        use File::*;
 
-        moves
-    }
+        let m = match board_move {
+            BoardMove::MovePiece(m) => m,
+            _ => return *board_move,
+        };
 
-    /// Returns the Zobrist-hash of the position. Is used to detect the repetition draw
-    #[inline]
-    pub fn get_hash(&self) -> PositionHashValueType { self.hash }
+        let back_rank = self.side_to_move.get_back_rank();
+        let (source, destination) = (m.get_source_square(), m.get_destination_square());
+        let king_square = self.get_king_square(self.side_to_move);
+        if (m.get_piece_type() != King)
+            | (source != king_square)
+            | (source != Square::from_rank_file(back_rank, E))
+            | (destination.get_rank() != back_rank)
+            | m.get_promotion().is_some()
+        {
+            return *board_move;
+        }
 
-    /// Returns position status on the board
+        let rights = self.get_castle_rights(self.side_to_move);
+        match destination.get_file() {
+            G | H if rights.has_kingside() => BoardMove::CastleKingSide,
+            C | A if rights.has_queenside() => BoardMove::CastleQueenSide,
+            _ => *board_move,
+        }
+    }
+
+    /// Finds the legal move which is represented by specified SAN-string (Standard Algebraic
+    /// Notation, "Nf3", "exd5", "O-O", "e8=Q+"). Check and checkmate marks are optional. The
+    /// promotion may be written without "=" and with the lowercase piece letter ("e8Q", "e8=q").
+    /// Also accepts alternative castling spellings ("0-0", "Kg1") and the long notation of legal
+    /// moves (see ``ChessBoard::parse_uci``)
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidBoardMoveRepresentation`` if there is no legal move represented by
+    /// this string
     ///
     /// # Examples
     /// ```
-    /// use libchess::{BoardStatus::*, ChessBoard, Color::*};
-    /// let board = ChessBoard::from_fen("Q4k2/8/5K2/8/8/8/8/8 b - - 0 1").unwrap();
-    /// assert_eq!(board.get_status(), CheckMated(Black));
+    /// use libchess::{mv, squares::*, BoardMove, ChessBoard, PieceMove, PieceType::*};
+    /// let board = ChessBoard::default();
+    /// assert_eq!(board.parse_san("Nf3").unwrap(), mv!(Knight, G1, F3));
+    ///
+    /// let board = ChessBoard::from_fen("3r2k1/4P3/8/8/8/8/8/K7 w - - 0 1").unwrap();
+    /// assert_eq!(
+    ///     board.parse_san("exd8=N+").unwrap(),
+    ///     mv!(Pawn, E7, D8, Knight)
+    /// );
+    /// assert_eq!(board.parse_san("exd8n").unwrap(), mv!(Pawn, E7, D8, Knight));
+    /// assert_eq!(board.parse_san("e8Q+").unwrap(), mv!(Pawn, E7, E8, Queen));
     /// ```
-    pub fn get_status(&self) -> BoardStatus {
-        if self.is_terminal_position {
-            if self.checks.count_ones() > 0 {
-                BoardStatus::CheckMated(self.side_to_move)
-            } else {
-                BoardStatus::Stalemate
+    pub fn parse_san(&self, san: &str) -> Result<BoardMove, Error> {
+        let san = normalize_san_promotion(san.trim_end_matches(['+', '#']));
+        let san = san.as_str();
+        for m in self.get_legal_moves() {
+            let m_san = m.to_string(MovePropertiesOnBoard::new(&m, self)?);
+            if m_san.trim_end_matches(['+', '#']) == san {
+                return Ok(m);
             }
-        } else if self.is_theoretical_draw_on_board() {
-            BoardStatus::TheoreticalDrawDeclared
-        } else if self.moves_since_capture_or_pawn_move >= 100 {
-            BoardStatus::FiftyMovesDrawDeclared
-        } else {
-            BoardStatus::Ongoing
         }
-    }
-
-    /// Check sufficiency for both sides to checkmate each other. Is used to determine theoretical
-    /// draws
-    pub fn is_theoretical_draw_on_board(&self) -> bool {
-        let white_pieces_number = self.get_color_mask(White).count_ones();
-        let black_pieces_number = self.get_color_mask(Black).count_ones();
 
-        if (white_pieces_number > 2) | (black_pieces_number > 2) {
-            return false;
+        if let Some(m) = self.parse_castling_alias(san) {
+            return Ok(m);
+        }
+        match self.parse_uci(san) {
+            Ok(m) if self.make_move(&m).is_ok() => Ok(m),
+            _ => Err(Error::InvalidBoardMoveRepresentation),
         }
-
-        let bishops_and_knights =
-            self.get_piece_type_mask(Knight) | self.get_piece_type_mask(Bishop);
-
-        let white_can_not_checkmate = match white_pieces_number {
-            1 => true, // Only white king is on the board
-            2 => !(self.get_color_mask(White) & bishops_and_knights).is_blank(), /* only white king and white bishop or knight are on the board */
-            _ => unreachable!(),
-        };
-        let black_can_not_checkmate = match black_pieces_number {
-            1 => true, // Only black king is on the board
-            2 => !(self.get_color_mask(Black) & bishops_and_knights).is_blank(), /* only black king and white black or knight are on the board */
-            _ => unreachable!(),
-        };
-
-        white_can_not_checkmate & black_can_not_checkmate
     }
 
-    /// Represents chess moves in short mode without ambiguities in PGN-like strings
-    pub fn get_move_ambiguity_type(
+    /// Finds the legal move which is represented by SAN-string (see ``ChessBoard::parse_san``).
+    /// Pawn moves to the last rank without the promotion piece ("e8", "dxe8+") are handled
+    /// according to the policy
+    ///
+    /// # Errors
+    /// ``LibChessError::MissingPromotionPiece`` if the promotion piece is not specified and the
+    /// policy does not choose it
+    ///
+    /// ``LibChessError::InvalidBoardMoveRepresentation`` if there is no legal move represented by
+    /// this string
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::PromotionPolicy::*;
+    /// use libchess::{mv, squares::*, BoardMove, ChessBoard, PieceMove, PieceType::*};
+    /// use std::str::FromStr;
+    /// let board = ChessBoard::from_str("8/4P3/8/8/8/8/2k5/K7 w - - 0 1").unwrap();
+    /// assert!(board.parse_san_with_promotion("e8", Strict).is_err());
+    /// assert_eq!(
+    ///     board.parse_san_with_promotion("e8", AutoQueen).unwrap(),
+    ///     mv!(Pawn, E7, E8, Queen)
+    /// );
+    /// assert_eq!(
+    ///     board
+    ///         .parse_san_with_promotion("e8", Prompt(|_, _, _| Some(Knight)))
+    ///         .unwrap(),
+    ///     mv!(Pawn, E7, E8, Knight)
+    /// );
+    /// ```
+    pub fn parse_san_with_promotion(
         &self,
-        piece_move: &PieceMove,
-    ) -> Result<DisplayAmbiguityType, Error> {
-        use DisplayAmbiguityType::*;
-
-        if !self.is_legal_move(&BoardMove::MovePiece(*piece_move)) {
-            return Err(Error::IllegalMoveDetected);
+        san: &str,
+        policy: PromotionPolicy,
+    ) -> Result<BoardMove, Error> {
+        if let Ok(m) = self.parse_san(san) {
+            return Ok(m);
         }
 
-        let piece_type = piece_move.get_piece_type();
-        let source = piece_move.get_source_square();
-        let destination = piece_move.get_destination_square();
-
-        if piece_type == Pawn {
-            if source.get_file() != destination.get_file() {
-                return Ok(ExtraFile);
-            }
-        } else if piece_type == King {
-            return Ok(Neither);
-        } else {
-            let piece_moves = match piece_type {
-                Knight => KNIGHT.get_moves(destination),
-                Bishop => BISHOP.get_moves(destination),
-                Rook => ROOK.get_moves(destination),
-                Queen => QUEEN.get_moves(destination),
-                _ => unreachable!(),
-            };
-
-            let between_filter = |x: &Square| match piece_type {
-                Knight => true,
-                _ => BETWEEN
-                    .get(*x, piece_move.get_destination_square())
-                    .map_or(BLANK, |x| x & self.combined_mask)
-                    .is_blank(),
-            };
-
-            let pieces_mask =
-                self.get_piece_type_mask(piece_type) & self.get_color_mask(self.side_to_move);
-            if (piece_moves & pieces_mask).filter(between_filter).count() > 1 {
-                if (BitBoard::from_file(source.get_file()) & pieces_mask).count_ones() > 1 {
-                    return Ok(ExtraRank);
-                } else {
-                    return Ok(ExtraFile);
+        let san = san.trim_end_matches(['+', '#']);
+        for m in self.get_legal_moves() {
+            if let BoardMove::MovePiece(piece_move) = m {
+                if piece_move.get_promotion() != Some(Queen) {
+                    continue;
+                }
+                let m_san = m.to_string(MovePropertiesOnBoard::new(&m, self)?);
+                if m_san.trim_end_matches(['+', '#']).trim_end_matches("=Q") == san {
+                    let incomplete = PieceMove::new(
+                        Pawn,
+                        piece_move.get_source_square(),
+                        piece_move.get_destination_square(),
+                        None,
+                    )?;
+                    return self.complete_promotion(incomplete, policy);
                 }
             }
         }
 
-        Ok(Neither)
+        match self.parse_uci_with_promotion(san, policy) {
+            Ok(m) if self.make_move(&m).is_ok() => Ok(m),
+            Err(Error::MissingPromotionPiece) => Err(Error::MissingPromotionPiece),
+            _ => Err(Error::InvalidBoardMoveRepresentation),
+        }
     }
 
-    /// The method which allows to make moves on the board. Modifies the board object if the move
-    /// is legal
-    ///
-    /// The simplest way to generate moves is by picking one from a set of available moves:
-    /// ``board.get_legal_moves()`` or by simply creating a new move via macros: ``mv!()``,
-    /// ``castle_king_side!()`` and ``castle_queen_side!()``
+    /// Converts the UCI-string into the move (see ``ChessBoard::parse_uci``). Pawn moves to the
+    /// last rank without the promotion piece ("e7e8") are handled according to the policy
     ///
     /// # Errors
-    /// ``LibChessError::IllegalMoveDetected`` if specified move is not legal
+    /// ``LibChessError::MissingPromotionPiece`` if the promotion piece is not specified and the
+    /// policy does not choose it
+    ///
+    /// ``LibChessError::InvalidBoardMoveRepresentation`` if the string can't be parsed or there is
+    /// no piece on the source square
     ///
     /// # Examples
     /// ```
-    /// use libchess::PieceType::*;
-    /// use libchess::{castle_king_side, castle_queen_side, mv};
-    /// use libchess::{squares::*, BoardMove, ChessBoard, PieceMove};
-    ///
-    /// let mut board = ChessBoard::default();
-    /// board.make_move(&mv!(Pawn, E2, E4)).unwrap();
-    /// println!("{}", board);
+    /// use libchess::PromotionPolicy::*;
+    /// use libchess::{mv, squares::*, BoardMove, ChessBoard, PieceMove, PieceType::*};
+    /// use std::str::FromStr;
+    /// let board = ChessBoard::from_str("8/4P3/8/8/8/8/2k5/K7 w - - 0 1").unwrap();
+    /// assert_eq!(
+    ///     board.parse_uci_with_promotion("e7e8", AutoQueen).unwrap(),
+    ///     mv!(Pawn, E7, E8, Queen)
+    /// );
+    /// assert_eq!(
+    ///     board.parse_uci_with_promotion("e7e8r", Strict).unwrap(),
+    ///     mv!(Pawn, E7, E8, Rook)
+    /// );
     /// ```
-    pub fn make_move_mut(&mut self, next_move: &BoardMove) -> Result<&mut Self, Error> {
-        if !self.is_legal_move(next_move) {
-            return Err(Error::IllegalMoveDetected);
+    pub fn parse_uci_with_promotion(
+        &self,
+        uci: &str,
+        policy: PromotionPolicy,
+    ) -> Result<BoardMove, Error> {
+        match self.parse_uci(uci)? {
+            BoardMove::MovePiece(m) => self.complete_promotion(m, policy),
+            m => Ok(m),
         }
-        unsafe { Ok(self.make_move_mut_unchecked(next_move)) }
     }
 
-    /// The unsafe version of ``ChessBoard::make_move_mut`` method. It does not perform the check if
-    /// the move is legal or not. It is only useful for performance reasons during the process of
-    /// engine search of the best move. Often used in pair with ``ChessBoard::get_legal_moves``
-    /// which guaranties that any provided move will be legal one
-    ///
-    /// # Safety
-    ///
-    /// Be very careful while using this method because in case of illegal ``next_move`` it
-    /// can lead to application panic or to unpredictable changes of the position. If you are not
-    /// 100% sure that ``next_move`` will be a legal move - use ``ChessBoard::make_move_mut``
-    /// instead
-    pub unsafe fn make_move_mut_unchecked(&mut self, next_move: &BoardMove) -> &mut Self {
-        use File::*;
-
-        match next_move {
-            BoardMove::MovePiece(m) => {
-                self.move_piece(m).clear_square_if_en_passant_capture(m);
-            }
-            BoardMove::CastleKingSide => {
-                let back_rank = self.side_to_move.get_back_rank();
-                self.move_piece(
-                    &PieceMove::new(
-                        King,
-                        Square::from_rank_file(back_rank, E),
-                        Square::from_rank_file(back_rank, G),
-                        None,
-                    )
-                    .unwrap(),
-                );
-                self.move_piece(
-                    &PieceMove::new(
-                        Rook,
-                        Square::from_rank_file(back_rank, H),
-                        Square::from_rank_file(back_rank, F),
-                        None,
-                    )
-                    .unwrap(),
-                );
-            }
-            BoardMove::CastleQueenSide => {
-                let back_rank = self.side_to_move.get_back_rank();
-                self.move_piece(
-                    &PieceMove::new(
-                        PieceType::King,
-                        Square::from_rank_file(back_rank, E),
-                        Square::from_rank_file(back_rank, C),
-                        None,
-                    )
-                    .unwrap(),
-                );
-                self.move_piece(
-                    &PieceMove::new(
-                        Rook,
-                        Square::from_rank_file(back_rank, A),
-                        Square::from_rank_file(back_rank, D),
-                        None,
-                    )
-                    .unwrap(),
-                );
-            }
+    /// Adds the promotion piece chosen by the policy to the pawn move to the last rank which
+    /// doesn't have it. Other moves are returned unchanged
+    fn complete_promotion(
+        &self,
+        m: PieceMove,
+        policy: PromotionPolicy,
+    ) -> Result<BoardMove, Error> {
+        if !matches!(
+            self.validate_promotion(&m),
+            Err(Error::MissingPromotionPiece)
+        ) {
+            return Ok(BoardMove::MovePiece(m));
         }
 
-        let opposite_side = !self.side_to_move;
-        self.update_move_number()
-            .update_moves_since_capture(next_move)
-            .update_castling_rights(next_move)
-            .set_side_to_move(opposite_side)
-            .update_en_passant(next_move)
-            .update_pins_and_checks()
-            .update_terminal_status();
-
-        self
+        let (source, destination) = (m.get_source_square(), m.get_destination_square());
+        let promotion = match policy {
+            PromotionPolicy::Strict => None,
+            PromotionPolicy::AutoQueen => Some(Queen),
+            PromotionPolicy::Prompt(choose) => choose(self, source, destination),
+        }
+        .ok_or(Error::MissingPromotionPiece)?;
+        Ok(BoardMove::MovePiece(PieceMove::new(
+            Pawn,
+            source,
+            destination,
+            Some(promotion),
+        )?))
     }
 
-    /// The method which allows to make moves on the board. Returns a new board instance
-    /// if the move is legal
-    ///
-    /// The simplest way to generate moves is by picking one from a set of available moves:
-    /// ``board.get_legal_moves()`` or by simply creating a new move via macros: ``mv!()``,
-    /// ``castle_king_side!()`` and ``castle_queen_side!()``
+    /// Finds the legal move which is represented by SAN-string with the piece letters of specified
+    /// locale (see ``ChessBoard::parse_san``). English letters are accepted as well unless they
+    /// clash with the locale letters
     ///
     /// # Errors
-    /// ``LibChessError::IllegalMoveDetected`` if specified move is not legal
+    /// ``LibChessError::InvalidBoardMoveRepresentation`` if there is no legal move represented by
+    /// this string
     ///
     /// # Examples
     /// ```
     /// use libchess::PieceType::*;
-    /// use libchess::{castle_king_side, castle_queen_side, mv};
-    /// use libchess::{squares::*, BoardMove, ChessBoard, PieceMove};
-    ///
+    /// use libchess::{mv, squares::*, BoardMove, ChessBoard, NotationLocale, PieceMove};
     /// let board = ChessBoard::default();
-    /// let next_board = board.make_move(&mv!(Pawn, E2, E4)).unwrap();
-    /// println!("{}", next_board);
+    /// let locale = NotationLocale::russian();
+    /// assert_eq!(
+    ///     board.parse_localized_san("Кf3", &locale).unwrap(),
+    ///     mv!(Knight, G1, F3)
+    /// );
     /// ```
-    pub fn make_move(&self, next_move: &BoardMove) -> Result<Self, Error> {
-        let mut next_board = *self;
-        next_board.make_move_mut(next_move)?;
-        Ok(next_board)
+    pub fn parse_localized_san(
+        &self,
+        san: &str,
+        locale: &NotationLocale,
+    ) -> Result<BoardMove, Error> {
+        self.parse_san(&locale.to_english(san))
     }
 
-    /// The unsafe version of ``ChessBoard::make_move`` method. It does not perform the check if
-    /// the move is legal or not. It is only useful for performance reasons during the process of
-    /// engine search of the best move. Often used in pair with ``ChessBoard::get_legal_moves``
-    /// which guaranties that any provided move will be legal one
+    /// Converts the UCI-string (Universal Chess Interface long algebraic notation, "e2e4", "e7e8q",
+    /// "e1g1") into the move. The type of moving piece is taken from the board. Common variations
+    /// of the long notation typed by users are also accepted: uppercase coordinates ("E2E4"),
+    /// dashes and capture marks ("e2-e4", "d4xe5"), check marks, promotion with "=" ("e7e8=Q")
+    /// and the piece letter prefix ("Ng1-f3+"), which must match the piece on the source square
     ///
-    /// # Safety
+    /// # Errors
+    /// ``LibChessError::InvalidBoardMoveRepresentation`` if the string can't be parsed or there is
+    /// no piece on the source square
     ///
-    /// Be very careful while using this method because in case of illegal ``next_move`` it
-    /// can lead to application panic or to unpredictable changes of the position. If you are not
-    /// 100% sure that ``next_move`` will be a legal move - use ``ChessBoard::make_move`` instead
-    pub unsafe fn make_move_unchecked(&self, next_move: &BoardMove) -> Self {
-        let mut next_board = *self;
-        next_board.make_move_mut_unchecked(next_move);
-        next_board
-    }
-
-    fn get_piece_moves_mask(&self, piece_type: PieceType, square: Square) -> BitBoard {
-        let color_mask = self.get_color_mask(self.side_to_move);
-
-        let truncate_rays = |pt: PieceType, square: Square| {
-            let slice = match pt {
-                Bishop => 4..8,
-                Rook => 0..4,
-                Queen => 0..8,
-                _ => unreachable!(),
-            };
+    /// # Examples
+    /// ```
+    /// use libchess::{mv, squares::*, BoardMove, ChessBoard, PieceMove, PieceType::*};
+    /// let board = ChessBoard::default();
+    /// assert_eq!(board.parse_uci("g1f3").unwrap(), mv!(Knight, G1, F3));
+    /// assert_eq!(board.parse_uci("Ng1-f3+").unwrap(), mv!(Knight, G1, F3));
+    /// assert_eq!(board.parse_uci("E2E4").unwrap(), mv!(Pawn, E2, E4));
+    /// ```
+    pub fn parse_uci(&self, uci: &str) -> Result<BoardMove, Error> {
+        let (piece_letter, uci) =
+            normalize_long_notation(uci).ok_or(Error::InvalidBoardMoveRepresentation)?;
+        let uci = uci.as_str();
+        if !(4..=5).contains(&uci.len()) {
+            return Err(Error::InvalidBoardMoveRepresentation);
+        }
 
-            let mut legals = BLANK;
-            slice.for_each(|i| {
-                let ray = RAYS.get(square)[i];
-                legals ^= match i {
-                    0 | 2 | 4 | 5 => (ray & self.combined_mask).last_bit_square(),
-                    1 | 3 | 6 | 7 => (ray & self.combined_mask).first_bit_square(),
-                    _ => unreachable!(),
-                }
-                .map_or(ray, |s| {
-                    BETWEEN.get(square, s).unwrap() ^ BitBoard::from_square(s)
-                });
-            });
-            legals & !color_mask
+        let source =
+            Square::from_str(&uci[..2]).map_err(|_| Error::InvalidBoardMoveRepresentation)?;
+        let destination =
+            Square::from_str(&uci[2..4]).map_err(|_| Error::InvalidBoardMoveRepresentation)?;
+        let promotion = match &uci[4..] {
+            "" => None,
+            p => Some(PieceType::from_str(p).map_err(|_| Error::InvalidBoardMoveRepresentation)?),
         };
-
-        match piece_type {
-            Pawn => {
-                let ep = self.get_en_passant().map_or(BLANK, BitBoard::from_square);
-                let capturing_squares = self.get_color_mask(!self.side_to_move) | ep;
-                let single_move = PAWN.get_moves(square, self.side_to_move) & !self.combined_mask;
-                let double_move = if single_move.is_blank() {
-                    BLANK
-                } else {
-                    PAWN.get_double_moves(square, self.side_to_move) & !self.combined_mask
-                };
-
-                single_move
-                    | double_move
-                    | (PAWN.get_captures(square, self.side_to_move) & capturing_squares)
-            }
-            Knight => KNIGHT.get_moves(square) & !color_mask,
-            King => KING.get_moves(square) & !color_mask,
-            Bishop => truncate_rays(Bishop, square),
-            Rook => truncate_rays(Rook, square),
-            Queen => truncate_rays(Queen, square),
+        let piece_type = self
+            .get_piece_type_on(source)
+            .ok_or(Error::InvalidBoardMoveRepresentation)?;
+        if piece_letter.is_some_and(|letter| letter != piece_type) {
+            return Err(Error::InvalidBoardMoveRepresentation);
         }
+
+        Ok(
+            self.normalize_castling(&BoardMove::MovePiece(PieceMove::new(
+                piece_type,
+                source,
+                destination,
+                promotion,
+            )?)),
+        )
     }
 
-    fn get_check_mask_after_piece_move(self, m: &PieceMove) -> BitBoard {
-        self.clone()
-            .move_piece(m)
-            .clear_square_if_en_passant_capture(m)
+    /// Applies a whitespace-separated sequence of SAN-moves to the board and returns the final
+    /// position
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidMoveInLine`` with the failing token and its ply (counting from 0)
+    /// if any move can't be parsed or is illegal
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::ChessBoard;
+    /// let board = ChessBoard::default()
+    ///     .apply_san_line("e4 e5 Nf3 Nc6 Bb5")
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     board.as_fen(),
+    ///     "r1bqkbnr/pppp1ppp/2n5/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3"
+    /// );
+    /// ```
+    pub fn apply_san_line(&self, line: &str) -> Result<Self, Error> {
+        self.apply_line(line, Self::parse_san)
+    }
+
+    /// Applies a whitespace-separated sequence of UCI-moves to the board and returns the final
+    /// position
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidMoveInLine`` with the failing token and its ply (counting from 0)
+    /// if any move can't be parsed or is illegal
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::ChessBoard;
+    /// let board = ChessBoard::default()
+    ///     .apply_uci_line("e2e4 e7e5 g1f3 b8c6 f1b5")
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     board.as_fen(),
+    ///     "r1bqkbnr/pppp1ppp/2n5/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3"
+    /// );
+    /// ```
+    pub fn apply_uci_line(&self, line: &str) -> Result<Self, Error> {
+        self.apply_line(line, Self::parse_uci)
+    }
+
+    /// Applies a fragment of PGN movetext ("12... Rxe4 13. Qd3 Rxe1+") to the board and returns
+    /// the final position and the applied moves. The move numbers are optional, but if present
+    /// they must match the move number and the side to move of the board. Comments, variations,
+    /// NAGs ("$1"), move suffixes ("!?") and the game result are skipped
+    ///
+    /// # Errors
+    /// ``LibChessError::WrongMoveNumber`` if the move number doesn't match the position
+    ///
+    /// ``LibChessError::InvalidMoveInLine`` with the failing token and its ply (counting from 0)
+    /// if any move can't be parsed or is illegal
+    ///
+    /// ``LibChessError::InvalidPGNString`` if the comments or variations are not closed
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::ChessBoard;
+    /// let board = ChessBoard::default().apply_san_line("e4 e5 Nf3").unwrap();
+    /// let (board, moves) = board
+    ///     .apply_movetext("2... Nc6 {the main line} 3. Bb5 (3. Bc4 Bc5) a6!? 4. Ba4 *")
+    ///     .unwrap();
+    /// assert_eq!(moves.len(), 4);
+    /// assert_eq!(
+    ///     board.as_fen(),
+    ///     "r1bqkbnr/1ppp1ppp/p1n5/4p3/B3P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 1 4"
+    /// );
+    /// assert!(board.apply_movetext("7. O-O").is_err());
+    /// ```
+    pub fn apply_movetext(&self, movetext: &str) -> Result<(Self, Vec<BoardMove>), Error> {
+        let mut board = *self;
+        let mut moves = Vec::new();
+        for token in strip_pgn_comments(movetext)?.split_whitespace() {
+            if ["1-0", "0-1", "1/2-1/2", "*"].contains(&token) | token.starts_with('$') {
+                continue;
+            }
+
+            // the move number may be written together with the move ("12...Rxe4")
+            // the digits are the move number only if the dots follow them ("0-0" is the castling)
+            let digits = token.len() - token.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            let dots = token[digits..].len() - token[digits..].trim_start_matches('.').len();
+            let digits = if dots > 0 { digits } else { 0 };
+            if digits > 0 {
+                let is_expected_side = match dots {
+                    1 => board.side_to_move == White,
+                    _ => board.side_to_move == Black,
+                };
+                if (token[..digits].parse::<usize>() != Ok(board.get_move_number()))
+                    | !is_expected_side
+                {
+                    return Err(Error::WrongMoveNumber);
+                }
+            }
+            let san = token[digits + dots..].trim_end_matches(['!', '?']);
+            if san.is_empty() {
+                continue;
+            }
+
+            let error = || Error::InvalidMoveInLine {
+                token: san.to_string(),
+                ply:   moves.len(),
+            };
+            let m = board.parse_san(san).map_err(|_| error())?;
+            board.make_move_mut(&m).map_err(|_| error())?;
+            moves.push(m);
+        }
+        Ok((board, moves))
+    }
+
+    /// Solves the chess problem with specified stipulation by the exhaustive search. For the
+    /// direct mate and the selfmate the solutions are the key moves (the play after the key
+    /// depends on the defence), for the helpmate the solutions are the complete lines
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{ChessBoard, Stipulation};
+    /// let board = ChessBoard::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    /// let solutions = board.solve(Stipulation::Mate(1));
+    /// assert_eq!(solutions, vec![vec![board.parse_san("Ra8").unwrap()]]);
+    ///
+    /// let board = ChessBoard::from_fen("7k/8/6K1/8/8/8/8/R7 b - - 0 1").unwrap();
+    /// let solutions = board.solve(Stipulation::Helpmate(1));
+    /// assert_eq!(solutions, vec![board.apply_movetext("Kg8 Ra8#").unwrap().1]);
+    /// ```
+    pub fn solve(&self, stipulation: Stipulation) -> Vec<Vec<BoardMove>> {
+        if stipulation.get_moves_number() == 0 {
+            return Vec::new();
+        }
+        match stipulation {
+            Stipulation::Mate(n) => self
+                .get_legal_moves()
+                .into_iter()
+                .filter(|m| stipulations::is_mate_after_key(&self.make_move(m).unwrap(), n))
+                .map(|m| vec![m])
+                .collect(),
+            Stipulation::Selfmate(n) => self
+                .get_legal_moves()
+                .into_iter()
+                .filter(|m| stipulations::is_selfmate_after_key(&self.make_move(m).unwrap(), n))
+                .map(|m| vec![m])
+                .collect(),
+            Stipulation::Helpmate(n) => {
+                let mut solutions = Vec::new();
+                stipulations::collect_helpmates(self, 2 * n, &mut Vec::new(), &mut solutions);
+                solutions
+            }
+        }
+    }
+
+    /// Returns the number of leaf nodes of the legal moves tree of specified depth (perft). The
+    /// last ply is counted without making the moves and the transposed subtrees are counted
+    /// once, so the function is suitable for the deep tests of the moves generation
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::ChessBoard;
+    /// let board = ChessBoard::default();
+    /// assert_eq!(board.perft(0), 1);
+    /// assert_eq!(board.perft(3), 8902);
+    /// ```
+    pub fn perft(&self, depth: usize) -> u64 {
+        perfts::count_nodes(self, depth, &mut HashMap::new())
+    }
+
+    /// Returns the statistics of the moves on each depth of the legal moves tree (the first item
+    /// is for the moves of this position). Unlike ``ChessBoard::perft`` all moves are made, so
+    /// it is much slower
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::ChessBoard;
+    /// let board = ChessBoard::from_fen("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+    /// let stats = board.perft_detailed(2);
+    /// assert_eq!(stats[0].nodes, board.get_legal_moves().len() as u64);
+    /// assert_eq!(stats[0].castles, 2);
+    /// assert_eq!(stats[0].checks, 2); // Ra8+ and Rh8+
+    /// ```
+    pub fn perft_detailed(&self, depth: usize) -> Vec<PerftStats> {
+        let mut stats = vec![PerftStats::default(); depth];
+        perfts::collect_stats(self, &mut stats);
+        stats
+    }
+
+    /// Checks whether two move sequences played from this position transpose: both must be
+    /// legal and lead to the same position. The positions are compared by the hash and exactly
+    /// (the pieces placement, the side to move, the castling rights and the capturable en
+    /// passant square), the move counters are ignored
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidMoveInLine`` with the failing move and its ply (counting from 0)
+    /// if any move of either sequence is illegal
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::ChessBoard;
+    /// let board = ChessBoard::default();
+    /// let line = |s: &str| board.apply_movetext(s).unwrap().1;
+    /// assert!(board
+    ///     .is_transposition(&line("d4 Nf6 c4 e6"), &line("c4 e6 d4 Nf6"))
+    ///     .unwrap());
+    /// // the rook returns to h1, but the castling rights are lost
+    /// assert!(!board
+    ///     .is_transposition(&line("Nf3 Nf6 Rg1 Ng8 Rh1 Nf6"), &line("Nf3 Nf6"))
+    ///     .unwrap());
+    /// ```
+    pub fn is_transposition(
+        &self,
+        first: &[BoardMove],
+        second: &[BoardMove],
+    ) -> Result<bool, Error> {
+        Ok(self.apply_moves(first)? == self.apply_moves(second)?)
+    }
+
+    /// Applies the sequence of moves to the board and returns the final position
+    fn apply_moves(&self, moves: &[BoardMove]) -> Result<Self, Error> {
+        let mut board = *self;
+        for (ply, m) in moves.iter().enumerate() {
+            board
+                .make_move_mut(m)
+                .map_err(|_| Error::InvalidMoveInLine {
+                    token: format!("{m}"),
+                    ply,
+                })?;
+        }
+        Ok(board)
+    }
+
+    fn apply_line(
+        &self,
+        line: &str,
+        parse: impl Fn(&Self, &str) -> Result<BoardMove, Error>,
+    ) -> Result<Self, Error> {
+        let mut board = *self;
+        for (ply, token) in line.split_whitespace().enumerate() {
+            let error = || Error::InvalidMoveInLine {
+                token: token.to_string(),
+                ply,
+            };
+            let m = parse(&board, token).map_err(|_| error())?;
+            board.make_move_mut(&m).map_err(|_| error())?;
+        }
+        Ok(board)
+    }
+
+    /// Recognizes non-standard castling spellings: with zeros ("0-0") or as a king move to the
+    /// castling destination or to its own rook square ("Kg1", "Kxh1")
+    fn parse_castling_alias(&self, token: &str) -> Option<BoardMove> {
+        if let Ok(m @ (BoardMove::CastleKingSide | BoardMove::CastleQueenSide)) =
+            BoardMove::from_str(token)
+        {
+            return Some(m);
+        }
+
+        if !token.starts_with('K') {
+            return None;
+        }
+        let destination = Square::from_str(token.get(token.len().checked_sub(2)?..)?).ok()?;
+        let king_move = BoardMove::MovePiece(
+            PieceMove::new(
+                King,
+                self.get_king_square(self.side_to_move),
+                destination,
+                None,
+            )
+            .ok()?,
+        );
+        match self.normalize_castling(&king_move) {
+            BoardMove::MovePiece(_) => None,
+            m => Some(m),
+        }
+    }
+
+    /// Returns the mask of all squares attacked (or defended) by pieces of specified color
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{squares::*, BitBoard, ChessBoard, Color::*};
+    /// use std::str::FromStr;
+    /// let board = ChessBoard::from_str("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    /// let attacked = board.get_attacked_squares(White);
+    /// assert!(!(attacked & BitBoard::from_square(A8)).is_blank());
+    /// assert!((attacked & BitBoard::from_square(B2)).is_blank());
+    /// ```
+    pub fn get_attacked_squares(&self, color: Color) -> BitBoard {
+        self.get_attacks_mask(color, self.combined_mask)
+    }
+
+    /// Returns the squares attacked (or defended) by the piece on specified square. Sliding
+    /// pieces are blocked by the pieces on the board. Blank mask is returned for empty squares
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{squares::*, BitBoard, ChessBoard, BLANK};
+    /// use std::str::FromStr;
+    /// let board = ChessBoard::from_str("4k3/8/8/8/8/8/P7/R3K3 w - - 0 1").unwrap();
+    /// assert_eq!(
+    ///     board.attacks_from(A1),
+    ///     BitBoard::from_square(A2)
+    ///         | BitBoard::from_square(B1)
+    ///         | BitBoard::from_square(C1)
+    ///         | BitBoard::from_square(D1)
+    ///         | BitBoard::from_square(E1)
+    /// );
+    /// assert_eq!(board.attacks_from(A2), BitBoard::from_square(B3));
+    /// assert_eq!(board.attacks_from(A3), BLANK);
+    /// ```
+    pub fn attacks_from(&self, square: Square) -> BitBoard {
+        let Some(Piece(piece_type, color)) = self.get_piece_on(square) else {
+            return BLANK;
+        };
+        match piece_type {
+            Pawn => PAWN.get_captures(square, color),
+            Knight => KNIGHT.get_moves(square),
+            King => KING.get_moves(square),
+            Bishop => Self::get_ray_attacks(square, 4..8, self.combined_mask),
+            Rook => Self::get_ray_attacks(square, 0..4, self.combined_mask),
+            Queen => Self::get_ray_attacks(square, 0..8, self.combined_mask),
+        }
+    }
+
+    /// Returns the number of pieces of specified color attacking (or defending) each square
+    /// (indexed by ``Square::to_index``). Sliding pieces are blocked by any piece, so batteries
+    /// are not counted through the front piece
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{squares::*, ChessBoard, Color::*};
+    /// use std::str::FromStr;
+    /// let board = ChessBoard::from_str("4k3/8/8/8/8/2N5/8/R3K3 w - - 0 1").unwrap();
+    /// let control = board.control_map(White);
+    /// assert_eq!(control[D1.to_index()], 3); // the rook, the knight and the king
+    /// assert_eq!(control[A8.to_index()], 1);
+    /// assert_eq!(control[H8.to_index()], 0);
+    /// ```
+    pub fn control_map(&self, color: Color) -> [u8; SQUARES_NUMBER] {
+        let mut control = [0; SQUARES_NUMBER];
+        for square in self.get_color_mask(color) {
+            for attacked in self.attacks_from(square) {
+                control[attacked.to_index()] += 1;
+            }
+        }
+        control
+    }
+
+    /// Returns the squares which the sliding piece on specified square "sees" through the first
+    /// blocker on each of its rays (up to and including the second blocker). Direct attacks are
+    /// not included. Blank mask is returned for empty squares and non-sliding pieces
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{squares::*, BitBoard, ChessBoard, File};
+    /// use std::str::FromStr;
+    /// // the rook on a1 sees the file through its own queen on a2 up to the knight on a5
+    /// let board = ChessBoard::from_str("4k3/8/8/n7/8/8/Q7/R3K3 w - - 0 1").unwrap();
+    /// assert_eq!(
+    ///     board.xray_attacks(A1) & BitBoard::from_file(File::A),
+    ///     BitBoard::from_square(A3) | BitBoard::from_square(A4) | BitBoard::from_square(A5)
+    /// );
+    /// ```
+    pub fn xray_attacks(&self, square: Square) -> BitBoard {
+        let rays = match self.get_piece_type_on(square) {
+            Some(Bishop) => 4..8,
+            Some(Rook) => 0..4,
+            Some(Queen) => 0..8,
+            _ => return BLANK,
+        };
+        let attacks = Self::get_ray_attacks(square, rays.clone(), self.combined_mask);
+        let blockers = attacks & self.combined_mask;
+        attacks ^ Self::get_ray_attacks(square, rays, self.combined_mask ^ blockers)
+    }
+
+    /// Returns the mask of bishops of specified color standing on light or dark squares
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{squares::*, BitBoard, ChessBoard, Color::*, SquareColor::*};
+    /// let bishops = ChessBoard::default().bishops_on_color(White, Light);
+    /// assert_eq!(bishops, BitBoard::from_square(F1));
+    /// ```
+    pub fn bishops_on_color(&self, color: Color, square_color: SquareColor) -> BitBoard {
+        self.get_piece_type_mask(Bishop)
+            & self.get_color_mask(color)
+            & BitBoard::from_square_color(square_color)
+    }
+
+    /// Checks if the side has bishops on both light and dark squares
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{ChessBoard, Color::*};
+    /// let board = ChessBoard::default()
+    ///     .apply_san_line("e4 d5 Bb5+ c6 Bxc6+ Nxc6")
+    ///     .unwrap();
+    /// assert!(!board.has_bishop_pair(White));
+    /// assert!(board.has_bishop_pair(Black));
+    /// ```
+    pub fn has_bishop_pair(&self, color: Color) -> bool {
+        !self.bishops_on_color(color, SquareColor::Light).is_blank()
+            & !self.bishops_on_color(color, SquareColor::Dark).is_blank()
+    }
+
+    /// Checks if the pawn on specified square is passed: there are no opponent's pawns in front of
+    /// it on its own and adjacent files. Returns false if there is no pawn on the square
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{squares::*, ChessBoard};
+    /// use std::str::FromStr;
+    /// let board = ChessBoard::from_str("4k3/8/1p6/P7/4P3/3p4/8/4K3 w - - 0 1").unwrap();
+    /// assert!(!board.is_passed_pawn(A5)); // the pawn on b6 is in front on the adjacent file
+    /// assert!(board.is_passed_pawn(E4)); // the pawn on d3 is behind
+    /// assert!(board.is_passed_pawn(D3));
+    /// assert!(!board.is_passed_pawn(E1));
+    /// ```
+    pub fn is_passed_pawn(&self, square: Square) -> bool {
+        match self.get_piece_on(square) {
+            Some(Piece(Pawn, color)) => (Self::get_front_span(square, color)
+                & self.get_piece_type_mask(Pawn)
+                & self.get_color_mask(!color))
+            .is_blank(),
+            _ => false,
+        }
+    }
+
+    /// Checks if there are no pawns of both sides on specified file
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{ChessBoard, File};
+    /// let board = ChessBoard::default()
+    ///     .apply_san_line("e4 d5 exd5 e5")
+    ///     .unwrap();
+    /// assert!(!board.is_open_file(File::E));
+    /// let board = board.apply_san_line("Qe2 Qxd5 Qxe5+ Qxe5+").unwrap();
+    /// assert!(board.is_open_file(File::E));
+    /// ```
+    pub fn is_open_file(&self, file: File) -> bool {
+        (BitBoard::from_file(file) & self.get_piece_type_mask(Pawn)).is_blank()
+    }
+
+    /// Checks if specified file is semi-open for the side: the side has no pawns on the file
+    /// while the opponent has. Open files (see ``ChessBoard::is_open_file``) are not semi-open
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{ChessBoard, Color::*, File};
+    /// let board = ChessBoard::default().apply_san_line("e4 d5 exd5").unwrap();
+    /// assert!(board.is_semi_open_file(File::E, White));
+    /// assert!(board.is_semi_open_file(File::D, Black));
+    /// assert!(!board.is_semi_open_file(File::D, White));
+    /// ```
+    pub fn is_semi_open_file(&self, file: File, color: Color) -> bool {
+        let pawns = BitBoard::from_file(file) & self.get_piece_type_mask(Pawn);
+        (pawns & self.get_color_mask(color)).is_blank()
+            & !(pawns & self.get_color_mask(!color)).is_blank()
+    }
+
+    /// Returns the weak color complex of the side: the squares of the color which the side has no
+    /// bishop for, while the opponent has it and most of the side's pawns stand on the other color
+    /// (so they don't cover these squares either)
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{ChessBoard, Color::*, SquareColor::*};
+    /// use std::str::FromStr;
+    /// let board = ChessBoard::from_str("4k3/8/4b3/3p1p2/2pP1P2/2P1P3/3B4/4K3 w - - 0 1").unwrap();
+    /// assert_eq!(board.weak_color_complex(White), Some(Light));
+    /// assert_eq!(board.weak_color_complex(Black), Some(Dark));
+    /// ```
+    pub fn weak_color_complex(&self, color: Color) -> Option<SquareColor> {
+        let pawns = self.get_piece_type_mask(Pawn) & self.get_color_mask(color);
+        [SquareColor::Light, SquareColor::Dark]
+            .into_iter()
+            .find(|&square_color| {
+                let complex = BitBoard::from_square_color(square_color);
+                self.bishops_on_color(color, square_color).is_blank()
+                    & !self.bishops_on_color(!color, square_color).is_blank()
+                    & ((pawns & !complex).count_ones() > (pawns & complex).count_ones())
+            })
+    }
+
+    /// Returns the center control of specified side: the numbers of squares of the center (d4,
+    /// e4, d5, e5) and of the extended center (c3-f6) which are attacked and occupied by the side
+    pub fn center_control(&self, color: Color) -> CenterControl {
+        let attacked = self.get_attacked_squares(color);
+        let occupied = self.get_color_mask(color);
+        CenterControl {
+            center_attacked:          (attacked & CENTER_SQUARES).count_ones(),
+            center_occupied:          (occupied & CENTER_SQUARES).count_ones(),
+            extended_center_attacked: (attacked & EXTENDED_CENTER_SQUARES).count_ones(),
+            extended_center_occupied: (occupied & EXTENDED_CENTER_SQUARES).count_ones(),
+        }
+    }
+
+    /// Returns the space of specified side: the number of squares behind the opponent's pawn
+    /// line (closer to the side than the most advanced opponent's pawn of the file, or the side's
+    /// half of the board if the file has no opponent's pawns) which are attacked by the side and
+    /// are not attacked by the opponent's pawns
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{ChessBoard, Color::*};
+    /// let board = ChessBoard::default();
+    /// assert_eq!(board.space(White), board.space(Black));
+    ///
+    /// let board = board.apply_san_line("e4 e6 d4 d6 e5 d5").unwrap();
+    /// assert!(board.space(White) > board.space(Black));
+    /// ```
+    pub fn space(&self, color: Color) -> u32 {
+        let opponent_pawns = self.get_piece_type_mask(Pawn) & self.get_color_mask(!color);
+        let opponent_pawn_attacks = opponent_pawns
+            .into_iter()
+            .fold(BLANK, |acc, square| acc | PAWN.get_captures(square, !color));
+        (self.get_attacked_squares(color)
+            & Self::get_space_zone(color, opponent_pawns)
+            & !opponent_pawn_attacks)
+            .count_ones()
+    }
+
+    /// Returns the material signature of the position: the pieces of white and then of black
+    /// (from the king to the pawns) separated by "v". Is used as the key of endgame tables
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::ChessBoard;
+    /// let board = ChessBoard::from_fen("4k3/8/8/8/8/8/3NPR2/4K3 w - - 0 1").unwrap();
+    /// assert_eq!(board.get_material_signature(), "KRNPvK");
+    /// assert_eq!(
+    ///     ChessBoard::default().get_material_signature(),
+    ///     "KQRRBBNNPPPPPPPPvKQRRBBNNPPPPPPPP"
+    /// );
+    /// ```
+    pub fn get_material_signature(&self) -> String {
+        let side_signature = |color: Color| -> String {
+            [King, Queen, Rook, Bishop, Knight, Pawn]
+                .iter()
+                .map(|piece_type| {
+                    let count = (self.get_color_mask(color)
+                        & self.get_piece_type_mask(*piece_type))
+                    .count_ones();
+                    format!("{piece_type}").repeat(count as usize)
+                })
+                .collect()
+        };
+        format!("{}v{}", side_signature(White), side_signature(Black))
+    }
+
+    /// Returns the class of the endgame by the pieces on the board (see ``EndgameClass``)
+    pub fn get_endgame_class(&self) -> EndgameClass {
+        let count = |piece_type: PieceType| self.get_piece_type_mask(piece_type).count_ones();
+        EndgameClass::classify(
+            count(Pawn),
+            count(Bishop) + count(Knight),
+            count(Rook),
+            count(Queen),
+        )
+    }
+
+    /// Returns the phase of the game by the material and the development (see ``GamePhase``)
+    pub fn get_game_phase(&self) -> GamePhase {
+        let pieces = (self.combined_mask
+            & !self.get_piece_type_mask(Pawn)
+            & !self.get_piece_type_mask(King))
+        .count_ones();
+        let is_developed = |color: Color| {
+            (self.get_color_mask(color) & BitBoard::from_rank(color.get_back_rank())).count_ones()
+                < OPENING_MIN_BACK_RANK_PIECES
+        };
+        if pieces <= ENDGAME_MAX_PIECES {
+            GamePhase::Endgame
+        } else if (pieces < OPENING_MIN_PIECES) | is_developed(White) | is_developed(Black) {
+            GamePhase::Middlegame
+        } else {
+            GamePhase::Opening
+        }
+    }
+
+    /// Returns the exact result (from the side to move perspective) if the position is the king
+    /// and pawn vs king endgame, otherwise returns None
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{ChessBoard, WinDrawLoss};
+    /// use std::str::FromStr;
+    /// let board = ChessBoard::from_str("4k3/8/4K3/4P3/8/8/8/8 b - - 0 1").unwrap();
+    /// assert_eq!(board.probe_kpk(), Some(WinDrawLoss::Loss));
+    ///
+    /// let board = ChessBoard::from_str("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1").unwrap();
+    /// assert_eq!(board.probe_kpk(), Some(WinDrawLoss::Win));
+    /// ```
+    pub fn probe_kpk(&self) -> Option<WinDrawLoss> {
+        let pawns = self.get_piece_type_mask(Pawn);
+        if (self.combined_mask.count_ones() != 3) | (pawns.count_ones() != 1) {
+            return None;
+        }
+
+        let pawn = pawns.to_square();
+        let strong_side = self.get_piece_color_on(pawn).unwrap();
+
+        // the strong side is always white with the pawn on files A-D after normalization
+        let flip_rank = if strong_side == White { 0 } else { 56 };
+        let flip_file = if pawn.get_file().to_index() < 4 { 0 } else { 7 };
+        let normalize = |square: Square| {
+            Square::new((square.to_index() ^ flip_rank ^ flip_file) as u8).unwrap()
+        };
+
+        let strong_to_move = self.side_to_move == strong_side;
+        let result = KPK_BITBASE.probe(
+            strong_to_move,
+            normalize(self.get_king_square(strong_side)),
+            normalize(self.get_king_square(!strong_side)),
+            normalize(pawn),
+        );
+
+        Some(match (result, strong_to_move) {
+            (WinDrawLoss::Win, false) => WinDrawLoss::Loss,
+            (result, _) => result,
+        })
+    }
+
+    /// Returns true if current side has at least one legal move
+    #[inline]
+    pub fn is_terminal(&self) -> bool { self.is_terminal_position }
+
+    /// Returns a Vec of all legal moves for current board. The Vec is pre-allocated with the
+    /// capacity ``MAX_LEGAL_MOVES``, so it is never reallocated during the generation
+    ///
+    /// The order of the moves is deterministic and is kept between releases: the moves are
+    /// sorted by ``BoardMove::sort_key`` (by the piece type, then by the source and destination
+    /// squares, the castling moves are the last)
+    #[inline]
+    pub fn get_legal_moves(&self) -> LegalMoves {
+        self.get_legal_moves_with_capacity(MAX_LEGAL_MOVES)
+    }
+
+    /// Returns all legal moves (see ``ChessBoard::get_legal_moves``) in the Vec pre-allocated
+    /// with specified capacity. Allows to size the buffers when the expected number of moves is
+    /// known (e.g. for arena allocators). The Vec grows as usual if the hint is too small
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{ChessBoard, MAX_LEGAL_MOVES};
+    /// let board = ChessBoard::default();
+    /// let moves = board.get_legal_moves_with_capacity(32);
+    /// assert_eq!(moves.len(), 20);
+    /// assert_eq!(moves.capacity(), 32);
+    /// assert_eq!(board.get_legal_moves().capacity(), MAX_LEGAL_MOVES);
+    /// ```
+    pub fn get_legal_moves_with_capacity(&self, capacity_hint: usize) -> LegalMoves {
+        let mut moves = Vec::with_capacity(capacity_hint);
+        let color_mask = self.get_color_mask(self.side_to_move);
+        let check_mask = self.get_check_mask();
+        let king_danger_mask = self.get_king_danger_mask();
+
+        for piece_type in PieceType::iter() {
+            for square in color_mask & self.get_piece_type_mask(piece_type) {
+                self.extend_with_piece_legal_moves(
+                    piece_type,
+                    square,
+                    check_mask,
+                    king_danger_mask,
+                    &mut moves,
+                );
+            }
+        }
+        self.extend_with_castling_moves(check_mask, &mut moves);
+
+        moves
+    }
+
+    /// Returns the legal non-capturing moves which give check (directly or by discovery) without
+    /// generating all quiet moves: the destinations are limited by the squares from which each
+    /// piece type attacks the opponent's king, and only the pieces which shield the line of own
+    /// sliding piece to the king may move anywhere off that line. Promotions are not included
+    /// (as usually they are searched with the captures). The moves are in the order of
+    /// ``ChessBoard::get_legal_moves``
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{mv, squares::*, BoardMove, ChessBoard, PieceMove, PieceType::*};
+    /// use std::str::FromStr;
+    /// // the knight on e4 shields the rook, so any knight's move is a discovered check
+    /// let board = ChessBoard::from_str("4k3/8/8/8/4N3/8/8/4RK2 w - - 0 1").unwrap();
+    /// let checks = board.get_quiet_checks();
+    /// assert_eq!(checks.len(), 8);
+    /// assert!(checks.contains(&mv!(Knight, E4, F6)));
+    /// ```
+    pub fn get_quiet_checks(&self) -> LegalMoves {
+        let mut moves = Vec::new();
+        let color = self.side_to_move;
+        let king_square = self.get_king_square(!color);
+        let color_mask = self.get_color_mask(color);
+        let check_mask = self.get_check_mask();
+        let king_danger_mask = self.get_king_danger_mask();
+        let candidates = self.get_discovered_check_candidates(king_square, color);
+
+        let bishop_checks = Self::get_ray_attacks(king_square, 4..8, self.combined_mask);
+        let rook_checks = Self::get_ray_attacks(king_square, 0..4, self.combined_mask);
+        let quiet_mask = !self.combined_mask;
+        let mut quiet_pawn_mask = quiet_mask & !BitBoard::from_rank(color.get_promotion_rank());
+        if let Some(square) = self.en_passant {
+            quiet_pawn_mask &= !BitBoard::from_square(square);
+        }
+
+        for piece_type in PieceType::iter() {
+            let (direct_checks, quiet_mask) = match piece_type {
+                Pawn => (PAWN.get_captures(king_square, !color), quiet_pawn_mask),
+                Knight => (KNIGHT.get_moves(king_square), quiet_mask),
+                Bishop => (bishop_checks, quiet_mask),
+                Rook => (rook_checks, quiet_mask),
+                Queen => (bishop_checks | rook_checks, quiet_mask),
+                King => (BLANK, quiet_mask),
+            };
+            for square in color_mask & self.get_piece_type_mask(piece_type) {
+                let mut checks_mask = direct_checks;
+                if !(BitBoard::from_square(square) & candidates).is_blank() {
+                    checks_mask |= !self.get_pin_line(king_square, square);
+                }
+                let mut moves_mask =
+                    self.get_piece_moves_mask(piece_type, square) & quiet_mask & checks_mask;
+                if piece_type == King {
+                    moves_mask &= !king_danger_mask;
+                }
+                if !moves_mask.is_blank() {
+                    self.extend_with_piece_moves_in_mask(
+                        piece_type, square, moves_mask, check_mask, &mut moves,
+                    );
+                }
+            }
+        }
+
+        let mut castling_moves = Vec::new();
+        self.extend_with_castling_moves(check_mask, &mut castling_moves);
+        moves.extend(castling_moves.into_iter().filter(|m| {
+            !self
+                .make_move(m)
+                .is_ok_and(|board| board.get_check_mask().is_blank())
+        }));
+
+        moves
+    }
+
+    /// Returns the legal captures (including en passant ones and capturing promotions) without
+    /// generating the quiet moves. The moves are in the order of ``ChessBoard::get_legal_moves``
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{mv, squares::*, BoardMove, ChessBoard, PieceMove, PieceType::*};
+    /// use std::str::FromStr;
+    /// let board = ChessBoard::from_str("4k3/8/3q4/2p5/3P4/8/8/3QK3 w - - 0 1").unwrap();
+    /// assert_eq!(board.get_legal_captures(), vec![mv!(Pawn, D4, C5)]);
+    /// assert_eq!(
+    ///     board.get_legal_captures().len() + board.get_legal_quiets().len(),
+    ///     board.get_legal_moves().len()
+    /// );
+    /// ```
+    #[inline]
+    pub fn get_legal_captures(&self) -> LegalMoves { self.get_legal_moves_of_kind(true) }
+
+    /// Returns the legal non-capturing moves (including castling and non-capturing promotions).
+    /// The moves are in the order of ``ChessBoard::get_legal_moves``
+    #[inline]
+    pub fn get_legal_quiets(&self) -> LegalMoves { self.get_legal_moves_of_kind(false) }
+
+    /// Generates either the legal captures or the legal non-capturing moves by limiting the
+    /// destinations of the pieces with the squares of the opponent's pieces (and the en passant
+    /// square for the pawns)
+    fn get_legal_moves_of_kind(&self, captures: bool) -> LegalMoves {
+        let mut moves = Vec::new();
+        let color_mask = self.get_color_mask(self.side_to_move);
+        let check_mask = self.get_check_mask();
+        let king_danger_mask = self.get_king_danger_mask();
+        let victims = self.get_color_mask(!self.side_to_move);
+        let en_passant = self.en_passant.map_or(BLANK, BitBoard::from_square);
+
+        for piece_type in PieceType::iter() {
+            let captures_mask = match piece_type {
+                Pawn => victims | en_passant,
+                _ => victims,
+            };
+            let kind_mask = match captures {
+                true => captures_mask,
+                false => !captures_mask,
+            };
+            for square in color_mask & self.get_piece_type_mask(piece_type) {
+                let mut moves_mask = self.get_piece_moves_mask(piece_type, square) & kind_mask;
+                if piece_type == King {
+                    moves_mask &= !king_danger_mask;
+                }
+                if !moves_mask.is_blank() {
+                    self.extend_with_piece_moves_in_mask(
+                        piece_type, square, moves_mask, check_mask, &mut moves,
+                    );
+                }
+            }
+        }
+        if !captures {
+            self.extend_with_castling_moves(check_mask, &mut moves);
+        }
+
+        moves
+    }
+
+    /// Returns all legal moves for current board reusing the legal moves of some earlier
+    /// ``previous`` position with the same side to move (usually the position before the last
+    /// two plies). Only the pieces whose moves could be affected by the changes between the
+    /// positions are regenerated: the king, the pieces which have moved or were pinned, and the
+    /// pieces whose lines (or pawn pushes and captures) cross the changed squares. Falls back to
+    /// the full generation if any of positions has a check or en passant square, or if the sides
+    /// to move are different
+    ///
+    /// The result contains the same moves as ``get_legal_moves`` in the same order
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{mv, squares::*, BoardMove, ChessBoard, PieceMove, PieceType::*};
+    /// let previous = ChessBoard::default();
+    /// let previous_moves = previous.get_legal_moves();
+    /// let board = previous
+    ///     .make_move(&mv!(Knight, G1, F3))
+    ///     .unwrap()
+    ///     .make_move(&mv!(Knight, G8, F6))
+    ///     .unwrap();
+    /// let moves = board.update_legal_moves(&previous, &previous_moves);
+    /// assert_eq!(moves.len(), board.get_legal_moves().len());
+    /// ```
+    pub fn update_legal_moves(
+        &self,
+        previous: &ChessBoard,
+        previous_moves: &[BoardMove],
+    ) -> LegalMoves {
+        let check_mask = self.get_check_mask();
+        if (self.side_to_move != previous.side_to_move)
+            | !check_mask.is_blank()
+            | !previous.checks.is_blank()
+            | self.en_passant.is_some()
+            | previous.en_passant.is_some()
+        {
+            return self.get_legal_moves();
+        }
+
+        let mut changed_squares = self.combined_mask ^ previous.combined_mask;
+        for color in [White, Black] {
+            changed_squares |= self.get_color_mask(color) ^ previous.get_color_mask(color);
+        }
+        for piece_type in PieceType::iter() {
+            changed_squares |=
+                self.get_piece_type_mask(piece_type) ^ previous.get_piece_type_mask(piece_type);
+        }
+        let regenerated_squares = changed_squares | self.pinned | previous.pinned;
+
+        let mut moves = Vec::with_capacity(MAX_LEGAL_MOVES);
+        let mut reused_squares = BLANK;
+        let color_mask = self.get_color_mask(self.side_to_move);
+        let king_danger_mask = self.get_king_danger_mask();
+        for piece_type in PieceType::iter() {
+            for square in color_mask & self.get_piece_type_mask(piece_type) {
+                let is_affected = (piece_type == King)
+                    | !(BitBoard::from_square(square) & regenerated_squares).is_blank()
+                    | !(previous.get_piece_dependency_mask(piece_type, square) & changed_squares)
+                        .is_blank();
+                if is_affected {
+                    self.extend_with_piece_legal_moves(
+                        piece_type,
+                        square,
+                        check_mask,
+                        king_danger_mask,
+                        &mut moves,
+                    );
+                } else {
+                    reused_squares |= BitBoard::from_square(square);
+                }
+            }
+        }
+        moves.extend(previous_moves.iter().filter(|board_move| match board_move {
+            BoardMove::MovePiece(m) => {
+                !(BitBoard::from_square(m.get_source_square()) & reused_squares).is_blank()
+            }
+            BoardMove::CastleKingSide | BoardMove::CastleQueenSide => false,
+        }));
+        moves.sort_unstable_by_key(BoardMove::sort_key);
+        self.extend_with_castling_moves(check_mask, &mut moves);
+
+        moves
+    }
+
+    /// Plays the game from this position with the moves chosen randomly according to the policy
+    /// until it is finished or ``max_plies`` moves are made. Returns the final status and the
+    /// moves of the playout. The playout is finished by checkmate, stalemate, insufficient
+    /// material, the 50-move rule and the threefold repetition (counted within the playout
+    /// only). ``GameStatus::Ongoing`` is returned if the playout is cut by ``max_plies`` or the
+    /// policy gives zero weights to all moves
+    ///
+    /// The playout depends only on the position, the policy and the state of the generator, so it
+    /// is reproducible with the seeded one (e.g. ``StdRng::seed_from_u64``). The moves chosen for
+    /// a given seed are not guaranteed to be the same between releases
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{ChessBoard, Color::*, GameStatus, PlayoutPolicy};
+    /// use rand::rngs::StdRng;
+    /// use rand::SeedableRng;
+    ///
+    /// let board = ChessBoard::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    /// let mut rng = StdRng::seed_from_u64(0);
+    /// let policy = PlayoutPolicy::new(1.0, 1000.0); // checks are almost always chosen
+    /// let (status, moves) = board.random_playout(&policy, &mut rng, 1);
+    /// assert_eq!(status, GameStatus::CheckMated(Black));
+    /// assert_eq!(moves.len(), 1);
+    /// ```
+    pub fn random_playout(
+        &self,
+        policy: &PlayoutPolicy,
+        rng: &mut impl Rng,
+        max_plies: usize,
+    ) -> (GameStatus, Vec<BoardMove>) {
+        let mut board = *self;
+        let mut moves = Vec::new();
+        let mut repetitions: HashMap<PositionHashValueType, usize> = HashMap::new();
+        repetitions.insert(board.hash, 1);
+
+        loop {
+            let status = GameStatus::from(board.get_status());
+            if (status != GameStatus::Ongoing) | (moves.len() >= max_plies) {
+                return (status, moves);
+            }
+
+            let Some(next_move) = policy.choose(&board, &board.get_legal_moves(), rng) else {
+                return (status, moves);
+            };
+            // the move is chosen from the legal moves
+            unsafe { board.make_move_mut_unchecked(&next_move) };
+            moves.push(next_move);
+
+            let count = repetitions.entry(board.hash).or_insert(0);
+            *count += 1;
+            if *count >= PLAYOUT_REPETITIONS {
+                return (GameStatus::RepetitionDrawDeclared, moves);
+            }
+        }
+    }
+
+    /// Returns the Zobrist hash of the position after the move without making it: only the
+    /// changes of the hash are computed. The move is not checked for legality
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{mv, squares::*, BoardMove, ChessBoard, PieceMove, PieceType::*};
+    /// let board = ChessBoard::default();
+    /// let m = mv!(Knight, G1, F3);
+    /// assert_eq!(
+    ///     board.get_child_hash(&m),
+    ///     board.make_move(&m).unwrap().get_hash()
+    /// );
+    /// ```
+    pub fn get_child_hash(&self, board_move: &BoardMove) -> PositionHashValueType {
+        use File::*;
+
+        let color = self.side_to_move;
+        let back_rank = color.get_back_rank();
+        let on_back_rank = |file| Square::from_rank_file(back_rank, file);
+        let mut hash = self.hash ^ ZOBRIST.get_black_to_move_value();
+        if self.is_en_passant_capturable() {
+            hash ^= ZOBRIST.get_en_passant_value(self.en_passant.unwrap());
+        }
+        let mut move_piece = |piece_type, source, destination| {
+            hash ^= ZOBRIST.get_piece_square_value(Piece(piece_type, color), source)
+                ^ ZOBRIST.get_piece_square_value(Piece(piece_type, color), destination);
+        };
+
+        let board_move = self.normalize_castling(board_move);
+        let (source, destination) = match board_move {
+            BoardMove::MovePiece(m) => (m.get_source_square(), m.get_destination_square()),
+            _ => (on_back_rank(E), on_back_rank(E)),
+        };
+        match board_move {
+            BoardMove::CastleKingSide => {
+                move_piece(King, on_back_rank(E), on_back_rank(G));
+                move_piece(Rook, on_back_rank(H), on_back_rank(F));
+            }
+            BoardMove::CastleQueenSide => {
+                move_piece(King, on_back_rank(E), on_back_rank(C));
+                move_piece(Rook, on_back_rank(A), on_back_rank(D));
+            }
+            BoardMove::MovePiece(m) => {
+                let piece_type = m.get_piece_type();
+                hash ^= ZOBRIST.get_piece_square_value(Piece(piece_type, color), source)
+                    ^ ZOBRIST.get_piece_square_value(
+                        Piece(m.get_promotion().unwrap_or(piece_type), color),
+                        destination,
+                    );
+                if let Some(captured) = self.get_piece_on(destination) {
+                    hash ^= ZOBRIST.get_piece_square_value(captured, destination);
+                }
+                if m.is_en_passant_move(self) {
+                    let captured_square =
+                        Square::from_rank_file(source.get_rank(), destination.get_file());
+                    hash ^= ZOBRIST.get_piece_square_value(Piece(Pawn, !color), captured_square);
+                }
+                if m.is_double_push() {
+                    let en_passant = Square::from_rank_file(
+                        Rank::from_index(
+                            (source.get_rank().to_index() + destination.get_rank().to_index()) / 2,
+                        )
+                        .unwrap(),
+                        destination.get_file(),
+                    );
+                    let capturing_pawns = PAWN.get_captures(en_passant, color)
+                        & self.get_piece_type_mask(Pawn)
+                        & self.get_color_mask(!color);
+                    if !capturing_pawns.is_blank() {
+                        hash ^= ZOBRIST.get_en_passant_value(en_passant);
+                    }
+                }
+            }
+        }
+
+        for color in Color::iter() {
+            let lost = CastlingRights::get_lost_on(color, source)
+                + CastlingRights::get_lost_on(color, destination);
+            let rights = self.get_castle_rights(color);
+            if (lost != Neither) & (rights - lost != rights) {
+                hash ^= ZOBRIST.get_castling_rights_value(rights, color)
+                    ^ ZOBRIST.get_castling_rights_value(rights - lost, color);
+            }
+        }
+        hash
+    }
+
+    /// Returns all legal moves with the hashes of the positions after them (see
+    /// ``ChessBoard::get_child_hash``). Allows to look up the children in the transposition
+    /// table without making the moves
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::ChessBoard;
+    /// let board = ChessBoard::default();
+    /// for (m, hash) in board.get_successors() {
+    ///     assert_eq!(hash, board.make_move(&m).unwrap().get_hash());
+    /// }
+    /// ```
+    pub fn get_successors(&self) -> Vec<(BoardMove, PositionHashValueType)> {
+        self.get_legal_moves()
+            .into_iter()
+            .map(|m| (m, self.get_child_hash(&m)))
+            .collect()
+    }
+
+    /// Returns the number of legal moves for current board. Works the same as
+    /// ``board.get_legal_moves().len()`` but is much faster because the moves are counted by
+    /// destination masks without being generated
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::ChessBoard;
+    /// let board = ChessBoard::default();
+    /// assert_eq!(board.count_legal_moves(), 20);
+    /// ```
+    pub fn count_legal_moves(&self) -> usize {
+        let king_square = self.get_king_square(self.side_to_move);
+        let king_moves =
+            self.get_piece_moves_mask(King, king_square) & !self.get_king_danger_mask();
+        let mut count = king_moves.count_ones() as usize;
+
+        // only the king can move in case of double check
+        let target_mask = match self.checks.count_ones() {
+            0 => !BLANK,
+            1 => {
+                let checker = self.checks.to_square();
+                self.checks | BETWEEN.get(king_square, checker).unwrap_or(BLANK)
+            }
+            _ => return count,
+        };
+
+        let color_mask = self.get_color_mask(self.side_to_move);
+        let promotion_rank = self.side_to_move.get_promotion_rank();
+        for piece_type in PieceType::iter().filter(|&pt| pt != King) {
+            for square in color_mask & self.get_piece_type_mask(piece_type) {
+                let mut moves_mask = self.get_piece_moves_mask(piece_type, square);
+                if !(BitBoard::from_square(square) & self.pinned).is_blank() {
+                    moves_mask &= self.get_pin_line(king_square, square);
+                }
+
+                if piece_type != Pawn {
+                    count += (moves_mask & target_mask).count_ones() as usize;
+                    continue;
+                }
+
+                // en passant capture may expose the king in many ways, so it is checked directly
+                if let Some(ep) = self.en_passant {
+                    let ep_mask = BitBoard::from_square(ep);
+                    if !(moves_mask & ep_mask).is_blank()
+                        && self
+                            .get_check_mask_after_piece_move(
+                                &PieceMove::new(Pawn, square, ep, None).unwrap(),
+                            )
+                            .is_blank()
+                    {
+                        count += 1;
+                    }
+                    moves_mask &= !ep_mask;
+                }
+                for destination in moves_mask & target_mask {
+                    count += if destination.get_rank() == promotion_rank { 4 } else { 1 };
+                }
+            }
+        }
+
+        count
+            + match self.castling_is_available_on_board(Some(self.checks)) {
+                BothSides => 2,
+                KingSide | QueenSide => 1,
+                Neither => 0,
+            }
+    }
+
+    /// Returns the flags of the move (capture, double push, en passant, promotion, castling) on
+    /// this board. The move is not checked for legality
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{mv, squares::*, BoardMove, ChessBoard, PieceMove, PieceType::*};
+    /// let board = ChessBoard::default();
+    /// let flags = board.get_move_flags(&mv!(Pawn, E2, E4));
+    /// assert!(flags.is_double_push & !flags.is_capture);
+    /// ```
+    #[inline]
+    pub fn get_move_flags(&self, board_move: &BoardMove) -> MoveFlags {
+        MoveFlags::new(board_move, self)
+    }
+
+    /// Returns the Zobrist-hash of the position. Is used to detect the repetition draw
+    #[inline]
+    pub fn get_hash(&self) -> PositionHashValueType { self.hash }
+
+    /// Returns position status on the board
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{BoardStatus::*, ChessBoard, Color::*};
+    /// let board = ChessBoard::from_fen("Q4k2/8/5K2/8/8/8/8/8 b - - 0 1").unwrap();
+    /// assert_eq!(board.get_status(), CheckMated(Black));
+    /// ```
+    pub fn get_status(&self) -> BoardStatus {
+        if self.is_terminal_position {
+            if self.checks.count_ones() > 0 {
+                BoardStatus::CheckMated(self.side_to_move)
+            } else {
+                BoardStatus::Stalemate
+            }
+        } else if self.is_theoretical_draw_on_board() {
+            BoardStatus::TheoreticalDrawDeclared
+        } else if self.get_moves_since_capture_or_pawn_move() >= FIFTY_MOVES_PLIES {
+            BoardStatus::FiftyMovesDrawDeclared
+        } else {
+            BoardStatus::Ongoing
+        }
+    }
+
+    /// Returns the report of all draw conditions which are met in the position. The move counter
+    /// conditions are not reported for the checkmate position, because the checkmate has the
+    /// priority over them
+    pub fn draw_report(&self) -> DrawReport {
+        let is_checkmate = self.is_terminal_position & (self.checks.count_ones() > 0);
+        let moves_since_capture = self.get_moves_since_capture_or_pawn_move();
+        DrawReport {
+            fifty_move_claimable: !is_checkmate & (moves_since_capture >= FIFTY_MOVES_PLIES),
+            seventy_five_forced: !is_checkmate & (moves_since_capture >= SEVENTY_FIVE_MOVES_PLIES),
+            insufficient_material: self.is_theoretical_draw_on_board(),
+            stalemate: self.is_terminal_position & (self.checks.count_ones() == 0),
+        }
+    }
+
+    /// Check sufficiency for both sides to checkmate each other. Is used to determine theoretical
+    /// draws
+    pub fn is_theoretical_draw_on_board(&self) -> bool {
+        let white_pieces_number = self.get_color_mask(White).count_ones();
+        let black_pieces_number = self.get_color_mask(Black).count_ones();
+
+        if (white_pieces_number > 2) | (black_pieces_number > 2) {
+            return false;
+        }
+
+        let bishops_and_knights =
+            self.get_piece_type_mask(Knight) | self.get_piece_type_mask(Bishop);
+
+        let white_can_not_checkmate = match white_pieces_number {
+            1 => true, // Only white king is on the board
+            2 => !(self.get_color_mask(White) & bishops_and_knights).is_blank(), /* only white king and white bishop or knight are on the board */
+            _ => unreachable!(),
+        };
+        let black_can_not_checkmate = match black_pieces_number {
+            1 => true, // Only black king is on the board
+            2 => !(self.get_color_mask(Black) & bishops_and_knights).is_blank(), /* only black king and white black or knight are on the board */
+            _ => unreachable!(),
+        };
+
+        white_can_not_checkmate & black_can_not_checkmate
+    }
+
+    /// Represents chess moves in short mode without ambiguities in PGN-like strings
+    pub fn get_move_ambiguity_type(
+        &self,
+        piece_move: &PieceMove,
+    ) -> Result<DisplayAmbiguityType, Error> {
+        use DisplayAmbiguityType::*;
+
+        if !self.is_legal_move(&BoardMove::MovePiece(*piece_move)) {
+            return Err(Error::IllegalMoveDetected);
+        }
+
+        let piece_type = piece_move.get_piece_type();
+        let source = piece_move.get_source_square();
+        let destination = piece_move.get_destination_square();
+
+        if piece_type == Pawn {
+            if source.get_file() != destination.get_file() {
+                return Ok(ExtraFile);
+            }
+        } else if piece_type == King {
+            return Ok(Neither);
+        } else {
+            let piece_moves = match piece_type {
+                Knight => KNIGHT.get_moves(destination),
+                Bishop => BISHOP.get_moves(destination),
+                Rook => ROOK.get_moves(destination),
+                Queen => QUEEN.get_moves(destination),
+                _ => unreachable!(),
+            };
+
+            let between_filter = |x: &Square| match piece_type {
+                Knight => true,
+                _ => BETWEEN
+                    .get(*x, piece_move.get_destination_square())
+                    .map_or(BLANK, |x| x & self.combined_mask)
+                    .is_blank(),
+            };
+
+            let pieces_mask =
+                self.get_piece_type_mask(piece_type) & self.get_color_mask(self.side_to_move);
+            if (piece_moves & pieces_mask).filter(between_filter).count() > 1 {
+                if (BitBoard::from_file(source.get_file()) & pieces_mask).count_ones() > 1 {
+                    return Ok(ExtraRank);
+                } else {
+                    return Ok(ExtraFile);
+                }
+            }
+        }
+
+        Ok(Neither)
+    }
+
+    /// The method which allows to make moves on the board. Modifies the board object if the move
+    /// is legal
+    ///
+    /// The simplest way to generate moves is by picking one from a set of available moves:
+    /// ``board.get_legal_moves()`` or by simply creating a new move via macros: ``mv!()``,
+    /// ``castle_king_side!()`` and ``castle_queen_side!()``
+    ///
+    /// # Errors
+    /// ``LibChessError::MissingPromotionPiece`` if the pawn moves to the last rank without the
+    /// promotion piece
+    ///
+    /// ``LibChessError::UnexpectedPromotionPiece`` if the promotion piece is specified for any
+    /// other move
+    ///
+    /// ``LibChessError::IllegalMoveDetected`` if specified move is not legal
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::PieceType::*;
+    /// use libchess::{castle_king_side, castle_queen_side, mv};
+    /// use libchess::{squares::*, BoardMove, ChessBoard, PieceMove};
+    ///
+    /// let mut board = ChessBoard::default();
+    /// board.make_move(&mv!(Pawn, E2, E4)).unwrap();
+    /// println!("{}", board);
+    /// ```
+    pub fn make_move_mut(&mut self, next_move: &BoardMove) -> Result<&mut Self, Error> {
+        let next_move = &self.validate_move(next_move)?;
+        unsafe { Ok(self.make_move_mut_unchecked(next_move)) }
+    }
+
+    /// The unsafe version of ``ChessBoard::make_move_mut`` method. It does not perform the check if
+    /// the move is legal or not. It is only useful for performance reasons during the process of
+    /// engine search of the best move. Often used in pair with ``ChessBoard::get_legal_moves``
+    /// which guaranties that any provided move will be legal one
+    ///
+    /// # Safety
+    ///
+    /// Be very careful while using this method because in case of illegal ``next_move`` it
+    /// can lead to application panic or to unpredictable changes of the position. If you are not
+    /// 100% sure that ``next_move`` will be a legal move - use ``ChessBoard::make_move_mut``
+    /// instead
+    pub unsafe fn make_move_mut_unchecked(&mut self, next_move: &BoardMove) -> &mut Self {
+        self.apply_move(next_move, &mut ())
+    }
+
+    /// Makes the move without legality checks notifying the backend about every piece put on or
+    /// removed from the board
+    fn apply_move(
+        &mut self,
+        next_move: &BoardMove,
+        backend: &mut impl EvaluatorBackend,
+    ) -> &mut Self {
+        use File::*;
+
+        // the en-passant square must leave the hash while the position is the same as when it was
+        // added there
+        if self.is_en_passant_capturable() {
+            self.hash ^= ZOBRIST.get_en_passant_value(self.en_passant.unwrap());
+        }
+
+        match next_move {
+            BoardMove::MovePiece(m) => {
+                self.move_piece(m, backend)
+                    .clear_square_if_en_passant_capture(m, backend);
+            }
+            BoardMove::CastleKingSide => {
+                let back_rank = self.side_to_move.get_back_rank();
+                self.move_piece(
+                    &PieceMove::new(
+                        King,
+                        Square::from_rank_file(back_rank, E),
+                        Square::from_rank_file(back_rank, G),
+                        None,
+                    )
+                    .unwrap(),
+                    backend,
+                );
+                self.move_piece(
+                    &PieceMove::new(
+                        Rook,
+                        Square::from_rank_file(back_rank, H),
+                        Square::from_rank_file(back_rank, F),
+                        None,
+                    )
+                    .unwrap(),
+                    backend,
+                );
+            }
+            BoardMove::CastleQueenSide => {
+                let back_rank = self.side_to_move.get_back_rank();
+                self.move_piece(
+                    &PieceMove::new(
+                        PieceType::King,
+                        Square::from_rank_file(back_rank, E),
+                        Square::from_rank_file(back_rank, C),
+                        None,
+                    )
+                    .unwrap(),
+                    backend,
+                );
+                self.move_piece(
+                    &PieceMove::new(
+                        Rook,
+                        Square::from_rank_file(back_rank, A),
+                        Square::from_rank_file(back_rank, D),
+                        None,
+                    )
+                    .unwrap(),
+                    backend,
+                );
+            }
+        }
+
+        let opposite_side = !self.side_to_move;
+        self.update_move_number()
+            .update_moves_since_capture(next_move)
+            .update_castling_rights(next_move)
+            .set_side_to_move(opposite_side)
+            .update_en_passant(next_move)
+            .update_pins_and_checks()
+            .update_terminal_status();
+
+        self
+    }
+
+    /// The method which allows to make moves on the board. Returns a new board instance
+    /// if the move is legal
+    ///
+    /// The simplest way to generate moves is by picking one from a set of available moves:
+    /// ``board.get_legal_moves()`` or by simply creating a new move via macros: ``mv!()``,
+    /// ``castle_king_side!()`` and ``castle_queen_side!()``
+    ///
+    /// # Errors
+    /// ``LibChessError::MissingPromotionPiece`` if the pawn moves to the last rank without the
+    /// promotion piece
+    ///
+    /// ``LibChessError::UnexpectedPromotionPiece`` if the promotion piece is specified for any
+    /// other move
+    ///
+    /// ``LibChessError::IllegalMoveDetected`` if specified move is not legal
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::PieceType::*;
+    /// use libchess::{castle_king_side, castle_queen_side, mv};
+    /// use libchess::{squares::*, BoardMove, ChessBoard, PieceMove};
+    ///
+    /// let board = ChessBoard::default();
+    /// let next_board = board.make_move(&mv!(Pawn, E2, E4)).unwrap();
+    /// println!("{}", next_board);
+    /// ```
+    pub fn make_move(&self, next_move: &BoardMove) -> Result<Self, Error> {
+        let mut next_board = *self;
+        next_board.make_move_mut(next_move)?;
+        Ok(next_board)
+    }
+
+    /// The same as ``ChessBoard::make_move``, but also checks that the move is of the kind the
+    /// caller expects (e.g. the client believes the move is a capture). Allows to catch the
+    /// desynchronization of the client's and the server's positions
+    ///
+    /// # Errors
+    /// ``LibChessError::IntentMismatch`` if the move is of the other kind in this position
+    ///
+    /// The same errors as ``ChessBoard::make_move``
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{mv, squares::*, BoardMove, ChessBoard, MoveIntent, PieceMove, PieceType::*};
+    /// let board = ChessBoard::default();
+    /// assert!(board
+    ///     .make_move_with_intent(&mv!(Pawn, E2, E4), MoveIntent::DoublePush)
+    ///     .is_ok());
+    /// assert!(board
+    ///     .make_move_with_intent(&mv!(Knight, G1, F3), MoveIntent::Capture)
+    ///     .is_err());
+    /// ```
+    pub fn make_move_with_intent(
+        &self,
+        next_move: &BoardMove,
+        intent: MoveIntent,
+    ) -> Result<Self, Error> {
+        next_move.check_intent(self, intent)?;
+        self.make_move(next_move)
+    }
+
+    /// The same as ``ChessBoard::make_move``, but also returns the description of what the move
+    /// did: the captured piece, en passant, castling side, promotion, and the status and the hash
+    /// of the new board
+    ///
+    /// # Errors
+    /// The same errors as ``ChessBoard::make_move``
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{mv, squares::*, BoardMove, ChessBoard, Color::*, Piece, PieceMove};
+    /// use libchess::{BoardStatus, PieceType::*};
+    /// let board = ChessBoard::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+    /// let (next_board, outcome) = board.make_move_with_outcome(&mv!(Pawn, E5, D6)).unwrap();
+    /// assert_eq!(outcome.captured, Some((Piece(Pawn, Black), D5)));
+    /// assert!(outcome.is_en_passant);
+    /// assert_eq!(outcome.status, BoardStatus::Ongoing);
+    /// assert_eq!(outcome.hash, next_board.get_hash());
+    /// ```
+    pub fn make_move_with_outcome(
+        &self,
+        next_move: &BoardMove,
+    ) -> Result<(Self, MoveOutcome), Error> {
+        let next_board = self.make_move(next_move)?;
+        let (captured, is_en_passant, castling, promotion) =
+            match self.normalize_castling(next_move) {
+                BoardMove::CastleKingSide => (None, false, Some(KingSide), None),
+                BoardMove::CastleQueenSide => (None, false, Some(QueenSide), None),
+                BoardMove::MovePiece(m) => {
+                    let destination = m.get_destination_square();
+                    let is_en_passant = m.is_en_passant_move(self);
+                    let captured = if is_en_passant {
+                        let square = Square::from_rank_file(
+                            m.get_source_square().get_rank(),
+                            destination.get_file(),
+                        );
+                        Some((Piece(Pawn, !self.side_to_move), square))
+                    } else {
+                        self.get_piece_on(destination)
+                            .map(|piece| (piece, destination))
+                    };
+                    (captured, is_en_passant, None, m.get_promotion())
+                }
+            };
+
+        let outcome = MoveOutcome {
+            captured,
+            is_en_passant,
+            castling,
+            promotion,
+            status: next_board.get_status(),
+            hash: next_board.get_hash(),
+        };
+        Ok((next_board, outcome))
+    }
+
+    /// The same as ``ChessBoard::make_move``, but also notifies the backend about every piece
+    /// put on or removed from the board by the move (the capture, castling and promotion consist
+    /// of several such changes). The backend must be synchronized with this board before (see
+    /// ``ChessBoard::load_into_backend``)
+    ///
+    /// # Errors
+    /// The same errors as ``ChessBoard::make_move``. The backend is not notified if the move is
+    /// not made
+    #[cfg(feature = "evaluator-backend")]
+    pub fn make_move_with_backend(
+        &self,
+        next_move: &BoardMove,
+        backend: &mut impl EvaluatorBackend,
+    ) -> Result<Self, Error> {
+        let next_move = &self.validate_move(next_move)?;
+        let mut next_board = *self;
+        next_board.apply_move(next_move, backend);
+        Ok(next_board)
+    }
+
+    /// Notifies the backend about all pieces on the board as if they were put on the empty board
+    #[cfg(feature = "evaluator-backend")]
+    pub fn load_into_backend(&self, backend: &mut impl EvaluatorBackend) {
+        for square in self.combined_mask {
+            backend.on_piece_added(self.get_piece_on(square).unwrap(), square);
+        }
+    }
+
+    /// Converts the move of the ``chess`` crate to the move on this board. The king moves to
+    /// the castling squares are converted to the castling moves
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidBoardMoveRepresentation`` if the source square is empty. The move
+    /// is not checked to be legal
+    #[cfg(feature = "chess")]
+    pub fn parse_chess_move(&self, chess_move: chess::ChessMove) -> Result<BoardMove, Error> {
+        let source = Square::from(chess_move.get_source());
+        let piece_type = self
+            .get_piece_type_on(source)
+            .ok_or(Error::InvalidBoardMoveRepresentation)?;
+        let piece_move = PieceMove::new(
+            piece_type,
+            source,
+            chess_move.get_dest().into(),
+            chess_move.get_promotion().map(PieceType::from),
+        )?;
+        Ok(self.normalize_castling(&BoardMove::MovePiece(piece_move)))
+    }
+
+    /// Returns the source and destination squares and the promotion piece of the move. Castling
+    /// is represented as the move of the king by two squares
+    fn get_move_squares(&self, board_move: &BoardMove) -> (Square, Square, Option<PieceType>) {
+        let king_square = self.get_king_square(self.side_to_move);
+        let castling_square =
+            |file| Square::from_rank_file(self.side_to_move.get_back_rank(), file);
+        match board_move {
+            BoardMove::MovePiece(m) => (
+                m.get_source_square(),
+                m.get_destination_square(),
+                m.get_promotion(),
+            ),
+            BoardMove::CastleKingSide => (king_square, castling_square(File::G), None),
+            BoardMove::CastleQueenSide => (king_square, castling_square(File::C), None),
+        }
+    }
+
+    /// Converts the move on this board to the move of the ``chess`` crate (castling is
+    /// represented as the king move)
+    #[cfg(feature = "chess")]
+    pub fn to_chess_move(&self, board_move: &BoardMove) -> chess::ChessMove {
+        let (source, destination, promotion) = self.get_move_squares(board_move);
+        chess::ChessMove::new(
+            source.into(),
+            destination.into(),
+            promotion.map(chess::Piece::from),
+        )
+    }
+
+    /// Returns the index of the move in the AlphaZero-style policy table of ``POLICY_SIZE``
+    /// items (see ``policy_encodings`` module for the layout). The castling is encoded as the
+    /// move of the king by two squares. Returns None if the move can't be encoded (the legal
+    /// moves are always encoded)
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{mv, squares::*, BoardMove, ChessBoard, PieceMove, PieceType::*};
+    /// let board = ChessBoard::default();
+    /// // the knight move to the 2 ranks up and 1 file right from G1
+    /// assert_eq!(
+    ///     board.get_policy_index(&mv!(Knight, G1, F3)),
+    ///     Some(63 * 64 + 6)
+    /// );
+    ///
+    /// // the same move of black has the same index
+    /// let board = board.apply_san_line("e4").unwrap();
+    /// assert_eq!(
+    ///     board.get_policy_index(&mv!(Knight, G8, F6)),
+    ///     Some(63 * 64 + 6)
+    /// );
+    /// ```
+    pub fn get_policy_index(&self, board_move: &BoardMove) -> Option<usize> {
+        let (source, destination, promotion) = self.get_move_squares(board_move);
+        policy_encodings::encode_move(source, destination, promotion, self.side_to_move)
+    }
+
+    /// Returns the legal move which has specified index in the policy table (see
+    /// ``ChessBoard::get_policy_index``) or None if there is no such legal move
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::ChessBoard;
+    /// let board = ChessBoard::default();
+    /// let m = board.get_policy_move(63 * 64 + 6).unwrap();
+    /// assert_eq!(m, board.parse_san("Nf3").unwrap());
+    /// assert_eq!(board.get_policy_move(0), None);
+    /// ```
+    pub fn get_policy_move(&self, index: usize) -> Option<BoardMove> {
+        self.get_legal_moves()
+            .into_iter()
+            .find(|m| self.get_policy_index(m) == Some(index))
+    }
+
+    /// Returns the mask of the legal moves in the policy table (see
+    /// ``ChessBoard::get_policy_index``), which is used to mask out the illegal moves of the
+    /// network's output
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{ChessBoard, POLICY_SIZE};
+    /// let mask = ChessBoard::default().get_policy_mask();
+    /// assert_eq!(mask.len(), POLICY_SIZE);
+    /// assert_eq!(mask.iter().filter(|&&legal| legal).count(), 20);
+    /// ```
+    pub fn get_policy_mask(&self) -> Vec<bool> {
+        let mut mask = vec![false; POLICY_SIZE];
+        for m in self.get_legal_moves() {
+            mask[self.get_policy_index(&m).unwrap()] = true;
+        }
+        mask
+    }
+
+    /// The unsafe version of ``ChessBoard::make_move`` method. It does not perform the check if
+    /// the move is legal or not. It is only useful for performance reasons during the process of
+    /// engine search of the best move. Often used in pair with ``ChessBoard::get_legal_moves``
+    /// which guaranties that any provided move will be legal one
+    ///
+    /// # Safety
+    ///
+    /// Be very careful while using this method because in case of illegal ``next_move`` it
+    /// can lead to application panic or to unpredictable changes of the position. If you are not
+    /// 100% sure that ``next_move`` will be a legal move - use ``ChessBoard::make_move`` instead
+    pub unsafe fn make_move_unchecked(&self, next_move: &BoardMove) -> Self {
+        let mut next_board = *self;
+        next_board.make_move_mut_unchecked(next_move);
+        next_board
+    }
+
+    /// Extends ``moves`` with the legal moves of the piece on the square
+    fn extend_with_piece_legal_moves(
+        &self,
+        piece_type: PieceType,
+        square: Square,
+        check_mask: BitBoard,
+        king_danger_mask: BitBoard,
+        moves: &mut LegalMoves,
+    ) {
+        let mut moves_mask = self.get_piece_moves_mask(piece_type, square);
+        if piece_type == King {
+            moves_mask &= !king_danger_mask;
+        }
+        self.extend_with_piece_moves_in_mask(piece_type, square, moves_mask, check_mask, moves);
+    }
+
+    /// Extends ``moves`` with the moves of the piece to the squares of ``moves_mask`` (which must
+    /// be a subset of the pseudo-legal destinations, the king's destinations must be safe) which
+    /// don't leave the king in check
+    fn extend_with_piece_moves_in_mask(
+        &self,
+        piece_type: PieceType,
+        square: Square,
+        moves_mask: BitBoard,
+        check_mask: BitBoard,
+        moves: &mut LegalMoves,
+    ) {
+        let piece_moves = moves_mask
+            .map(|s| PieceMove::new(piece_type, square, s, None).unwrap())
+            .filter(|pm| {
+                if piece_type == King {
+                    return true;
+                }
+                if !check_mask.is_blank()
+                    | pm.is_en_passant_move(self)
+                    | !(BitBoard::from_square(pm.get_source_square()) & self.pinned).is_blank()
+                {
+                    return self.get_check_mask_after_piece_move(pm).is_blank();
+                }
+                true
+            });
+
+        if piece_type == Pawn {
+            piece_moves.for_each(|m| {
+                let destination = m.get_destination_square();
+                let promotion_rank = self.side_to_move.get_promotion_rank();
+                if destination.get_rank() == promotion_rank {
+                    // Generate promotion moves
+                    let (s, d) = (m.get_source_square(), destination);
+                    moves.extend_from_slice(&[
+                        mv!(Pawn, s, d, Knight),
+                        mv!(Pawn, s, d, Bishop),
+                        mv!(Pawn, s, d, Rook),
+                        mv!(Pawn, s, d, Queen),
+                    ]);
+                } else {
+                    moves.push(BoardMove::MovePiece(m));
+                }
+            })
+        } else {
+            moves.extend(piece_moves.map(BoardMove::MovePiece));
+        }
+    }
+
+    /// Extends ``moves`` with the legal castling moves
+    fn extend_with_castling_moves(&self, check_mask: BitBoard, moves: &mut LegalMoves) {
+        moves.extend_from_slice(
+            match self.castling_is_available_on_board(Some(check_mask)) {
+                QueenSide => &[castle_queen_side!()],
+                KingSide => &[castle_king_side!()],
+                BothSides => &[castle_king_side!(), castle_queen_side!()],
+                Neither => &[],
+            },
+        );
+    }
+
+    /// Returns the squares which contents define the moves of the piece on the square (the
+    /// blockers of sliding pieces are included)
+    fn get_piece_dependency_mask(&self, piece_type: PieceType, square: Square) -> BitBoard {
+        match piece_type {
+            Pawn => {
+                PAWN.get_moves(square, self.side_to_move)
+                    | PAWN.get_double_moves(square, self.side_to_move)
+                    | PAWN.get_captures(square, self.side_to_move)
+            }
+            Knight => KNIGHT.get_moves(square),
+            King => KING.get_moves(square),
+            Bishop => Self::get_ray_attacks(square, 4..8, self.combined_mask),
+            Rook => Self::get_ray_attacks(square, 0..4, self.combined_mask),
+            Queen => Self::get_ray_attacks(square, 0..8, self.combined_mask),
+        }
+    }
+
+    fn get_piece_moves_mask(&self, piece_type: PieceType, square: Square) -> BitBoard {
+        let color_mask = self.get_color_mask(self.side_to_move);
+
+        match piece_type {
+            Pawn => {
+                let ep = self.get_en_passant().map_or(BLANK, BitBoard::from_square);
+                let capturing_squares = self.get_color_mask(!self.side_to_move) | ep;
+                let single_move = PAWN.get_moves(square, self.side_to_move) & !self.combined_mask;
+                let double_move = if single_move.is_blank() {
+                    BLANK
+                } else {
+                    PAWN.get_double_moves(square, self.side_to_move) & !self.combined_mask
+                };
+
+                single_move
+                    | double_move
+                    | (PAWN.get_captures(square, self.side_to_move) & capturing_squares)
+            }
+            Knight => KNIGHT.get_moves(square) & !color_mask,
+            King => KING.get_moves(square) & !color_mask,
+            Bishop => Self::get_ray_attacks(square, 4..8, self.combined_mask) & !color_mask,
+            Rook => Self::get_ray_attacks(square, 0..4, self.combined_mask) & !color_mask,
+            Queen => Self::get_ray_attacks(square, 0..8, self.combined_mask) & !color_mask,
+        }
+    }
+
+    /// Returns squares attacked by sliding piece along specified rays (0..4 - rook rays, 4..8 -
+    /// bishop rays) considering ``occupancy`` as blockers
+    fn get_ray_attacks(square: Square, rays: Range<usize>, occupancy: BitBoard) -> BitBoard {
+        let mut attacks = BLANK;
+        rays.for_each(|i| {
+            let ray = RAYS.get(square)[i];
+            attacks ^= match i {
+                0 | 2 | 4 | 5 => (ray & occupancy).last_bit_square(),
+                1 | 3 | 6 | 7 => (ray & occupancy).first_bit_square(),
+                _ => unreachable!(),
+            }
+            .map_or(ray, |s| {
+                BETWEEN.get(square, s).unwrap() ^ BitBoard::from_square(s)
+            });
+        });
+        attacks
+    }
+
+    /// Returns the squares in front of the pawn of specified color on its own and adjacent files
+    fn get_front_span(square: Square, color: Color) -> BitBoard {
+        let file = square.get_file();
+        let files = [file.left(), Ok(file), file.right()]
+            .into_iter()
+            .flatten()
+            .fold(BLANK, |acc, f| acc | BitBoard::from_file(f));
+        let rank = square.get_rank().to_index();
+        let ranks = match color {
+            White => rank + 1..RANKS_NUMBER,
+            Black => 0..rank,
+        };
+        ranks.fold(BLANK, |acc, r| {
+            acc | BitBoard::from_rank(Rank::from_index(r).unwrap())
+        }) & files
+    }
+
+    /// Returns the squares of each file which are closer to the side than all the opponent's
+    /// pawns on the file (the side's half of the board for the files without them)
+    fn get_space_zone(color: Color, opponent_pawns: BitBoard) -> BitBoard {
+        let ranks_mask = |ranks: Range<usize>| {
+            ranks.fold(BLANK, |acc, r| {
+                acc | BitBoard::from_rank(Rank::from_index(r).unwrap())
+            })
+        };
+        FILES.iter().fold(BLANK, |zone, &file| {
+            let file_mask = BitBoard::from_file(file);
+            let pawns = opponent_pawns & file_mask;
+            let ranks = match (color, pawns.first_bit_square(), pawns.last_bit_square()) {
+                (White, Some(square), _) => 0..square.get_rank().to_index(),
+                (Black, _, Some(square)) => square.get_rank().to_index() + 1..RANKS_NUMBER,
+                (White, ..) => 0..RANKS_NUMBER / 2,
+                (Black, ..) => RANKS_NUMBER / 2..RANKS_NUMBER,
+            };
+            zone | (ranks_mask(ranks) & file_mask)
+        })
+    }
+
+    /// Returns squares attacked by pieces of specified color considering ``occupancy`` as
+    /// blockers for sliding pieces
+    fn get_attacks_mask(&self, color: Color, occupancy: BitBoard) -> BitBoard {
+        let color_mask = self.get_color_mask(color);
+        let bishops_and_queens = self.get_piece_type_mask(Bishop) | self.get_piece_type_mask(Queen);
+        let rooks_and_queens = self.get_piece_type_mask(Rook) | self.get_piece_type_mask(Queen);
+
+        let mut attacks = BLANK;
+        for square in color_mask & self.get_piece_type_mask(Pawn) {
+            attacks |= PAWN.get_captures(square, color);
+        }
+        for square in color_mask & self.get_piece_type_mask(Knight) {
+            attacks |= KNIGHT.get_moves(square);
+        }
+        for square in color_mask & self.get_piece_type_mask(King) {
+            attacks |= KING.get_moves(square);
+        }
+        for square in color_mask & bishops_and_queens {
+            attacks |= Self::get_ray_attacks(square, 4..8, occupancy);
+        }
+        for square in color_mask & rooks_and_queens {
+            attacks |= Self::get_ray_attacks(square, 0..4, occupancy);
+        }
+        attacks
+    }
+
+    /// Returns squares which are not safe for the king of the side to move: all squares attacked
+    /// by the opponent if the king is removed from the board (so the king can't step back along
+    /// the line of attacking sliding piece)
+    fn get_king_danger_mask(&self) -> BitBoard {
+        let king_mask = BitBoard::from_square(self.get_king_square(self.side_to_move));
+        self.get_attacks_mask(!self.side_to_move, self.combined_mask & !king_mask)
+    }
+
+    fn get_check_mask_after_piece_move(self, m: &PieceMove) -> BitBoard {
+        self.clone()
+            .move_piece(m, &mut ())
+            .clear_square_if_en_passant_capture(m, &mut ())
             .update_pins_and_checks()
             .get_check_mask()
     }
 
-    fn move_piece(&mut self, piece_move: &PieceMove) -> &mut Self {
-        let source = piece_move.get_source_square();
-        let color = self.get_piece_color_on(source).unwrap();
-        self.clear_square(source).put_piece(
-            piece_move.get_promotion().map_or(
-                Piece(piece_move.get_piece_type(), color),
-                |new_piece_type| Piece(new_piece_type, color),
+    fn move_piece(
+        &mut self,
+        piece_move: &PieceMove,
+        backend: &mut impl EvaluatorBackend,
+    ) -> &mut Self {
+        let source = piece_move.get_source_square();
+        let color = self.get_piece_color_on(source).unwrap();
+        self.clear_square(source, backend).put_piece(
+            piece_move.get_promotion().map_or(
+                Piece(piece_move.get_piece_type(), color),
+                |new_piece_type| Piece(new_piece_type, color),
+            ),
+            piece_move.get_destination_square(),
+            backend,
+        )
+    }
+
+    fn clear_square_if_en_passant_capture(
+        &mut self,
+        piece_move: &PieceMove,
+        backend: &mut impl EvaluatorBackend,
+    ) -> &mut Self {
+        if piece_move.is_en_passant_move(self) {
+            let captured_square = match self.side_to_move {
+                White => piece_move.get_destination_square().down().unwrap(),
+                Black => piece_move.get_destination_square().up().unwrap(),
+            };
+            self.clear_square(captured_square, backend);
+        }
+        self
+    }
+
+    fn set_move_number(&mut self, value: u32) -> &mut Self {
+        self.move_number = value;
+        self
+    }
+
+    fn set_moves_since_capture_or_pawn_move(&mut self, value: u32) -> &mut Self {
+        self.moves_since_capture_or_pawn_move = value;
+        self
+    }
+
+    fn set_side_to_move(&mut self, color: Color) -> &mut Self {
+        if color != self.side_to_move {
+            self.hash ^= ZOBRIST.get_black_to_move_value();
+            self.side_to_move = color;
+        }
+
+        self
+    }
+
+    fn set_castling_rights(&mut self, color: Color, rights: CastlingRights) -> &mut Self {
+        let current_rights = self.castle_rights[color.to_index()];
+        if current_rights != rights {
+            self.hash ^= ZOBRIST.get_castling_rights_value(current_rights, color);
+            self.hash ^= ZOBRIST.get_castling_rights_value(rights, color);
+        }
+
+        self.castle_rights[color.to_index()] = rights;
+        self
+    }
+
+    fn set_en_passant(&mut self, square: Option<Square>) -> &mut Self {
+        if self.is_en_passant_capturable() {
+            self.hash ^= ZOBRIST.get_en_passant_value(self.en_passant.unwrap());
+        }
+        self.en_passant = square;
+        if self.is_en_passant_capturable() {
+            self.hash ^= ZOBRIST.get_en_passant_value(self.en_passant.unwrap());
+        }
+
+        self
+    }
+
+    fn put_piece(
+        &mut self,
+        piece: Piece,
+        square: Square,
+        backend: &mut impl EvaluatorBackend,
+    ) -> &mut Self {
+        if !self.is_empty_square(square) {
+            self.clear_square(square, backend);
+        }
+        let mask = BitBoard::from_square(square);
+        self.combined_mask ^= mask;
+        self.pieces_mask[piece.0.to_index()] ^= mask;
+        self.colors_mask[piece.1.to_index()] ^= mask;
+        self.hash ^= ZOBRIST.get_piece_square_value(piece, square);
+        backend.on_piece_added(piece, square);
+        self
+    }
+
+    fn clear_square(&mut self, square: Square, backend: &mut impl EvaluatorBackend) -> &mut Self {
+        if let Some(piece) = self.get_piece_on(square) {
+            let mask = !BitBoard::from_square(square);
+            self.combined_mask &= mask;
+            self.pieces_mask[piece.0.to_index()] &= mask;
+            self.colors_mask[piece.1.to_index()] &= mask;
+            self.hash ^= ZOBRIST.get_piece_square_value(piece, square);
+            backend.on_piece_removed(piece, square);
+        }
+        self
+    }
+
+    fn update_pins_and_checks(&mut self) -> &mut Self {
+        (self.pinned, self.checks) = self.pins_and_checks_for(self.side_to_move);
+        self
+    }
+
+    /// Sets the en-passant square after the move. The hash value of the previous en-passant
+    /// square is expected to be already removed
+    fn update_en_passant(&mut self, last_move: &BoardMove) -> &mut Self {
+        self.en_passant = None;
+        match last_move {
+            BoardMove::MovePiece(m) => {
+                let src_rank_index = m.get_source_square().get_rank().to_index();
+                let dest_rank_index = m.get_destination_square().get_rank().to_index();
+                if m.is_double_push() {
+                    let en_passant_square = Square::from_rank_file(
+                        Rank::from_index((src_rank_index + dest_rank_index) / 2).unwrap(),
+                        m.get_destination_square().get_file(),
+                    );
+                    self.set_en_passant(Some(en_passant_square))
+                } else {
+                    self.set_en_passant(None)
+                }
+            }
+            _ => self.set_en_passant(None),
+        };
+        self
+    }
+
+    fn update_castling_rights(&mut self, last_move: &BoardMove) -> &mut Self {
+        let (source, destination) = match last_move {
+            BoardMove::MovePiece(m) => (m.get_source_square(), m.get_destination_square()),
+            _ => {
+                let king_square =
+                    Square::from_rank_file(self.side_to_move.get_back_rank(), File::E);
+                (king_square, king_square)
+            }
+        };
+
+        for color in Color::iter() {
+            let lost = CastlingRights::get_lost_on(color, source)
+                + CastlingRights::get_lost_on(color, destination);
+            if lost != Neither {
+                self.set_castling_rights(color, self.get_castle_rights(color) - lost);
+            }
+        }
+        self
+    }
+
+    fn update_move_number(&mut self) -> &mut Self {
+        if self.side_to_move == Black {
+            self.move_number = self.move_number.saturating_add(1);
+        }
+        self
+    }
+
+    fn update_moves_since_capture(&mut self, last_move: &BoardMove) -> &mut Self {
+        match last_move {
+            BoardMove::MovePiece(m)
+                if (m.get_piece_type() == Pawn) | m.is_capture_on_board(self) =>
+            {
+                self.moves_since_capture_or_pawn_move = 0;
+            }
+            _ => {
+                self.moves_since_capture_or_pawn_move =
+                    self.moves_since_capture_or_pawn_move.saturating_add(1);
+            }
+        }
+        self
+    }
+
+    fn update_terminal_status(&mut self) -> &mut Self {
+        // the position is terminal if current side does not have legal moves. Counting is much
+        // cheaper than generating the moves because it doesn't make the moves on cloned boards
+        self.is_terminal_position = self.count_legal_moves() == 0;
+        self
+    }
+
+    /// Returns the pieces of ``color`` which give the discovered check to the opponent's king
+    /// on ``king_square`` when they leave the line: the only blockers between the king and the
+    /// sliding pieces of ``color``
+    fn get_discovered_check_candidates(&self, king_square: Square, color: Color) -> BitBoard {
+        let bishops_and_queens = self.get_piece_type_mask(Bishop) | self.get_piece_type_mask(Queen);
+        let rooks_and_queens = self.get_piece_type_mask(Rook) | self.get_piece_type_mask(Queen);
+        let sliders = self.get_color_mask(color)
+            & (BISHOP.get_moves(king_square) & bishops_and_queens
+                | ROOK.get_moves(king_square) & rooks_and_queens);
+
+        let mut candidates = BLANK;
+        for slider in sliders {
+            let between = self.combined_mask & BETWEEN.get(king_square, slider).unwrap();
+            if between.count_ones() == 1 {
+                candidates |= between;
+            }
+        }
+        candidates & self.get_color_mask(color)
+    }
+
+    /// Returns the line (from the king's square to the edge of the board) along which the
+    /// piece on specified square is pinned
+    fn get_pin_line(&self, king_square: Square, square: Square) -> BitBoard {
+        let square_mask = BitBoard::from_square(square);
+        RAYS.get(king_square)
+            .into_iter()
+            .find(|ray| !(*ray & square_mask).is_blank())
+            .unwrap_or(BLANK)
+    }
+
+    /// Returns the pieces of ``color`` pinned to the square and the pieces of opposite color
+    /// attacking the square
+    fn get_pins_and_checks(&self, square: Square, color: Color) -> (BitBoard, BitBoard) {
+        let opposite = !color;
+        let bishops_and_queens = self.get_piece_type_mask(Bishop) | self.get_piece_type_mask(Queen);
+        let rooks_and_queens = self.get_piece_type_mask(Rook) | self.get_piece_type_mask(Queen);
+
+        let (diagonal_moves, rect_moves) = (BISHOP.get_moves(square), ROOK.get_moves(square));
+        let attackers = self.get_color_mask(opposite)
+            & (diagonal_moves & bishops_and_queens | rect_moves & rooks_and_queens);
+
+        let (mut pinned, mut checks) = (BLANK, BLANK);
+        for attacker in attackers {
+            let between = self.get_combined_mask() & BETWEEN.get(square, attacker).unwrap();
+            match between.count_ones() {
+                0 => checks |= BitBoard::from_square(attacker),
+                1 => pinned |= between,
+                _ => {}
+            }
+        }
+        pinned &= self.get_color_mask(color);
+
+        checks |= self.get_color_mask(opposite)
+            & (KNIGHT.get_moves(square) & self.get_piece_type_mask(Knight)
+                | KING.get_moves(square) & self.get_piece_type_mask(King));
+
+        checks |= {
+            let mut pawns_attacks = BLANK;
+            if let Ok(rank) = match color {
+                White => square.up(),
+                Black => square.down(),
+            }
+            .map(|x| x.get_rank())
+            {
+                let opposite_pawns = self.get_color_mask(opposite) & self.get_piece_type_mask(Pawn);
+
+                if let Ok(file) = square.left().map(|x| x.get_file()) {
+                    let pawn_square = Square::from_rank_file(rank, file);
+                    pawns_attacks |= opposite_pawns & BitBoard::from_square(pawn_square);
+                }
+
+                if let Ok(file) = square.right().map(|x| x.get_file()) {
+                    let pawn_square = Square::from_rank_file(rank, file);
+                    pawns_attacks |= opposite_pawns & BitBoard::from_square(pawn_square);
+                }
+            }
+            pawns_attacks
+        };
+
+        (pinned, checks)
+    }
+
+    fn is_under_attack(&self, square: Square) -> bool {
+        !self
+            .get_pins_and_checks(square, self.side_to_move)
+            .1
+            .is_blank()
+    }
+}
+
+/// Brings the user's long notation of the move ("Ng1-f3+", "E7xD8=Q") to the UCI-like form
+/// ("g1f3", "e7d8q"). Returns the piece type from the optional prefix separately
+fn normalize_long_notation(token: &str) -> Option<(Option<PieceType>, String)> {
+    let token: String = token
+        .trim()
+        .trim_end_matches(['+', '#', '!', '?'])
+        .chars()
+        .filter(|c| !matches!(c, '-' | 'x' | 'X' | ':' | '='))
+        .collect();
+    if !token.is_ascii() {
+        return None;
+    }
+
+    // the piece prefix is followed by two squares, unlike the file of pawn's source square
+    let is_square = |s: &str| Square::from_str(&s.to_lowercase()).is_ok();
+    let has_prefix = (token.len() >= 5) && is_square(&token[1..3]) && is_square(&token[3..5]);
+    let (piece_type, token) = match has_prefix {
+        true => (Some(PieceType::from_str(&token[..1]).ok()?), &token[1..]),
+        false => (None, token.as_str()),
+    };
+    Some((piece_type, token.to_lowercase()))
+}
+
+/// Rewrites the promotion suffix of SAN-move into the canonical form: "e8Q", "e8=q" and "e8q"
+/// become "e8=Q". Other strings are returned unchanged
+pub(crate) fn normalize_san_promotion(san: &str) -> String {
+    let Some(piece) = san.chars().last().filter(|c| "nbrqNBRQ".contains(*c)) else {
+        return san.to_string();
+    };
+    let prefix = san[..san.len() - 1].trim_end_matches('=');
+    let mut chars = prefix.chars().rev();
+    match (chars.next(), chars.next()) {
+        (Some('1' | '8'), Some('a'..='h')) => format!("{prefix}={}", piece.to_ascii_uppercase()),
+        _ => san.to_string(),
+    }
+}
+
+/// Returns the number of characters in the string ignoring the terminal colors escape sequences
+fn visible_width(s: &str) -> usize {
+    let mut width = 0;
+    let mut is_escape = false;
+    for c in s.chars() {
+        match c {
+            '\x1b' => is_escape = true,
+            'm' if is_escape => is_escape = false,
+            _ if is_escape => {}
+            _ => width += 1,
+        }
+    }
+    width
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{squares, squares::*, BoardMove, PieceMove, Square};
+
+    pub fn noindent(text: &str) -> String { text.replace("\n", "").replace(" ", "") }
+
+    #[test]
+    fn create_from_string() {
+        assert_eq!(
+            format!("{}", BoardBuilder::from(ChessBoard::default())),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+        );
+    }
+
+    #[test]
+    fn piece_types_and_colors() {
+        let board = ChessBoard::default();
+        assert_eq!(board.get_piece_type_on(A2).unwrap(), Pawn);
+        assert_eq!(board.get_piece_type_on(A1).unwrap(), Rook);
+        assert_eq!(board.get_piece_type_on(B1).unwrap(), Knight);
+        assert_eq!(board.get_piece_type_on(C1).unwrap(), Bishop);
+        assert_eq!(board.get_piece_type_on(E1).unwrap(), King);
+        assert_eq!(board.get_piece_type_on(D1).unwrap(), Queen);
+
+        assert_eq!(board.get_piece_color_on(A1).unwrap(), White);
+        assert_eq!(board.get_piece_color_on(A8).unwrap(), Black);
+    }
+
+    #[test]
+    fn square_emptiness() {
+        let board = ChessBoard::default();
+        let a1 = A1;
+        let a3 = A3;
+        assert!(!board.is_empty_square(a1));
+        assert!(board.is_empty_square(a3));
+    }
+
+    #[rustfmt::skip]
+    #[test]
+    fn display_representation() {
+        let board = ChessBoard::default();
+        let board_str =
+        "   white  KQkq
+            ╔════════════════════════╗
+         8  ║ r  n  b  q  k  b  n  r ║
+         7  ║ p  p  p  p  p  p  p  p ║
+         6  ║                        ║
+         5  ║                        ║
+         4  ║                        ║
+         3  ║                        ║
+         2  ║ P  P  P  P  P  P  P  P ║
+         1  ║ R  N  B  Q  K  B  N  R ║
+            ╚════════════════════════╝
+              a  b  c  d  e  f  g  h
+        ";
+        println!("{}", board);
+        assert_eq!(
+            noindent(
+                format!("{}", board)
+                    .replace("\u{1b}[47;30m", "")
+                    .replace("\u{1b}[47m", "")
+                    .replace("\u{1b}[0m", "").as_str()
+            ),
+            noindent(board_str)
+        );
+
+        let board_str =
+        "   white  KQkq
+            ╔════════════════════════╗
+         1  ║ R  N  B  K  Q  B  N  R ║
+         2  ║ P  P  P  P  P  P  P  P ║
+         3  ║                        ║
+         4  ║                        ║
+         5  ║                        ║
+         6  ║                        ║
+         7  ║ p  p  p  p  p  p  p  p ║
+         8  ║ r  n  b  k  q  b  n  r ║
+            ╚════════════════════════╝
+              h  g  f  e  d  c  b  a
+        ";
+        println!("{}", board);
+        assert_eq!(
+            noindent(
+                board.render_flipped()
+                    .replace("\u{1b}[47;30m", "")
+                    .replace("\u{1b}[47m", "")
+                    .replace("\u{1b}[0m", "").as_str()
             ),
-            piece_move.get_destination_square(),
-        )
+            noindent(board_str)
+        );
     }
 
-    fn clear_square_if_en_passant_capture(&mut self, piece_move: &PieceMove) -> &mut Self {
-        if piece_move.is_en_passant_move(self) {
-            self.clear_square(match self.side_to_move {
-                White => piece_move.get_destination_square().down().unwrap(),
-                Black => piece_move.get_destination_square().up().unwrap(),
-            });
-        }
-        self
+    #[test]
+    fn compact_rendering() {
+        let board = ChessBoard::default().apply_san_line("e4 c5").unwrap();
+        assert_eq!(
+            board.render_compact(),
+            "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq"
+        );
+        assert_eq!(
+            board.render_small(BoardOrientation::BlackBottom),
+            "RNBKQBNR\nPPP.PPPP\n........\n...P....\n.....p..\n........\nppppp.pp\nrnbkqbnr\n"
+        );
     }
 
-    fn set_move_number(&mut self, value: usize) -> &mut Self {
-        self.move_number = value;
-        self
+    #[test]
+    fn pin_and_check_probes() {
+        let board = ChessBoard::from_fen("4k3/4r3/8/b7/8/2N5/4B3/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            board.pins_and_checks_for(White),
+            (board.get_pin_mask(), board.get_check_mask())
+        );
+        assert_eq!(
+            board.pins_and_checks_for(White).0,
+            BitBoard::from_square(E2) | BitBoard::from_square(C3)
+        );
+        assert_eq!(board.pins_and_checks_for(Black), (BLANK, BLANK));
+        assert!(board.checkers_of(White).is_blank());
+        assert_eq!(
+            board.pins_and_checks_at(Black, F3),
+            (BLANK, BitBoard::from_square(E2))
+        );
     }
 
-    fn set_moves_since_capture_or_pawn_move(&mut self, value: usize) -> &mut Self {
-        self.moves_since_capture_or_pawn_move = value;
-        self
+    #[test]
+    fn movetext_fragments() {
+        let board = ChessBoard::from_fen(
+            "r1bq1rk1/ppp2ppp/2n5/3pp3/4n3/2P2N2/P1PP1PPP/R1BQKB1R b KQ - 0 12",
+        )
+        .unwrap();
+        let (next_board, moves) = board
+            .apply_movetext("12...Qe7! ; the main line\n13. d3 $1 Nf6{developing}14.Be2")
+            .unwrap();
+        assert_eq!(moves.len(), 4);
+        assert_eq!(next_board.get_move_number(), 14);
+        assert_eq!(next_board.get_side_to_move(), Black);
+
+        assert!(matches!(
+            board.apply_movetext("12. Qe7"),
+            Err(Error::WrongMoveNumber)
+        ));
+        assert!(matches!(
+            board.apply_movetext("11... Qe7"),
+            Err(Error::WrongMoveNumber)
+        ));
+        assert!(matches!(
+            board.apply_movetext("Qe7 Qe2 Qe2"),
+            Err(Error::InvalidMoveInLine { ply: 2, .. })
+        ));
+        assert!(matches!(
+            board.apply_movetext("Qe7 (Qe8"),
+            Err(Error::InvalidPGNString)
+        ));
+
+        // the castling written with zeros is not taken for the move number
+        let board = ChessBoard::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        for movetext in ["0-0", "1. 0-0", "1.0-0 {[%emt 0:00:05]}"] {
+            let (_, moves) = board.apply_movetext(movetext).unwrap();
+            assert_eq!(moves, vec![BoardMove::CastleKingSide], "{movetext}");
+        }
     }
 
-    fn set_side_to_move(&mut self, color: Color) -> &mut Self {
-        if color != self.side_to_move {
-            self.hash ^= ZOBRIST.get_black_to_move_value();
-            self.side_to_move = color;
+    #[test]
+    fn random_playouts() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for policy in [PlayoutPolicy::default(), PlayoutPolicy::new(10.0, 5.0)] {
+            for _ in 0..20 {
+                let board = ChessBoard::default();
+                let (status, moves) = board.random_playout(&policy, &mut rng, 1000);
+                let mut replayed = board;
+                for m in moves.iter() {
+                    replayed.make_move_mut(m).unwrap();
+                }
+                match status {
+                    GameStatus::RepetitionDrawDeclared => {}
+                    GameStatus::Ongoing => assert_eq!(moves.len(), 1000),
+                    _ => assert_ne!(replayed.get_status(), BoardStatus::Ongoing),
+                }
+            }
         }
 
-        self
+        let (status, moves) =
+            ChessBoard::default().random_playout(&PlayoutPolicy::default(), &mut rng, 10);
+        assert_eq!((status, moves.len()), (GameStatus::Ongoing, 10));
+        let mate = ChessBoard::from_fen("Q4k2/8/5K2/8/8/8/8/8 b - - 0 1").unwrap();
+        let (status, moves) = mate.random_playout(&PlayoutPolicy::default(), &mut rng, 10);
+        assert_eq!((status, moves.len()), (GameStatus::CheckMated(Black), 0));
     }
 
-    fn set_castling_rights(&mut self, color: Color, rights: CastlingRights) -> &mut Self {
-        let current_rights = self.castle_rights[color.to_index()];
-        if current_rights != rights {
-            self.hash ^= ZOBRIST.get_castling_rights_value(current_rights, color);
-            self.hash ^= ZOBRIST.get_castling_rights_value(rights, color);
+    #[test]
+    fn child_hashes() {
+        use crate::testing::random_board;
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut boards = vec![
+            ChessBoard::from_fen("r3k2r/1P6/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1").unwrap(),
+            ChessBoard::from_fen("r3k2r/8/8/8/4p3/8/3P4/R3K2R w KQkq - 0 1").unwrap(),
+        ];
+        boards.extend((0..200).map(|_| random_board(&ChessBoard::default(), 40, &mut rng)));
+        for board in boards {
+            for (m, hash) in board.get_successors() {
+                assert_eq!(
+                    hash,
+                    board.make_move(&m).unwrap().get_hash(),
+                    "{} {m}",
+                    board.as_fen()
+                );
+            }
         }
-
-        self.castle_rights[color.to_index()] = rights;
-        self
     }
 
-    fn set_en_passant(&mut self, square: Option<Square>) -> &mut Self {
-        if let Some(sq) = self.en_passant {
-            self.hash ^= ZOBRIST.get_en_passant_value(sq);
-        }
-        if let Some(sq) = square {
-            self.hash ^= ZOBRIST.get_en_passant_value(sq);
-        }
+    #[test]
+    fn canonical_forms() {
+        let board = ChessBoard::from_fen("r3k2r/8/8/8/3pP3/8/8/R3K2R b Kq e3 3 20").unwrap();
+        assert_eq!(board.get_mirrored().get_mirrored(), board);
+        assert_eq!(
+            board.get_mirrored().as_fen(),
+            "r3k2r/8/8/3Pp3/8/8/8/R3K2R w Qk e6 3 20"
+        );
 
-        self.en_passant = square;
-        self
+        let form = CanonicalForm::default();
+        assert_eq!(
+            board.get_canonical_fen(&form),
+            "r3k2r/8/8/8/3pP3/8/8/R3K2R b Kq e3 0 1"
+        );
+        let form = CanonicalForm {
+            mirror_to_white: true,
+            strip_counters:  false,
+        };
+        assert_eq!(
+            board.get_canonical_fen(&form),
+            "r3k2r/8/8/3Pp3/8/8/8/R3K2R w Qk e6 3 20"
+        );
+
+        // the en passant capture is illegal because of the pin, so the square is removed
+        let board = ChessBoard::from_fen("8/8/8/8/k2pP2R/8/8/4K3 b - e3 0 1").unwrap();
+        assert_eq!(
+            board.get_canonical_fen(&CanonicalForm::default()),
+            "8/8/8/8/k2pP2R/8/8/4K3 b - - 0 1"
+        );
     }
 
-    fn put_piece(&mut self, piece: Piece, square: Square) -> &mut Self {
-        if !self.is_empty_square(square) {
-            self.clear_square(square);
-        }
-        let mask = BitBoard::from_square(square);
-        self.combined_mask ^= mask;
-        self.pieces_mask[piece.0.to_index()] ^= mask;
-        self.colors_mask[piece.1.to_index()] ^= mask;
-        self.hash ^= ZOBRIST.get_piece_square_value(piece, square);
-        self
+    #[test]
+    fn legal_moves_capacity() {
+        // the position with the maximal known number of legal moves
+        let board =
+            ChessBoard::from_fen("R6R/3Q4/1Q4Q1/4Q3/2Q4Q/Q4Q2/pp1Q4/kBNN1KB1 w - - 0 1").unwrap();
+        let moves = board.get_legal_moves();
+        assert_eq!(moves.len(), MAX_LEGAL_MOVES);
+        assert_eq!(moves.capacity(), MAX_LEGAL_MOVES);
+
+        let moves = board.get_legal_moves_with_capacity(10);
+        assert_eq!(moves.len(), MAX_LEGAL_MOVES);
+        assert!(ChessBoard::default()
+            .get_legal_moves_with_capacity(0)
+            .iter()
+            .eq(ChessBoard::default().get_legal_moves().iter()));
     }
 
-    fn clear_square(&mut self, square: Square) -> &mut Self {
-        if let Some(piece) = self.get_piece_on(square) {
-            let mask = !BitBoard::from_square(square);
-            self.combined_mask &= mask;
-            self.pieces_mask[piece.0.to_index()] &= mask;
-            self.colors_mask[piece.1.to_index()] &= mask;
-            self.hash ^= ZOBRIST.get_piece_square_value(piece, square);
+    #[test]
+    fn ascii_grid_setup() {
+        let grid = ChessBoard::default().render_small(BoardOrientation::WhiteBottom);
+        let board = ChessBoard::from_ascii_grid(&grid, White, BothSides, BothSides).unwrap();
+        assert_eq!(board, ChessBoard::default());
+
+        let invalid_grids = [
+            "........\n".repeat(7),
+            "........\n".repeat(9),
+            format!("{}.......\n", "........\n".repeat(7)),
+            format!("{}....x...\n", "........\n".repeat(7)),
+        ];
+        for grid in invalid_grids.iter() {
+            assert!(matches!(
+                ChessBoard::from_ascii_grid(grid, White, Neither, Neither),
+                Err(Error::InvalidBoardGrid { .. })
+            ));
         }
-        self
-    }
 
-    fn update_pins_and_checks(&mut self) -> &mut Self {
-        (self.pinned, self.checks) =
-            self.get_pins_and_checks(self.get_king_square(self.side_to_move));
-        self
+        // the grid is parsed, but the position is invalid (two white kings)
+        let grid = format!("k.......\n{}K......K\n", "........\n".repeat(6));
+        assert!(ChessBoard::from_ascii_grid(&grid, White, Neither, Neither).is_err());
     }
 
-    fn update_en_passant(&mut self, last_move: &BoardMove) -> &mut Self {
-        match last_move {
-            BoardMove::MovePiece(m) => {
-                let src_rank_index = m.get_source_square().get_rank().to_index();
-                let dest_rank_index = m.get_destination_square().get_rank().to_index();
-                if (m.get_piece_type() == Pawn) & (src_rank_index.abs_diff(dest_rank_index) == 2) {
-                    let en_passant_square = Square::from_rank_file(
-                        Rank::from_index((src_rank_index + dest_rank_index) / 2).unwrap(),
-                        m.get_destination_square().get_file(),
-                    );
-                    self.set_en_passant(Some(en_passant_square))
-                } else {
-                    self.set_en_passant(None)
-                }
-            }
-            _ => self.set_en_passant(None),
-        };
-        self
+    #[test]
+    fn endgame_classification() {
+        let class = |fen: &str| ChessBoard::from_fen(fen).unwrap().get_endgame_class();
+        assert_eq!(
+            class("4k3/8/8/8/8/8/8/4K3 w - - 0 1"),
+            EndgameClass::BareKings
+        );
+        assert_eq!(
+            class("4k3/4p3/8/8/8/8/4P3/4K3 w - - 0 1"),
+            EndgameClass::PawnEnding
+        );
+        assert_eq!(
+            class("4k3/4b3/8/8/8/8/4N3/4K3 w - - 0 1"),
+            EndgameClass::MinorPieceEnding
+        );
+        assert_eq!(
+            class("4k3/4r3/8/8/8/8/4R3/4K3 w - - 0 1"),
+            EndgameClass::RookEnding
+        );
+        assert_eq!(
+            class("4k3/4r3/8/8/8/8/4B3/4K3 w - - 0 1"),
+            EndgameClass::RookAndMinorPieceEnding
+        );
+        assert_eq!(
+            class("4k3/4q3/8/8/8/8/4Q3/4K3 w - - 0 1"),
+            EndgameClass::QueenEnding
+        );
+        assert_eq!(
+            class("4k3/4q3/8/8/8/8/4R3/4K3 w - - 0 1"),
+            EndgameClass::QueenAndPieceEnding
+        );
+        assert_eq!(
+            class("r3k3/1n1q4/8/8/8/8/1N1Q4/R3K2R w - - 0 1"),
+            EndgameClass::NotEndgame
+        );
+
+        let board = ChessBoard::from_fen("4k3/4p3/8/8/8/8/4Q3/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.get_material_signature(), "KQvKP");
+        assert_eq!(board.get_mirrored().get_material_signature(), "KPvKQ");
     }
 
-    fn update_castling_rights(&mut self, last_move: &BoardMove) -> &mut Self {
-        use File::*;
-        let opposite = !self.side_to_move;
-
-        if (self.get_castle_rights(opposite) != Neither) & last_move.piece_move().is_ok() {
-            let destination = last_move.piece_move().unwrap().get_destination_square();
-            let opposite_back_rank = opposite.get_back_rank();
-            self.set_castling_rights(
-                opposite,
-                self.get_castle_rights(opposite)
-                    - if destination == Square::from_rank_file(opposite_back_rank, H) {
-                        KingSide
-                    } else if destination == Square::from_rank_file(opposite_back_rank, A) {
-                        QueenSide
-                    } else {
-                        Neither
-                    },
+    #[test]
+    fn promotion_spellings() {
+        let board = ChessBoard::from_fen("2r5/1P6/3k4/8/8/8/8/K7 w - - 0 1").unwrap();
+        for san in ["bxc8=N+", "bxc8N+", "bxc8=n", "bxc8n+", "b7c8n"] {
+            assert_eq!(
+                board.parse_san(san).unwrap(),
+                mv!(Pawn, B7, C8, Knight),
+                "{san}"
             );
         }
-
-        if self.get_castle_rights(self.side_to_move) != Neither {
-            self.set_castling_rights(
-                self.side_to_move,
-                self.get_castle_rights(self.side_to_move)
-                    - match last_move {
-                        BoardMove::MovePiece(m) => match m.get_piece_type() {
-                            Rook => match m.get_source_square().get_file() {
-                                File::H => KingSide,
-                                File::A => QueenSide,
-                                _ => Neither,
-                            },
-                            King => BothSides,
-                            _ => Neither,
-                        },
-                        _ => BothSides,
-                    },
+        for san in ["b8=Q", "b8Q", "b8q", "b8=q+"] {
+            assert_eq!(
+                board.parse_san(san).unwrap(),
+                mv!(Pawn, B7, B8, Queen),
+                "{san}"
             );
         }
+        assert!(board.parse_san("b8=K").is_err());
+        assert!(board.parse_san("b8").is_err());
 
-        self
+        let board_move = board.parse_san("bxc8n").unwrap();
+        let properties = MovePropertiesOnBoard::new(&board_move, &board).unwrap();
+        assert_eq!(board_move.to_string(properties), "bxc8=N+");
     }
 
-    fn update_move_number(&mut self) -> &mut Self {
-        if self.side_to_move == Black {
-            self.move_number += 1;
-        }
-        self
+    #[test]
+    fn transpositions() {
+        let board = ChessBoard::default();
+        let line = |s: &str| board.apply_movetext(s).unwrap().1;
+        assert!(board.is_transposition(&[], &[]).unwrap());
+        assert!(board
+            .is_transposition(&line("Nf3 Nf6 Ng1 Ng8"), &[])
+            .unwrap());
+        assert!(!board.is_transposition(&line("Nf3 Nf6 Ng1"), &[]).unwrap());
+
+        // the en passant square counts only if the capture is possible
+        let first = [mv!(Pawn, E2, E4)];
+        let second = [
+            mv!(Pawn, E2, E3),
+            mv!(King, E8, D8),
+            mv!(King, E1, D1),
+            mv!(King, D8, E8),
+            mv!(King, D1, D2),
+            mv!(King, E8, D8),
+            mv!(King, D2, E1),
+            mv!(King, D8, E8),
+            mv!(Pawn, E3, E4),
+        ];
+        let board = ChessBoard::from_fen("4k3/8/8/8/5p2/8/4P3/4K3 w - - 0 1").unwrap();
+        assert!(!board.is_transposition(&first, &second).unwrap());
+        let board = ChessBoard::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+        assert!(board.is_transposition(&first, &second).unwrap());
+
+        assert!(matches!(
+            board.is_transposition(&first, &second[1..]),
+            Err(Error::InvalidMoveInLine { ply: 0, .. })
+        ));
     }
 
-    fn update_moves_since_capture(&mut self, last_move: &BoardMove) -> &mut Self {
-        match last_move {
-            BoardMove::MovePiece(m) => {
-                if (m.get_piece_type() == Pawn) | m.is_capture_on_board(self) {
-                    self.moves_since_capture_or_pawn_move = 0;
-                } else {
-                    self.moves_since_capture_or_pawn_move += 1;
-                }
-            }
-            _ => {
-                self.moves_since_capture_or_pawn_move += 1;
-            }
-        }
-        self
-    }
+    #[test]
+    fn partial_rendering() {
+        let board = ChessBoard::from_fen("4k3/3qp3/8/8/8/8/3QP3/4K3 w - - 0 1").unwrap();
+        let small = |visibility: PieceVisibility| {
+            board
+                .get_visible_board(&visibility)
+                .render_small(BoardOrientation::WhiteBottom)
+        };
+        assert_eq!(
+            small(PieceVisibility::pawn_structure()),
+            "........\n....p...\n........\n........\n........\n........\n....P...\n........\n"
+        );
+        assert_eq!(small(PieceVisibility::blindfold()), "........\n".repeat(8));
 
-    fn update_terminal_status(&mut self) -> &mut Self {
-        // To define whether the position is terminal one, we should understand that current side
-        // does not have legal moves. The simplest way to do this is just by calling
-        // board.get_legal_moves().len(). But we could avoid iterating over all available
-        // moves for most of the cases and find only the first legal move.
-        // Moreover, we do not need to process castling and promotions because for checkmate and
-        // stalemate it is unnecessary
-        let color_mask = self.get_color_mask(self.side_to_move);
-        for piece_type in PieceType::iter() {
-            for square in color_mask & self.get_piece_type_mask(piece_type) {
-                if self
-                    .get_piece_moves_mask(piece_type, square)
-                    .into_iter()
-                    .map(|s| {
-                        self.get_check_mask_after_piece_move(
-                            &PieceMove::new(piece_type, square, s, None).unwrap(),
-                        )
-                    })
-                    .any(|x| x.is_blank())
-                {
-                    self.is_terminal_position = false;
-                    return self;
-                }
-            }
-        }
+        let mut visibility = PieceVisibility::all();
+        visibility.hide_color(Black).hide_piece_type(Queen);
+        assert_eq!(
+            small(visibility),
+            "........\n........\n........\n........\n........\n........\n....P...\n....K...\n"
+        );
+        assert_eq!(
+            small(PieceVisibility::all()),
+            board.render_small(BoardOrientation::WhiteBottom)
+        );
 
-        self.is_terminal_position = true;
-        self
+        let rendered =
+            board.render_partial(&PieceVisibility::blindfold(), BoardOrientation::WhiteBottom);
+        assert_eq!(
+            visible_width(rendered.lines().nth(2).unwrap()),
+            BOARD_RENDER_WIDTH
+        );
+        assert!(!rendered.contains('Q'));
     }
 
-    fn get_pins_and_checks(&self, square: Square) -> (BitBoard, BitBoard) {
-        let opposite = !self.side_to_move;
-        let bishops_and_queens = self.get_piece_type_mask(Bishop) | self.get_piece_type_mask(Queen);
-        let rooks_and_queens = self.get_piece_type_mask(Rook) | self.get_piece_type_mask(Queen);
+    #[test]
+    fn tolerant_long_notation() {
+        let board = ChessBoard::default();
+        for token in ["e2e4", "e2-e4", "E2E4", "E2-E4", "Pe2-e4", " e2e4+ "] {
+            assert_eq!(board.parse_uci(token).unwrap(), mv!(Pawn, E2, E4));
+        }
+        assert_eq!(board.parse_uci("Ng1-f3+").unwrap(), mv!(Knight, G1, F3));
+        assert_eq!(board.parse_san("Ng1-f3").unwrap(), mv!(Knight, G1, F3));
+        assert!(board.parse_uci("Bg1-f3").is_err());
+        assert!(board.parse_san("e2e5").is_err());
 
-        let (diagonal_moves, rect_moves) = (BISHOP.get_moves(square), ROOK.get_moves(square));
-        let attackers = self.get_color_mask(opposite)
-            & (diagonal_moves & bishops_and_queens | rect_moves & rooks_and_queens);
+        let board = ChessBoard::from_str("k2r4/4P3/8/3p4/4P3/8/8/4K2R w K - 0 1").unwrap();
+        assert_eq!(board.parse_uci("e4xd5").unwrap(), mv!(Pawn, E4, D5));
+        assert_eq!(
+            board.parse_uci("E7xD8=Q#").unwrap(),
+            mv!(Pawn, E7, D8, Queen)
+        );
+        assert_eq!(board.parse_uci("Ke1-g1").unwrap(), castle_king_side!());
+        assert_eq!(board.parse_san("Ke1-g1").unwrap(), castle_king_side!());
+    }
 
-        let (mut pinned, mut checks) = (BLANK, BLANK);
-        for attacker in attackers {
-            let between = self.get_combined_mask() & BETWEEN.get(square, attacker).unwrap();
-            match between.count_ones() {
-                0 => checks |= BitBoard::from_square(attacker),
-                1 => pinned |= between,
-                _ => {}
-            }
+    #[test]
+    fn promotion_policies() {
+        use crate::PromotionPolicy::*;
+        let board = ChessBoard::from_str("3rk3/4P3/8/8/8/8/8/K7 w - - 0 1").unwrap();
+        for san in ["exd8", "exd8+", "e7d8"] {
+            assert!(matches!(
+                board.parse_san_with_promotion(san, Strict),
+                Err(Error::MissingPromotionPiece)
+            ));
+            assert_eq!(
+                board.parse_san_with_promotion(san, AutoQueen).unwrap(),
+                mv!(Pawn, E7, D8, Queen)
+            );
         }
-        pinned &= self.get_color_mask(self.side_to_move);
-
-        checks |= self.get_color_mask(opposite)
-            & (KNIGHT.get_moves(square) & self.get_piece_type_mask(Knight)
-                | KING.get_moves(square) & self.get_piece_type_mask(King));
+        assert!(matches!(
+            board.parse_san_with_promotion("e8", AutoQueen),
+            Err(Error::InvalidBoardMoveRepresentation)
+        ));
+        assert!(board
+            .parse_san_with_promotion("exd8", Prompt(|_, _, _| None))
+            .is_err());
+        assert_eq!(
+            board
+                .parse_san_with_promotion("exd8=R", Prompt(|_, _, _| None))
+                .unwrap(),
+            mv!(Pawn, E7, D8, Rook)
+        );
 
-        checks |= {
-            let mut pawns_attacks = BLANK;
-            if let Ok(rank) = match self.side_to_move {
-                White => square.up(),
-                Black => square.down(),
-            }
-            .map(|x| x.get_rank())
-            {
-                let opposite_pawns = self.get_color_mask(opposite) & self.get_piece_type_mask(Pawn);
+        // the callback receives the move squares
+        let choose = |_: &ChessBoard, source: Square, _: Square| match source {
+            squares::E7 => Some(Bishop),
+            _ => None,
+        };
+        assert_eq!(
+            board
+                .parse_uci_with_promotion("e7d8", Prompt(choose))
+                .unwrap(),
+            mv!(Pawn, E7, D8, Bishop)
+        );
+        assert_eq!(
+            board.parse_uci_with_promotion("a1b1", Strict).unwrap(),
+            mv!(King, A1, B1)
+        );
+    }
 
-                if let Ok(file) = square.left().map(|x| x.get_file()) {
-                    let pawn_square = Square::from_rank_file(rank, file);
-                    pawns_attacks |= opposite_pawns & BitBoard::from_square(pawn_square);
-                }
+    #[test]
+    fn promotion_completeness() {
+        let board = ChessBoard::from_str("8/4P3/8/8/8/8/3k1p2/K7 w - - 0 1").unwrap();
+        let missing = mv!(Pawn, E7, E8);
+        assert!(!board.is_legal_move(&missing));
+        assert!(matches!(
+            board.make_move(&missing),
+            Err(Error::MissingPromotionPiece)
+        ));
+        assert!(board.is_legal_move(&mv!(Pawn, E7, E8, Queen)));
+
+        let unexpected = mv!(King, A1, B2, Queen);
+        assert!(!board.is_legal_move(&unexpected));
+        assert!(matches!(
+            board.make_move(&unexpected),
+            Err(Error::UnexpectedPromotionPiece)
+        ));
+        assert!(!board.is_legal_move(&mv!(King, A1, B1, Queen)));
+
+        let board = board.make_move(&mv!(King, A1, B2)).unwrap();
+        assert!(!board.is_legal_move(&mv!(Pawn, F2, F1)));
+        assert!(board.is_legal_move(&mv!(Pawn, F2, F1, Knight)));
+        assert!(matches!(
+            ChessBoard::default().make_move(&mv!(Pawn, E2, E4, Queen)),
+            Err(Error::UnexpectedPromotionPiece)
+        ));
+    }
 
-                if let Ok(file) = square.right().map(|x| x.get_file()) {
-                    let pawn_square = Square::from_rank_file(rank, file);
-                    pawns_attacks |= opposite_pawns & BitBoard::from_square(pawn_square);
+    #[test]
+    fn attacks_from() {
+        let board = ChessBoard::from_str(
+            "r1bqkbnr/pppp1ppp/2n5/4p3/3PP3/5N2/PPP2PPP/RNBQKB1R b KQkq - 0 3",
+        )
+        .unwrap();
+        for square in (0..SQUARES_NUMBER as u8).map(|i| Square::new(i).unwrap()) {
+            let attacks = board.attacks_from(square);
+            match board.get_piece_color_on(square) {
+                Some(color) => {
+                    assert_eq!(attacks & !board.get_attacked_squares(color), BLANK)
                 }
+                None => assert_eq!(attacks, BLANK),
             }
-            pawns_attacks
-        };
+        }
+        assert_eq!(board.attacks_from(C6).count_ones(), 8);
+        assert_eq!(board.attacks_from(D1).count_ones(), 8);
+        assert_eq!(board.attacks_from(F1).count_ones(), 6); // g2 and the diagonal up to a6
+        assert_eq!(
+            board.attacks_from(E5),
+            BitBoard::from_square(D4) | BitBoard::from_square(F4)
+        );
+    }
 
-        (pinned, checks)
+    #[test]
+    fn xray_attacks() {
+        let board = ChessBoard::default();
+        assert_eq!(board.xray_attacks(squares::E2), BLANK);
+        assert_eq!(board.xray_attacks(squares::E4), BLANK);
+        // a3-a7 behind the pawn and c1 behind the knight
+        assert_eq!(board.xray_attacks(squares::A1).count_ones(), 6);
+
+        // the bishop pins the knight to the king and the queen sees behind the rook battery
+        let board = ChessBoard::from_str("4k3/8/2n5/1B6/8/8/4R3/4Q1K1 b - - 0 1").unwrap();
+        let xray = board.xray_attacks(squares::B5);
+        assert!(!(xray & BitBoard::from_square(squares::D7)).is_blank());
+        assert!(!(xray & BitBoard::from_square(squares::E8)).is_blank());
+        assert!(!(xray & BitBoard::from_square(squares::F1)).is_blank());
+        assert_eq!(xray.count_ones(), 3);
+
+        let xray = board.xray_attacks(squares::E1);
+        assert_eq!(
+            xray & BitBoard::from_file(File::E),
+            BitBoard::from_file(File::E)
+                & !BitBoard::from_rank(Rank::First)
+                & !BitBoard::from_rank(Rank::Second)
+        );
+        // the queen on e1 has the king on g1 on its rank: h1 is behind it
+        assert!(!(xray & BitBoard::from_square(squares::H1)).is_blank());
     }
 
-    fn is_under_attack(&self, square: Square) -> bool {
-        !self.get_pins_and_checks(square).1.is_blank()
+    #[test]
+    fn color_complexes() {
+        use SquareColor::*;
+        let board = ChessBoard::default();
+        for color in Color::iter() {
+            assert!(board.has_bishop_pair(color));
+            assert_eq!(board.weak_color_complex(color), None);
+            assert_eq!(board.bishops_on_color(color, Light).count_ones(), 1);
+            assert_eq!(board.bishops_on_color(color, Dark).count_ones(), 1);
+        }
+
+        // two bishops on the same color are not a pair
+        let board = ChessBoard::from_str("4k3/8/8/8/8/8/2B1B3/4K3 w - - 0 1").unwrap();
+        assert!(!board.has_bishop_pair(White));
+        assert_eq!(board.bishops_on_color(White, Light).count_ones(), 2);
+
+        // black has no light-squared bishop and pawns on dark squares
+        let board = ChessBoard::from_str("4k3/2b5/3p1p2/2p1p3/8/8/4B3/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.weak_color_complex(Black), Some(Light));
+        assert_eq!(board.weak_color_complex(White), None);
+        let board = ChessBoard::from_str("4k3/2b5/8/8/8/8/4B3/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.weak_color_complex(Black), None);
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{squares::*, BoardMove, PieceMove, Square};
 
-    pub fn noindent(text: &str) -> String { text.replace("\n", "").replace(" ", "") }
+    #[test]
+    fn pawn_structure_queries() {
+        let board = ChessBoard::from_str("4k3/pp3p2/8/2P3pP/1P6/8/5P2/4K3 w - g6 0 1").unwrap();
+        assert!(board.is_passed_pawn(squares::H5)); // the pawn on g5 is beside, not in front
+        assert!(!board.is_passed_pawn(squares::C5));
+        assert!(!board.is_passed_pawn(squares::B4));
+        assert!(!board.is_passed_pawn(squares::G5));
+        assert!(!board.is_passed_pawn(squares::A7));
+        assert!(!board.is_passed_pawn(squares::E8));
+
+        for file in FILES {
+            let pawns = (BitBoard::from_file(file) & board.get_piece_type_mask(Pawn)).count_ones();
+            assert_eq!(board.is_open_file(file), pawns == 0);
+            assert!(!(board.is_semi_open_file(file, White) & board.is_semi_open_file(file, Black)));
+        }
+        assert!(board.is_open_file(File::D));
+        assert!(board.is_semi_open_file(File::A, White));
+        assert!(board.is_semi_open_file(File::C, Black));
+        assert!(!board.is_semi_open_file(File::B, White));
+        assert!(!board.is_semi_open_file(File::D, White));
+    }
 
     #[test]
-    fn create_from_string() {
+    fn center_and_space() {
+        let board = ChessBoard::from_str("4k3/8/8/8/3Q4/8/8/4K3 w - - 0 1").unwrap();
         assert_eq!(
-            format!("{}", BoardBuilder::from(ChessBoard::default())),
-            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+            board.center_control(White),
+            CenterControl {
+                center_attacked:          3,
+                center_occupied:          1,
+                extended_center_attacked: 11,
+                extended_center_occupied: 1,
+            }
         );
+        assert_eq!(board.center_control(Black), CenterControl::default());
+
+        // the pawn on c3 limits the space on its file and takes d2 away
+        let board = ChessBoard::from_str("4k3/8/8/8/8/2p5/8/2R1K3 w - - 0 1").unwrap();
+        assert_eq!(board.space(White), 8);
+        // the side's half of the board without the opponent's pawns
+        assert_eq!(board.space(Black), 5);
+        let board = ChessBoard::from_str("4k3/8/8/8/8/8/2p5/2R1K3 w - - 0 1").unwrap();
+        assert_eq!(board.space(White), 6);
     }
 
     #[test]
-    fn piece_types_and_colors() {
-        let board = ChessBoard::default();
-        assert_eq!(board.get_piece_type_on(A2).unwrap(), Pawn);
-        assert_eq!(board.get_piece_type_on(A1).unwrap(), Rook);
-        assert_eq!(board.get_piece_type_on(B1).unwrap(), Knight);
-        assert_eq!(board.get_piece_type_on(C1).unwrap(), Bishop);
-        assert_eq!(board.get_piece_type_on(E1).unwrap(), King);
-        assert_eq!(board.get_piece_type_on(D1).unwrap(), Queen);
-
-        assert_eq!(board.get_piece_color_on(A1).unwrap(), White);
-        assert_eq!(board.get_piece_color_on(A8).unwrap(), Black);
+    fn quiet_checks() {
+        let expected = |board: &ChessBoard| {
+            board
+                .get_legal_moves()
+                .into_iter()
+                .filter(|m| {
+                    let flags = board.get_move_flags(m);
+                    !flags.is_capture
+                        & !flags.is_promotion
+                        & !board.make_move(m).unwrap().get_check_mask().is_blank()
+                })
+                .collect::<Vec<_>>()
+        };
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            // the pawn shields the bishop and the king shields the rook
+            "7k/8/5P2/8/3B4/8/7r/1K6 w - - 0 1",
+            "k7/8/8/8/K7/8/8/R7 w - - 0 1",
+            // castling with check by the rook
+            "5k2/8/8/8/8/8/8/4K2R w K - 0 1",
+        ] {
+            let board = ChessBoard::from_str(fen).unwrap();
+            assert_eq!(board.get_quiet_checks(), expected(&board), "{fen}");
+            for m in board.get_legal_moves() {
+                let next = board.make_move(&m).unwrap();
+                assert_eq!(next.get_quiet_checks(), expected(&next), "{fen} {m}");
+            }
+        }
+        let board = ChessBoard::from_str("5k2/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        assert!(board.get_quiet_checks().contains(&castle_king_side!()));
     }
 
     #[test]
-    fn square_emptiness() {
+    fn control_maps() {
         let board = ChessBoard::default();
-        let a1 = A1;
-        let a3 = A3;
-        assert_eq!(board.is_empty_square(a1), false);
-        assert_eq!(board.is_empty_square(a3), true);
+        let white = board.control_map(White);
+        assert_eq!(white[squares::D3.to_index()], 2);
+        assert_eq!(white[squares::F3.to_index()], 3);
+        assert_eq!(white[squares::D1.to_index()], 1);
+        assert_eq!(white[squares::E4.to_index()], 0);
+
+        // the sum of the map is consistent with the attacked squares
+        let board = ChessBoard::from_str(
+            "r1bqk2r/ppp2ppp/2n2n2/3pp3/1b1PP3/2N2N2/PPP2PPP/R1BQKB1R w KQkq - 0 5",
+        )
+        .unwrap();
+        for color in Color::iter() {
+            let control = board.control_map(color);
+            let attacked = board.get_attacked_squares(color);
+            for (index, count) in control.iter().enumerate() {
+                let square_mask = BitBoard::from_square(Square::new(index as u8).unwrap());
+                assert_eq!(*count > 0, !(attacked & square_mask).is_blank());
+            }
+        }
+        assert_eq!(board.control_map(Black)[squares::E4.to_index()], 2);
     }
 
-    #[rustfmt::skip]
     #[test]
-    fn display_representation() {
+    fn draw_reports() {
+        let report = ChessBoard::default().draw_report();
+        assert!(!report.is_any());
+        assert_eq!(report.to_string(), "no draw conditions");
+
+        let report = ChessBoard::from_str("7k/5Q2/6K1/8/8/8/8/8 b - - 150 120")
+            .unwrap()
+            .draw_report();
+        assert!(report.stalemate & report.seventy_five_forced & report.fifty_move_claimable);
+        assert!(!report.insufficient_material);
+
+        let report = ChessBoard::from_str("R5k1/5ppp/8/8/8/8/8/6K1 b - - 120 90")
+            .unwrap()
+            .draw_report();
+        assert!(!report.is_any());
+
+        let report = ChessBoard::from_str("8/8/4k3/8/8/2BK4/8/8 w - - 10 90")
+            .unwrap()
+            .draw_report();
+        assert_eq!(report.to_string(), "insufficient material to checkmate");
+    }
+
+    #[test]
+    fn diff_rendering() {
         let board = ChessBoard::default();
-        let board_str =
-        "   white  KQkq
-            ╔════════════════════════╗
-         8  ║ r  n  b  q  k  b  n  r ║
-         7  ║ p  p  p  p  p  p  p  p ║
-         6  ║                        ║
-         5  ║                        ║
-         4  ║                        ║
-         3  ║                        ║
-         2  ║ P  P  P  P  P  P  P  P ║
-         1  ║ R  N  B  Q  K  B  N  R ║
-            ╚════════════════════════╝
-              a  b  c  d  e  f  g  h
-        ";
-        println!("{}", board);
-        assert_eq!(
-            noindent(
-                format!("{}", board)
-                    .replace("\u{1b}[47;30m", "")
-                    .replace("\u{1b}[47m", "")
-                    .replace("\u{1b}[0m", "").as_str()
-            ),
-            noindent(board_str)
-        );
+        let next_board = board
+            .apply_san_line("e4 d5 exd5 Nf6 Nf3 Nxd5 Be2 e5 O-O")
+            .unwrap();
+        let diff = board.get_diff_mask(&next_board);
+        for square in [E2, D7, G8, G1, F1, E1, H1, E7] {
+            assert!(!(diff & BitBoard::from_square(square)).is_blank());
+        }
+        assert_eq!(diff.count_ones(), 11);
+        assert_eq!(board.get_diff_mask(&board), BLANK);
 
-        let board_str =
-        "   white  KQkq
-            ╔════════════════════════╗
-         1  ║ R  N  B  K  Q  B  N  R ║
-         2  ║ P  P  P  P  P  P  P  P ║
-         3  ║                        ║
-         4  ║                        ║
-         5  ║                        ║
-         6  ║                        ║
-         7  ║ p  p  p  p  p  p  p  p ║
-         8  ║ r  n  b  k  q  b  n  r ║
-            ╚════════════════════════╝
-              h  g  f  e  d  c  b  a
-        ";
-        println!("{}", board);
-        assert_eq!(
-            noindent(
-                format!("{}", board.render_flipped())
-                    .replace("\u{1b}[47;30m", "")
-                    .replace("\u{1b}[47m", "")
-                    .replace("\u{1b}[0m", "").as_str()
-            ),
-            noindent(board_str)
-        );
+        let rendered = board.render_diff(&next_board);
+        assert_eq!(rendered.lines().count(), 12);
+        for line in rendered.lines().skip(1).take(10) {
+            assert_eq!(visible_width(line), 2 * BOARD_RENDER_WIDTH + 4);
+        }
+        assert_eq!(visible_width(&" e ".black().on_yellow().to_string()), 3);
     }
 
     #[test]
@@ -1471,6 +4957,129 @@ mod tests {
         assert_ne!(board.get_hash(), another_board.get_hash());
     }
 
+    #[test]
+    fn hash_of_uncapturable_en_passant() {
+        let board = ChessBoard::default();
+        let with_en_passant = board.apply_san_line("e4").unwrap();
+        let without_en_passant =
+            ChessBoard::from_str("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1")
+                .unwrap();
+        assert_eq!(with_en_passant.get_en_passant(), Some(E3));
+        assert_eq!(with_en_passant.get_hash(), without_en_passant.get_hash());
+
+        let with_en_passant = board.apply_san_line("e4 Nf6 e5 d5").unwrap();
+        let without_en_passant =
+            ChessBoard::from_str("rnbqkb1r/ppp1pppp/5n2/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq - 0 3")
+                .unwrap();
+        assert!(with_en_passant.is_en_passant_capturable());
+        assert_ne!(with_en_passant.get_hash(), without_en_passant.get_hash());
+
+        // hash is updated incrementally the same way as it is calculated from scratch
+        for line in [
+            "e4 Nf6 e5 d5 Nf3",
+            "e4 Nf6 e5 d5 exd6",
+            "d4 e5 d5 c5 dxc6 Nf6 c4",
+        ] {
+            let board = board.apply_san_line(line).unwrap();
+            assert_eq!(
+                board.get_hash(),
+                ChessBoard::from_str(&board.as_fen()).unwrap().get_hash()
+            );
+        }
+    }
+
+    #[test]
+    fn position_equality() {
+        use std::collections::HashSet;
+
+        let board = ChessBoard::default();
+        let transposed = board.apply_san_line("Nf3 Nf6 Ng1 Ng8").unwrap();
+        assert_ne!(transposed.get_move_number(), board.get_move_number());
+        assert_eq!(transposed, board);
+
+        // uncapturable en-passant square doesn't differ the position
+        let with_en_passant = board.apply_san_line("e4").unwrap();
+        let without_en_passant =
+            ChessBoard::from_str("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1")
+                .unwrap();
+        assert_eq!(with_en_passant, without_en_passant);
+        assert_ne!(with_en_passant, board);
+
+        let positions: HashSet<ChessBoard> =
+            [board, transposed, with_en_passant, without_en_passant]
+                .into_iter()
+                .collect();
+        assert_eq!(positions.len(), 2);
+    }
+
+    #[test]
+    fn attacked_squares() {
+        let board = ChessBoard::from_str("4k3/8/8/3p4/8/8/8/R3K3 w - - 0 1").unwrap();
+        let squares: Vec<Square> = board.get_attacked_squares(Black).into_iter().collect();
+        assert_eq!(squares, vec![C4, E4, D7, E7, F7, D8, F8]);
+
+        // the king can't step back along the line of the checking rook
+        let board = ChessBoard::from_str("4k3/8/8/8/8/8/8/r3K3 w - - 0 1").unwrap();
+        let destinations: Vec<Square> = board
+            .get_legal_moves()
+            .into_iter()
+            .map(|m| m.piece_move().unwrap().get_destination_square())
+            .collect();
+        assert_eq!(destinations, vec![D2, E2, F2]);
+    }
+
+    #[test]
+    fn kpk_probing() {
+        let probe = |fen: &str| ChessBoard::from_str(fen).unwrap().probe_kpk();
+
+        // the king in front of the pawn on the 6th rank
+        assert_eq!(
+            probe("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1"),
+            Some(WinDrawLoss::Win)
+        );
+        // the side to move takes the opposition
+        assert_eq!(
+            probe("4k3/8/8/4K3/4P3/8/8/8 w - - 0 1"),
+            Some(WinDrawLoss::Win)
+        );
+        assert_eq!(
+            probe("4k3/8/8/4K3/4P3/8/8/8 b - - 0 1"),
+            Some(WinDrawLoss::Draw)
+        );
+        // the defending king holds the opposition
+        assert_eq!(
+            probe("8/4k3/8/4K3/4P3/8/8/8 w - - 0 1"),
+            Some(WinDrawLoss::Draw)
+        );
+        // stalemate
+        assert_eq!(
+            probe("4k3/4P3/4K3/8/8/8/8/8 b - - 0 1"),
+            Some(WinDrawLoss::Draw)
+        );
+        // the rook pawn with the defending king in the corner
+        assert_eq!(
+            probe("k7/8/1K6/P7/8/8/8/8 w - - 0 1"),
+            Some(WinDrawLoss::Draw)
+        );
+        // the unprotected pawn is captured
+        assert_eq!(
+            probe("8/8/8/4k3/4P3/8/8/K7 b - - 0 1"),
+            Some(WinDrawLoss::Draw)
+        );
+        // the black pawn runs to promotion
+        assert_eq!(
+            probe("7k/8/8/8/8/p7/8/7K b - - 0 1"),
+            Some(WinDrawLoss::Win)
+        );
+        assert_eq!(
+            probe("7k/8/8/8/8/p7/8/7K w - - 0 1"),
+            Some(WinDrawLoss::Loss)
+        );
+
+        assert_eq!(probe("4k3/8/8/8/8/8/4P3/3QK3 w - - 0 1"), None);
+        assert_eq!(probe("4k3/8/8/8/8/8/8/4K3 w - - 0 1"), None);
+    }
+
     #[test]
     fn checks_and_pins() {
         let board =
@@ -1498,6 +5107,28 @@ mod tests {
             "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 1"
         )
         .is_ok());
+
+        // the counters must fit into u32 and leave room for the next move
+        let board =
+            ChessBoard::from_str("4k3/8/8/8/8/8/8/4K2R b K - 4294967294 4294967294").unwrap();
+        let board = board.make_move(&mv!(King, E8, E7)).unwrap();
+        assert_eq!(board.get_move_number(), u32::MAX as usize);
+        let board = board.make_move(&mv!(King, E1, E2)).unwrap();
+        let board = board.make_move(&mv!(King, E7, E8)).unwrap();
+        assert_eq!(board.get_move_number(), u32::MAX as usize);
+        assert_eq!(
+            board.get_moves_since_capture_or_pawn_move(),
+            u32::MAX as usize
+        );
+        assert!(ChessBoard::from_str("4k3/8/8/8/8/8/8/4K2R w K - 0 4294967295").is_err());
+        assert!(ChessBoard::from_str("4k3/8/8/8/8/8/8/4K2R w K - 0 4294967296").is_err());
+        assert!(ChessBoard::from_str("4k3/8/8/8/8/8/8/4K2R w K - 4294967296 1").is_err());
+        let mut builder = BoardBuilder::from(ChessBoard::default());
+        builder.set_move_number(u32::MAX as usize + 1);
+        assert!(matches!(
+            ChessBoard::try_from(builder),
+            Err(Error::WrongMoveNumber)
+        ));
     }
 
     #[test]
@@ -1561,7 +5192,7 @@ mod tests {
         board
             .get_legal_moves()
             .iter()
-            .for_each(|one| assert_eq!(board.is_legal_move(one), true));
+            .for_each(|one| assert!(board.is_legal_move(one)));
     }
 
     #[test]
@@ -1598,6 +5229,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn en_passant_legality() {
+        use EnPassantIllegality::*;
+        let check = |fen: &str, source: Square| {
+            let board = ChessBoard::from_str(fen).unwrap();
+            let result = board.check_en_passant(source);
+            assert_eq!(result.is_ok(), board.is_en_passant_legal());
+            assert_eq!(
+                result.is_ok(),
+                board.get_legal_moves().contains(&mv![
+                    Pawn,
+                    source,
+                    board.get_en_passant().unwrap()
+                ])
+            );
+            result
+        };
+
+        assert_eq!(check("8/8/8/1Pp5/8/8/8/K6k w - c6 0 1", B5), Ok(()));
+        assert_eq!(
+            check("8/8/8/KPp4r/8/8/8/7k w - c6 0 1", B5),
+            Err(HorizontalDiscovery { attacker: H5 })
+        );
+        assert_eq!(
+            check("8/8/8/q1pP1K2/8/8/8/7k w - c6 0 1", D5),
+            Err(HorizontalDiscovery { attacker: A5 })
+        );
+        assert_eq!(
+            check("8/8/b7/1Pp5/8/8/4K3/7k w - c6 0 1", B5),
+            Err(PinnedPawn { attacker: A6 })
+        );
+        assert_eq!(check("8/3b4/8/1Pp5/K7/8/8/7k w - c6 0 1", B5), Ok(()));
+        assert_eq!(
+            check("8/b7/8/1Pp5/8/4K3/8/7k w - c6 0 1", B5),
+            Err(CapturedPawnDiscovery { attacker: A7 })
+        );
+        assert_eq!(
+            check("1b6/8/8/2pPK3/8/8/8/7k w - c6 0 1", D5),
+            Err(UnresolvedCheck { attacker: B8 })
+        );
+        assert_eq!(check("7k/8/8/8/3pP3/8/8/R3K3 b - e3 0 1", D4), Ok(()));
+
+        let board = ChessBoard::default();
+        assert_eq!(board.check_en_passant(E2), Err(NoEnPassantSquare));
+        assert!(!board.is_en_passant_legal());
+    }
+
     #[test]
     fn castling() {
         let board =
@@ -1623,6 +5301,172 @@ mod tests {
         assert!(!board.get_castle_rights(White).has_queenside());
         assert!(board.get_castle_rights(Black).has_kingside());
         assert!(!board.get_castle_rights(Black).has_queenside());
+
+        // promotion with capture of the rook on its initial square
+        let board = ChessBoard::from_str("r3k2r/6P1/8/8/8/8/8/4K3 w kq - 0 1")
+            .unwrap()
+            .make_move(&mv!(Pawn, G7, H8, Queen))
+            .unwrap();
+        assert_eq!(board.get_castle_rights(Black), QueenSide);
+        assert_eq!(
+            board.get_hash(),
+            ChessBoard::from_str(&board.as_fen()).unwrap().get_hash()
+        );
+
+        // the rook which is not on its initial square doesn't affect the rights
+        let board = ChessBoard::from_str("r3k2r/8/8/7R/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let next_board = board.make_move(&mv!(Rook, H5, H6)).unwrap();
+        assert_eq!(next_board.get_castle_rights(White), BothSides);
+        let next_board = board.make_move(&mv!(Rook, H5, H8)).unwrap();
+        assert_eq!(next_board.get_castle_rights(Black), QueenSide);
+        assert_eq!(next_board.get_castle_rights(White), BothSides);
+
+        let next_board = board.make_move(&castle_queen_side!()).unwrap();
+        assert_eq!(next_board.get_castle_rights(White), Neither);
+        assert_eq!(next_board.get_castle_rights(Black), BothSides);
+    }
+
+    #[test]
+    fn apply_lines() {
+        let board = ChessBoard::default();
+        assert_eq!(
+            board
+                .apply_san_line("e4 e5 Nf3 Nc6 Bb5 a6 Bxc6 dxc6 O-O")
+                .unwrap(),
+            board
+                .apply_uci_line("e2e4 e7e5 g1f3 b8c6 f1b5 a7a6 b5c6 d7c6 e1g1")
+                .unwrap()
+        );
+
+        match board.apply_san_line("e4 e5 Nf3 Nf6 Nf6") {
+            Err(Error::InvalidMoveInLine { token, ply }) => {
+                assert_eq!(token, "Nf6");
+                assert_eq!(ply, 4);
+            }
+            _ => panic!("the line must fail"),
+        }
+        assert!(board.apply_uci_line("e2e4 e7e5 e1e2 e8e9").is_err());
+
+        let board = ChessBoard::from_str("1r5k/P7/7K/8/8/8/8/8 w - - 0 1").unwrap();
+        assert_eq!(
+            board.apply_uci_line("a7b8n").unwrap(),
+            board.apply_san_line("axb8=N").unwrap()
+        );
+    }
+
+    #[test]
+    fn inconsistent_castling_rights() {
+        use squares::*;
+
+        match ChessBoard::from_str("r3k3/8/8/8/8/8/8/R3K2R w KQkq - 0 1") {
+            Err(Error::InvalidBoardInconsistentCastlingRights {
+                color,
+                side,
+                rook_square,
+            }) => {
+                assert_eq!(color, Black);
+                assert_eq!(side, KingSide);
+                assert_eq!(rook_square, H8);
+            }
+            _ => panic!("castling rights must be inconsistent"),
+        }
+        match ChessBoard::from_str("4k3/8/8/8/8/8/8/R2K3R w Q - 0 1") {
+            Err(Error::InvalidBoardInconsistentCastlingRights {
+                color,
+                side,
+                rook_square,
+            }) => {
+                assert_eq!(color, White);
+                assert_eq!(side, QueenSide);
+                assert_eq!(rook_square, A1);
+            }
+            _ => panic!("castling rights must be inconsistent"),
+        }
+
+        let board = ChessBoard::from_fen_lenient("r3k3/8/8/8/8/8/8/R2K3R w KQkq - 0 1").unwrap();
+        assert_eq!(board.get_castle_rights(White), Neither);
+        assert_eq!(board.get_castle_rights(Black), QueenSide);
+        assert_eq!(board.as_fen(), "r3k3/8/8/8/8/8/8/R2K3R w q - 0 1");
+    }
+
+    #[test]
+    fn fen_parse_options() {
+        let parse = |fen, en_passant| {
+            let options = FenParseOptions {
+                en_passant,
+                ..Default::default()
+            };
+            ChessBoard::from_fen_with_options(fen, &options)
+        };
+
+        // no pawn made the double push to e4
+        let inconsistent = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq e3 0 1";
+        for policy in [EnPassantPolicy::Keep, EnPassantPolicy::Strict] {
+            assert!(matches!(
+                parse(inconsistent, policy),
+                Err(Error::InvalidBoardInconsistentEnPassant)
+            ));
+        }
+        assert_eq!(
+            parse(inconsistent, EnPassantPolicy::Strip)
+                .unwrap()
+                .as_fen(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1"
+        );
+
+        // the capture is legal, so the square is kept by any policy
+        let fen = "rnbqkbnr/ppp1pppp/8/8/2Pp4/8/PP1PPPPP/RNBQKBNR b KQkq c3 0 1";
+        for policy in [
+            EnPassantPolicy::Keep,
+            EnPassantPolicy::Strip,
+            EnPassantPolicy::Strict,
+        ] {
+            assert_eq!(parse(fen, policy).unwrap().get_en_passant(), Some(C3));
+        }
+
+        // the errors unrelated to en passant are not hidden
+        assert!(parse("8/8/8/8/8/8/8/K6k x - - 0 1", EnPassantPolicy::Strip).is_err());
+
+        let options = FenParseOptions {
+            en_passant:       EnPassantPolicy::Strip,
+            lenient_castling: true,
+        };
+        let board =
+            ChessBoard::from_fen_with_options("r3k3/8/8/8/4P3/8/8/R2K3R b KQkq e3 0 1", &options)
+                .unwrap();
+        assert_eq!(board.as_fen(), "r3k3/8/8/8/4P3/8/8/R2K3R b q - 0 1");
+    }
+
+    #[test]
+    fn castling_notations() {
+        let board =
+            ChessBoard::from_str("r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R b Kq - 0 1").unwrap();
+        assert_eq!(
+            board.normalize_castling(&mv!(King, E8, C8)),
+            castle_queen_side!()
+        );
+        assert_eq!(
+            board.normalize_castling(&mv!(King, E8, A8)),
+            castle_queen_side!()
+        );
+        // the mislabeled move is not taken for the castling
+        assert_eq!(
+            board.normalize_castling(&mv!(Pawn, E8, A8)),
+            mv!(Pawn, E8, A8)
+        );
+        assert!(board.make_move(&mv!(Pawn, E8, A8)).is_err());
+        assert_eq!(
+            board.normalize_castling(&mv!(King, E8, G8)),
+            mv!(King, E8, G8)
+        );
+        assert_eq!(
+            board.normalize_castling(&mv!(King, E1, G1)),
+            mv!(King, E1, G1)
+        );
+
+        let board = board.make_move(&mv!(King, E8, C8)).unwrap();
+        assert_eq!(board.get_piece_on(D8), Some(Piece(Rook, Black)));
+        assert_eq!(board.get_piece_on(C8), Some(Piece(King, Black)));
     }
 
     #[test]
@@ -1630,6 +5474,112 @@ mod tests {
         assert!(ChessBoard::from_str("Q3k3/8/4K3/8/8/8/8/8 w - - 0 1").is_err());
     }
 
+    #[test]
+    fn update_legal_moves() {
+        use crate::testing::random_board;
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..200 {
+            let previous = random_board(&ChessBoard::default(), 30, &mut rng);
+            let previous_moves = previous.get_legal_moves();
+            let board = random_board(&previous, 2, &mut rng);
+            let expected = board.get_legal_moves();
+            let moves = board.update_legal_moves(&previous, &previous_moves);
+            assert_eq!(moves, expected, "{}", board.as_fen());
+            assert!(
+                expected
+                    .windows(2)
+                    .all(|pair| pair[0].sort_key() < pair[1].sort_key()),
+                "{}",
+                board.as_fen()
+            );
+        }
+    }
+
+    #[test]
+    #[ignore = "benchmark, run with --ignored --nocapture"]
+    fn update_legal_moves_benchmark() {
+        use crate::testing::random_board;
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+        use std::time::Instant;
+
+        const ITERATIONS: usize = 10000;
+        let mut rng = StdRng::seed_from_u64(0);
+        let pairs: Vec<(ChessBoard, LegalMoves, ChessBoard)> = (0..100)
+            .map(|_| {
+                let previous = random_board(&ChessBoard::default(), 20, &mut rng);
+                let board = random_board(&previous, 2, &mut rng);
+                (previous, previous.get_legal_moves(), board)
+            })
+            .collect();
+
+        let start = Instant::now();
+        for _ in 0..ITERATIONS / pairs.len() {
+            for (_, _, board) in pairs.iter() {
+                std::hint::black_box(board.get_legal_moves());
+            }
+        }
+        let full = start.elapsed();
+
+        let start = Instant::now();
+        for _ in 0..ITERATIONS / pairs.len() {
+            for (previous, previous_moves, board) in pairs.iter() {
+                std::hint::black_box(board.update_legal_moves(previous, previous_moves));
+            }
+        }
+        let incremental = start.elapsed();
+        println!("full regeneration: {full:?}, incremental update: {incremental:?}");
+    }
+
+    #[test]
+    fn captures_and_quiets() {
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "8/8/8/KPp4r/8/8/8/7k w - c6 0 2",
+        ] {
+            let board = ChessBoard::from_str(fen).unwrap();
+            let captures = board.get_legal_captures();
+            let quiets = board.get_legal_quiets();
+            assert!(captures.iter().all(|m| m.is_capture_on_board(&board)));
+            assert!(!quiets.iter().any(|m| m.is_capture_on_board(&board)));
+
+            let mut moves = [captures, quiets].concat();
+            moves.sort_by_key(BoardMove::sort_key);
+            assert_eq!(moves, board.get_legal_moves());
+        }
+    }
+
+    #[test]
+    fn legal_moves_counting() {
+        fn check_counts(board: &ChessBoard, depth: usize) {
+            let moves = board.get_legal_moves();
+            assert_eq!(board.count_legal_moves(), moves.len(), "{}", board.as_fen());
+            if depth > 1 {
+                for m in moves {
+                    check_counts(&board.make_move(&m).unwrap(), depth - 1);
+                }
+            }
+        }
+
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "8/8/8/KPp4r/8/8/8/7k w - c6 0 2", // en passant capture exposes the king
+            "4k3/8/8/8/8/8/1q6/R3K2R w KQ - 0 1",
+            "4k3/8/8/8/1b6/8/8/r3K2R w K - 0 1", // double check
+        ] {
+            check_counts(&ChessBoard::from_str(fen).unwrap(), 3);
+        }
+    }
+
     fn perft_get_branches(position: &ChessBoard) -> Vec<(BoardMove, ChessBoard)> {
         position
             .get_legal_moves()
@@ -1642,7 +5592,7 @@ mod tests {
         let mut boards = vec![position];
         let mut positions_counter = vec![0; recursion_level];
 
-        for i in 0..recursion_level {
+        for counter in positions_counter.iter_mut() {
             let mut x = vec![];
             boards.iter().for_each(|b| {
                 let t = perft_get_branches(b);
@@ -1650,7 +5600,7 @@ mod tests {
             });
 
             boards = x;
-            positions_counter[i] = boards.len();
+            *counter = boards.len();
         }
         positions_counter
     }
@@ -1662,7 +5612,7 @@ mod tests {
 
         perft_calculate_positions(position, MOVES_NUMBER)
             .into_iter()
-            .zip([20, 400, 8902, 197281, 4865609].into_iter())
+            .zip([20, 400, 8902, 197281, 4865609])
             .for_each(|(a, b)| assert_eq!(a, b));
     }
 
@@ -1676,7 +5626,7 @@ mod tests {
 
         perft_calculate_positions(position, MOVES_NUMBER)
             .into_iter()
-            .zip([48, 2039, 97862, 4085603, 193690690].into_iter())
+            .zip([48, 2039, 97862, 4085603, 193690690])
             .for_each(|(a, b)| assert_eq!(a, b));
     }
 
@@ -1687,7 +5637,7 @@ mod tests {
 
         perft_calculate_positions(position, MOVES_NUMBER)
             .into_iter()
-            .zip([14, 191, 2812, 43238, 674624].into_iter())
+            .zip([14, 191, 2812, 43238, 674624])
             .for_each(|(a, b)| assert_eq!(a, b));
     }
 
@@ -1701,7 +5651,7 @@ mod tests {
 
         perft_calculate_positions(position, MOVES_NUMBER)
             .into_iter()
-            .zip([6, 264, 9467, 422333, 15833292].into_iter())
+            .zip([6, 264, 9467, 422333, 15833292])
             .for_each(|(a, b)| assert_eq!(a, b));
     }
 
@@ -1714,7 +5664,7 @@ mod tests {
 
         perft_calculate_positions(position, MOVES_NUMBER)
             .into_iter()
-            .zip([44, 1486, 62379, 2103487, 89941194].into_iter())
+            .zip([44, 1486, 62379, 2103487, 89941194])
             .for_each(|(a, b)| assert_eq!(a, b));
     }
 
@@ -1728,7 +5678,7 @@ mod tests {
 
         perft_calculate_positions(position, MOVES_NUMBER)
             .into_iter()
-            .zip([46, 2079, 89890, 3894594, 164075551].into_iter())
+            .zip([46, 2079, 89890, 3894594, 164075551])
             .for_each(|(a, b)| assert_eq!(a, b));
     }
 }
@@ -0,0 +1,185 @@
+//! Conversions to and from the types of the ``chess`` crate (requires ``chess`` feature)
+//!
+//! The squares, bitboards, colors, piece types and castling rights are converted directly. The
+//! boards are converted to the ``chess`` crate through FEN, so positions which it doesn't support
+//! (e.g. castling with non-standard rook files) can't be converted. The moves of the ``chess``
+//! crate don't contain the piece type and describe castling as the king move, so they are
+//! converted in the context of the board (see ``ChessBoard::parse_chess_move`` and
+//! ``ChessBoard::to_chess_move``)
+
+use crate::errors::LibChessError as Error;
+use crate::{BitBoard, BoardBuilder, CastlingRights, ChessBoard, Color, Piece, PieceType, Square};
+use std::str::FromStr;
+
+impl From<Square> for chess::Square {
+    #[inline]
+    fn from(square: Square) -> Self { chess::ALL_SQUARES[square.to_index()] }
+}
+
+impl From<chess::Square> for Square {
+    #[inline]
+    fn from(square: chess::Square) -> Self { Square::new(square.to_int()).unwrap() }
+}
+
+impl From<BitBoard> for chess::BitBoard {
+    #[inline]
+    fn from(bitboard: BitBoard) -> Self { chess::BitBoard::new(bitboard.bits()) }
+}
+
+impl From<chess::BitBoard> for BitBoard {
+    #[inline]
+    fn from(bitboard: chess::BitBoard) -> Self { BitBoard::new(bitboard.0) }
+}
+
+impl From<Color> for chess::Color {
+    #[inline]
+    fn from(color: Color) -> Self {
+        match color {
+            Color::White => chess::Color::White,
+            Color::Black => chess::Color::Black,
+        }
+    }
+}
+
+impl From<chess::Color> for Color {
+    #[inline]
+    fn from(color: chess::Color) -> Self {
+        match color {
+            chess::Color::White => Color::White,
+            chess::Color::Black => Color::Black,
+        }
+    }
+}
+
+impl From<PieceType> for chess::Piece {
+    #[inline]
+    fn from(piece_type: PieceType) -> Self { chess::ALL_PIECES[piece_type.to_index()] }
+}
+
+impl From<chess::Piece> for PieceType {
+    #[inline]
+    fn from(piece: chess::Piece) -> Self { PieceType::from_index(piece.to_index()).unwrap() }
+}
+
+impl From<CastlingRights> for chess::CastleRights {
+    #[inline]
+    fn from(rights: CastlingRights) -> Self {
+        match rights {
+            CastlingRights::Neither => chess::CastleRights::NoRights,
+            CastlingRights::KingSide => chess::CastleRights::KingSide,
+            CastlingRights::QueenSide => chess::CastleRights::QueenSide,
+            CastlingRights::BothSides => chess::CastleRights::Both,
+        }
+    }
+}
+
+impl From<chess::CastleRights> for CastlingRights {
+    #[inline]
+    fn from(rights: chess::CastleRights) -> Self {
+        match rights {
+            chess::CastleRights::NoRights => CastlingRights::Neither,
+            chess::CastleRights::KingSide => CastlingRights::KingSide,
+            chess::CastleRights::QueenSide => CastlingRights::QueenSide,
+            chess::CastleRights::Both => CastlingRights::BothSides,
+        }
+    }
+}
+
+impl TryFrom<&ChessBoard> for chess::Board {
+    type Error = Error;
+
+    fn try_from(board: &ChessBoard) -> Result<Self, Self::Error> {
+        let fen = board.as_fen();
+        chess::Board::from_str(&fen).map_err(|_| Error::InvalidFENString { s: fen })
+    }
+}
+
+impl TryFrom<&chess::Board> for ChessBoard {
+    type Error = Error;
+
+    fn try_from(board: &chess::Board) -> Result<Self, Self::Error> {
+        let mut builder = BoardBuilder::new();
+        for square in *board.combined() {
+            let piece = Piece(
+                board.piece_on(square).unwrap().into(),
+                board.color_on(square).unwrap().into(),
+            );
+            builder.place(piece, square.into());
+        }
+        for color in chess::ALL_COLORS {
+            builder.set_castling_rights(color.into(), board.castle_rights(color).into());
+        }
+        // the ``chess`` crate keeps the square of the pawn which may be captured en passant
+        let side_to_move = board.side_to_move();
+        builder
+            .set_side_to_move(side_to_move.into())
+            .set_en_passant(
+                board
+                    .en_passant()
+                    .map(|square| square.uforward(side_to_move).into()),
+            )
+            .set_move_number(1);
+        ChessBoard::try_from(builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{squares::*, CASTLING_RIGHTS_NUMBER, SQUARES_NUMBER};
+
+    #[test]
+    fn chess_crate_conversions() {
+        for square in (0..SQUARES_NUMBER as u8).map(|i| Square::new(i).unwrap()) {
+            let converted = chess::Square::from(square);
+            assert_eq!(converted.to_string(), square.to_string());
+            assert_eq!(Square::from(converted), square);
+        }
+        for piece_type in PieceType::iter() {
+            assert_eq!(PieceType::from(chess::Piece::from(piece_type)), piece_type);
+        }
+        for color in Color::iter() {
+            assert_eq!(Color::from(chess::Color::from(color)), color);
+        }
+        for rights in (0..CASTLING_RIGHTS_NUMBER).map(|i| CastlingRights::from_index(i).unwrap()) {
+            assert_eq!(
+                CastlingRights::from(chess::CastleRights::from(rights)),
+                rights
+            );
+        }
+
+        let board = ChessBoard::from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        )
+        .unwrap();
+        let converted = chess::Board::try_from(&board).unwrap();
+        assert_eq!(ChessBoard::try_from(&converted).unwrap(), board);
+        assert_eq!(
+            BitBoard::from(*converted.combined()),
+            board.get_combined_mask()
+        );
+        assert_eq!(
+            chess::BitBoard::from(board.get_color_mask(Color::Black)),
+            *converted.color_combined(chess::Color::Black)
+        );
+
+        let mut moves = chess::MoveGen::new_legal(&converted)
+            .map(|m| board.parse_chess_move(m).unwrap())
+            .collect::<Vec<_>>();
+        moves.sort_by_key(crate::BoardMove::sort_key);
+        assert_eq!(moves, board.get_legal_moves());
+        for m in moves {
+            let chess_move = board.to_chess_move(&m);
+            assert_eq!(
+                ChessBoard::try_from(&converted.make_move_new(chess_move)).unwrap(),
+                board.make_move(&m).unwrap()
+            );
+        }
+
+        let castling = board.parse_chess_move(chess::ChessMove::new(E1.into(), C1.into(), None));
+        assert_eq!(castling.unwrap(), crate::BoardMove::CastleQueenSide);
+        assert!(board
+            .parse_chess_move(chess::ChessMove::new(A3.into(), A4.into(), None))
+            .is_err());
+    }
+}
@@ -2,8 +2,10 @@ use crate::errors::LibChessError as Error;
 use crate::Rank;
 use std::fmt;
 use std::ops::Not;
+use std::str::FromStr;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Color {
     White,
     Black,
@@ -24,15 +26,40 @@ impl Not for Color {
 }
 
 impl fmt::Display for Color {
+    /// Displays the color as "white"/"black" or in the short FEN form "w"/"b" with the alternate
+    /// flag
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::Color::*;
+    /// assert_eq!(format!("{White}"), "white");
+    /// assert_eq!(format!("{Black:#}"), "b");
+    /// ```
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let display_str = match self {
-            Color::White => "white",
-            Color::Black => "black",
+        let display_str = match (self, f.alternate()) {
+            (Color::White, false) => "white",
+            (Color::Black, false) => "black",
+            (Color::White, true) => "w",
+            (Color::Black, true) => "b",
         };
         write!(f, "{display_str}")
     }
 }
 
+impl FromStr for Color {
+    type Err = Error;
+
+    /// Parses the color from the full or the short form case-insensitively ("white", "w",
+    /// "Black", "B", etc.)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "w" | "white" => Ok(Color::White),
+            "b" | "black" => Ok(Color::Black),
+            _ => Err(Error::InvalidColorRepresentation),
+        }
+    }
+}
+
 impl Color {
     #[inline]
     pub fn to_index(&self) -> usize { *self as usize }
@@ -62,6 +89,45 @@ impl Color {
         }
     }
 
+    /// Returns the rank of the pawns in the initial position
+    #[inline]
+    pub fn get_pawn_start_rank(&self) -> Rank {
+        match self {
+            Color::White => Rank::Second,
+            Color::Black => Rank::Seventh,
+        }
+    }
+
+    /// Returns the direction of the pawn moves along the ranks: 1 for white and -1 for black
+    #[inline]
+    pub fn get_forward_direction(&self) -> i32 {
+        match self {
+            Color::White => 1,
+            Color::Black => -1,
+        }
+    }
+
+    /// Returns the rank as it is seen from the side of the player: the back rank of the player
+    /// is always the first one
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{Color::*, Rank};
+    /// assert_eq!(White.get_relative_rank(Rank::Second), Rank::Second);
+    /// assert_eq!(Black.get_relative_rank(Rank::Second), Rank::Seventh);
+    /// assert_eq!(
+    ///     Black.get_relative_rank(Black.get_promotion_rank()),
+    ///     Rank::Eighth
+    /// );
+    /// ```
+    #[inline]
+    pub fn get_relative_rank(&self, rank: Rank) -> Rank {
+        match self {
+            Color::White => rank,
+            Color::Black => Rank::from_index(7 - rank.to_index()).unwrap(),
+        }
+    }
+
     pub fn iter() -> impl Iterator<Item = Color> {
         use Color::*;
         [White, Black].iter().copied()
@@ -78,4 +144,34 @@ mod tests {
             assert_eq!(Color::from_index(i).unwrap().to_index(), i);
         }
     }
+
+    #[test]
+    fn representation() {
+        for color in Color::iter() {
+            assert_eq!(Color::from_str(&format!("{color}")).unwrap(), color);
+            assert_eq!(Color::from_str(&format!("{color:#}")).unwrap(), color);
+        }
+        assert_eq!(Color::from_str("White").unwrap(), Color::White);
+        assert_eq!(Color::from_str("B").unwrap(), Color::Black);
+        assert!(Color::from_str("").is_err());
+        assert!(Color::from_str("red").is_err());
+    }
+
+    #[test]
+    fn relative_ranks() {
+        for color in Color::iter() {
+            assert_eq!(color.get_relative_rank(color.get_back_rank()), Rank::First);
+            assert_eq!(
+                color.get_relative_rank(color.get_pawn_start_rank()),
+                Rank::Second
+            );
+            assert_eq!(
+                color.get_relative_rank(color.get_promotion_rank()),
+                Rank::Eighth
+            );
+            let start = color.get_pawn_start_rank().to_index() as i32;
+            let back = color.get_back_rank().to_index() as i32;
+            assert_eq!(start - back, color.get_forward_direction());
+        }
+    }
 }
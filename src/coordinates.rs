@@ -1,14 +1,33 @@
 use super::File;
 use super::Rank;
 use crate::errors::LibChessError as Error;
+use crate::BitBoard;
 use std::fmt;
 use std::str::FromStr;
 
 pub const SQUARES_NUMBER: usize = 64;
 
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Square(u8);
 
+/// Color of the square on the board (light or dark complex)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SquareColor {
+    Light,
+    Dark,
+}
+
+impl std::ops::Not for SquareColor {
+    type Output = SquareColor;
+
+    fn not(self) -> SquareColor {
+        match self {
+            SquareColor::Light => SquareColor::Dark,
+            SquareColor::Dark => SquareColor::Light,
+        }
+    }
+}
+
 impl fmt::Display for Square {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -105,7 +124,7 @@ impl Square {
     }
 
     pub fn is_light(&self) -> bool {
-        if (self.get_rank().to_index() + self.get_file().to_index()) % 2 == 0 {
+        if (self.get_rank().to_index() + self.get_file().to_index()).is_multiple_of(2) {
             return false;
         }
         true
@@ -113,6 +132,48 @@ impl Square {
 
     #[inline]
     pub fn is_dark(&self) -> bool { !self.is_light() }
+
+    #[inline]
+    pub fn get_square_color(&self) -> SquareColor {
+        if self.is_light() {
+            SquareColor::Light
+        } else {
+            SquareColor::Dark
+        }
+    }
+
+    /// Returns the squares of the mask in ascending order (from a1 to h8)
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{squares::*, BitBoard, Square};
+    /// let mask = BitBoard::from_square(D5) | BitBoard::from_square(E4);
+    /// assert_eq!(Square::all_from_bitboard(mask), vec![E4, D5]);
+    /// ```
+    #[inline]
+    pub fn all_from_bitboard(bitboard: BitBoard) -> Vec<Square> { bitboard.collect() }
+
+    /// Parses the comma-separated list of squares ("e4, d5"). The order of squares is kept and
+    /// the whitespaces around them are ignored. The empty string is the empty list
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidSquareRepresentation`` if any item of the list is not a square
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{squares::*, Square};
+    /// assert_eq!(Square::from_notation_list("e4, d5").unwrap(), vec![E4, D5]);
+    /// assert!(Square::from_notation_list("").unwrap().is_empty());
+    /// assert!(Square::from_notation_list("e4,,d5").is_err());
+    /// ```
+    pub fn from_notation_list(s: &str) -> Result<Vec<Square>, Error> {
+        if s.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        s.split(',')
+            .map(|item| Square::from_str(item.trim()))
+            .collect()
+    }
 }
 
 macro_rules! define_square {
@@ -194,6 +255,23 @@ pub mod squares {
 mod tests {
     use super::*;
 
+    #[test]
+    fn square_colors() {
+        use crate::{BitBoard, DARK_SQUARES, LIGHT_SQUARES};
+        for index in 0..SQUARES_NUMBER {
+            let square = Square::new(index as u8).unwrap();
+            let mask = BitBoard::from_square(square);
+            assert_eq!(square.is_light(), !(mask & LIGHT_SQUARES).is_blank());
+            assert_eq!(square.is_dark(), !(mask & DARK_SQUARES).is_blank());
+            assert_eq!(
+                BitBoard::from_square_color(square.get_square_color()) & mask,
+                mask
+            );
+        }
+        assert_eq!(!SquareColor::Light, SquareColor::Dark);
+        assert_eq!(squares::A1.get_square_color(), SquareColor::Dark);
+    }
+
     #[test]
     fn create_from_string() {
         assert_eq!(Square::from_str("e2").unwrap(), squares::E2);
@@ -219,10 +297,10 @@ mod tests {
     fn test_light_dark() {
         use squares::*;
 
-        assert_eq!(A1.is_light(), false);
-        assert_eq!(E4.is_light(), true);
-        assert_eq!(A3.is_dark(), true);
-        assert_eq!(E6.is_dark(), false);
+        assert!(!A1.is_light());
+        assert!(E4.is_light());
+        assert!(A3.is_dark());
+        assert!(!E6.is_dark());
     }
 
     #[test]
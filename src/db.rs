@@ -0,0 +1,378 @@
+//! PGN databases
+//!
+//! This module implements reading of PGN-files with many games (game by game, without loading
+//! the whole file into memory) and the aggregate statistics of such databases: the results
+//! distribution, the most common openings, the average length of games and their termination
+//! types
+
+use crate::errors::LibChessError as Error;
+use crate::{Color, Game, GameHistory, GameStatus, MoveNumbering, PositionHashValueType};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+/// Iterator over the PGN-strings of separate games read from the stream. A new game starts
+/// with a tag line ("[...]") following the movetext of the previous game
+///
+/// # Examples
+/// ```
+/// use libchess::db::PgnReader;
+/// use libchess::Game;
+/// let database = "[Event \"1\"]\n\n1. e4 e5 1-0\n\n[Event \"2\"]\n\n1. d4 d5 0-1\n";
+/// let games: Vec<Game> = PgnReader::new(database.as_bytes())
+///     .map(|pgn| Game::from_pgn(&pgn.unwrap()).unwrap())
+///     .collect();
+/// assert_eq!(games.len(), 2);
+/// ```
+pub struct PgnReader<R: BufRead> {
+    lines:   std::io::Lines<R>,
+    pending: Option<String>,
+}
+
+impl<R: BufRead> PgnReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            lines:   reader.lines(),
+            pending: None,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = Result<String, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut pgn = self.pending.take().unwrap_or_default();
+        let mut has_movetext = false;
+
+        for line in self.lines.by_ref() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    return Some(Err(Error::InvalidPGNStream {
+                        description: e.to_string(),
+                    }))
+                }
+            };
+            let trimmed = line.trim();
+            if trimmed.starts_with('[') & has_movetext {
+                self.pending = Some(format!("{line}\n"));
+                return Some(Ok(pgn));
+            }
+            has_movetext |= !trimmed.is_empty() & !trimmed.starts_with('[');
+            if !trimmed.is_empty() | !pgn.is_empty() {
+                pgn.push_str(&line);
+                pgn.push('\n');
+            }
+        }
+
+        match pgn.trim().is_empty() {
+            true => None,
+            false => Some(Ok(pgn)),
+        }
+    }
+}
+
+/// Numbers of games by their results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResultsDistribution {
+    pub white_wins: usize,
+    pub black_wins: usize,
+    pub draws:      usize,
+}
+
+/// Statistics of the position reached after the opening moves
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OpeningStatistics {
+    /// Hash of the position reached after the opening
+    pub hash:    PositionHashValueType,
+    /// Movetext of the first game which reached this position
+    pub line:    String,
+    pub games:   usize,
+    pub results: ResultsDistribution,
+}
+
+/// Aggregate statistics of the PGN database (see ``collect_statistics``)
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DatabaseReport {
+    /// Number of successfully parsed games
+    pub games:         usize,
+    /// Number of games which can't be parsed
+    pub invalid_games: usize,
+    pub results:       ResultsDistribution,
+    /// Average length of games in plies
+    pub average_plies: f64,
+    /// Numbers of games by termination type ("checkmate", "resignation", etc.)
+    pub terminations:  BTreeMap<String, usize>,
+    /// Openings sorted by the number of games (the most common first)
+    pub openings:      Vec<OpeningStatistics>,
+    /// Number of plies which are considered as the opening
+    pub opening_plies: usize,
+}
+
+#[cfg(feature = "serde")]
+impl DatabaseReport {
+    pub fn to_json(&self) -> String { serde_json::to_string(self).unwrap() }
+}
+
+impl ResultsDistribution {
+    fn add(&mut self, status: GameStatus) -> &mut Self {
+        use GameStatus::*;
+        match status {
+            CheckMated(loser) | Resigned(loser) | Forfeited(loser) => match loser {
+                Color::White => self.black_wins += 1,
+                Color::Black => self.white_wins += 1,
+            },
+            Ongoing | DrawOffered(_) => {}
+            _ => self.draws += 1,
+        }
+        self
+    }
+}
+
+/// Returns the termination type of the finished game. Results of PGN-games without the final
+/// position (e.g. lost on time) are imported as resignations and draw agreements
+fn get_termination_type(status: GameStatus) -> &'static str {
+    use GameStatus::*;
+    match status {
+        Ongoing | DrawOffered(_) => "unfinished",
+        CheckMated(_) => "checkmate",
+        Resigned(_) => "resignation",
+        Forfeited(_) => "forfeit",
+        FiftyMovesDrawDeclared => "fifty moves rule",
+        TheoreticalDrawDeclared => "insufficient material",
+        RepetitionDrawDeclared => "repetition",
+        DrawAccepted => "agreement",
+        Stalemate => "stalemate",
+    }
+}
+
+/// Reads all games from the PGN-stream and collects their aggregate statistics. Openings are
+/// grouped by the position reached after ``opening_plies`` plies (so transpositions are counted
+/// together), games which are shorter are not counted in the openings
+///
+/// # Errors
+/// ``LibChessError::InvalidPGNStream`` if the stream can't be read. Games which can't be parsed
+/// are counted in ``DatabaseReport::invalid_games`` and don't stop the processing
+///
+/// # Examples
+/// ```
+/// use libchess::db::collect_statistics;
+/// let database = "[Event \"1\"]\n\n1. e4 e5 2. Nf3 1-0\n\n\
+///                 [Event \"2\"]\n\n1. Nf3 e5 2. e4 1/2-1/2\n\n\
+///                 [Event \"3\"]\n\n1. d4 0-1\n";
+/// let report = collect_statistics(database.as_bytes(), 3).unwrap();
+/// assert_eq!(report.games, 3);
+/// assert_eq!(report.results.draws, 1);
+/// assert_eq!(report.openings[0].games, 2); // the transposition is counted as the same opening
+/// assert_eq!(report.openings[0].line, "1.e4 e5 2.Nf3 ");
+/// ```
+pub fn collect_statistics(
+    reader: impl BufRead,
+    opening_plies: usize,
+) -> Result<DatabaseReport, Error> {
+    let mut report = DatabaseReport {
+        opening_plies,
+        ..Default::default()
+    };
+    let mut openings: HashMap<PositionHashValueType, OpeningStatistics> = HashMap::new();
+    let mut total_plies = 0;
+
+    for pgn in PgnReader::new(reader) {
+        let game = match Game::from_pgn(&pgn?) {
+            Ok(game) => game,
+            Err(_) => {
+                report.invalid_games += 1;
+                continue;
+            }
+        };
+
+        let status = game.get_game_status();
+        let history = game.get_action_history();
+        report.games += 1;
+        report.results.add(status);
+        total_plies += history.get_ply_count().to_index();
+        *report
+            .terminations
+            .entry(get_termination_type(status).to_string())
+            .or_insert(0) += 1;
+
+        if let Ok(position) = history.get_position_on_move(opening_plies) {
+            let opening =
+                openings
+                    .entry(position.get_hash())
+                    .or_insert_with(|| OpeningStatistics {
+                        hash:    position.get_hash(),
+                        line:    opening_line(&game, opening_plies),
+                        games:   0,
+                        results: ResultsDistribution::default(),
+                    });
+            opening.games += 1;
+            opening.results.add(status);
+        }
+    }
+
+    if report.games > 0 {
+        report.average_plies = total_plies as f64 / report.games as f64;
+    }
+    report.openings = openings.into_values().collect();
+    report
+        .openings
+        .sort_by(|a, b| b.games.cmp(&a.games).then_with(|| a.line.cmp(&b.line)));
+    Ok(report)
+}
+
+/// Returns the PGN database of the games: the games in the export format (see ``Game::as_pgn``)
+/// separated by the blank lines
+fn format_games(games: &[Game]) -> String {
+    let pgns: Vec<String> = games.iter().map(|game| game.as_pgn()).collect();
+    format!("{}\n", pgns.join("\n\n"))
+}
+
+/// Writes the games to the stream as the PGN database. Every game has the tags of the Seven
+/// Tag Roster in the standard order followed by other tags in alphabetical order, the tag
+/// values are escaped and the movetext is wrapped to fit 80 columns. The whole database is
+/// formatted before writing, so nothing is written if any game can't be formatted
+///
+/// # Errors
+/// ``LibChessError::InvalidPGNStream`` if the stream can't be written
+///
+/// # Examples
+/// ```
+/// use libchess::db::{write_games, PgnReader};
+/// use libchess::Game;
+/// let mut first = Game::from_san_moves([], &["e4", "e5"]).unwrap();
+/// first.get_metadata_mut().set_event("The \"Open\"");
+/// let second = Game::from_san_moves([], &["d4"]).unwrap();
+///
+/// let mut buffer = Vec::new();
+/// write_games(&mut buffer, &[first, second]).unwrap();
+/// let database = String::from_utf8(buffer).unwrap();
+/// assert!(database.starts_with("[Event \"The \\\"Open\\\"\"]\n[Site \"?\"]\n"));
+/// assert_eq!(PgnReader::new(database.as_bytes()).count(), 2);
+/// ```
+pub fn write_games(mut writer: impl Write, games: &[Game]) -> Result<(), Error> {
+    let database = format_games(games);
+    writer
+        .write_all(database.as_bytes())
+        .and_then(|_| writer.flush())
+        .map_err(|e| Error::InvalidPGNStream {
+            description: e.to_string(),
+        })
+}
+
+/// Saves the games to the PGN file (see ``write_games``). The database is written to the
+/// temporary file next to the target one, which then replaces the target, so the readers never
+/// see the partially written file
+///
+/// # Errors
+/// ``LibChessError::InvalidPGNStream`` if the file can't be written
+pub fn save_games(path: impl AsRef<Path>, games: &[Game]) -> Result<(), Error> {
+    let path = path.as_ref();
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+
+    let error = |e: std::io::Error| Error::InvalidPGNStream {
+        description: e.to_string(),
+    };
+    fs::write(&temporary, format_games(games)).map_err(error)?;
+    fs::rename(&temporary, path).map_err(|e| {
+        let _ = fs::remove_file(&temporary);
+        error(e)
+    })
+}
+
+/// Returns the movetext of the first plies of the game
+fn opening_line(game: &Game, plies: usize) -> String {
+    let history = game.get_action_history();
+    let mut opening = GameHistory::from_position(history.get_starting_position());
+    for ply in 0..plies {
+        opening.push(history.get_moves()[ply], history.get_positions()[ply + 1]);
+    }
+    opening.to_movetext(MoveNumbering::FromPosition, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn writing_games() {
+        let games: Vec<Game> = ["examples/pgn_data/game1.pgn", "examples/pgn_data/game2.pgn"]
+            .iter()
+            .map(|path| Game::from_pgn(&fs::read_to_string(path).unwrap()).unwrap())
+            .collect();
+        let mut games = games;
+        games[1].get_metadata_mut().set_site("C:\\chess \"club\"");
+
+        let mut buffer = Vec::new();
+        write_games(&mut buffer, &games).unwrap();
+        let database = String::from_utf8(buffer).unwrap();
+        assert!(database
+            .lines()
+            .filter(|line| !line.starts_with('['))
+            .all(|line| line.len() < 80));
+        assert!(database.contains("[Site \"C:\\\\chess \\\"club\\\"\"]\n"));
+        assert!(database.ends_with(" 1-0\n"));
+
+        let restored: Vec<Game> = PgnReader::new(database.as_bytes())
+            .map(|pgn| Game::from_pgn(&pgn.unwrap()).unwrap())
+            .collect();
+        assert_eq!(restored.len(), 2);
+        for (game, restored) in games.iter().zip(restored.iter()) {
+            assert_eq!(game.as_pgn(), restored.as_pgn());
+        }
+        assert_eq!(
+            restored[1].get_metadata().get_site(),
+            Some("C:\\chess \"club\"")
+        );
+
+        let path = std::env::temp_dir().join("libchess_writing_games.pgn");
+        save_games(&path, &games).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), database);
+        fs::remove_file(&path).unwrap();
+
+        let mut buffer = Vec::new();
+        write_games(&mut buffer, &[]).unwrap();
+        assert_eq!(buffer, b"\n");
+    }
+
+    #[test]
+    fn reading_stream() {
+        let database = format!(
+            "\n\n{}\n{}\n\n[Event \"broken\"]\n\n1. e4 e4 1-0\n",
+            fs::read_to_string("examples/pgn_data/game1.pgn").unwrap(),
+            fs::read_to_string("examples/pgn_data/game2.pgn").unwrap(),
+        );
+        let pgns: Vec<String> = PgnReader::new(database.as_bytes())
+            .map(|pgn| pgn.unwrap())
+            .collect();
+        assert_eq!(pgns.len(), 3);
+        assert!(pgns[0].starts_with("[Event \"Live Chess\"]"));
+
+        let report = collect_statistics(database.as_bytes(), 2).unwrap();
+        assert_eq!(report.games, 2);
+        assert_eq!(report.invalid_games, 1);
+        assert_eq!(
+            report.results,
+            ResultsDistribution {
+                white_wins: 2,
+                black_wins: 0,
+                draws:      0,
+            }
+        );
+        assert_eq!(report.terminations["checkmate"], 1);
+        assert_eq!(report.terminations["resignation"], 1);
+        assert!(report.average_plies > 2.0);
+        assert_eq!(report.openings.iter().map(|o| o.games).sum::<usize>(), 2);
+
+        let empty = collect_statistics("".as_bytes(), 2).unwrap();
+        assert_eq!(empty.games, 0);
+        assert_eq!(empty.average_plies, 0.0);
+    }
+}
@@ -0,0 +1,127 @@
+//! Forced draw search
+//!
+//! This module implements the bounded search of the line which forces the draw by the
+//! repetition or by the move counters (see ``Game::find_forced_draw``). Like the solver of chess
+//! problems it is the plain exhaustive search over the legal moves, so it is intended for the
+//! small numbers of plies only. The repetitions of the positions are counted by the game history
+//! and by the searched line together
+
+use crate::{BoardMove, ChessBoard, PositionHashValueType};
+use std::collections::HashMap;
+
+/// Number of the position occurrences which allows to claim the draw
+const THREEFOLD_REPETITION: usize = 3;
+
+/// State of the search: the counters of the positions of the game and of the current line
+pub(crate) struct DrawSearch<F: Fn(PositionHashValueType) -> usize> {
+    game_counters: F,
+    line_counters: HashMap<PositionHashValueType, usize>,
+}
+
+impl<F: Fn(PositionHashValueType) -> usize> DrawSearch<F> {
+    pub(crate) fn new(game_counters: F) -> Self {
+        Self {
+            game_counters,
+            line_counters: HashMap::new(),
+        }
+    }
+
+    /// Returns true if the draw may be claimed or is declared in the position
+    pub(crate) fn is_draw(&self, board: &ChessBoard) -> bool {
+        let hash = board.get_hash();
+        let repetitions =
+            (self.game_counters)(hash) + self.line_counters.get(&hash).copied().unwrap_or(0);
+        board.draw_report().is_any() | (repetitions >= THREEFOLD_REPETITION)
+    }
+
+    /// Returns the result of ``search`` in the position after the move (the position is counted
+    /// while it is searched)
+    fn after_move(
+        &mut self,
+        board: &ChessBoard,
+        board_move: BoardMove,
+        search: impl FnOnce(&mut Self, &ChessBoard) -> Option<Vec<BoardMove>>,
+    ) -> Option<Vec<BoardMove>> {
+        let next = board.make_move(&board_move).unwrap();
+        let hash = next.get_hash();
+        *self.line_counters.entry(hash).or_insert(0) += 1;
+        let result = match self.is_draw(&next) {
+            true => Some(Vec::new()),
+            false => search(self, &next),
+        };
+        *self.line_counters.get_mut(&hash).unwrap() -= 1;
+
+        result.map(|mut line| {
+            line.insert(0, board_move);
+            line
+        })
+    }
+
+    /// Returns the line of at most ``plies`` plies in which the side to move forces the draw
+    pub(crate) fn force(&mut self, board: &ChessBoard, plies: usize) -> Option<Vec<BoardMove>> {
+        if plies == 0 {
+            return None;
+        }
+        board
+            .get_legal_moves()
+            .into_iter()
+            .find_map(|m| self.after_move(board, m, |s, next| s.defend(next, plies - 1)))
+    }
+
+    /// Returns the line against the most stubborn defence of the side to move (None if the
+    /// side to move avoids the draw in ``plies`` plies or is checkmated)
+    fn defend(&mut self, board: &ChessBoard, plies: usize) -> Option<Vec<BoardMove>> {
+        let moves = board.get_legal_moves();
+        if (plies == 0) | moves.is_empty() {
+            return None;
+        }
+        let mut longest: Option<Vec<BoardMove>> = None;
+        for m in moves {
+            let line = self.after_move(board, m, |s, next| s.force(next, plies - 1))?;
+            if longest.as_ref().is_none_or(|l| line.len() > l.len()) {
+                longest = Some(line);
+            }
+        }
+        longest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mv, squares::*, Action, Game, PieceMove, PieceType::*};
+
+    #[test]
+    fn forced_draws() {
+        // perpetual check: the black king is shuttled between h8 and g8
+        let mut game = Game::from_fen("5r1k/5p1p/8/6Q1/8/1r6/r4PPP/6K1 w - - 0 1").unwrap();
+        let cycle = [
+            mv!(Queen, G5, F6),
+            mv!(King, H8, G8),
+            mv!(Queen, F6, G5),
+            mv!(King, G8, H8),
+        ];
+        for m in cycle {
+            game.make_move(&Action::MakeMove(m)).unwrap();
+        }
+        assert_eq!(game.find_forced_draw(3), None);
+        assert_eq!(game.find_forced_draw(6), Some(cycle.to_vec()));
+
+        // the 50-move rule: black has only the king moves which don't reset the counter
+        let game = Game::from_fen("8/8/8/8/3k4/8/8/R3K3 w - - 98 80").unwrap();
+        assert_eq!(game.find_forced_draw(1), None);
+        let line = game.find_forced_draw(2).unwrap();
+        assert_eq!(line.len(), 2);
+        let mut board = game.get_position();
+        for m in line.iter() {
+            board.make_move_mut(m).unwrap();
+        }
+        assert!(board.draw_report().fifty_move_claimable);
+
+        // black pushes the pawn which resets the counter
+        let game = Game::from_fen("8/7p/8/8/3k4/8/8/4K3 w - - 98 80").unwrap();
+        assert_eq!(game.find_forced_draw(2), None);
+        let game = Game::from_fen("8/8/8/8/3k4/8/8/R3K3 w - - 100 80").unwrap();
+        assert_eq!(game.find_forced_draw(2), Some(vec![]));
+    }
+}
@@ -0,0 +1,79 @@
+//! Endgame classification
+//!
+//! This module defines the classes of endgames by the material on the board (see
+//! ``ChessBoard::get_endgame_class``). Endgame-specific evaluation and tablebase probing are
+//! usually routed by this classification and by the material signature (see
+//! ``ChessBoard::get_material_signature``)
+
+use std::fmt;
+
+/// The maximal number of pieces (except kings and pawns) of both sides in the endgame
+pub const ENDGAME_MAX_PIECES: u32 = 6;
+
+/// Class of the endgame by the pieces on the board (except kings and pawns)
+///
+/// # Examples
+/// ```
+/// use libchess::{ChessBoard, EndgameClass};
+/// let board = ChessBoard::from_fen("4k3/4r3/8/8/8/8/4PR2/4K3 w - - 0 1").unwrap();
+/// assert_eq!(board.get_material_signature(), "KRPvKR");
+/// assert_eq!(board.get_endgame_class(), EndgameClass::RookEnding);
+///
+/// assert_eq!(
+///     ChessBoard::default().get_endgame_class(),
+///     EndgameClass::NotEndgame
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EndgameClass {
+    /// There are more than ``ENDGAME_MAX_PIECES`` pieces on the board
+    NotEndgame,
+    /// Only the kings are on the board
+    BareKings,
+    /// Kings and pawns only
+    PawnEnding,
+    /// Bishops and knights (and pawns) only
+    MinorPieceEnding,
+    /// Rooks (and pawns) only
+    RookEnding,
+    /// Rooks and minor pieces (and pawns) only
+    RookAndMinorPieceEnding,
+    /// Queens (and pawns) only
+    QueenEnding,
+    /// Queens and any other pieces (and pawns)
+    QueenAndPieceEnding,
+}
+
+impl fmt::Display for EndgameClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let class = match self {
+            EndgameClass::NotEndgame => "not an endgame",
+            EndgameClass::BareKings => "bare kings",
+            EndgameClass::PawnEnding => "pawn ending",
+            EndgameClass::MinorPieceEnding => "minor piece ending",
+            EndgameClass::RookEnding => "rook ending",
+            EndgameClass::RookAndMinorPieceEnding => "rook and minor piece ending",
+            EndgameClass::QueenEnding => "queen ending",
+            EndgameClass::QueenAndPieceEnding => "queen and piece ending",
+        };
+        write!(f, "{class}")
+    }
+}
+
+impl EndgameClass {
+    /// Classifies the endgame by the numbers of pieces of both sides (except kings)
+    pub(crate) fn classify(pawns: u32, minors: u32, rooks: u32, queens: u32) -> Self {
+        if minors + rooks + queens > ENDGAME_MAX_PIECES {
+            return EndgameClass::NotEndgame;
+        }
+        match (minors > 0, rooks > 0, queens > 0) {
+            (_, _, true) if minors + rooks > 0 => EndgameClass::QueenAndPieceEnding,
+            (_, _, true) => EndgameClass::QueenEnding,
+            (true, true, false) => EndgameClass::RookAndMinorPieceEnding,
+            (false, true, false) => EndgameClass::RookEnding,
+            (true, false, false) => EndgameClass::MinorPieceEnding,
+            (false, false, false) if pawns > 0 => EndgameClass::PawnEnding,
+            (false, false, false) => EndgameClass::BareKings,
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::{CastlingRights, Color, MoveIntent, Square};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -36,6 +37,9 @@ pub enum LibChessError {
     #[error("Invalid color index : {}", n)]
     InvalidColorIndex { n: usize },
 
+    #[error("Invalid color representation string")]
+    InvalidColorRepresentation,
+
     // Board Moves Errors
     #[error("Invalid move representation string")]
     InvalidBoardMoveRepresentation,
@@ -43,6 +47,12 @@ pub enum LibChessError {
     #[error("Pawn can't be promoted to pawn")]
     InvalidPromotionPiece,
 
+    #[error("Pawn move to the last rank must specify the promotion piece")]
+    MissingPromotionPiece,
+
+    #[error("Promotion piece is allowed only for pawn moves to the last rank")]
+    UnexpectedPromotionPiece,
+
     #[error("Invalid move for current board")]
     InvalidMoveForCurrentBoard,
 
@@ -50,6 +60,9 @@ pub enum LibChessError {
     #[error("Invalid FEN string: {}", s)]
     InvalidFENString { s: String },
 
+    #[error("Invalid board grid: {}", s)]
+    InvalidBoardGrid { s: String },
+
     #[error("Invalid position: colors overlapping detected")]
     InvalidPositionColorsOverlap,
 
@@ -68,8 +81,27 @@ pub enum LibChessError {
     #[error("Invalid board: en passant square does not have a pawn on it")]
     InvalidBoardInconsistentEnPassant,
 
-    #[error("Invalid board: inconsistent castling rights")]
-    InvalidBoardInconsistentCastlingRights,
+    #[error(
+        "Invalid board: inconsistent castling rights ({:?} for {}), king and rook on {} are expected",
+        side,
+        color,
+        rook_square
+    )]
+    InvalidBoardInconsistentCastlingRights {
+        color:       Color,
+        side:        CastlingRights,
+        rook_square: Square,
+    },
+
+    #[error(
+        "Move intent mismatch: expected {}, but the move is {}",
+        expected,
+        actual
+    )]
+    IntentMismatch {
+        expected: MoveIntent,
+        actual:   MoveIntent,
+    },
 
     #[error("Illegal move detected")]
     IllegalMoveDetected,
@@ -77,6 +109,9 @@ pub enum LibChessError {
     #[error("Chess move was not associated with the board")]
     NotAssociatedBoardMove,
 
+    #[error("Can't apply move \"{}\" on ply {} of the line", token, ply)]
+    InvalidMoveInLine { token: String, ply: usize },
+
     // Game Process Errors
     #[error("Illegal action detected")]
     IllegalActionDetected,
@@ -90,9 +125,74 @@ pub enum LibChessError {
     #[error("Game is already finished")]
     GameIsAlreadyFinished,
 
+    #[error("Illegal move attempt by {} (strike {})", color, strikes)]
+    IllegalMoveStrike { color: Color, strikes: usize },
+
+    #[error("Draw claim rejected: less than 50 moves were made since last capture or pawn move")]
+    InvalidFiftyMoveDrawClaim,
+
+    #[error("Draw claim rejected: the position has not been repeated 3 times")]
+    InvalidThreefoldDrawClaim,
+
     #[error("Wrong move number")]
     WrongMoveNumber,
 
     #[error("Invalid initialization PGN-string")]
     InvalidPGNString,
+
+    #[error("Invalid move time: {}", s)]
+    InvalidMoveTime { s: String },
+
+    #[error("Invalid annotation command: {}", s)]
+    InvalidAnnotation { s: String },
+
+    #[error("Invalid value of PGN tag {}: \"{}\"", tag, value)]
+    InvalidMetadataTag { tag: String, value: String },
+
+    #[error("Game file can't be read or written: {}", description)]
+    InvalidGameFile { description: String },
+
+    #[error("PGN stream can't be read: {}", description)]
+    InvalidPGNStream { description: String },
+
+    #[error("Opening book can't be written: {}", description)]
+    InvalidBookStream { description: String },
+
+    #[error("Invalid opening book weighting: {}", description)]
+    InvalidBookWeighting { description: String },
+
+    #[error("Position index can't be read or written: {}", description)]
+    InvalidIndexStream { description: String },
+
+    #[error("Puzzle stream can't be read: {}", description)]
+    InvalidPuzzleStream { description: String },
+
+    #[error("Position counter can't be read or written: {}", description)]
+    InvalidPositionCounter { description: String },
+
+    #[error("Invalid JSON string: {}", s)]
+    InvalidJSONString { s: String },
+
+    #[error("Invalid position pattern: \"{}\"", s)]
+    InvalidPositionPattern { s: String },
+
+    #[error("Invalid stipulation: \"{}\"", s)]
+    InvalidStipulation { s: String },
+
+    #[error("Invalid puzzle record: \"{}\"", s)]
+    InvalidPuzzle { s: String },
+
+    #[error("Invalid piece letter for notation locale: \"{}\"", letter)]
+    InvalidNotationLocale { letter: String },
+
+    // Analysis Errors
+    #[error("Analysis line is empty or contains illegal moves")]
+    InvalidAnalysisLine,
+
+    // Testing Errors
+    #[error("Invariant violation: {description}")]
+    InvariantViolation { description: String },
+
+    #[error("UCI engine failed: {description}")]
+    UciEngineFailure { description: String },
 }
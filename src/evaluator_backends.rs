@@ -0,0 +1,138 @@
+//! Evaluator backends
+//!
+//! This module defines the hooks which allow external evaluators (e.g. NNUE-like networks with
+//! incrementally updated accumulators) to stay in sync with the board: the backend is notified
+//! about every piece put on or removed from the board while the move is made, so it never needs
+//! to re-scan the whole position
+
+use crate::{ChessBoard, Piece, Square};
+#[cfg(feature = "evaluator-backend")]
+use crate::{Color, PieceType, Valuation};
+
+/// External evaluator which is updated incrementally while the moves are made (see
+/// ``ChessBoard::make_move_with_backend``)
+#[cfg_attr(not(feature = "evaluator-backend"), allow(dead_code))]
+pub trait EvaluatorBackend {
+    /// Is called after the piece is put on the square
+    fn on_piece_added(&mut self, piece: Piece, square: Square);
+
+    /// Is called after the piece is removed from the square (moved away or captured)
+    fn on_piece_removed(&mut self, piece: Piece, square: Square);
+
+    /// Returns the evaluation of the board in centipawns from the perspective of the side to
+    /// move. The board is the one the backend was synchronized with
+    fn evaluate(&self, board: &ChessBoard) -> i32;
+}
+
+/// The backend which ignores all notifications. Is used for making moves without any backend
+impl EvaluatorBackend for () {
+    #[inline(always)]
+    fn on_piece_added(&mut self, _piece: Piece, _square: Square) {}
+
+    #[inline(always)]
+    fn on_piece_removed(&mut self, _piece: Piece, _square: Square) {}
+
+    #[inline(always)]
+    fn evaluate(&self, _board: &ChessBoard) -> i32 { 0 }
+}
+
+/// The reference backend: the material balance accumulated incrementally
+///
+/// # Examples
+/// ```
+/// use libchess::{ChessBoard, EvaluatorBackend, MaterialAccumulator, Valuation};
+/// let board = ChessBoard::default();
+/// let mut accumulator = MaterialAccumulator::new(Valuation::default());
+/// board.load_into_backend(&mut accumulator);
+///
+/// let mut board = board.apply_san_line("e4 d5").unwrap();
+/// let capture = board.parse_san("exd5").unwrap();
+/// board = board
+///     .make_move_with_backend(&capture, &mut accumulator)
+///     .unwrap();
+/// assert_eq!(accumulator.evaluate(&board), -100); // black to move is a pawn down
+/// ```
+#[cfg(feature = "evaluator-backend")]
+#[derive(Debug, Clone, Default)]
+pub struct MaterialAccumulator {
+    valuation: Valuation,
+    /// Material balance from white's perspective
+    balance:   i32,
+}
+
+#[cfg(feature = "evaluator-backend")]
+impl MaterialAccumulator {
+    #[inline]
+    pub fn new(valuation: Valuation) -> Self {
+        Self {
+            valuation,
+            balance: 0,
+        }
+    }
+
+    /// Returns the accumulated material balance from white's perspective
+    #[inline]
+    pub fn get_balance(&self) -> i32 { self.balance }
+
+    fn get_signed_value(&self, piece: Piece) -> i32 {
+        let value = match piece.0 {
+            PieceType::King => 0,
+            piece_type => self.valuation.get_value(piece_type),
+        };
+        match piece.1 {
+            Color::White => value,
+            Color::Black => -value,
+        }
+    }
+}
+
+#[cfg(feature = "evaluator-backend")]
+impl EvaluatorBackend for MaterialAccumulator {
+    fn on_piece_added(&mut self, piece: Piece, _square: Square) {
+        self.balance += self.get_signed_value(piece);
+    }
+
+    fn on_piece_removed(&mut self, piece: Piece, _square: Square) {
+        self.balance -= self.get_signed_value(piece);
+    }
+
+    fn evaluate(&self, board: &ChessBoard) -> i32 {
+        match board.get_side_to_move() {
+            Color::White => self.balance,
+            Color::Black => -self.balance,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "evaluator-backend"))]
+mod tests {
+    use super::*;
+    use crate::testing::random_board;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+
+    #[test]
+    fn accumulator_stays_in_sync() {
+        let valuation = Valuation::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..20 {
+            let mut board = random_board(&ChessBoard::default(), 10, &mut rng);
+            let mut accumulator = MaterialAccumulator::new(valuation);
+            board.load_into_backend(&mut accumulator);
+
+            for _ in 0..100 {
+                let Some(m) = board.get_legal_moves().choose(&mut rng).copied() else {
+                    break;
+                };
+                board = board.make_move_with_backend(&m, &mut accumulator).unwrap();
+                assert_eq!(
+                    accumulator.get_balance(),
+                    valuation.get_material_balance(&board),
+                    "{}",
+                    board.as_fen()
+                );
+            }
+        }
+    }
+}
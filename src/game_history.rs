@@ -1,51 +1,124 @@
 use crate::errors::LibChessError as Error;
-use crate::{BoardMove, ChessBoard, Color, MovePropertiesOnBoard};
+use crate::time_reports::format_emt;
+use crate::{
+    AnalysisLine, Annotations, BoardMove, BoardOrientation, ChessBoard, Color, GamePhase,
+    GameStats, MoveNumber, MovePropertiesOnBoard, MultiPv, NotationLocale, PieceVisibility, Ply,
+    BLANK,
+};
 use std::fmt;
+use std::time::Duration;
 
 const HISTORY_DEFAULT_CAPACITY: usize = 200;
+/// The text of the PGN comment after the move which stalemates the opponent
+const STALEMATE_COMMENT: &str = "Stalemate";
 
 #[derive(Debug, Clone)]
 pub struct GameHistory {
-    positions: Vec<ChessBoard>,
-    moves:     Vec<BoardMove>,
-    metadata:  Vec<MovePropertiesOnBoard>,
+    positions:   Vec<ChessBoard>,
+    moves:       Vec<BoardMove>,
+    metadata:    Vec<MovePropertiesOnBoard>,
+    analysis:    Vec<Option<MultiPv>>,
+    times:       Vec<Option<Duration>>,
+    annotations: Vec<Option<Annotations>>,
 }
 
 impl Default for GameHistory {
     #[inline]
     fn default() -> Self {
         Self {
-            positions: Vec::with_capacity(HISTORY_DEFAULT_CAPACITY),
-            moves:     Vec::with_capacity(HISTORY_DEFAULT_CAPACITY),
-            metadata:  Vec::with_capacity(HISTORY_DEFAULT_CAPACITY),
+            positions:   Vec::with_capacity(HISTORY_DEFAULT_CAPACITY),
+            moves:       Vec::with_capacity(HISTORY_DEFAULT_CAPACITY),
+            metadata:    Vec::with_capacity(HISTORY_DEFAULT_CAPACITY),
+            analysis:    Vec::with_capacity(HISTORY_DEFAULT_CAPACITY),
+            times:       Vec::with_capacity(HISTORY_DEFAULT_CAPACITY),
+            annotations: Vec::with_capacity(HISTORY_DEFAULT_CAPACITY),
+        }
+    }
+}
+
+/// Defines how the moves are numbered in the movetext
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MoveNumbering {
+    /// Numbers continue the move number of the starting position (as required by PGN)
+    #[default]
+    FromPosition,
+    /// The first move is always numbered as 1
+    FromOne,
+}
+
+/// Defines how the boards of the game history are rendered (see ``GameHistory::render_ply``)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// Orientation of the boards. ``None`` means the board is shown from the perspective of
+    /// the side to move
+    pub orientation: Option<BoardOrientation>,
+    /// Piece symbols of the boards and moves
+    pub locale: NotationLocale,
+    /// Pieces shown on the boards (e.g. none for blindfold training)
+    pub visibility: PieceVisibility,
+    /// Render the small grid of piece letters instead of the framed colored board
+    pub small: bool,
+    /// Highlight the squares changed by the last move (is ignored for the small boards)
+    pub highlight_last_move: bool,
+    /// The board is inserted into the report after every ``board_interval`` moves (the final
+    /// position is always shown). Zero means that only the final position is shown
+    pub board_interval: usize,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            orientation: Some(BoardOrientation::WhiteBottom),
+            locale: NotationLocale::default(),
+            visibility: PieceVisibility::all(),
+            small: false,
+            highlight_last_move: true,
+            board_interval: 10,
         }
     }
 }
 
 impl fmt::Display for GameHistory {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if self.positions.is_empty() {
-            write!(f, "")
-        } else {
-            let mut game_history_string;
-            let first_move_string = self.moves[0].to_string(self.metadata[0]);
-            match self.positions[0].get_side_to_move() {
-                Color::White => game_history_string = format!("1.{first_move_string} "),
-                Color::Black => game_history_string = format!("1. ... {first_move_string}"),
-            }
+        write!(f, "{}", self.to_movetext(MoveNumbering::default(), None))
+    }
+}
 
-            let white_starting = self.positions[0].get_side_to_move() == Color::White;
-            for i in 1..self.moves.len() {
-                let mut next_move_string = self.moves[i].to_string(self.metadata[i]);
-                next_move_string = if (i % 2 != 0) ^ white_starting {
-                    format!("{}.{next_move_string} ", (i + 2) / 2)
-                } else {
-                    format!("{next_move_string} ")
-                };
-                game_history_string = format!("{game_history_string}{next_move_string}");
+/// Display adapter which interleaves the moves of the game history with the board snapshots
+/// (see ``GameHistory::display_with_boards``)
+#[derive(Debug, Clone, Copy)]
+pub struct GameHistoryDisplay<'a> {
+    history: &'a GameHistory,
+    options: &'a RenderOptions,
+}
+
+impl fmt::Display for GameHistoryDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let history = self.history;
+        let plies_per_board = 2 * self.options.board_interval;
+        let mut line = Vec::new();
+        for ply in 0..history.moves.len() {
+            let board = history.positions[ply];
+            let move_string =
+                history.moves[ply].to_localized_string(history.metadata[ply], &self.options.locale);
+            let move_number = board.get_move_number();
+            line.push(match board.get_side_to_move() {
+                Color::White => format!("{move_number}.{move_string}"),
+                Color::Black if line.is_empty() => format!("{move_number}...{move_string}"),
+                Color::Black => move_string,
+            });
+
+            let is_last = ply + 1 == history.moves.len();
+            if is_last | ((plies_per_board > 0) && ((ply + 1) % plies_per_board == 0)) {
+                writeln!(f, "{}", line.join(" "))?;
+                line.clear();
+                write!(f, "{}", history.render_ply(ply + 1, self.options).unwrap())?;
             }
-            write!(f, "{game_history_string}")
         }
+        if history.moves.is_empty() {
+            write!(f, "{}", history.render_ply(0, self.options).unwrap())?;
+        }
+        Ok(())
     }
 }
 
@@ -56,14 +129,77 @@ impl GameHistory {
         result
     }
 
-    pub fn get_position_on_move(&self, move_number: usize) -> Result<ChessBoard, Error> {
-        if move_number < self.positions.len() {
-            Ok(self.positions[move_number])
-        } else {
-            Err(Error::WrongMoveNumber)
-        }
+    /// Returns the position before the move on specified ply (the position after the last move
+    /// is available on the ply equal to the number of moves made)
+    ///
+    /// # Errors
+    /// ``LibChessError::WrongMoveNumber`` if there is no position on this ply
+    pub fn get_position_on_move(&self, ply: impl Into<Ply>) -> Result<ChessBoard, Error> {
+        self.positions
+            .get(ply.into().to_index())
+            .copied()
+            .ok_or(Error::WrongMoveNumber)
     }
 
+    /// Returns the number of moves (plies) made
+    #[inline]
+    pub fn get_ply_count(&self) -> Ply { Ply::new(self.moves.len()) }
+
+    /// Returns the move number of the move made on specified ply (taking into account the move
+    /// number of the starting position)
+    ///
+    /// # Errors
+    /// ``LibChessError::WrongMoveNumber`` if there is no position on this ply
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::PieceType::*;
+    /// use libchess::{mv, squares::*, Action, BoardMove, Game, MoveNumber, PieceMove, Ply};
+    /// let mut game = Game::default();
+    /// game.make_move(&Action::MakeMove(mv!(Pawn, E2, E4)))
+    ///     .unwrap();
+    /// game.make_move(&Action::MakeMove(mv!(Pawn, E7, E5)))
+    ///     .unwrap();
+    /// let history = game.get_action_history();
+    /// assert_eq!(history.get_ply_count(), Ply::new(2));
+    /// assert_eq!(
+    ///     history.get_move_number_on_ply(1).unwrap(),
+    ///     MoveNumber::new(1)
+    /// );
+    /// assert_eq!(
+    ///     history.get_move_number_on_ply(2).unwrap(),
+    ///     MoveNumber::new(2)
+    /// );
+    /// ```
+    pub fn get_move_number_on_ply(&self, ply: impl Into<Ply>) -> Result<MoveNumber, Error> {
+        Ok(MoveNumber::from_counter(
+            self.get_position_on_move(ply)?.get_move_number(),
+        ))
+    }
+
+    /// Returns the position the history starts from (e.g. set up by the "FEN" tag of PGN). Its
+    /// move number and side to move define the numbering of the moves
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{ChessBoard, GameHistory, MoveNumbering};
+    /// let board =
+    ///     ChessBoard::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3")
+    ///         .unwrap();
+    /// let mut history = GameHistory::from_position(board);
+    /// let next_board = board.apply_san_line("Bb5").unwrap();
+    /// history.push(board.parse_san("Bb5").unwrap(), next_board);
+    ///
+    /// assert_eq!(history.get_starting_position(), board);
+    /// assert_eq!(
+    ///     history.to_movetext(MoveNumbering::FromPosition, None),
+    ///     "3.Bb5 "
+    /// );
+    /// assert_eq!(history.to_movetext(MoveNumbering::FromOne, None), "1.Bb5 ");
+    /// ```
+    #[inline]
+    pub fn get_starting_position(&self) -> ChessBoard { self.positions[0] }
+
     pub fn get_last_position(&self) -> ChessBoard { *self.positions.last().unwrap() }
 
     pub fn push(&mut self, board_move: BoardMove, new_position: ChessBoard) -> &mut Self {
@@ -71,6 +207,9 @@ impl GameHistory {
             .push(MovePropertiesOnBoard::new(&board_move, &self.get_last_position()).unwrap());
         self.positions.push(new_position);
         self.moves.push(board_move);
+        self.analysis.push(None);
+        self.times.push(None);
+        self.annotations.push(None);
         self
     }
 
@@ -79,6 +218,343 @@ impl GameHistory {
     pub fn get_moves(&self) -> &Vec<BoardMove> { &self.moves }
 
     pub fn get_metadata(&self) -> &Vec<MovePropertiesOnBoard> { &self.metadata }
+
+    /// Returns the statistics of the moves and the phases of the game (see ``GameStats``)
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{Game, GamePhase};
+    /// let game = Game::from_san_moves([], &["e4", "d5", "exd5", "Qxd5", "Nc3", "Qe5+"]).unwrap();
+    /// let stats = game.get_action_history().stats();
+    /// assert_eq!((stats.captures, stats.checks, stats.quiet_moves), (2, 1, 4));
+    /// assert_eq!(stats.middlegame_ply, None);
+    /// ```
+    pub fn stats(&self) -> GameStats {
+        let mut stats = GameStats {
+            plies: self.moves.len(),
+            ..Default::default()
+        };
+        for (board_move, properties) in self.moves.iter().zip(self.metadata.iter()) {
+            let is_promotion = match board_move {
+                BoardMove::MovePiece(m) => m.get_promotion().is_some(),
+                BoardMove::CastleKingSide | BoardMove::CastleQueenSide => false,
+            };
+            let is_castling = !matches!(board_move, BoardMove::MovePiece(_));
+            stats.captures += properties.is_capture as usize;
+            stats.checks += properties.is_check as usize;
+            stats.castles += is_castling as usize;
+            stats.promotions += is_promotion as usize;
+            stats.quiet_moves += !(properties.is_capture | is_promotion | is_castling) as usize;
+        }
+
+        let first_ply_in = |phase: GamePhase| {
+            self.positions
+                .iter()
+                .position(|position| position.get_game_phase() >= phase)
+        };
+        stats.middlegame_ply = first_ply_in(GamePhase::Middlegame);
+        stats.endgame_ply = first_ply_in(GamePhase::Endgame);
+        stats
+    }
+
+    /// Renders the history as PGN movetext with specified numbering of moves. The result token
+    /// ("1-0", "0-1", "1/2-1/2" or "*") is appended to the end if it is passed. If the first move
+    /// is made by black, it is numbered like "12...Nf6"
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{mv, BoardMove, Game, MoveNumbering, PieceMove};
+    /// use libchess::{squares::*, Action, PieceType::*};
+    /// let mut game =
+    ///     Game::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+    /// game.make_move(&Action::MakeMove(mv!(Pawn, E7, E5)))
+    ///     .unwrap();
+    /// game.make_move(&Action::MakeMove(mv!(Knight, G1, F3)))
+    ///     .unwrap();
+    ///
+    /// let history = game.get_action_history();
+    /// assert_eq!(
+    ///     history.to_movetext(MoveNumbering::FromPosition, Some("*")),
+    ///     "1...e5 2.Nf3 *"
+    /// );
+    /// ```
+    pub fn to_movetext(&self, numbering: MoveNumbering, result: Option<&str>) -> String {
+        let mut movetext = String::new();
+        for ply in 0..self.moves.len() {
+            let move_string = self.moves[ply].to_string(self.metadata[ply]);
+            let move_number = self.get_displayed_move_number(ply, numbering);
+            movetext += &match self.positions[ply].get_side_to_move() {
+                Color::White => format!("{move_number}.{move_string} "),
+                // the move number must be repeated after the comment or variation
+                Color::Black
+                    if (ply == 0)
+                        || self.analysis[ply - 1].is_some()
+                        || self.get_comment(ply - 1).is_some() =>
+                {
+                    format!("{move_number}...{move_string} ")
+                }
+                Color::Black => format!("{move_string} "),
+            };
+            if let Some(comment) = self.get_comment(ply) {
+                movetext += &format!("{{ {comment} }} ");
+            }
+            movetext += &self.analysis_to_string(ply, numbering);
+        }
+
+        if let Some(result) = result {
+            movetext += result;
+        }
+        movetext
+    }
+
+    /// Renders the position before the move on specified ply (the position after the last move
+    /// is available on the ply equal to the number of moves made) with specified options
+    ///
+    /// # Errors
+    /// ``LibChessError::WrongMoveNumber`` if there is no position on this ply
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{Game, RenderOptions};
+    /// let game = Game::from_san_moves([], &["e4", "e5", "Ke2"]).unwrap();
+    /// let options = RenderOptions {
+    ///     orientation: None,
+    ///     small: true,
+    ///     ..Default::default()
+    /// };
+    /// // the board is shown from the perspective of the side to move
+    /// assert_eq!(
+    ///     game.get_action_history().render_ply(3, &options).unwrap(),
+    ///     "RNB.QBNR\nPPPKPPPP\n........\n...P....\n...p....\n........\nppp.pppp\nrnbkqbnr\n"
+    /// );
+    /// ```
+    pub fn render_ply(
+        &self,
+        ply: impl Into<Ply>,
+        options: &RenderOptions,
+    ) -> Result<String, Error> {
+        let ply = ply.into().to_index();
+        let board = self.get_position_on_move(ply)?;
+        let orientation = options
+            .orientation
+            .unwrap_or_else(|| BoardOrientation::from_color(board.get_side_to_move()));
+        let visible_board = board.get_visible_board(&options.visibility);
+        if options.small {
+            return Ok(visible_board.render_small(orientation));
+        }
+
+        let highlighted = match ply.checked_sub(1) {
+            Some(previous) if options.highlight_last_move => {
+                self.positions[previous].get_diff_mask(&board)
+            }
+            _ => BLANK,
+        };
+        Ok(visible_board.render(orientation, highlighted, &options.locale, None))
+    }
+
+    /// Returns the display adapter which renders the moves interleaved with the board snapshots
+    /// after every ``options.board_interval`` moves and after the last move. Is handy for
+    /// terminal-based game reports and debugging of long move sequences
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{Game, RenderOptions};
+    /// let game = Game::from_san_moves([], &["e4", "e5", "Nf3", "Nc6", "Bb5"]).unwrap();
+    /// let options = RenderOptions {
+    ///     board_interval: 2,
+    ///     small: true,
+    ///     ..Default::default()
+    /// };
+    /// let report = format!(
+    ///     "{}",
+    ///     game.get_action_history().display_with_boards(&options)
+    /// );
+    /// assert!(report.starts_with("1.e4 e5 2.Nf3 Nc6\nr.bqkbnr\n"));
+    /// assert!(report.contains("\n3.Bb5\nr.bqkbnr\n"));
+    /// ```
+    pub fn display_with_boards<'a>(&'a self, options: &'a RenderOptions) -> GameHistoryDisplay<'a> {
+        GameHistoryDisplay {
+            history: self,
+            options,
+        }
+    }
+
+    /// Attaches the engine analysis to the move made on specified ply. The analysis describes
+    /// the position before the move, so its lines are the alternatives to the move played
+    ///
+    /// # Errors
+    /// ``LibChessError::WrongMoveNumber`` if the move on this ply was not made yet
+    ///
+    /// ``LibChessError::InvalidAnalysisLine`` if any of analysis lines is illegal for the position
+    pub fn set_analysis(
+        &mut self,
+        ply: impl Into<Ply>,
+        analysis: MultiPv,
+    ) -> Result<&mut Self, Error> {
+        let ply = ply.into().to_index();
+        if ply >= self.moves.len() {
+            return Err(Error::WrongMoveNumber);
+        }
+        analysis.validate(&self.positions[ply])?;
+        self.analysis[ply] = Some(analysis);
+        Ok(self)
+    }
+
+    /// Returns the engine analysis attached to the move made on specified ply
+    pub fn get_analysis(&self, ply: impl Into<Ply>) -> Option<&MultiPv> {
+        self.analysis
+            .get(ply.into().to_index())
+            .and_then(|x| x.as_ref())
+    }
+
+    /// Records the time spent on the move made on specified ply. It is exported to PGN as
+    /// ``[%emt H:MM:SS]`` comment
+    ///
+    /// # Errors
+    /// ``LibChessError::WrongMoveNumber`` if the move on this ply was not made yet
+    pub fn set_move_time(
+        &mut self,
+        ply: impl Into<Ply>,
+        duration: Duration,
+    ) -> Result<&mut Self, Error> {
+        let time = self
+            .times
+            .get_mut(ply.into().to_index())
+            .ok_or(Error::WrongMoveNumber)?;
+        *time = Some(duration);
+        Ok(self)
+    }
+
+    /// Returns the time spent on the move made on specified ply (if it was recorded)
+    pub fn get_move_time(&self, ply: impl Into<Ply>) -> Option<Duration> {
+        self.times.get(ply.into().to_index()).copied().flatten()
+    }
+
+    /// Attaches the graphical annotations (arrows, square highlights and labels) to the move made
+    /// on specified ply. They describe the position after the move and are exported to PGN as
+    /// ``[%cal ...]`` and ``[%csl ...]`` comments
+    ///
+    /// # Errors
+    /// ``LibChessError::WrongMoveNumber`` if the move on this ply was not made yet
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{squares::*, AnnotationColor::*, Annotations, Game};
+    /// let mut game = Game::from_san_moves([], &["e4", "e5"]).unwrap();
+    /// let mut annotations = Annotations::new();
+    /// annotations.add_arrow(G1, F3, Green).add_highlight(E5, Red);
+    /// game.set_annotations(1, annotations).unwrap();
+    /// assert_eq!(
+    ///     format!("{}", game.get_action_history()),
+    ///     "1.e4 e5 { [%csl Re5] [%cal Gg1f3] } "
+    /// );
+    /// ```
+    pub fn set_annotations(
+        &mut self,
+        ply: impl Into<Ply>,
+        annotations: Annotations,
+    ) -> Result<&mut Self, Error> {
+        let slot = self
+            .annotations
+            .get_mut(ply.into().to_index())
+            .ok_or(Error::WrongMoveNumber)?;
+        *slot = Some(annotations);
+        Ok(self)
+    }
+
+    /// Returns the graphical annotations attached to the move made on specified ply
+    pub fn get_annotations(&self, ply: impl Into<Ply>) -> Option<&Annotations> {
+        self.annotations
+            .get(ply.into().to_index())
+            .and_then(|x| x.as_ref())
+    }
+
+    /// Returns the PGN comment commands (the move time and annotations) of the move made on
+    /// specified ply
+    fn get_comment_commands(&self, ply: usize) -> Option<String> {
+        let time = self.times[ply].map(|time| format!("[%emt {}]", format_emt(time)));
+        let annotations = self.annotations[ply]
+            .as_ref()
+            .and_then(|a| a.to_pgn_commands());
+        match (time, annotations) {
+            (Some(time), Some(annotations)) => Some(format!("{time} {annotations}")),
+            (time, annotations) => time.or(annotations),
+        }
+    }
+
+    /// Returns the PGN comment of the move made on specified ply: the note of the stalemate
+    /// followed by the comment commands
+    fn get_comment(&self, ply: usize) -> Option<String> {
+        let stalemate = self.metadata[ply]
+            .is_stalemate
+            .then(|| STALEMATE_COMMENT.to_string());
+        match (stalemate, self.get_comment_commands(ply)) {
+            (Some(stalemate), Some(commands)) => Some(format!("{stalemate} {commands}")),
+            (stalemate, commands) => stalemate.or(commands),
+        }
+    }
+
+    /// Returns the move number which is displayed for the move made on specified ply
+    fn get_displayed_move_number(&self, ply: usize, numbering: MoveNumbering) -> usize {
+        match numbering {
+            MoveNumbering::FromPosition => self.positions[ply].get_move_number(),
+            MoveNumbering::FromOne => match self.get_starting_position().get_side_to_move() {
+                Color::White => ply / 2 + 1,
+                Color::Black => ply.div_ceil(2) + 1,
+            },
+        }
+    }
+
+    /// Renders the analysis attached to specified ply: the evaluation of the move played as a
+    /// comment and all other lines as variations
+    fn analysis_to_string(&self, ply: usize, numbering: MoveNumbering) -> String {
+        let analysis = match self.get_analysis(ply) {
+            Some(a) => a,
+            None => return String::new(),
+        };
+
+        let mut result = String::new();
+        for line in analysis.get_lines() {
+            if line.get_first_move() == Some(self.moves[ply]) {
+                result += &format!("{{ {} }} ", line.get_evaluation());
+            }
+        }
+        for line in analysis.get_lines() {
+            if line.get_first_move() != Some(self.moves[ply]) {
+                result += &format!("({}) ", self.variation_to_string(ply, line, numbering));
+            }
+        }
+        result
+    }
+
+    fn variation_to_string(
+        &self,
+        ply: usize,
+        line: &AnalysisLine,
+        numbering: MoveNumbering,
+    ) -> String {
+        let mut board = self.positions[ply];
+        let mut move_number = self.get_displayed_move_number(ply, numbering);
+        let mut tokens = Vec::with_capacity(line.get_moves().len() + 1);
+
+        for (i, m) in line.get_moves().iter().enumerate() {
+            let properties = MovePropertiesOnBoard::new(m, &board).unwrap();
+            tokens.push(match board.get_side_to_move() {
+                Color::White => format!("{move_number}.{}", m.to_string(properties)),
+                Color::Black if i == 0 => format!("{move_number}...{}", m.to_string(properties)),
+                Color::Black => m.to_string(properties),
+            });
+            if properties.is_stalemate {
+                tokens.push(format!("{{ {STALEMATE_COMMENT} }}"));
+            }
+            if board.get_side_to_move() == Color::Black {
+                move_number += 1;
+            }
+            board.make_move_mut(m).unwrap();
+        }
+        tokens.push(format!("{{ {} }}", line.get_evaluation()));
+        tokens.join(" ")
+    }
 }
 
 #[cfg(test)]
@@ -86,6 +562,93 @@ mod tests {
     use crate::*;
     use crate::{squares::*, PieceType::*};
 
+    #[test]
+    fn history_stats() {
+        let board = ChessBoard::from_fen("rn2k2r/1P6/8/8/8/8/8/RNB1K2R w KQkq - 0 1").unwrap();
+        let mut history = GameHistory::from_position(board);
+        let mut position = board;
+        for san in ["O-O", "Nc6", "bxa8=Q+", "Ke7", "Qxh8"] {
+            let m = position.parse_san(san).unwrap();
+            position = position.make_move(&m).unwrap();
+            history.push(m, position);
+        }
+        assert_eq!(
+            history.stats(),
+            GameStats {
+                plies:          5,
+                captures:       2,
+                checks:         1,
+                castles:        1,
+                promotions:     1,
+                quiet_moves:    2,
+                middlegame_ply: Some(0),
+                endgame_ply:    Some(5),
+            }
+        );
+        assert_eq!(
+            GameHistory::from_position(ChessBoard::default()).stats(),
+            GameStats::default()
+        );
+    }
+
+    #[test]
+    fn history_rendering() {
+        let options = RenderOptions {
+            small: true,
+            board_interval: 1,
+            ..Default::default()
+        };
+        let game = Game::from_san_moves([], &["e4", "e5", "Nf3", "Nc6", "Bb5"]).unwrap();
+        let history = game.get_action_history();
+        let report = format!("{}", history.display_with_boards(&options));
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines.len(), 3 * 9);
+        assert_eq!(lines[0], "1.e4 e5");
+        assert_eq!(lines[9], "2.Nf3 Nc6");
+        assert_eq!(lines[18], "3.Bb5");
+        assert_eq!(
+            lines[19..].join("\n") + "\n",
+            history.render_ply(5, &options).unwrap()
+        );
+
+        let options = RenderOptions {
+            board_interval: 0,
+            ..options
+        };
+        let report = format!("{}", history.display_with_boards(&options));
+        assert!(report.starts_with("1.e4 e5 2.Nf3 Nc6 3.Bb5\n"));
+        assert_eq!(report.lines().count(), 9);
+
+        // the first move is made by black
+        let mut game =
+            Game::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+        game.make_move(&Action::MakeMove(mv!(Pawn, E7, E5)))
+            .unwrap();
+        let history = game.get_action_history();
+        let report = format!("{}", history.display_with_boards(&options));
+        assert!(report.starts_with("1...e5\n"));
+
+        let blindfold = RenderOptions {
+            visibility: PieceVisibility::blindfold(),
+            ..options.clone()
+        };
+        assert_eq!(
+            history.render_ply(1, &blindfold).unwrap(),
+            "........\n".repeat(8)
+        );
+        assert!(history.render_ply(1, &RenderOptions::default()).is_ok());
+        assert!(history.render_ply(2, &RenderOptions::default()).is_err());
+        assert_eq!(
+            format!(
+                "{}",
+                Game::default()
+                    .get_action_history()
+                    .display_with_boards(&options)
+            ),
+            ChessBoard::default().render_small(BoardOrientation::WhiteBottom)
+        );
+    }
+
     #[test]
     fn de_riviere_paul_morphy_1863() {
         let mut game = Game::default();
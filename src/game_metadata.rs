@@ -0,0 +1,547 @@
+//! Game metadata
+//!
+//! This module implements the PGN tags of the game: the Seven Tag Roster, the typed accessors
+//! for the common optional tags (players' ratings, ECO code, time control and termination) and
+//! the access to arbitrary tags
+
+use crate::errors::LibChessError as Error;
+use crate::Color;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// The tags of the Seven Tag Roster in the order of exporting to PGN
+pub(crate) const METADATA_PRIMARY_KEYS: [&str; 7] =
+    ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+/// Values of optional tags which mean that the value is unknown
+const UNKNOWN_VALUES: [&str; 3] = ["", "?", "-"];
+
+/// One period of the time control
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeControlPeriod {
+    /// Number of moves to make during the period (``None`` for the sudden death period)
+    pub moves:     Option<u16>,
+    /// Time given for the period
+    pub time:      Duration,
+    /// Time added after each move
+    pub increment: Duration,
+}
+
+impl TimeControlPeriod {
+    /// Creates the sudden death period with the increment
+    #[inline]
+    pub fn new(time: Duration, increment: Duration) -> Self {
+        Self {
+            moves: None,
+            time,
+            increment,
+        }
+    }
+}
+
+impl fmt::Display for TimeControlPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(moves) = self.moves {
+            write!(f, "{moves}/")?;
+        }
+        write!(f, "{}", self.time.as_secs())?;
+        if !self.increment.is_zero() {
+            write!(f, "+{}", self.increment.as_secs())?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for TimeControlPeriod {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || Error::InvalidMetadataTag {
+            tag:   "TimeControl".to_string(),
+            value: s.to_string(),
+        };
+        let (moves, time) = match s.split_once('/') {
+            Some((moves, time)) => (Some(moves.parse().map_err(|_| error())?), time),
+            None => (None, s),
+        };
+        let (time, increment) = time.split_once('+').unwrap_or((time, "0"));
+        let seconds = |value: &str| value.parse().map(Duration::from_secs).map_err(|_| error());
+
+        Ok(Self {
+            moves,
+            time: seconds(time)?,
+            increment: seconds(increment)?,
+        })
+    }
+}
+
+/// Time control of the game as it is described by the "TimeControl" PGN tag
+///
+/// # Examples
+/// ```
+/// use libchess::{TimeControl, TimeControlPeriod};
+/// use std::time::Duration;
+/// let time_control: TimeControl = "40/7200:3600+30".parse().unwrap();
+/// assert_eq!(
+///     time_control,
+///     TimeControl::Periods(vec![
+///         TimeControlPeriod {
+///             moves:     Some(40),
+///             time:      Duration::from_secs(7200),
+///             increment: Duration::ZERO,
+///         },
+///         TimeControlPeriod::new(Duration::from_secs(3600), Duration::from_secs(30)),
+///     ])
+/// );
+/// assert_eq!(time_control.to_string(), "40/7200:3600+30");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeControl {
+    /// The time control is unknown ("?")
+    Unknown,
+    /// The game is played without the time control ("-")
+    Unlimited,
+    /// The sandclock with specified time ("*180")
+    Sandclock(Duration),
+    /// The sequence of periods, the last one is repeated ("40/7200:3600+30")
+    Periods(Vec<TimeControlPeriod>),
+}
+
+impl TimeControl {
+    /// Creates the single period time control with the increment ("300+2")
+    #[inline]
+    pub fn incremental(time: Duration, increment: Duration) -> Self {
+        TimeControl::Periods(vec![TimeControlPeriod::new(time, increment)])
+    }
+}
+
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TimeControl::Unknown => write!(f, "?"),
+            TimeControl::Unlimited => write!(f, "-"),
+            TimeControl::Sandclock(time) => write!(f, "*{}", time.as_secs()),
+            TimeControl::Periods(periods) => {
+                let periods: Vec<String> = periods.iter().map(|p| p.to_string()).collect();
+                write!(f, "{}", periods.join(":"))
+            }
+        }
+    }
+}
+
+impl FromStr for TimeControl {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "?" => Ok(TimeControl::Unknown),
+            "-" => Ok(TimeControl::Unlimited),
+            _ => match s.strip_prefix('*') {
+                Some(seconds) => seconds
+                    .parse()
+                    .map(|seconds| TimeControl::Sandclock(Duration::from_secs(seconds)))
+                    .map_err(|_| Error::InvalidMetadataTag {
+                        tag:   "TimeControl".to_string(),
+                        value: s.to_string(),
+                    }),
+                None => Ok(TimeControl::Periods(
+                    s.split(':')
+                        .map(TimeControlPeriod::from_str)
+                        .collect::<Result<_, _>>()?,
+                )),
+            },
+        }
+    }
+}
+
+/// The reason of the game termination as it is described by the "Termination" PGN tag
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Termination {
+    Abandoned,
+    Adjudication,
+    Death,
+    Emergency,
+    Normal,
+    RulesInfraction,
+    TimeForfeit,
+    Unterminated,
+    /// Non-standard description of the termination
+    Other(String),
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let termination = match self {
+            Termination::Abandoned => "abandoned",
+            Termination::Adjudication => "adjudication",
+            Termination::Death => "death",
+            Termination::Emergency => "emergency",
+            Termination::Normal => "normal",
+            Termination::RulesInfraction => "rules infraction",
+            Termination::TimeForfeit => "time forfeit",
+            Termination::Unterminated => "unterminated",
+            Termination::Other(description) => description,
+        };
+        write!(f, "{termination}")
+    }
+}
+
+impl From<&str> for Termination {
+    /// Parses the standard values case-insensitively, other values are kept as they are
+    fn from(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "abandoned" => Termination::Abandoned,
+            "adjudication" => Termination::Adjudication,
+            "death" => Termination::Death,
+            "emergency" => Termination::Emergency,
+            "normal" => Termination::Normal,
+            "rules infraction" => Termination::RulesInfraction,
+            "time forfeit" => Termination::TimeForfeit,
+            "unterminated" => Termination::Unterminated,
+            _ => Termination::Other(s.to_string()),
+        }
+    }
+}
+
+/// PGN tags of the game. The tags with typed values have their own accessors, any other tag
+/// can be accessed by ``get_value`` and ``set_value``
+///
+/// # Examples
+/// ```
+/// use libchess::{Color::*, Game, Termination, TimeControl};
+/// use std::time::Duration;
+/// let mut game = Game::default();
+/// game.get_metadata_mut()
+///     .set_player(White, "Carlsen")
+///     .set_elo(White, 2830)
+///     .set_time_control(&TimeControl::incremental(
+///         Duration::from_secs(180),
+///         Duration::from_secs(2),
+///     ))
+///     .set_termination(&Termination::TimeForfeit);
+///
+/// let metadata = game.get_metadata();
+/// assert_eq!(metadata.get_player(White), Some("Carlsen"));
+/// assert_eq!(metadata.get_elo(White), Some(2830));
+/// assert_eq!(metadata.get_elo(Black), None);
+/// assert_eq!(
+///     metadata.get_value("TimeControl".to_string()),
+///     Some(&"180+2".to_string())
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct GameMetadata {
+    metadata: BTreeMap<String, String>,
+}
+
+impl Default for GameMetadata {
+    #[inline]
+    fn default() -> Self {
+        let mut metadata = BTreeMap::new();
+        metadata.insert("Event".to_string(), "?".to_string());
+        metadata.insert("Site".to_string(), "?".to_string());
+        metadata.insert("Date".to_string(), "?".to_string());
+        metadata.insert("Round".to_string(), "?".to_string());
+        metadata.insert("White".to_string(), "Player 1".to_string());
+        metadata.insert("Black".to_string(), "Player 2".to_string());
+        metadata.insert("Result".to_string(), "?".to_string());
+        Self::new(metadata)
+    }
+}
+
+impl GameMetadata {
+    #[inline]
+    fn new(metadata: BTreeMap<String, String>) -> Self { Self { metadata } }
+
+    pub fn get_value(&self, tag: String) -> Option<&String> { self.metadata.get(&tag) }
+
+    /// Sets the value of any tag. The value is not validated (see ``GameMetadata::validate``)
+    pub fn set_value(&mut self, tag: String, value: String) { self.metadata.insert(tag, value); }
+
+    /// Removes the tag and returns its value
+    pub fn remove_value(&mut self, tag: String) -> Option<String> { self.metadata.remove(&tag) }
+
+    /// Returns an iterator over all tags and their values (sorted by tags)
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> { self.metadata.iter() }
+
+    /// Checks that the values of the typed tags ("WhiteElo", "BlackElo", "ECO" and
+    /// "TimeControl") can be parsed. Unknown values ("?" or "-") are valid
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidMetadataTag`` with the first invalid tag
+    pub fn validate(&self) -> Result<(), Error> {
+        for color in [Color::White, Color::Black] {
+            self.parse_elo(color)?;
+        }
+        self.parse_eco()?;
+        self.parse_time_control()?;
+        Ok(())
+    }
+
+    /// Returns the copy of the metadata where the values of the typed tags which can't be parsed
+    /// (see ``GameMetadata::validate``) are replaced by "?", so the exported PGN can be read back
+    pub(crate) fn to_valid(&self) -> Self {
+        let mut metadata = self.clone();
+        for (tag, is_valid) in [
+            (
+                Self::elo_tag(Color::White),
+                self.parse_elo(Color::White).is_ok(),
+            ),
+            (
+                Self::elo_tag(Color::Black),
+                self.parse_elo(Color::Black).is_ok(),
+            ),
+            ("ECO".to_string(), self.parse_eco().is_ok()),
+            ("TimeControl".to_string(), self.parse_time_control().is_ok()),
+        ] {
+            if !is_valid {
+                metadata.set_str(&tag, "?");
+            }
+        }
+        metadata
+    }
+
+    fn get_str(&self, tag: &str) -> Option<&str> { self.metadata.get(tag).map(|s| s.as_str()) }
+
+    /// Returns the value of the optional tag if it is known
+    fn get_known_value(&self, tag: &str) -> Option<&str> {
+        self.get_str(tag)
+            .filter(|value| !UNKNOWN_VALUES.contains(value))
+    }
+
+    fn set_str(&mut self, tag: &str, value: &str) -> &mut Self {
+        self.metadata.insert(tag.to_string(), value.to_string());
+        self
+    }
+
+    #[inline]
+    pub fn get_event(&self) -> Option<&str> { self.get_str("Event") }
+
+    #[inline]
+    pub fn set_event(&mut self, event: &str) -> &mut Self { self.set_str("Event", event) }
+
+    #[inline]
+    pub fn get_site(&self) -> Option<&str> { self.get_str("Site") }
+
+    #[inline]
+    pub fn set_site(&mut self, site: &str) -> &mut Self { self.set_str("Site", site) }
+
+    /// Returns the date of the game in "YYYY.MM.DD" format (unknown parts are "??")
+    #[inline]
+    pub fn get_date(&self) -> Option<&str> { self.get_str("Date") }
+
+    #[inline]
+    pub fn set_date(&mut self, date: &str) -> &mut Self { self.set_str("Date", date) }
+
+    #[inline]
+    pub fn get_round(&self) -> Option<&str> { self.get_str("Round") }
+
+    #[inline]
+    pub fn set_round(&mut self, round: &str) -> &mut Self { self.set_str("Round", round) }
+
+    /// Returns the name of the player of specified color ("White" or "Black" tag)
+    #[inline]
+    pub fn get_player(&self, color: Color) -> Option<&str> { self.get_str(Self::player_tag(color)) }
+
+    #[inline]
+    pub fn set_player(&mut self, color: Color, name: &str) -> &mut Self {
+        self.set_str(Self::player_tag(color), name)
+    }
+
+    /// Returns the result of the game ("1-0", "0-1", "1/2-1/2" or "?"). The result is updated by
+    /// the game when it is finished
+    #[inline]
+    pub fn get_result(&self) -> Option<&str> { self.get_str("Result") }
+
+    fn player_tag(color: Color) -> &'static str {
+        match color {
+            Color::White => "White",
+            Color::Black => "Black",
+        }
+    }
+
+    fn elo_tag(color: Color) -> String { format!("{}Elo", Self::player_tag(color)) }
+
+    fn parse_elo(&self, color: Color) -> Result<Option<u16>, Error> {
+        let tag = Self::elo_tag(color);
+        self.get_known_value(&tag)
+            .map(|value| {
+                value.parse().map_err(|_| Error::InvalidMetadataTag {
+                    tag:   tag.clone(),
+                    value: value.to_string(),
+                })
+            })
+            .transpose()
+    }
+
+    /// Returns the rating of the player of specified color ("WhiteElo" or "BlackElo" tag).
+    /// Returns ``None`` if the rating is unknown or invalid
+    #[inline]
+    pub fn get_elo(&self, color: Color) -> Option<u16> { self.parse_elo(color).ok().flatten() }
+
+    #[inline]
+    pub fn set_elo(&mut self, color: Color, elo: u16) -> &mut Self {
+        self.set_str(&Self::elo_tag(color), &elo.to_string())
+    }
+
+    fn parse_eco(&self) -> Result<Option<&str>, Error> {
+        self.get_known_value("ECO")
+            .map(|eco| match Self::is_valid_eco(eco) {
+                true => Ok(eco),
+                false => Err(Error::InvalidMetadataTag {
+                    tag:   "ECO".to_string(),
+                    value: eco.to_string(),
+                }),
+            })
+            .transpose()
+    }
+
+    fn is_valid_eco(eco: &str) -> bool {
+        let bytes = eco.as_bytes();
+        (bytes.len() == 3)
+            && (b'A'..=b'E').contains(&bytes[0])
+            && bytes[1..].iter().all(u8::is_ascii_digit)
+    }
+
+    /// Returns the code of the opening in the Encyclopaedia of Chess Openings ("ECO" tag).
+    /// Returns ``None`` if the code is unknown or invalid
+    #[inline]
+    pub fn get_eco(&self) -> Option<&str> { self.parse_eco().ok().flatten() }
+
+    /// Sets the ECO code of the opening
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidMetadataTag`` if the code is not a letter from "A" to "E"
+    /// followed by two digits
+    pub fn set_eco(&mut self, eco: &str) -> Result<&mut Self, Error> {
+        if !Self::is_valid_eco(eco) {
+            return Err(Error::InvalidMetadataTag {
+                tag:   "ECO".to_string(),
+                value: eco.to_string(),
+            });
+        }
+        Ok(self.set_str("ECO", eco))
+    }
+
+    fn parse_time_control(&self) -> Result<Option<TimeControl>, Error> {
+        // "-" is the game without the time control, other unknown values are the same as "?"
+        self.get_str("TimeControl")
+            .map(
+                |value| match UNKNOWN_VALUES.contains(&value) & (value != "-") {
+                    true => Ok(TimeControl::Unknown),
+                    false => TimeControl::from_str(value),
+                },
+            )
+            .transpose()
+    }
+
+    /// Returns the time control of the game ("TimeControl" tag). Returns ``None`` if the tag is
+    /// missing or invalid
+    #[inline]
+    pub fn get_time_control(&self) -> Option<TimeControl> {
+        self.parse_time_control().ok().flatten()
+    }
+
+    #[inline]
+    pub fn set_time_control(&mut self, time_control: &TimeControl) -> &mut Self {
+        self.set_str("TimeControl", &time_control.to_string())
+    }
+
+    /// Returns the reason of the game termination ("Termination" tag)
+    #[inline]
+    pub fn get_termination(&self) -> Option<Termination> {
+        self.get_str("Termination").map(Termination::from)
+    }
+
+    #[inline]
+    pub fn set_termination(&mut self, termination: &Termination) -> &mut Self {
+        self.set_str("Termination", &termination.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Game;
+
+    #[test]
+    fn time_controls() {
+        for s in ["?", "-", "*180", "300", "300+2", "40/7200:20/3600:900+30"] {
+            assert_eq!(TimeControl::from_str(s).unwrap().to_string(), s);
+        }
+        assert_eq!(
+            TimeControl::from_str("300+2").unwrap(),
+            TimeControl::incremental(Duration::from_secs(300), Duration::from_secs(2))
+        );
+        for s in ["", "5 min", "40/", "300+", "*", "40/7200::900"] {
+            assert!(TimeControl::from_str(s).is_err(), "{s}");
+        }
+    }
+
+    #[test]
+    fn typed_tags() {
+        let pgn = "[Event \"Casual game\"]\n[White \"Player A\"]\n[Black \"Player B\"]\n\
+                   [WhiteElo \"2100\"]\n[BlackElo \"?\"]\n[ECO \"C20\"]\n\
+                   [TimeControl \"300+2\"]\n[Termination \"Normal\"]\n\n1. e4 e5 1/2-1/2";
+        let game = Game::from_pgn(pgn).unwrap();
+        let metadata = game.get_metadata();
+        assert_eq!(metadata.get_event(), Some("Casual game"));
+        assert_eq!(metadata.get_player(Color::Black), Some("Player B"));
+        assert_eq!(metadata.get_elo(Color::White), Some(2100));
+        assert_eq!(metadata.get_elo(Color::Black), None);
+        assert_eq!(metadata.get_eco(), Some("C20"));
+        assert_eq!(metadata.get_result(), Some("1/2-1/2"));
+        assert_eq!(metadata.get_termination(), Some(Termination::Normal));
+        assert_eq!(
+            metadata.get_time_control(),
+            Some(TimeControl::incremental(
+                Duration::from_secs(300),
+                Duration::from_secs(2)
+            ))
+        );
+        assert!(game.as_pgn().contains("[TimeControl \"300+2\"]"));
+
+        for tag in [
+            "[WhiteElo \"strong\"]",
+            "[ECO \"F99\"]",
+            "[TimeControl \"5 min\"]",
+        ] {
+            let pgn = format!("{tag}\n\n1. e4 e5 1-0");
+            assert!(matches!(
+                Game::from_pgn(&pgn),
+                Err(Error::InvalidMetadataTag { .. })
+            ));
+        }
+        for (value, time_control) in [
+            ("", TimeControl::Unknown),
+            ("?", TimeControl::Unknown),
+            ("-", TimeControl::Unlimited),
+        ] {
+            let pgn = format!("[TimeControl \"{value}\"]\n\n1. e4 e5 1-0");
+            let game = Game::from_pgn(&pgn).unwrap();
+            assert_eq!(game.get_metadata().get_time_control(), Some(time_control));
+        }
+
+        // the invalid values set without validation are exported as unknown ones
+        let mut game = Game::from_pgn("[ECO \"C20\"]\n\n1. e4 e5 1-0").unwrap();
+        let metadata = game.get_metadata_mut();
+        metadata.set_value("TimeControl".to_string(), "5 min".to_string());
+        metadata.set_value("WhiteElo".to_string(), "strong".to_string());
+        let pgn = game.as_pgn();
+        assert!(pgn.contains("[TimeControl \"?\"]") & pgn.contains("[WhiteElo \"?\"]"));
+        assert!(pgn.contains("[ECO \"C20\"]"));
+        assert!(Game::from_pgn(&pgn).is_ok());
+
+        let mut metadata = GameMetadata::default();
+        assert!(metadata.set_eco("Z00").is_err());
+        metadata.set_eco("B90").unwrap();
+        assert_eq!(
+            metadata
+                .set_termination(&Termination::Other("won on time".to_string()))
+                .get_termination(),
+            Some(Termination::Other("won on time".to_string()))
+        );
+    }
+}
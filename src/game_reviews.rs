@@ -0,0 +1,261 @@
+//! Game reviews
+//!
+//! This module builds the summary of the analyzed game (see ``Game::review``): the evaluation
+//! and material graphs, the key moments (the largest evaluation swings) and the accuracy of both
+//! players. The evaluations are taken from the engine analysis attached to the moves
+
+use crate::game_history::GameHistory;
+use crate::{Color, Evaluation, MovePropertiesOnBoard, Valuation, COLORS_NUMBER};
+
+/// Evaluations are clamped to this value (in centipawns) when the swings are calculated, so the
+/// forced mates and completely winning positions don't dominate the key moments
+pub const REVIEW_EVALUATION_LIMIT: i32 = 1000;
+
+/// Maximal number of key moments in the review
+const KEY_MOMENTS_NUMBER: usize = 3;
+
+/// The move which changed the evaluation the most
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyMoment {
+    pub ply:    usize,
+    /// The move in SAN
+    pub san:    String,
+    pub color:  Color,
+    pub before: Evaluation,
+    pub after:  Evaluation,
+    /// The loss of the player who made the move in centipawns (clamped to
+    /// ``REVIEW_EVALUATION_LIMIT``)
+    pub loss:   i32,
+}
+
+/// Summary of the analyzed game
+///
+/// # Examples
+/// ```
+/// use libchess::{AnalysisLine, Color::*, Evaluation, Game, MultiPv};
+/// let mut game = Game::from_san_moves([], &["e4", "e5", "Qh5", "Nc6", "Bc4", "Nf6"]).unwrap();
+///
+/// let board = game.get_action_history().get_positions()[5];
+/// let analysis = MultiPv::new(vec![
+///     AnalysisLine::new(
+///         Evaluation::Centipawns(20),
+///         vec![board.parse_san("g6").unwrap()],
+///     ),
+///     AnalysisLine::new(Evaluation::Mate(1), vec![board.parse_san("Nf6").unwrap()]),
+/// ]);
+/// game.set_analysis(5, analysis).unwrap();
+///
+/// let review = game.review();
+/// let blunder = &review.key_moments[0];
+/// assert_eq!(
+///     (blunder.san.as_str(), blunder.color, blunder.loss),
+///     ("Nf6", Black, 980)
+/// );
+/// assert!(review.get_accuracy(Black).unwrap() < 15.0);
+/// assert_eq!(review.get_accuracy(White), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameReview {
+    /// Evaluations of the positions (from the initial one to the one after the last move).
+    /// ``None`` if the position was not analyzed
+    pub evaluations: Vec<Option<Evaluation>>,
+    /// Material balance of the positions in centipawns (from white's perspective)
+    pub material:    Vec<i32>,
+    /// The largest swings sorted by the loss (the largest first)
+    pub key_moments: Vec<KeyMoment>,
+    /// Accuracy of the players in percents. ``None`` if there are no moves of the player with
+    /// evaluated positions before and after the move
+    pub accuracy:    [Option<f64>; COLORS_NUMBER],
+}
+
+impl GameReview {
+    /// Builds the review of the game history. The evaluation of the position before the move is
+    /// taken from the best line of the analysis attached to the move. The evaluation of the
+    /// position after the move is taken from the line of the same analysis which starts with
+    /// the move played, or from the best line of the analysis attached to the next move
+    pub fn from_history(history: &GameHistory, valuation: &Valuation) -> Self {
+        let positions = history.get_positions();
+        let moves = history.get_moves();
+
+        let best = |ply: usize| {
+            history
+                .get_analysis(ply)
+                .and_then(|analysis| analysis.get_best_line())
+                .map(|line| line.get_evaluation())
+        };
+        let played = |ply: usize| {
+            history
+                .get_analysis(ply)?
+                .get_lines()
+                .iter()
+                .find(|line| line.get_first_move() == Some(moves[ply]))
+                .map(|line| line.get_evaluation())
+        };
+
+        let evaluations = (0..positions.len())
+            .map(|ply| best(ply).or_else(|| ply.checked_sub(1).and_then(played)))
+            .collect();
+
+        let material = positions
+            .iter()
+            .map(|board| valuation.get_material_balance(board))
+            .collect();
+
+        let mut key_moments = Vec::new();
+        let mut accuracy_sums = [(0.0, 0); COLORS_NUMBER];
+        for (ply, board_move) in moves.iter().enumerate() {
+            let (Some(before), Some(after)) = (best(ply), played(ply).or_else(|| best(ply + 1)))
+            else {
+                continue;
+            };
+            let board = positions[ply];
+            let color = board.get_side_to_move();
+            let sign = match color {
+                Color::White => 1,
+                Color::Black => -1,
+            };
+            let (cp_before, cp_after) = (sign * to_centipawns(before), sign * to_centipawns(after));
+
+            let accuracy = get_move_accuracy(get_win_chance(cp_before), get_win_chance(cp_after));
+            accuracy_sums[color.to_index()].0 += accuracy;
+            accuracy_sums[color.to_index()].1 += 1;
+
+            let loss = cp_before - cp_after;
+            if loss > 0 {
+                let properties = MovePropertiesOnBoard::new(board_move, &board).unwrap();
+                key_moments.push(KeyMoment {
+                    ply,
+                    san: board_move.to_string(properties),
+                    color,
+                    before,
+                    after,
+                    loss,
+                });
+            }
+        }
+        key_moments.sort_by(|a, b| b.loss.cmp(&a.loss).then(a.ply.cmp(&b.ply)));
+        key_moments.truncate(KEY_MOMENTS_NUMBER);
+
+        Self {
+            evaluations,
+            material,
+            key_moments,
+            accuracy: accuracy_sums.map(|(sum, n)| match n {
+                0 => None,
+                n => Some(sum / n as f64),
+            }),
+        }
+    }
+
+    #[inline]
+    pub fn get_accuracy(&self, color: Color) -> Option<f64> { self.accuracy[color.to_index()] }
+
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String { serde_json::to_string(self).unwrap() }
+}
+
+/// Converts the evaluation to centipawns clamped to ``REVIEW_EVALUATION_LIMIT``
+fn to_centipawns(evaluation: Evaluation) -> i32 {
+    match evaluation {
+        Evaluation::Centipawns(cp) => cp.clamp(-REVIEW_EVALUATION_LIMIT, REVIEW_EVALUATION_LIMIT),
+        Evaluation::Mate(n) if n < 0 => -REVIEW_EVALUATION_LIMIT,
+        Evaluation::Mate(_) => REVIEW_EVALUATION_LIMIT,
+    }
+}
+
+/// Returns the winning chance (0-100) of the player with specified advantage in centipawns
+fn get_win_chance(cp: i32) -> f64 {
+    50.0 + 50.0 * (2.0 / (1.0 + (-0.00368208 * cp as f64).exp()) - 1.0)
+}
+
+/// Returns the accuracy of the move (0-100) by the drop of the winning chance of the player
+fn get_move_accuracy(before: f64, after: f64) -> f64 {
+    let drop = (before - after).max(0.0);
+    (103.1668 * (-0.04354 * drop).exp() - 3.1669).clamp(0.0, 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AnalysisLine, Game, MultiPv};
+
+    /// Attaches the evaluations to the first plies of the game. The analysis of every ply
+    /// consists of a single line starting with the move which was not played
+    fn analyzed_game(moves: &[&str], evaluations: &[i32]) -> Game {
+        let mut game = Game::from_san_moves([], moves).unwrap();
+        for (ply, cp) in evaluations.iter().enumerate() {
+            let history = game.get_action_history();
+            let played = history.get_moves()[ply];
+            let board_move = history.get_positions()[ply]
+                .get_legal_moves()
+                .into_iter()
+                .find(|m| *m != played)
+                .unwrap();
+            let line = AnalysisLine::new(Evaluation::Centipawns(*cp), vec![board_move]);
+            game.set_analysis(ply, MultiPv::new(vec![line])).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn review_graphs() {
+        let review = analyzed_game(&["e4", "d5", "exd5", "Qxd5"], &[30, 20, 60]).review();
+        assert_eq!(
+            review.evaluations,
+            vec![
+                Some(Evaluation::Centipawns(30)),
+                Some(Evaluation::Centipawns(20)),
+                Some(Evaluation::Centipawns(60)),
+                None,
+                None
+            ]
+        );
+        assert_eq!(review.material, vec![0, 0, 0, 100, 0]);
+        assert_eq!(review.key_moments.len(), 2);
+        assert_eq!(review.key_moments[0].san, "d5");
+        assert_eq!(review.key_moments[0].loss, 40);
+        assert_eq!(review.key_moments[1].san, "e4");
+        assert!(
+            review.get_accuracy(Color::White).unwrap() > review.get_accuracy(Color::Black).unwrap()
+        );
+
+        let review = Game::default().review();
+        assert_eq!(review.evaluations, vec![None]);
+        assert!(review.key_moments.is_empty());
+        assert_eq!(review.get_accuracy(Color::White), None);
+    }
+
+    #[test]
+    fn review_key_moments() {
+        let game = analyzed_game(
+            &["e4", "e5", "Nf3", "Nc6", "Bc4", "Nd4", "Nxe5"],
+            &[30, 20, 30, 30, 30, -50, 150],
+        );
+        let review = game.review();
+        let key_moments: Vec<(&str, i32)> = review
+            .key_moments
+            .iter()
+            .map(|moment| (moment.san.as_str(), moment.loss))
+            .collect();
+        assert_eq!(key_moments, vec![("Nd4", 200), ("Bc4", 80), ("e4", 10)]);
+        assert_eq!(review.key_moments[0].color, Color::Black);
+
+        // mates are clamped
+        let mut game = Game::from_san_moves([], &["f3", "e5", "g4"]).unwrap();
+        let board = game.get_action_history().get_positions()[2];
+        let analysis = MultiPv::new(vec![
+            AnalysisLine::new(
+                Evaluation::Centipawns(-50),
+                vec![board.parse_san("Nc3").unwrap()],
+            ),
+            AnalysisLine::new(Evaluation::Mate(-1), vec![board.parse_san("g4").unwrap()]),
+        ]);
+        game.set_analysis(2, analysis).unwrap();
+        let review = game.review();
+        assert_eq!(review.key_moments[0].san, "g4");
+        assert_eq!(review.key_moments[0].loss, REVIEW_EVALUATION_LIMIT - 50);
+        assert_eq!(review.evaluations[3], Some(Evaluation::Mate(-1)));
+    }
+}
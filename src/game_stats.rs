@@ -0,0 +1,71 @@
+//! Game statistics
+//!
+//! This module defines the phases of the game (see ``ChessBoard::get_game_phase``) and the
+//! summary of the game history (see ``GameHistory::stats``): the numbers of moves of each class
+//! and the plies where the middlegame and the endgame began. The statistics are intended for the
+//! reports over the imported games
+
+use std::fmt;
+
+/// The minimal number of pieces (except kings and pawns) of both sides in the opening
+pub const OPENING_MIN_PIECES: u32 = 11;
+
+/// The opening is finished when any side has less than this number of pieces on its back rank
+/// (three pieces are developed)
+pub const OPENING_MIN_BACK_RANK_PIECES: u32 = 6;
+
+/// Phase of the game by the material and the development of the pieces. The game is in the
+/// endgame if there are at most ``ENDGAME_MAX_PIECES`` pieces (except kings and pawns) on the
+/// board, in the opening if there are at least ``OPENING_MIN_PIECES`` of them and both sides
+/// have at least ``OPENING_MIN_BACK_RANK_PIECES`` pieces on their back ranks, and in the
+/// middlegame otherwise
+///
+/// # Examples
+/// ```
+/// use libchess::{ChessBoard, GamePhase};
+/// let board = ChessBoard::default();
+/// assert_eq!(board.get_game_phase(), GamePhase::Opening);
+/// let board = board.apply_san_line("e4 e5 Nf3 Nc6 Bc4 Bc5").unwrap();
+/// assert_eq!(board.get_game_phase(), GamePhase::Opening);
+/// let board = board.apply_san_line("Nc3").unwrap();
+/// assert_eq!(board.get_game_phase(), GamePhase::Middlegame);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GamePhase {
+    Opening,
+    Middlegame,
+    Endgame,
+}
+
+impl fmt::Display for GamePhase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let phase = match self {
+            GamePhase::Opening => "opening",
+            GamePhase::Middlegame => "middlegame",
+            GamePhase::Endgame => "endgame",
+        };
+        write!(f, "{phase}")
+    }
+}
+
+/// The statistics of the moves of the game (see ``GameHistory::stats``). The checks are counted
+/// independently of the other classes (e.g. the capture with check is counted twice)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameStats {
+    pub plies:          usize,
+    /// Captures including en passant ones
+    pub captures:       usize,
+    /// Moves giving check including checkmates
+    pub checks:         usize,
+    pub castles:        usize,
+    pub promotions:     usize,
+    /// Moves which are neither captures, nor promotions, nor castles
+    pub quiet_moves:    usize,
+    /// The ply of the first position of the middlegame or the endgame (None if the game didn't
+    /// leave the opening)
+    pub middlegame_ply: Option<usize>,
+    /// The ply of the first position of the endgame (None if the game didn't reach it)
+    pub endgame_ply:    Option<usize>,
+}
@@ -3,15 +3,30 @@
 //! Rules of the game, terminating conditions and recording the history of the game also
 //! implemented here  
 
+use crate::draw_searches::DrawSearch;
 use crate::errors::LibChessError as Error;
 use crate::game_history::GameHistory;
+use crate::game_metadata::METADATA_PRIMARY_KEYS;
+use crate::move_caches::SharedLegalMovesCache;
+use crate::time_reports::parse_emt;
 use crate::Color;
-use crate::{BoardBuilder, BoardMove, BoardStatus, ChessBoard, LegalMoves, MovePropertiesOnBoard};
+use crate::{
+    Annotations, BitBoard, BoardBuilder, BoardMove, BoardStatus, CacheStatistics, ChessBoard,
+    GameMetadata, GameReview, IllegalMoveAttempt, IllegalMovePolicy, InMemoryPositionCounter,
+    LegalMoves, MoveIntent, MoveNumber, MoveNumbering, MultiPv, NotationLocale, Piece, PieceType,
+    Ply, PositionCounter, PositionHashValueType, Square, Termination, TimeReport, Valuation,
+    STARTING_POSITION_FEN,
+};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 use regex::Regex;
 use std::collections::BTreeMap;
 use std::fmt;
+use std::fs;
+use std::path::Path;
 use std::str::FromStr;
-use textwrap::wrap;
+use std::time::Duration;
+use textwrap::{wrap, Options, WordSplitter};
 
 /// Represents available actions for the player
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -21,15 +36,19 @@ pub enum Action {
     AcceptDraw,
     DeclineDraw,
     Resign(Color),
+    ClaimFiftyMoveDraw,
+    ClaimThreefoldDraw,
 }
 
 /// Represents the status of the game
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GameStatus {
     Ongoing,
     DrawOffered(Color),
     CheckMated(Color),
     Resigned(Color),
+    Forfeited(Color),
     FiftyMovesDrawDeclared,
     TheoreticalDrawDeclared,
     RepetitionDrawDeclared,
@@ -37,6 +56,25 @@ pub enum GameStatus {
     Stalemate,
 }
 
+impl GameStatus {
+    #[inline]
+    fn is_ongoing_or_draw_offered(&self) -> bool {
+        matches!(self, GameStatus::Ongoing | GameStatus::DrawOffered(_))
+    }
+}
+
+impl From<BoardStatus> for GameStatus {
+    fn from(status: BoardStatus) -> Self {
+        match status {
+            BoardStatus::Ongoing => GameStatus::Ongoing,
+            BoardStatus::CheckMated(color) => GameStatus::CheckMated(color),
+            BoardStatus::Stalemate => GameStatus::Stalemate,
+            BoardStatus::TheoreticalDrawDeclared => GameStatus::TheoreticalDrawDeclared,
+            BoardStatus::FiftyMovesDrawDeclared => GameStatus::FiftyMovesDrawDeclared,
+        }
+    }
+}
+
 impl fmt::Display for GameStatus {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let status_string = match self {
@@ -44,6 +82,7 @@ impl fmt::Display for GameStatus {
             GameStatus::DrawOffered(color) => format!("draw offered by {}", *color),
             GameStatus::CheckMated(color) => format!("{} won by checkmate", !*color),
             GameStatus::Resigned(color) => format!("{} won by resignation", !*color),
+            GameStatus::Forfeited(color) => format!("{} won by forfeit", !*color),
             GameStatus::DrawAccepted => "draw declared by agreement".to_string(),
             GameStatus::FiftyMovesDrawDeclared => "draw declared by a 50 moves rule".to_string(),
             GameStatus::TheoreticalDrawDeclared => "draw: no enough pieces".to_string(),
@@ -54,38 +93,33 @@ impl fmt::Display for GameStatus {
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct GameMetadata {
-    metadata: BTreeMap<String, String>,
-}
-
-const METADATA_PRIMARY_KEYS: [&str; 7] =
-    ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
-const TEXT_WRAP_WIDTH: usize = 85;
-
-impl Default for GameMetadata {
-    #[inline]
-    fn default() -> Self {
-        let mut metadata = BTreeMap::new();
-        metadata.insert("Event".to_string(), "?".to_string());
-        metadata.insert("Site".to_string(), "?".to_string());
-        metadata.insert("Date".to_string(), "?".to_string());
-        metadata.insert("Round".to_string(), "?".to_string());
-        metadata.insert("White".to_string(), "Player 1".to_string());
-        metadata.insert("Black".to_string(), "Player 2".to_string());
-        metadata.insert("Result".to_string(), "?".to_string());
-        Self::new(metadata)
-    }
+/// The last move of the game for highlighting it on the board (see ``Game::last_move_info``).
+/// The source and destination squares of castling are the ones of the king
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LastMoveInfo {
+    pub board_move:      BoardMove,
+    /// The side which made the move
+    pub color:           Color,
+    pub source:          Square,
+    pub destination:     Square,
+    /// The captured piece (the pawn captured en passant is included)
+    pub captured:        Option<Piece>,
+    /// All squares changed by the move (e.g. both squares of the rook for castling)
+    pub changed_squares: BitBoard,
 }
 
-impl GameMetadata {
-    #[inline]
-    fn new(metadata: BTreeMap<String, String>) -> Self { Self { metadata } }
+/// The maximal length of the movetext lines in the exported PGN (as required by the standard)
+const TEXT_WRAP_WIDTH: usize = 79;
 
-    pub fn get_value(&self, tag: String) -> Option<&String> { self.metadata.get(&tag) }
-
-    pub fn set_value(&mut self, tag: String, value: String) { self.metadata.insert(tag, value); }
-}
+/// The tags which store the in-flight state of the saved game (see ``Game::to_saved_string``)
+const SAVED_DRAW_OFFER_TAG: &str = "DrawOffer";
+const SAVED_DRAW_CLAIMS_TAG: &str = "DrawClaimsRequired";
+const SAVED_ILLEGAL_MOVE_POLICY_TAG: &str = "IllegalMovePolicy";
+const SAVED_ILLEGAL_MOVES_TAG: &str = "IllegalMoveAttempts";
+const FIFTY_MOVES_RULE_PLIES: usize = 100;
+const SEVENTY_FIVE_MOVES_RULE_PLIES: usize = 150;
+const THREEFOLD_REPETITION: usize = 3;
+const FIVEFOLD_REPETITION: usize = 5;
 
 /// The Game of Chess object
 ///
@@ -129,29 +163,21 @@ impl GameMetadata {
 /// println!("{}", game.get_position());
 /// ```
 #[derive(Debug, Clone)]
-pub struct Game {
+pub struct Game<C = InMemoryPositionCounter> {
     position: ChessBoard,
     history: GameHistory,
-    unique_positions_counter: BTreeMap<u64, usize>,
+    unique_positions_counter: C,
     status: GameStatus,
     metadata: GameMetadata,
+    draw_claims_required: bool,
+    legal_moves_cache: SharedLegalMovesCache,
+    illegal_move_policy: IllegalMovePolicy,
+    illegal_move_attempts: Vec<IllegalMoveAttempt>,
 }
 
 impl Default for Game {
     #[inline]
-    fn default() -> Self {
-        let board = ChessBoard::default();
-        let mut result = Self {
-            position: board,
-            history: GameHistory::from_position(board),
-            unique_positions_counter: BTreeMap::new(),
-            status: GameStatus::Ongoing,
-            metadata: GameMetadata::default(),
-        };
-
-        result.update_game_status(None).position_counter_increment();
-        result
-    }
+    fn default() -> Self { Self::from_board(ChessBoard::default()) }
 }
 
 impl Game {
@@ -167,16 +193,8 @@ impl Game {
     /// ```
     #[inline]
     pub fn from_board(board: ChessBoard) -> Self {
-        let mut result = Self {
-            position: board,
-            history: GameHistory::from_position(board),
-            unique_positions_counter: BTreeMap::new(),
-            status: GameStatus::Ongoing,
-            metadata: GameMetadata::default(),
-        };
-
-        result.update_game_status(None).position_counter_increment();
-        result
+        Self::with_position_counter(board, InMemoryPositionCounter::new())
+            .expect("the in-memory position counter can't fail")
     }
 
     /// Creates a ``Game`` object and sets custom starting position by using FEN-string
@@ -218,6 +236,10 @@ impl Game {
     /// You will not able to make any moves or change the history of the game because it is finished.
     /// But in case when PGN was generated for the continuing game it, obviously, will be possible
     ///
+    /// The decisive games which are not finished by checkmate are considered as resigned, unless
+    /// the main line comments ("White resigns", "Black forfeits on time", etc.) or the
+    /// "Termination" tag ("time forfeit", "abandoned", "rules infraction") describe the forfeit
+    ///
     /// # Errors
     /// ``errors::LibChessError::InvalidPGNString`` in case when parser will fail to process the
     /// PGN-string passed into arguments
@@ -231,12 +253,44 @@ impl Game {
     /// println!("{}", game.get_position());
     /// ```
     pub fn from_pgn(pgn: &str) -> Result<Self, Error> {
+        Self::from_localized_pgn(pgn, &NotationLocale::default())
+    }
+
+    /// Creates the game from PGN-string with the piece letters of specified locale (see
+    /// ``Game::from_pgn``)
+    ///
+    /// # Errors
+    /// ``errors::LibChessError::InvalidPGNString`` in case when parser will fail to process the
+    /// PGN-string passed into arguments or a move has the piece letter which is neither the
+    /// locale's nor the English one
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{Game, NotationLocale};
+    /// let pgn = "[Event \"Partie\"]\n\n1. e4 e5 2. Sf3 Sc6 3. Lb5 a6 1-0";
+    /// let game = Game::from_localized_pgn(pgn, &NotationLocale::german()).unwrap();
+    /// assert_eq!(
+    ///     game.as_fen(),
+    ///     "r1bqkbnr/1ppp1ppp/p1n5/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 0 4"
+    /// );
+    /// ```
+    pub fn from_localized_pgn(pgn: &str, locale: &NotationLocale) -> Result<Self, Error> {
+        Self::parse_pgn(pgn, locale, false)
+    }
+
+    /// Parses the PGN-string (see ``Game::from_localized_pgn``). If ``allow_unfinished`` is set,
+    /// the movetext without the result token is considered as the ongoing game
+    fn parse_pgn(
+        pgn: &str,
+        locale: &NotationLocale,
+        allow_unfinished: bool,
+    ) -> Result<Self, Error> {
         use Color::*;
         let mut game = Game::default();
         let metadata_pattern = r#"(?x)\[
         (\s*[\w\d_]+) # key pattern
         \s+
-        "([\s\w\d:/\.\?,-]*)" # value pattern in quotes
+        "((?:[^"\\]|\\.)*)" # value pattern in quotes (with escaped quotes and backslashes)
         \s*
         \]"#;
 
@@ -245,39 +299,75 @@ impl Game {
             .captures_iter(pgn)
             .for_each(|cap| {
                 game.metadata
-                    .set_value(cap[1].to_string(), cap[2].to_string())
+                    .set_value(cap[1].to_string(), unescape_tag_value(&cap[2]))
             });
+        game.metadata.validate()?;
+
+        // the game may start from a custom position
+        if let Some(fen) = game.metadata.get_value("FEN".to_string()) {
+            let metadata = game.metadata.clone();
+            game = Game::from_fen(fen)?;
+            game.metadata = metadata;
+        }
 
         let pgn_moves_part = Regex::new(r"(\r?\n){2,}")
             .expect("Invalid regex")
             .split(pgn)
             .nth(1)
             .ok_or(Error::InvalidPGNString)?;
-
-        let moves_pattern = r"(?x)
+        let (pgn_moves_part, comments) = strip_pgn_annotations(pgn_moves_part)?;
+        let pgn_moves_part = &pgn_moves_part;
+
+        // English letters are accepted besides the locale ones (see
+        // ``ChessBoard::parse_localized_san``). Any other capital letter is captured as well, so
+        // the move fails to parse instead of being read without its piece letter
+        let piece = format!(r"(?:{}|\p{{Lu}})", locale.get_piece_pattern());
+        let moves_pattern = format!(
+            r"(?x)
         (
             (
-                ([nNbBrRqQkK]*[a-h]*[1-8]*x*[a-h][1-8])
-                |(O-O(-O)?)
+                ({piece}*[a-h]*[1-8]*x*[a-h][1-8])
+                |([O0]-[O0](-[O0])?)
             )
-            (=[nNbBrRqQ])?
+            (=?{piece})?
             \+?\#?
-        )";
+        )
+        |(\[%emt\s+(?P<emt>[^\]\s]+)\s*\])
+        |(\[%(?P<command>cal|csl)\s+(?P<args>[^\]]+)\])"
+        );
 
-        for cap in Regex::new(moves_pattern)
+        for cap in Regex::new(&moves_pattern)
             .expect("Invalid regex")
             .captures_iter(pgn_moves_part)
         {
-            let capture = cap[0].to_string();
-            let pos = game.get_position();
-            let legal_moves = BTreeMap::from_iter(
-                game.get_legal_moves()
-                    .into_iter()
-                    .map(|m| (m, MovePropertiesOnBoard::new(&m, &pos).unwrap()))
-                    .map(|(m, metadata)| (m.to_string(metadata), m)),
-            );
+            if let Some(emt) = cap.name("emt") {
+                let ply = game.history.get_ply_count().to_index();
+                let time = parse_emt(emt.as_str()).map_err(|_| Error::InvalidPGNString)?;
+                game.history
+                    .set_move_time(ply.wrapping_sub(1), time)
+                    .map_err(|_| Error::InvalidPGNString)?;
+                continue;
+            }
+            if let (Some(command), Some(args)) = (cap.name("command"), cap.name("args")) {
+                let ply = game.history.get_ply_count().to_index().wrapping_sub(1);
+                let mut annotations = game
+                    .history
+                    .get_annotations(ply)
+                    .cloned()
+                    .unwrap_or_default();
+                annotations
+                    .add_pgn_command(command.as_str(), args.as_str())
+                    .map_err(|_| Error::InvalidPGNString)?;
+                game.history
+                    .set_annotations(ply, annotations)
+                    .map_err(|_| Error::InvalidPGNString)?;
+                continue;
+            }
 
-            let current_move = *legal_moves.get(&capture).ok_or(Error::InvalidPGNString)?;
+            let current_move = game
+                .get_position()
+                .parse_localized_san(&cap[0], locale)
+                .map_err(|_| Error::InvalidPGNString)?;
             game.make_move(&Action::MakeMove(current_move))?;
         }
 
@@ -286,12 +376,17 @@ impl Game {
                 .expect("Invalid regex")
                 .captures_iter(pgn_moves_part)
                 .nth(0)
-                .map(|x| x.get(0).unwrap())
-                .ok_or(Error::InvalidPGNString)?;
+                .map(|x| x.get(0).unwrap());
+            let result_cap = match result_cap {
+                Some(result_cap) => result_cap,
+                None if allow_unfinished => return Ok(game),
+                None => return Err(Error::InvalidPGNString),
+            };
 
+            let termination = game.metadata.get_termination();
             match result_cap.as_str() {
-                "1-0" => game.make_move(&Action::Resign(Black)).unwrap(),
-                "0-1" => game.make_move(&Action::Resign(White)).unwrap(),
+                "1-0" => game.finish_decisive(Black, &comments, termination),
+                "0-1" => game.finish_decisive(White, &comments, termination),
                 "1/2-1/2" => game
                     .make_move(&Action::OfferDraw(White))
                     .unwrap()
@@ -304,13 +399,163 @@ impl Game {
         Ok(game)
     }
 
+    /// Restores the game saved by ``Game::save``
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidGameFile`` if the file can't be read
+    ///
+    /// The same errors as ``Game::from_saved_string``
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let saved = fs::read_to_string(path).map_err(|e| Error::InvalidGameFile {
+            description: e.to_string(),
+        })?;
+        Self::from_saved_string(&saved)
+    }
+
+    /// Restores the game from the string produced by ``Game::to_saved_string``. Unlike
+    /// ``Game::from_pgn`` the unfinished games are accepted and the in-flight state (the pending
+    /// draw offer, the draw claims mode, the illegal move policy and attempts) is restored
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidPGNString`` if the string can't be parsed or the saved state can't
+    /// be applied to the game
+    pub fn from_saved_string(saved: &str) -> Result<Self, Error> {
+        let mut game = Self::parse_pgn(saved, &NotationLocale::default(), true)?;
+        let mut state = |tag: &str| game.metadata.remove_value(tag.to_string());
+
+        let draw_offer = state(SAVED_DRAW_OFFER_TAG);
+        let draw_claims_required = state(SAVED_DRAW_CLAIMS_TAG);
+        let policy = state(SAVED_ILLEGAL_MOVE_POLICY_TAG);
+        let attempts = state(SAVED_ILLEGAL_MOVES_TAG);
+        if let Some(value) = draw_claims_required {
+            game.set_draw_claims_required(match value.as_str() {
+                "1" => true,
+                "0" => false,
+                _ => return Err(Error::InvalidPGNString),
+            });
+        }
+        if let Some(color) = draw_offer {
+            let color = Color::from_str(&color).map_err(|_| Error::InvalidPGNString)?;
+            game.make_move(&Action::OfferDraw(color))
+                .map_err(|_| Error::InvalidPGNString)?;
+        }
+
+        if let Some(policy) = policy {
+            game.set_illegal_move_policy(parse_illegal_move_policy(&policy)?);
+        }
+        // the attempts are restored as they were recorded (the forfeit is already in the status)
+        for attempt in attempts.iter().flat_map(|a| a.split_whitespace()) {
+            let mut parts = attempt.splitn(3, ':');
+            let (ply, color, board_move) = (parts.next(), parts.next(), parts.next());
+            let ply = ply.and_then(|ply| ply.parse::<usize>().ok());
+            let color = color.and_then(|color| Color::from_str(color).ok());
+            let board_move = board_move.and_then(|m| BoardMove::from_str(m).ok());
+            let (Some(ply), Some(color), Some(board_move)) = (ply, color, board_move) else {
+                return Err(Error::InvalidPGNString);
+            };
+            let strike = game.get_illegal_move_strikes(color) + 1;
+            game.illegal_move_attempts.push(IllegalMoveAttempt {
+                color,
+                board_move,
+                ply: Ply::new(ply),
+                strike,
+            });
+        }
+        Ok(game)
+    }
+
+    /// Creates the game from the list of SAN-moves (see ``ChessBoard::parse_san``) and the
+    /// metadata tags. If there is the "FEN" tag, the game starts from this position
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidMoveInLine`` with the first token which is not a legal move in the
+    /// position (or can't be made because the game is finished) and its ply
+    ///
+    /// ``LibChessError::InvalidFENString`` and other position errors if the "FEN" tag is invalid
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{Color::*, Game, GameStatus};
+    /// let game = Game::from_san_moves(
+    ///     [("White".to_string(), "Morphy".to_string())],
+    ///     &["e4", "e5", "Qh5", "Nc6", "Bc4", "Nf6", "Qxf7#"],
+    /// )
+    /// .unwrap();
+    /// assert_eq!(game.get_game_status(), GameStatus::CheckMated(Black));
+    /// assert_eq!(
+    ///     game.get_metadata().get_value("White".to_string()),
+    ///     Some(&"Morphy".to_string())
+    /// );
+    /// ```
+    pub fn from_san_moves(
+        tags: impl IntoIterator<Item = (String, String)>,
+        moves: &[&str],
+    ) -> Result<Self, Error> {
+        let mut metadata = GameMetadata::default();
+        tags.into_iter()
+            .for_each(|(tag, value)| metadata.set_value(tag, value));
+
+        let mut game = match metadata.get_value("FEN".to_string()) {
+            Some(fen) => Game::from_fen(fen)?,
+            None => Game::default(),
+        };
+        game.metadata = metadata;
+
+        for (ply, token) in moves.iter().enumerate() {
+            let invalid_move = || Error::InvalidMoveInLine {
+                token: token.to_string(),
+                ply,
+            };
+            let m = game
+                .get_position()
+                .parse_san(token)
+                .map_err(|_| invalid_move())?;
+            game.make_move(&Action::MakeMove(m))
+                .map_err(|_| invalid_move())?;
+        }
+        Ok(game)
+    }
+}
+
+impl<C: PositionCounter> Game<C> {
+    /// Creates a ``Game`` object with the custom storage of position counters (see
+    /// ``PositionCounter``). The initial position is counted in the storage
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidPositionCounter`` if the storage fails
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{ChessBoard, Game, InMemoryPositionCounter};
+    /// let counter = InMemoryPositionCounter::new();
+    /// let game = Game::with_position_counter(ChessBoard::default(), counter).unwrap();
+    /// assert_eq!(game.get_position_counters().len(), 1);
+    /// ```
+    pub fn with_position_counter(board: ChessBoard, counter: C) -> Result<Self, Error> {
+        let mut result = Self {
+            position: board,
+            history: GameHistory::from_position(board),
+            unique_positions_counter: counter,
+            status: GameStatus::Ongoing,
+            metadata: GameMetadata::default(),
+            draw_claims_required: false,
+            legal_moves_cache: SharedLegalMovesCache::default(),
+            illegal_move_policy: IllegalMovePolicy::default(),
+            illegal_move_attempts: Vec::new(),
+        };
+
+        result.position_counter_increment(&board)?;
+        result.update_game_status(None)?;
+        Ok(result)
+    }
+
     /// Returns a FEN string representing current game position
     ///
     /// [FEN-string](https://en.wikipedia.org/wiki/Forsyth%E2%80%93Edwards_Notation)
     /// (Forsyth–Edwards Notation) - standard notation for describing a particular board position
     /// of a chess game. This method returns FEN-string for the current position in the game. To
     /// render any other position from the game history you can use
-    /// ``game.get_action_history().get_position_on_move(move_number: usize).as_fen()``
+    /// ``game.get_action_history().get_position_on_move(ply).as_fen()``
     ///
     /// # Examples
     /// ```
@@ -326,7 +571,8 @@ impl Game {
     ///
     /// [PGN-string](https://en.wikipedia.org/wiki/Portable_Game_Notation) file extension is a
     /// plain text representation of current game and allows you to export the game to any available
-    /// GUI for chess rendering/analysis
+    /// GUI for chess rendering/analysis. The typed tags with invalid values (see
+    /// ``GameMetadata::validate``) are written as unknown ("?")
     ///
     /// # Examples
     /// ```
@@ -341,31 +587,258 @@ impl Game {
     /// game.make_move(&Action::AcceptDraw).unwrap();
     /// println!("{}", game.as_pgn());
     /// ```
-    pub fn as_pgn(&self) -> String {
+    pub fn as_pgn(&self) -> String { self.format_pgn(&self.metadata) }
+
+    fn format_pgn(&self, metadata: &GameMetadata) -> String {
         let mut result = String::new();
-        let game_result_str = self.metadata.metadata.get("Result").unwrap();
-        let mut metadata = self.metadata.metadata.clone();
+        let game_result_str = metadata.get_result().unwrap();
+        let metadata = metadata.to_valid();
+        let mut metadata: BTreeMap<&str, String> = metadata
+            .iter()
+            .map(|(tag, value)| (tag.as_str(), value.clone()))
+            .collect();
+        let initial_position = self.history.get_starting_position();
+        // the move counters are compared too, so the position is set up if they differ
+        if initial_position.as_fen() != STARTING_POSITION_FEN {
+            metadata.insert("SetUp", "1".to_string());
+            metadata.insert("FEN", initial_position.as_fen());
+        }
         METADATA_PRIMARY_KEYS.into_iter().for_each(|key| {
-            result = format!("{result}[{} \"{}\"]\n", key, metadata.get(key).unwrap());
+            let value = escape_tag_value(metadata.get(key).unwrap());
+            result = format!("{result}[{key} \"{value}\"]\n");
             metadata.remove(key);
         });
-        metadata.keys().for_each(|key| {
-            result = format!("{result}[{} \"{}\"]\n", key, metadata.get(key).unwrap());
+        metadata.iter().for_each(|(key, value)| {
+            result = format!("{result}[{key} \"{}\"]\n", escape_tag_value(value));
         });
 
-        result = format!(
-            "{result}\n{} ",
-            wrap(
-                format!("{}", self.get_action_history()).as_str(),
-                TEXT_WRAP_WIDTH
-            )
-            .join("\n")
-        );
-        result += game_result_str;
+        let movetext = self
+            .history
+            .to_movetext(MoveNumbering::FromPosition, Some(game_result_str));
+        // the moves and the result tokens must not be split at the hyphens ("O-O", "1-0")
+        let options = Options::new(TEXT_WRAP_WIDTH)
+            .break_words(false)
+            .word_splitter(WordSplitter::NoHyphenation);
+        result = format!("{result}\n{}", wrap(&movetext, options).join("\n"));
 
         result
     }
 
+    /// Saves the game to the file, so it can be resumed exactly by ``Game::load`` (see
+    /// ``Game::to_saved_string``)
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidGameFile`` if the file can't be written
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        fs::write(path, self.to_saved_string()).map_err(|e| Error::InvalidGameFile {
+            description: e.to_string(),
+        })
+    }
+
+    /// Returns the PGN of the game with the in-flight state stored in the additional tags: the
+    /// pending draw offer, the draw claims mode, the illegal move policy and the recorded illegal
+    /// move attempts. ``Game`` has no running clock, so the clock remainders are not saved: they
+    /// are derived by the caller from the "TimeControl" tag and the recorded move times which
+    /// are the part of the PGN
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{Action, Color::*, Game, GameStatus};
+    /// let mut game = Game::from_san_moves([], &["e4", "e5"]).unwrap();
+    /// game.make_move(&Action::OfferDraw(White)).unwrap();
+    ///
+    /// let restored = Game::from_saved_string(&game.to_saved_string()).unwrap();
+    /// assert_eq!(restored.get_game_status(), GameStatus::DrawOffered(White));
+    /// assert_eq!(restored.get_position(), game.get_position());
+    /// ```
+    pub fn to_saved_string(&self) -> String {
+        let mut metadata = self.metadata.clone();
+        if let GameStatus::DrawOffered(color) = self.status {
+            let color = match color {
+                Color::White => "White",
+                Color::Black => "Black",
+            };
+            metadata.set_value(SAVED_DRAW_OFFER_TAG.to_string(), color.to_string());
+        }
+        metadata.set_value(
+            SAVED_DRAW_CLAIMS_TAG.to_string(),
+            (self.draw_claims_required as u8).to_string(),
+        );
+        let policy = match self.illegal_move_policy {
+            IllegalMovePolicy::Reject => None,
+            IllegalMovePolicy::Warn => Some("Warn".to_string()),
+            IllegalMovePolicy::ForfeitAfter(limit) => Some(format!("ForfeitAfter {limit}")),
+        };
+        if let Some(policy) = policy {
+            metadata.set_value(SAVED_ILLEGAL_MOVE_POLICY_TAG.to_string(), policy);
+        }
+        if !self.illegal_move_attempts.is_empty() {
+            let attempts: Vec<String> = self
+                .illegal_move_attempts
+                .iter()
+                .map(|a| format!("{}:{:#}:{}", a.ply, a.color, a.board_move))
+                .collect();
+            metadata.set_value(SAVED_ILLEGAL_MOVES_TAG.to_string(), attempts.join(" "));
+        }
+        self.format_pgn(&metadata)
+    }
+
+    /// Attaches the engine analysis (multiple ranked lines) to the move made on specified ply.
+    /// The analysis is exported to PGN: the evaluation of the move played is rendered as a comment
+    /// and the rest of the lines as variations
+    ///
+    /// # Errors
+    /// ``LibChessError::WrongMoveNumber`` if the move on this ply was not made yet
+    ///
+    /// ``LibChessError::InvalidAnalysisLine`` if any of analysis lines is illegal for the position
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{mv, squares::*, Action, BoardMove, Game, PieceMove, PieceType::*};
+    /// use libchess::{AnalysisLine, Evaluation, MultiPv};
+    ///
+    /// let mut game = Game::default();
+    /// game.make_move(&Action::MakeMove(mv!(Pawn, E2, E4)))
+    ///     .unwrap();
+    /// game.set_analysis(
+    ///     0,
+    ///     MultiPv::new(vec![
+    ///         AnalysisLine::new(Evaluation::Centipawns(30), vec![mv!(Pawn, E2, E4)]),
+    ///         AnalysisLine::new(Evaluation::Centipawns(25), vec![mv!(Pawn, D2, D4)]),
+    ///     ]),
+    /// )
+    /// .unwrap();
+    /// assert_eq!(
+    ///     format!("{}", game.get_action_history()),
+    ///     "1.e4 { +0.30 } (1.d4 { +0.25 }) "
+    /// );
+    /// ```
+    pub fn set_analysis(
+        &mut self,
+        ply: impl Into<Ply>,
+        analysis: MultiPv,
+    ) -> Result<&mut Self, Error> {
+        self.history.set_analysis(ply, analysis)?;
+        Ok(self)
+    }
+
+    /// Records the time spent on the move made on specified ply (see
+    /// ``GameHistory::set_move_time``)
+    ///
+    /// # Errors
+    /// ``LibChessError::WrongMoveNumber`` if the move on this ply was not made yet
+    pub fn set_move_time(
+        &mut self,
+        ply: impl Into<Ply>,
+        duration: Duration,
+    ) -> Result<&mut Self, Error> {
+        self.history.set_move_time(ply, duration)?;
+        Ok(self)
+    }
+
+    /// Attaches the graphical annotations to the move made on specified ply (see
+    /// ``GameHistory::set_annotations``)
+    ///
+    /// # Errors
+    /// ``LibChessError::WrongMoveNumber`` if the move on this ply was not made yet
+    pub fn set_annotations(
+        &mut self,
+        ply: impl Into<Ply>,
+        annotations: Annotations,
+    ) -> Result<&mut Self, Error> {
+        self.history.set_annotations(ply, annotations)?;
+        Ok(self)
+    }
+
+    /// Makes the action (see ``Game::make_move``) and records the time spent on it if it is a
+    /// move. Allows to simulate the chess clock by passing the time elapsed since the previous
+    /// move
+    ///
+    /// # Errors
+    /// The same as ``Game::make_move``
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{mv, squares::*, Action, BoardMove, Color::*, Game, PieceMove, PieceType::*};
+    /// use std::time::Duration;
+    /// let mut game = Game::default();
+    /// game.make_move_with_time(&Action::MakeMove(mv!(Pawn, E2, E4)), Duration::from_secs(3))
+    ///     .unwrap()
+    ///     .make_move_with_time(&Action::MakeMove(mv!(Pawn, E7, E5)), Duration::from_secs(7))
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     game.as_pgn().lines().last().unwrap(),
+    ///     "1.e4 { [%emt 0:00:03] } 1...e5 { [%emt 0:00:07] } ?"
+    /// );
+    /// assert_eq!(
+    ///     game.time_report().get_usage(Black).total,
+    ///     Duration::from_secs(7)
+    /// );
+    /// ```
+    pub fn make_move_with_time(
+        &mut self,
+        action: &Action,
+        elapsed: Duration,
+    ) -> Result<&mut Self, Error> {
+        self.make_move(action)?;
+        if let Action::MakeMove(_) = action {
+            let ply = self.history.get_ply_count().to_index() - 1;
+            self.history.set_move_time(ply, elapsed)?;
+        }
+        Ok(self)
+    }
+
+    /// Returns the summary of the game built from the engine analysis attached to the moves (see
+    /// ``GameReview``). The material is counted with the default valuation
+    pub fn review(&self) -> GameReview {
+        GameReview::from_history(&self.history, &Valuation::default())
+    }
+
+    /// Returns the time usage statistics of both players calculated from the recorded move
+    /// times
+    pub fn time_report(&self) -> TimeReport {
+        let mut report = TimeReport::default();
+        for ply in 0..self.history.get_ply_count().to_index() {
+            if let Some(time) = self.history.get_move_time(ply) {
+                let color = self.history.get_positions()[ply].get_side_to_move();
+                report.add(color, Ply::new(ply), time);
+            }
+        }
+        report
+    }
+
+    /// Applies the function to every position of the game history (from the starting position
+    /// to the current one) and its ply. The positions are processed in parallel if the
+    /// ``rayon`` feature is enabled. The results are returned in the order of plies
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{BoardStatus, Game};
+    /// let game = Game::from_san_moves([], &["f3", "e5", "g4", "Qh4#"]).unwrap();
+    /// let statuses = game.map_positions_parallel(|_, board| board.get_status());
+    /// assert_eq!(statuses.len(), 5);
+    /// assert_eq!(statuses[4], BoardStatus::CheckMated(libchess::Color::White));
+    ///
+    /// let legal_moves = game.map_positions_parallel(|_, board| board.count_legal_moves());
+    /// assert_eq!(legal_moves[..2], [20, 20]);
+    /// ```
+    pub fn map_positions_parallel<T, F>(&self, f: F) -> Vec<T>
+    where
+        T: Send,
+        F: Fn(Ply, &ChessBoard) -> T + Send + Sync,
+    {
+        let positions = self.history.get_positions();
+        #[cfg(feature = "rayon")]
+        let positions = positions.par_iter();
+        #[cfg(not(feature = "rayon"))]
+        let positions = positions.iter();
+
+        positions
+            .enumerate()
+            .map(|(ply, board)| f(Ply::new(ply), board))
+            .collect()
+    }
+
     /// Returns game's additional info
     #[inline]
     pub fn get_metadata(&self) -> &GameMetadata { &self.metadata }
@@ -379,6 +852,51 @@ impl Game {
     #[inline]
     pub fn get_action_history(&self) -> &GameHistory { &self.history }
 
+    /// Returns the info about the last move made in the game (None if no moves were made). Is
+    /// computed from the game history, so it is always consistent with the current position
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{squares::*, Color::*, Game, Piece, PieceType::*};
+    /// let game = Game::from_san_moves([], &["e4", "d5", "exd5"]).unwrap();
+    /// let info = game.last_move_info().unwrap();
+    /// assert_eq!((info.source, info.destination), (E4, D5));
+    /// assert_eq!(info.captured, Some(Piece(Pawn, Black)));
+    /// assert_eq!(info.color, White);
+    /// ```
+    pub fn last_move_info(&self) -> Option<LastMoveInfo> {
+        let board_move = *self.history.get_moves().last()?;
+        let positions = self.history.get_positions();
+        let (before, after) = (
+            positions[positions.len() - 2],
+            positions[positions.len() - 1],
+        );
+        let color = before.get_side_to_move();
+        let (source, destination, captured) = match board_move {
+            BoardMove::MovePiece(m) => {
+                let captured = if m.is_en_passant_move(&before) {
+                    Some(Piece(PieceType::Pawn, !color))
+                } else {
+                    before.get_piece_on(m.get_destination_square())
+                };
+                (m.get_source_square(), m.get_destination_square(), captured)
+            }
+            BoardMove::CastleKingSide | BoardMove::CastleQueenSide => (
+                before.get_king_square(color),
+                after.get_king_square(color),
+                None,
+            ),
+        };
+        Some(LastMoveInfo {
+            board_move,
+            color,
+            source,
+            destination,
+            captured,
+            changed_squares: before.get_diff_mask(&after),
+        })
+    }
+
     /// Returns the current game position mut
     #[inline]
     pub fn get_position_mut(&mut self) -> &mut ChessBoard { &mut self.position }
@@ -397,22 +915,242 @@ impl Game {
     pub fn get_side_to_move(&self) -> Color { self.get_position().get_side_to_move() }
 
     /// Returns number of times current position was arise
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidPositionCounter`` if the storage of position counters fails
     #[inline]
-    pub fn get_position_counter(&self, position: &ChessBoard) -> usize {
-        match self.unique_positions_counter.get(&position.get_hash()) {
-            Some(counter) => *counter,
-            None => 0,
+    pub fn get_position_counter(&self, position: &ChessBoard) -> Result<usize, Error> {
+        self.unique_positions_counter.get_count(position.get_hash())
+    }
+
+    /// Returns the storage of position counters
+    #[inline]
+    pub fn get_position_counters(&self) -> &C { &self.unique_positions_counter }
+
+    /// Returns the hashes of all positions of the game with the plies they arose on (the
+    /// starting position is on ply 0). The hash includes the side to move, so the positions
+    /// are counted separately for each side
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{Game, Ply};
+    /// let game = Game::from_san_moves([], &["Nf3", "Nf6", "Ng1", "Ng8"]).unwrap();
+    /// let history = game.position_history().collect::<Vec<_>>();
+    /// assert_eq!(history.len(), 5);
+    /// assert_eq!(history[0].0, history[4].0);
+    /// assert_eq!(history[4].1, Ply::new(4));
+    /// ```
+    pub fn position_history(&self) -> impl Iterator<Item = (PositionHashValueType, Ply)> + '_ {
+        self.history
+            .get_positions()
+            .iter()
+            .enumerate()
+            .map(|(ply, board)| (board.get_hash(), Ply::new(ply)))
+    }
+
+    /// Returns the snapshot of the counters of the positions which may still repeat: the
+    /// positions arisen since the last capture or pawn move (the earlier ones can't arise again).
+    /// Engines use it to seed their own repetition detection inside the search
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::Game;
+    /// let game = Game::from_san_moves([], &["e4", "Nf6", "Nf3", "Ng8", "Ng1"]).unwrap();
+    /// let snapshot = game.repetition_snapshot();
+    /// // the positions before "e4" are not included
+    /// assert_eq!(snapshot.len(), 4);
+    /// assert_eq!(snapshot[&game.get_position().get_hash()], 2);
+    /// ```
+    pub fn repetition_snapshot(&self) -> InMemoryPositionCounter {
+        let positions = self.history.get_positions();
+        let reversible_plies = self.get_position().get_moves_since_capture_or_pawn_move();
+        let mut snapshot = InMemoryPositionCounter::new();
+        for board in positions.iter().rev().take(reversible_plies + 1) {
+            *snapshot.entry(board.get_hash()).or_insert(0) += 1;
         }
+        snapshot
+    }
+
+    /// Searches the line of at most ``max_plies`` plies in which the side to move forces the
+    /// draw by the threefold repetition (counting the positions of the game history) or by the
+    /// 50-move rule against any defence. The line also finishes if the opponent chooses the
+    /// stalemate or the position with insufficient material. The shortest line is returned and
+    /// the opponent's moves in it are the most stubborn defence. Returns the empty line if the
+    /// draw may be claimed already and None if the draw can't be forced
+    ///
+    /// The search is exhaustive, so it is intended for the small numbers of plies only
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{mv, squares::*, Action, BoardMove, Game, PieceMove, PieceType::*};
+    /// let mut game = Game::default();
+    /// for m in [
+    ///     mv!(Knight, G1, F3),
+    ///     mv!(Knight, G8, F6),
+    ///     mv!(Knight, F3, G1),
+    /// ] {
+    ///     game.make_move(&Action::MakeMove(m)).unwrap();
+    /// }
+    /// assert_eq!(game.find_forced_draw(3), None);
+    /// for m in [
+    ///     mv!(Knight, F6, G8),
+    ///     mv!(Knight, G1, F3),
+    ///     mv!(Knight, G8, F6),
+    ///     mv!(Knight, F3, G1),
+    /// ] {
+    ///     game.make_move(&Action::MakeMove(m)).unwrap();
+    /// }
+    /// // the starting position arises for the third time after Ng8
+    /// assert_eq!(game.find_forced_draw(1), Some(vec![mv!(Knight, F6, G8)]));
+    /// ```
+    pub fn find_forced_draw(&self, max_plies: usize) -> Option<Vec<BoardMove>> {
+        let position = self.get_position();
+        // positions before the last capture or pawn move can't be repeated, so the snapshot
+        // counts the same repetitions as the storage of position counters
+        let snapshot = self.repetition_snapshot();
+        let mut search = DrawSearch::new(|hash| snapshot.get(&hash).copied().unwrap_or(0));
+        if search.is_draw(&position) {
+            return Some(Vec::new());
+        }
+        (1..=max_plies).find_map(|plies| search.force(&position, plies))
     }
 
     /// Returns a set of legal moves in current position. Duplicates the
-    /// functionality of the ``ChessBoard::get_legal_moves()``
+    /// functionality of the ``ChessBoard::get_legal_moves()``, but the moves are cached by the
+    /// position, so repeated requests (including returns to the previous positions) are cheap
     #[inline]
-    pub fn get_legal_moves(&self) -> LegalMoves { self.position.get_legal_moves() }
+    pub fn get_legal_moves(&self) -> LegalMoves {
+        self.legal_moves_cache
+            .lock()
+            .get_legal_moves(&self.position)
+    }
+
+    /// Sets the maximal number of positions in the legal moves cache (0 disables caching). The
+    /// oldest positions are evicted first
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::Game;
+    /// let mut game = Game::default();
+    /// game.set_legal_moves_cache_capacity(16);
+    /// game.get_legal_moves();
+    /// game.get_legal_moves();
+    ///
+    /// let statistics = game.get_legal_moves_cache_statistics();
+    /// assert_eq!((statistics.hits, statistics.misses), (1, 1));
+    /// assert_eq!((statistics.size, statistics.capacity), (1, 16));
+    /// ```
+    pub fn set_legal_moves_cache_capacity(&mut self, capacity: usize) -> &mut Self {
+        self.legal_moves_cache.get_mut().set_capacity(capacity);
+        self
+    }
+
+    /// Returns the statistics of the legal moves cache usage
+    #[inline]
+    pub fn get_legal_moves_cache_statistics(&self) -> CacheStatistics {
+        self.legal_moves_cache.lock().get_statistics()
+    }
+
+    /// Removes all positions from the legal moves cache (the statistics are kept)
+    pub fn clear_legal_moves_cache(&mut self) -> &mut Self {
+        self.legal_moves_cache.get_mut().clear();
+        self
+    }
+
+    /// Returns the move number of current position
+    #[inline]
+    pub fn get_move_number(&self) -> MoveNumber {
+        MoveNumber::from_counter(self.position.get_move_number())
+    }
+
+    /// Switches the way how the 50-move rule and the repetition rule are applied. By default
+    /// the draw is declared automatically after the 50 moves or the threefold repetition. If
+    /// claims are required (as in over-the-board tournaments) the draw must be claimed by
+    /// ``Action::ClaimFiftyMoveDraw`` or ``Action::ClaimThreefoldDraw``, and it is declared
+    /// automatically only after the 75 moves or the fivefold repetition
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::PieceType::*;
+    /// use libchess::{mv, squares::*, Action, BoardMove, Game, GameStatus, PieceMove};
+    /// let mut game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 60").unwrap();
+    /// game.set_draw_claims_required(true);
+    /// game.make_move(&Action::MakeMove(mv!(Rook, A1, A2)))
+    ///     .unwrap();
+    /// assert_eq!(game.get_game_status(), GameStatus::Ongoing);
+    ///
+    /// game.make_move(&Action::ClaimFiftyMoveDraw).unwrap();
+    /// assert_eq!(game.get_game_status(), GameStatus::FiftyMovesDrawDeclared);
+    /// ```
+    pub fn set_draw_claims_required(&mut self, value: bool) -> &mut Self {
+        self.draw_claims_required = value;
+        self
+    }
+
+    #[inline]
+    pub fn is_draw_claims_required(&self) -> bool { self.draw_claims_required }
+
+    /// Sets the policy of handling illegal move attempts (``IllegalMovePolicy::Reject`` by
+    /// default)
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{mv, squares::*, Action, BoardMove, Color::*, Game, GameStatus};
+    /// use libchess::{IllegalMovePolicy, PieceMove, PieceType::*};
+    /// let mut game = Game::default();
+    /// game.set_illegal_move_policy(IllegalMovePolicy::ForfeitAfter(2));
+    ///
+    /// let illegal = Action::MakeMove(mv!(Pawn, E2, E5));
+    /// assert!(game.make_move(&illegal).is_err());
+    /// assert_eq!(game.get_illegal_move_strikes(White), 1);
+    /// assert!(game.make_move(&illegal).is_err());
+    /// assert_eq!(game.get_game_status(), GameStatus::Forfeited(White));
+    /// ```
+    pub fn set_illegal_move_policy(&mut self, policy: IllegalMovePolicy) -> &mut Self {
+        self.illegal_move_policy = policy;
+        self
+    }
+
+    #[inline]
+    pub fn get_illegal_move_policy(&self) -> IllegalMovePolicy { self.illegal_move_policy }
+
+    /// Returns all recorded illegal move attempts in order they were made
+    #[inline]
+    pub fn get_illegal_move_attempts(&self) -> &[IllegalMoveAttempt] { &self.illegal_move_attempts }
+
+    /// Returns the number of recorded illegal move attempts of the player
+    pub fn get_illegal_move_strikes(&self, color: Color) -> usize {
+        self.illegal_move_attempts
+            .iter()
+            .filter(|attempt| attempt.color == color)
+            .count()
+    }
+
+    /// Records the illegal move attempt of the side to move according to the policy and returns
+    /// the error which should be reported to the player
+    fn record_illegal_move(&mut self, board_move: BoardMove) -> Error {
+        if self.illegal_move_policy == IllegalMovePolicy::Reject {
+            return Error::IllegalActionDetected;
+        }
+
+        let color = self.get_side_to_move();
+        let strikes = self.get_illegal_move_strikes(color) + 1;
+        self.illegal_move_attempts.push(IllegalMoveAttempt {
+            color,
+            board_move,
+            ply: self.history.get_ply_count(),
+            strike: strikes,
+        });
+        if self.illegal_move_policy.is_forfeit(strikes) {
+            self.forfeit(color);
+        }
+        Error::IllegalMoveStrike { color, strikes }
+    }
 
-    /// Returns a number of moves done since the first board was created
-    #[inline]
-    pub fn get_move_number(&self) -> usize { self.position.get_move_number() }
+    /// Finishes the game with the loss of specified player by forfeit
+    pub(crate) fn forfeit(&mut self, color: Color) -> &mut Self {
+        self.set_game_status(GameStatus::Forfeited(color))
+    }
 
     /// Returns a number of moves since last capture or pawn move (is used
     /// to determine the game termination by the 50-move rule)
@@ -421,6 +1159,20 @@ impl Game {
         self.position.get_moves_since_capture_or_pawn_move()
     }
 
+    /// Finishes the imported game which was lost by specified player not on the board (see
+    /// ``get_decisive_status``)
+    fn finish_decisive(
+        &mut self,
+        loser: Color,
+        comments: &[String],
+        termination: Option<Termination>,
+    ) -> &mut Self {
+        match get_decisive_status(loser, comments, termination) {
+            GameStatus::Forfeited(color) => self.forfeit(color),
+            _ => self.make_move(&Action::Resign(loser)).unwrap(),
+        }
+    }
+
     #[inline]
     fn set_game_status(&mut self, status: GameStatus) -> &mut Self {
         use {Color::*, GameStatus::*};
@@ -430,7 +1182,7 @@ impl Game {
                 "Result".to_string(),
                 match status {
                     Ongoing | DrawOffered(_) => "?".to_string(),
-                    CheckMated(color) | Resigned(color) => match color {
+                    CheckMated(color) | Resigned(color) | Forfeited(color) => match color {
                         White => "0-1".to_string(),
                         Black => "1-0".to_string(),
                     },
@@ -448,32 +1200,40 @@ impl Game {
     }
 
     #[inline]
-    fn position_counter_increment(&mut self) -> &mut Self {
-        self.unique_positions_counter.insert(
-            self.get_position().get_hash(),
-            self.get_position_counter(&self.get_position()) + 1,
-        );
-        self
+    fn position_counter_increment(&mut self, position: &ChessBoard) -> Result<&mut Self, Error> {
+        self.unique_positions_counter
+            .increment(position.get_hash())?;
+        Ok(self)
     }
 
-    fn update_game_status(&mut self, last_action: Option<&Action>) -> &mut Self {
+    fn update_game_status(&mut self, last_action: Option<&Action>) -> Result<&mut Self, Error> {
         self.set_game_status(match last_action {
             None | Some(Action::MakeMove(_)) => {
                 let position = self.get_position();
-                match position.get_status() {
-                    BoardStatus::CheckMated(c) => GameStatus::CheckMated(c),
-                    BoardStatus::TheoreticalDrawDeclared => GameStatus::TheoreticalDrawDeclared,
-                    BoardStatus::Stalemate => GameStatus::Stalemate,
-                    BoardStatus::FiftyMovesDrawDeclared => GameStatus::FiftyMovesDrawDeclared,
-                    BoardStatus::Ongoing => {
-                        if self.get_position_counter(&position) >= 3 {
+                match GameStatus::from(position.get_status()) {
+                    GameStatus::FiftyMovesDrawDeclared
+                        if !self.draw_claims_required
+                            | (position.get_moves_since_capture_or_pawn_move()
+                                >= SEVENTY_FIVE_MOVES_RULE_PLIES) =>
+                    {
+                        GameStatus::FiftyMovesDrawDeclared
+                    }
+                    GameStatus::Ongoing | GameStatus::FiftyMovesDrawDeclared => {
+                        let repetitions = match self.draw_claims_required {
+                            true => FIVEFOLD_REPETITION,
+                            false => THREEFOLD_REPETITION,
+                        };
+                        if self.get_position_counter(&position)? >= repetitions {
                             GameStatus::RepetitionDrawDeclared
                         } else {
                             GameStatus::Ongoing
                         }
                     }
+                    status => status,
                 }
             }
+            Some(Action::ClaimFiftyMoveDraw) => GameStatus::FiftyMovesDrawDeclared,
+            Some(Action::ClaimThreefoldDraw) => GameStatus::RepetitionDrawDeclared,
             Some(Action::OfferDraw(color)) => GameStatus::DrawOffered(*color),
             Some(Action::DeclineDraw) => GameStatus::Ongoing,
             Some(Action::AcceptDraw) => GameStatus::DrawAccepted,
@@ -484,7 +1244,26 @@ impl Game {
             println!("{}", self.get_game_status())
         }
 
-        self
+        Ok(self)
+    }
+
+    /// Checks that the draw claim (if the action is a claim) is confirmed by the position and
+    /// the history of the game
+    fn verify_draw_claim(&self, action: &Action) -> Result<(), Error> {
+        let position = self.get_position();
+        match action {
+            Action::ClaimFiftyMoveDraw
+                if position.get_moves_since_capture_or_pawn_move() < FIFTY_MOVES_RULE_PLIES =>
+            {
+                Err(Error::InvalidFiftyMoveDrawClaim)
+            }
+            Action::ClaimThreefoldDraw
+                if self.get_position_counter(&position)? < THREEFOLD_REPETITION =>
+            {
+                Err(Error::InvalidThreefoldDrawClaim)
+            }
+            _ => Ok(()),
+        }
     }
 
     /// This method is used to make moves during the game
@@ -499,6 +1278,14 @@ impl Game {
     /// ``errors::LibChessError::GameIsAlreadyFinished`` in case if player tries to make any action
     /// after the fame was ended
     ///
+    /// ``errors::LibChessError::IllegalMoveStrike`` instead of ``IllegalActionDetected`` for
+    /// illegal moves if the illegal move policy other than ``IllegalMovePolicy::Reject`` is set.
+    /// The attempt is recorded and the game may be finished by forfeit
+    ///
+    /// ``errors::LibChessError::InvalidFiftyMoveDrawClaim`` and
+    /// ``errors::LibChessError::InvalidThreefoldDrawClaim`` if the draw claim is not confirmed by
+    /// the position and the history of the game
+    ///
     /// # Examples
     /// ```
     /// use libchess::{mv, Action, BoardMove, Game, PieceMove};
@@ -509,14 +1296,20 @@ impl Game {
     /// ```
     pub fn make_move(&mut self, action: &Action) -> Result<&mut Self, Error> {
         use Action::*;
+        if self.get_game_status().is_ongoing_or_draw_offered() {
+            self.verify_draw_claim(action)?;
+        }
+
         match self.get_game_status() {
             GameStatus::Ongoing => match &action {
-                MakeMove(m) => match self.get_position_mut().make_move_mut(m) {
-                    Ok(_) => {
-                        self.position_counter_increment();
-                        self.history.push(*m, self.position);
+                MakeMove(m) => match self.get_position().make_move(m) {
+                    Ok(position) => {
+                        let m = self.get_position().normalize_castling(m);
+                        self.position_counter_increment(&position)?;
+                        self.position = position;
+                        self.history.push(m, position);
                     }
-                    Err(_) => return Err(Error::IllegalActionDetected),
+                    Err(_) => return Err(self.record_illegal_move(*m)),
                 },
                 AcceptDraw | DeclineDraw => return Err(Error::IllegalActionDetected),
                 _ => {}
@@ -528,8 +1321,172 @@ impl Game {
             _ => return Err(Error::GameIsAlreadyFinished),
         }
 
-        self.update_game_status(Some(action));
-        Ok(self)
+        self.update_game_status(Some(action))
+    }
+
+    /// Makes the move after checking that it is of the kind the client expects (see
+    /// ``ChessBoard::make_move_with_intent``). The mismatch is not counted as an illegal move
+    /// attempt since it means that the client's position is out of date. Illegal moves are
+    /// handled by ``Game::make_move`` whatever the intent is
+    ///
+    /// # Errors
+    /// ``LibChessError::IntentMismatch`` if the move is of the other kind in the current position
+    ///
+    /// The same errors as ``Game::make_move``
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{mv, squares::*, BoardMove, Game, MoveIntent, PieceMove, PieceType::*};
+    /// let mut game = Game::from_san_moves([], &["e4", "d5"]).unwrap();
+    /// assert!(game
+    ///     .make_move_with_intent(&mv!(Pawn, E4, D5), MoveIntent::Normal)
+    ///     .is_err());
+    /// assert!(game
+    ///     .make_move_with_intent(&mv!(Pawn, E4, D5), MoveIntent::Capture)
+    ///     .is_ok());
+    /// ```
+    pub fn make_move_with_intent(
+        &mut self,
+        board_move: &BoardMove,
+        intent: MoveIntent,
+    ) -> Result<&mut Self, Error> {
+        board_move.check_intent(&self.get_position(), intent)?;
+        self.make_move(&Action::MakeMove(*board_move))
+    }
+}
+
+/// Parses the illegal move policy saved by ``Game::to_saved_string`` ("Warn", "ForfeitAfter 3")
+fn parse_illegal_move_policy(value: &str) -> Result<IllegalMovePolicy, Error> {
+    match value.split_once(' ') {
+        None if value == "Reject" => Ok(IllegalMovePolicy::Reject),
+        None if value == "Warn" => Ok(IllegalMovePolicy::Warn),
+        Some(("ForfeitAfter", limit)) => limit
+            .parse()
+            .map(IllegalMovePolicy::ForfeitAfter)
+            .map_err(|_| Error::InvalidPGNString),
+        _ => Err(Error::InvalidPGNString),
+    }
+}
+
+/// Escapes the quotes and backslashes of the PGN tag value
+fn escape_tag_value(value: &str) -> String { value.replace('\\', "\\\\").replace('"', "\\\"") }
+
+/// Restores the PGN tag value with escaped quotes and backslashes
+fn unescape_tag_value(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => result.extend(chars.next()),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// Pattern of the commands of the PGN comments which are kept in the main line
+const PGN_COMMANDS_PATTERN: &str = r"\[%(emt|cal|csl)\s+[^\]]+\]";
+
+/// Removes comments (including the rest-of-line ";" ones) and variations from PGN movetext, so
+/// only the main line remains. The ``[%emt ...]``, ``[%cal ...]`` and ``[%csl ...]`` commands of
+/// the main line comments are kept. The texts of the main line comments are returned separately
+fn strip_pgn_annotations(movetext: &str) -> Result<(String, Vec<String>), Error> {
+    let commands_pattern = Regex::new(PGN_COMMANDS_PATTERN).expect("Invalid regex");
+    let mut result = String::with_capacity(movetext.len());
+    let (mut variation_depth, mut is_comment, mut is_line_comment) = (0usize, false, false);
+    let mut comment = String::new();
+    let mut comments = Vec::new();
+
+    for c in movetext.chars() {
+        match c {
+            '\n' if is_line_comment => {
+                is_line_comment = false;
+                if variation_depth == 0 {
+                    comments.push(comment.trim().to_string());
+                }
+                comment.clear();
+            }
+            '{' if !(is_comment | is_line_comment) => is_comment = true,
+            ';' if !(is_comment | is_line_comment) => is_line_comment = true,
+            '}' if is_comment => {
+                is_comment = false;
+                if variation_depth == 0 {
+                    for command in commands_pattern.find_iter(&comment) {
+                        result = format!("{result} {} ", command.as_str());
+                    }
+                    comments.push(comment.trim().to_string());
+                }
+                comment.clear();
+                // the comment separates the tokens around it ("Nc6{comment}Bb5")
+                result.push(' ');
+            }
+            _ if is_comment | is_line_comment => comment.push(c),
+            '(' => variation_depth += 1,
+            ')' => {
+                variation_depth = variation_depth
+                    .checked_sub(1)
+                    .ok_or(Error::InvalidPGNString)?;
+                result.push(' ');
+            }
+            _ if variation_depth > 0 => {}
+            _ => result.push(c),
+        }
+    }
+    if is_line_comment & (variation_depth == 0) {
+        comments.push(comment.trim().to_string());
+    }
+
+    if is_comment | (variation_depth > 0) {
+        return Err(Error::InvalidPGNString);
+    }
+    Ok((result, comments))
+}
+
+/// Removes comments, variations and the commands of comments from PGN movetext, so only the
+/// moves, the move numbers, the NAGs and the result of the main line remain
+pub(crate) fn strip_pgn_comments(movetext: &str) -> Result<String, Error> {
+    let (result, _) = strip_pgn_annotations(movetext)?;
+    Ok(Regex::new(PGN_COMMANDS_PATTERN)
+        .expect("Invalid regex")
+        .replace_all(&result, " ")
+        .into_owned())
+}
+
+/// Returns the status of the decisive game which was not finished on the board. The way the
+/// game was lost is taken from the main line comments ("White resigns", "Black forfeits on
+/// time", etc.) or from the "Termination" tag. Resignation is assumed by default
+fn get_decisive_status(
+    loser: Color,
+    comments: &[String],
+    termination: Option<Termination>,
+) -> GameStatus {
+    let pattern = Regex::new(
+        r"(?ix)\b(?P<color>white|black)\s+
+        (?P<action>resign(s|ed)|forfeit(s|ed)|(loses|lost)\s+on\s+time|flagged
+        |(wins|won)\s+(on\s+time|by\s+forfeit))\b",
+    )
+    .expect("Invalid regex");
+
+    for comment in comments.iter().rev() {
+        for cap in pattern.captures_iter(comment) {
+            let color = Color::from_str(&cap["color"]).unwrap();
+            let action = cap["action"].to_lowercase();
+            let is_winner = action.starts_with("w");
+            if (color == loser) == is_winner {
+                continue; // contradicts the result of the game
+            }
+            return match action.starts_with("resign") {
+                true => GameStatus::Resigned(loser),
+                false => GameStatus::Forfeited(loser),
+            };
+        }
+    }
+
+    match termination {
+        Some(Termination::TimeForfeit | Termination::Abandoned | Termination::RulesInfraction) => {
+            GameStatus::Forfeited(loser)
+        }
+        _ => GameStatus::Resigned(loser),
     }
 }
 
@@ -541,6 +1498,195 @@ mod tests {
     use crate::{squares::*, Color::*, PieceType::*};
     use std::fs;
 
+    #[test]
+    fn pgn_move_times() {
+        let pgn = "[Event \"?\"]\n\n1. e4 {[%emt 0:00:05]} e5 {Solid [%emt 0:00:12] reply} \
+                   2. Nf3 (2. f4 {[%emt 0:09:00]}) Nc6 {[%emt 0:01:03.5]} 1/2-1/2";
+        let game = Game::from_pgn(pgn).unwrap();
+        let history = game.get_action_history();
+        assert_eq!(history.get_move_time(0), Some(Duration::from_secs(5)));
+        assert_eq!(history.get_move_time(1), Some(Duration::from_secs(12)));
+        assert_eq!(history.get_move_time(2), None);
+        assert_eq!(
+            history.get_move_time(3),
+            Some(Duration::from_millis(63_500))
+        );
+
+        let report = game.time_report();
+        assert_eq!(report.get_usage(White).timed_moves, 1);
+        assert_eq!(report.get_usage(Black).total, Duration::from_millis(75_500));
+        assert_eq!(
+            report.get_usage(Black).longest,
+            Some((Ply::new(3), Duration::from_millis(63_500)))
+        );
+
+        let restored = Game::from_pgn(&game.as_pgn()).unwrap();
+        assert_eq!(restored.as_pgn(), game.as_pgn());
+        assert_eq!(restored.time_report(), report);
+
+        let mut game = Game::default();
+        assert!(game.set_move_time(0, Duration::from_secs(1)).is_err());
+        assert!(Game::from_pgn("[Event \"?\"]\n\n{[%emt 0:00:05]} 1. e4 1-0").is_err());
+    }
+
+    #[test]
+    fn pgn_annotations() {
+        use AnnotationColor::*;
+        let pgn =
+            "[Event \"?\"]\n\n1. e4 {[%cal Gg1f3,Rd7d5]} e5 {Центр [%csl Ye5][%emt 0:00:02]} \
+                   2. Nf3 (2. f4 {[%csl Rf4]}) Nc6 1-0";
+        let game = Game::from_pgn(pgn).unwrap();
+        let history = game.get_action_history();
+        let annotations = history.get_annotations(0).unwrap();
+        assert_eq!(
+            annotations.get_arrows(),
+            &vec![
+                Arrow {
+                    source:      G1,
+                    destination: F3,
+                    color:       Green,
+                },
+                Arrow {
+                    source:      D7,
+                    destination: D5,
+                    color:       Red,
+                },
+            ]
+        );
+        assert_eq!(
+            history.get_annotations(1).unwrap().get_highlight(E5),
+            Some(Yellow)
+        );
+        assert_eq!(history.get_move_time(1), Some(Duration::from_secs(2)));
+        assert!(history.get_annotations(2).is_none());
+
+        let restored = Game::from_pgn(&game.as_pgn()).unwrap();
+        assert_eq!(restored.as_pgn(), game.as_pgn());
+        assert!(game
+            .as_pgn()
+            .contains("1...e5 { [%emt 0:00:02] [%csl Ye5] } 2.Nf3"));
+
+        let mut game = Game::default();
+        assert!(game.set_annotations(0, Annotations::new()).is_err());
+        assert!(Game::from_pgn("[Event \"?\"]\n\n1. e4 {[%cal Xe2e4]} 1-0").is_err());
+    }
+
+    #[test]
+    fn illegal_move_arbitration() {
+        let illegal = Action::MakeMove(mv!(Knight, G1, G3));
+
+        let mut game = Game::default();
+        assert!(matches!(
+            game.make_move(&illegal),
+            Err(Error::IllegalActionDetected)
+        ));
+        assert!(game.get_illegal_move_attempts().is_empty());
+
+        game.set_illegal_move_policy(IllegalMovePolicy::Warn);
+        for strike in 1..=3 {
+            assert!(matches!(
+                game.make_move(&illegal),
+                Err(Error::IllegalMoveStrike { color: White, strikes }) if strikes == strike
+            ));
+        }
+        assert_eq!(game.get_game_status(), GameStatus::Ongoing);
+        // the illegal move is counted even if the intent doesn't match
+        assert!(matches!(
+            game.make_move_with_intent(&mv!(Knight, G1, G3), MoveIntent::Capture),
+            Err(Error::IllegalMoveStrike {
+                color:   White,
+                strikes: 4,
+            })
+        ));
+
+        game.set_illegal_move_policy(IllegalMovePolicy::ForfeitAfter(2));
+        game.make_move(&Action::MakeMove(mv!(Pawn, E2, E4)))
+            .unwrap();
+        assert!(game
+            .make_move(&Action::MakeMove(mv!(Pawn, E7, E4)))
+            .is_err());
+        assert_eq!(game.get_illegal_move_strikes(White), 4);
+        assert_eq!(game.get_illegal_move_strikes(Black), 1);
+        assert_eq!(
+            game.get_illegal_move_attempts().last().unwrap(),
+            &IllegalMoveAttempt {
+                color:      Black,
+                board_move: mv!(Pawn, E7, E4),
+                ply:        Ply::new(1),
+                strike:     1,
+            }
+        );
+
+        assert!(game
+            .make_move(&Action::MakeMove(mv!(Pawn, E7, E4)))
+            .is_err());
+        assert_eq!(game.get_game_status(), GameStatus::Forfeited(Black));
+        assert_eq!(
+            game.get_metadata().get_value("Result".to_string()).unwrap(),
+            "1-0"
+        );
+        assert!(matches!(
+            game.make_move(&Action::MakeMove(mv!(Pawn, E7, E5))),
+            Err(Error::GameIsAlreadyFinished)
+        ));
+    }
+
+    #[test]
+    fn last_move_info() {
+        assert_eq!(Game::default().last_move_info(), None);
+
+        let game = Game::from_san_moves([], &["e4", "Nf6", "e5", "d5", "exd6"]).unwrap();
+        let info = game.last_move_info().unwrap();
+        assert_eq!((info.source, info.destination), (E5, D6));
+        assert_eq!(info.captured, Some(Piece(Pawn, Color::Black)));
+        assert_eq!(
+            info.changed_squares,
+            BitBoard::from_square(E5) | BitBoard::from_square(D6) | BitBoard::from_square(D5)
+        );
+
+        let game =
+            Game::from_san_moves([], &["e4", "e5", "Nf3", "Nc6", "Bc4", "Bc5", "O-O"]).unwrap();
+        let info = game.last_move_info().unwrap();
+        assert_eq!(info.board_move, castle_king_side!());
+        assert_eq!(
+            (info.color, info.source, info.destination),
+            (Color::White, E1, G1)
+        );
+        assert_eq!(info.captured, None);
+        assert_eq!(info.changed_squares.count_ones(), 4);
+    }
+
+    #[test]
+    fn from_san_moves() {
+        let tags = [
+            ("Event".to_string(), "Test".to_string()),
+            (
+                "FEN".to_string(),
+                "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1".to_string(),
+            ),
+        ];
+        let game = Game::from_san_moves(tags.clone(), &["e4", "Kd7", "e5"]).unwrap();
+        assert_eq!(game.as_fen(), "8/3k4/8/4P3/8/8/8/4K3 b - - 0 2");
+        assert_eq!(game.get_action_history().get_ply_count(), Ply::new(3));
+        assert_eq!(
+            game.get_metadata().get_value("Event".to_string()),
+            Some(&"Test".to_string())
+        );
+
+        match Game::from_san_moves(tags, &["e4", "Kd7", "e6"]) {
+            Err(Error::InvalidMoveInLine { token, ply }) => {
+                assert_eq!(token, "e6");
+                assert_eq!(ply, 2);
+            }
+            _ => panic!("the move must be rejected"),
+        }
+
+        // moves after the checkmate are rejected
+        let moves = ["f3", "e5", "g4", "Qh4#", "a3"];
+        assert!(Game::from_san_moves([], &moves[..4]).is_ok());
+        assert!(Game::from_san_moves([], &moves).is_err());
+    }
+
     #[test]
     fn as_fen() {
         let game_fen = "rnbq1bnr/pppkpppp/8/3p4/4P3/5N2/PPPP1PPP/RNBQKB1R w KQ - 2 3";
@@ -608,6 +1754,63 @@ mod tests {
         assert_eq!(game.get_game_status(), GameStatus::RepetitionDrawDeclared);
     }
 
+    #[test]
+    fn draw_declaration_after_uncapturable_en_passant() {
+        // the first occurrence of the position has an en-passant square which can't be captured
+        let mut game = Game::default();
+        let moves = vec![
+            mv!(Pawn, E2, E4),
+            mv!(Knight, G8, F6),
+            mv!(Knight, G1, F3),
+            mv!(Knight, F6, G8),
+            mv!(Knight, F3, G1),
+            mv!(Knight, G8, F6),
+            mv!(Knight, G1, F3),
+            mv!(Knight, F6, G8),
+            mv!(Knight, F3, G1),
+        ];
+        for one in moves.into_iter() {
+            game.make_move(&Action::MakeMove(one)).unwrap();
+        }
+        assert_eq!(game.get_game_status(), GameStatus::RepetitionDrawDeclared);
+    }
+
+    #[test]
+    fn draw_claims() {
+        let moves = [
+            mv!(King, E3, D3),
+            mv!(King, E5, D5),
+            mv!(King, D3, E3),
+            mv!(King, D5, E5),
+        ];
+
+        let mut game = Game::from_fen("8/8/8/p3k3/P7/4K3/8/8 w - - 0 1").unwrap();
+        game.set_draw_claims_required(true);
+        assert!(matches!(
+            game.make_move(&Action::ClaimThreefoldDraw),
+            Err(Error::InvalidThreefoldDrawClaim)
+        ));
+        assert!(matches!(
+            game.make_move(&Action::ClaimFiftyMoveDraw),
+            Err(Error::InvalidFiftyMoveDrawClaim)
+        ));
+
+        for one in moves.iter().chain(moves.iter()) {
+            game.make_move(&Action::MakeMove(*one)).unwrap();
+        }
+        assert_eq!(game.get_game_status(), GameStatus::Ongoing);
+        game.make_move(&Action::ClaimThreefoldDraw).unwrap();
+        assert_eq!(game.get_game_status(), GameStatus::RepetitionDrawDeclared);
+
+        // the fivefold repetition is declared without a claim
+        let mut game = Game::from_fen("8/8/8/p3k3/P7/4K3/8/8 w - - 0 1").unwrap();
+        game.set_draw_claims_required(true);
+        for one in moves.iter().cycle().take(16) {
+            game.make_move(&Action::MakeMove(*one)).unwrap();
+        }
+        assert_eq!(game.get_game_status(), GameStatus::RepetitionDrawDeclared);
+    }
+
     #[test]
     fn resignation() {
         let mut game = Game::default();
@@ -802,6 +2005,474 @@ mod tests {
         assert_eq!(read_game.get_position(), game.get_position());
     }
 
+    #[test]
+    fn localized_pgn_read() {
+        let pgn = "[Event \"Партия\"]\n\n1. e4 e5 2. Кf3 Кc6 3. Сb5 Кf6 4. O-O Кxe4 5. Лe1 Кd6 \
+                   6. Кxe5 Сe7 7. Сf1 Кxe5 8. Лxe5 O-O 9. d4 Сf6 10. Лe1 Лe8 11. c3 Лxe1 \
+                   12. Фxe1 Кf5 13. Сf4 d6 14. Кd2 c6 15. Крh1 1/2-1/2";
+        let game = Game::from_localized_pgn(pgn, &NotationLocale::russian()).unwrap();
+        assert_eq!(game.get_action_history().get_ply_count(), Ply::new(29));
+        assert_eq!(
+            game.get_metadata().get_value("Event".to_string()).unwrap(),
+            "Партия"
+        );
+
+        let english_pgn = game.as_pgn();
+        let restored = Game::from_pgn(&english_pgn).unwrap();
+        assert_eq!(restored.get_position(), game.get_position());
+
+        let pgn = "[Event \"?\"]\n\n1. e4 e5 2. ♘f3 ♞c6 3. ♗b5 1-0";
+        let game = Game::from_localized_pgn(pgn, &NotationLocale::figurine()).unwrap();
+        assert_eq!(game.get_action_history().get_ply_count(), Ply::new(5));
+        assert!(Game::from_pgn(pgn).is_err());
+
+        // English letters are accepted, the unknown ones are not skipped as a part of the move
+        let pgn = "[Event \"Partie\"]\n\n1. e4 e5 2. Nf3 Sc6 1-0";
+        let game = Game::from_localized_pgn(pgn, &NotationLocale::german()).unwrap();
+        assert_eq!(game.get_position().get_piece_type_on(F3), Some(Knight));
+        let pgn = "[Event \"Партия\"]\n\n1. e4 e5 2. Nf3 Кc6 3. d4 exd4 1-0";
+        assert!(Game::from_localized_pgn(pgn, &NotationLocale::russian()).is_ok());
+        let pgn = "[Event \"Partie\"]\n\n1. e4 e5 2. Xf3 Sc6 1-0";
+        assert!(Game::from_localized_pgn(pgn, &NotationLocale::german()).is_err());
+    }
+
+    #[test]
+    fn pgn_termination_comments() {
+        let pgn = "[Event \"?\"]\n\n1. e4 e5 2. Nf3 { White resigns } 0-1";
+        assert_eq!(
+            Game::from_pgn(pgn).unwrap().get_game_status(),
+            GameStatus::Resigned(Color::White)
+        );
+
+        let pgn = "[Event \"?\"]\n\n1. e4 e5 2. Nf3 { Black forfeits on time } 1-0";
+        assert_eq!(
+            Game::from_pgn(pgn).unwrap().get_game_status(),
+            GameStatus::Forfeited(Color::Black)
+        );
+
+        let pgn = "[Event \"?\"]\n\n1. e4 e5 2. Nf3 { White wins on time } 1-0";
+        assert_eq!(
+            Game::from_pgn(pgn).unwrap().get_game_status(),
+            GameStatus::Forfeited(Color::Black)
+        );
+
+        // the comments in variations and the comments contradicting the result are ignored
+        let pgn = "[Event \"?\"]\n\n1. e4 e5 (1... c5 { White resigns }) 2. Nf3 \
+                   { Black resigns } 0-1";
+        assert_eq!(
+            Game::from_pgn(pgn).unwrap().get_game_status(),
+            GameStatus::Resigned(Color::White)
+        );
+
+        let pgn = "[Event \"?\"]\n[Termination \"time forfeit\"]\n\n1. e4 e5 0-1";
+        let game = Game::from_pgn(pgn).unwrap();
+        assert_eq!(game.get_game_status(), GameStatus::Forfeited(Color::White));
+        assert_eq!(
+            game.get_metadata().get_value("Result".to_string()),
+            Some(&"0-1".to_string())
+        );
+
+        let pgn = "[Event \"?\"]\n[Termination \"normal\"]\n\n1. e4 e5 0-1";
+        assert_eq!(
+            Game::from_pgn(pgn).unwrap().get_game_status(),
+            GameStatus::Resigned(Color::White)
+        );
+    }
+
+    #[test]
+    fn pgn_setup_numbering() {
+        let fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 20";
+        let pgn =
+            format!("[Event \"?\"]\n[SetUp \"1\"]\n[FEN \"{fen}\"]\n\n20... e5 21. Nf3 Nc6 1-0");
+        let game = Game::from_pgn(&pgn).unwrap();
+        let history = game.get_action_history();
+        assert_eq!(history.get_starting_position().as_fen(), fen);
+        assert_eq!(
+            history.get_move_number_on_ply(0).unwrap(),
+            MoveNumber::new(20)
+        );
+        assert_eq!(
+            history.get_move_number_on_ply(1).unwrap(),
+            MoveNumber::new(21)
+        );
+        assert_eq!(
+            history.to_movetext(MoveNumbering::FromPosition, None),
+            "20...e5 21.Nf3 Nc6 "
+        );
+        assert_eq!(
+            history.to_movetext(MoveNumbering::FromOne, None),
+            "1...e5 2.Nf3 Nc6 "
+        );
+        assert!(game.as_pgn().ends_with("\n\n20...e5 21.Nf3 Nc6 1-0"));
+
+        let restored = Game::from_pgn(&game.as_pgn()).unwrap();
+        assert_eq!(
+            restored.get_action_history().get_starting_position(),
+            history.get_starting_position()
+        );
+
+        // the starting position with other move counters is set up by the FEN tag
+        let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 4 3";
+        let pgn = format!("[Event \"?\"]\n[SetUp \"1\"]\n[FEN \"{fen}\"]\n\n3. e4 1-0");
+        let game = Game::from_pgn(&pgn).unwrap();
+        assert!(game.as_pgn().contains(&format!("[FEN \"{fen}\"]")));
+        let restored = Game::from_pgn(&game.as_pgn()).unwrap();
+        assert_eq!(
+            restored
+                .get_action_history()
+                .get_starting_position()
+                .as_fen(),
+            fen
+        );
+        assert!(!Game::default().as_pgn().contains("[FEN "));
+
+        // the fullmove counter 0 is accepted in FEN and is treated as the first move
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 0").unwrap();
+        assert_eq!(game.get_move_number(), MoveNumber::new(1));
+        game.make_move(&Action::MakeMove(mv!(King, E1, E2)))
+            .unwrap();
+        game.make_move(&Action::MakeMove(mv!(King, E8, E7)))
+            .unwrap();
+        game.make_move(&Action::MakeMove(mv!(Rook, A1, A7)))
+            .unwrap();
+        let history = game.get_action_history();
+        assert_eq!(
+            history.get_move_number_on_ply(0).unwrap(),
+            MoveNumber::new(1)
+        );
+        assert_eq!(
+            history.to_movetext(MoveNumbering::FromPosition, None),
+            "1.Ke2 Ke7 2.Ra7+ "
+        );
+        assert!(history.get_starting_position().as_fen().ends_with(" 0 1"));
+    }
+
+    #[test]
+    fn shared_legal_moves_cache() {
+        fn assert_sync<T: Send + Sync>() {}
+        assert_sync::<Game>();
+
+        let game = Game::default();
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| assert_eq!(game.get_legal_moves().len(), 20));
+            }
+        });
+        let statistics = game.get_legal_moves_cache_statistics();
+        assert_eq!((statistics.hits, statistics.misses), (3, 1));
+        assert_eq!(game.clone().get_legal_moves_cache_statistics(), statistics);
+    }
+
+    #[test]
+    fn pgn_promotion_spellings() {
+        let header =
+            "[Event \"?\"]\n[SetUp \"1\"]\n[FEN \"2r5/1P6/3k4/8/8/8/8/K6R w - - 0 1\"]\n\n";
+        for movetext in [
+            "1. bxc8=N+ Ke5 1-0",
+            "1. bxc8N+ Ke5 1-0",
+            "1. bxc8=n+ Ke5 1-0",
+            "1. bxc8n Ke5 1-0",
+        ] {
+            let game = Game::from_pgn(&format!("{header}{movetext}")).unwrap();
+            assert_eq!(
+                game.get_action_history().get_moves()[0],
+                mv!(Pawn, B7, C8, Knight),
+                "{movetext}"
+            );
+            assert!(game.as_pgn().contains("1.bxc8=N+ Ke5"), "{movetext}");
+        }
+
+        let game = Game::from_pgn(&format!("{header}1. b8Q+ Kc5 1-0")).unwrap();
+        assert_eq!(
+            game.get_action_history().get_moves()[0],
+            mv!(Pawn, B7, B8, Queen)
+        );
+        assert!(game.as_pgn().contains("1.b8=Q+ Kc5"));
+    }
+
+    #[test]
+    fn saving_games() {
+        let mut game = Game::from_san_moves([], &["e4", "e5", "Nf3"]).unwrap();
+        game.set_move_time(0, Duration::from_secs(5))
+            .unwrap()
+            .set_draw_claims_required(true)
+            .make_move(&Action::OfferDraw(Color::Black))
+            .unwrap();
+
+        let path = std::env::temp_dir().join("libchess_saving_games.pgn");
+        game.save(&path).unwrap();
+        let restored = Game::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            restored.get_game_status(),
+            GameStatus::DrawOffered(Color::Black)
+        );
+        assert_eq!(restored.get_position(), game.get_position());
+        assert!(restored.is_draw_claims_required());
+        assert_eq!(
+            restored.get_action_history().get_move_time(0),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            restored.get_metadata().get_value("DrawOffer".to_string()),
+            None
+        );
+        assert_eq!(restored.as_pgn(), game.as_pgn());
+
+        // the unfinished games can't be read from plain PGN
+        assert!(Game::from_pgn(&game.as_pgn()).is_err());
+        assert!(Game::from_saved_string(&game.as_pgn()).is_ok());
+
+        let finished = Game::from_san_moves([], &["f3", "e5", "g4", "Qh4#"]).unwrap();
+        let restored = Game::from_saved_string(&finished.to_saved_string()).unwrap();
+        assert_eq!(
+            restored.get_game_status(),
+            GameStatus::CheckMated(Color::White)
+        );
+
+        let invalid = game.to_saved_string().replace("\"Black\"]", "\"Nobody\"]");
+        assert!(Game::from_saved_string(&invalid).is_err());
+
+        // the illegal move policy and attempts
+        let mut game = Game::from_san_moves([], &["e4"]).unwrap();
+        game.set_illegal_move_policy(IllegalMovePolicy::ForfeitAfter(2));
+        assert!(game
+            .make_move(&Action::MakeMove(mv!(Pawn, E7, E4)))
+            .is_err());
+        let saved = game.to_saved_string();
+        assert!(saved.contains("[IllegalMovePolicy \"ForfeitAfter 2\"]"));
+        let restored = Game::from_saved_string(&saved).unwrap();
+        assert_eq!(
+            restored.get_illegal_move_policy(),
+            game.get_illegal_move_policy()
+        );
+        assert_eq!(
+            restored.get_illegal_move_attempts(),
+            game.get_illegal_move_attempts()
+        );
+        let mut restored = restored;
+        assert!(restored
+            .make_move(&Action::MakeMove(mv!(Pawn, E7, E4)))
+            .is_err());
+        assert_eq!(
+            restored.get_game_status(),
+            GameStatus::Forfeited(Color::Black)
+        );
+        let invalid = saved.replace("ForfeitAfter 2", "ForfeitAfter two");
+        assert!(Game::from_saved_string(&invalid).is_err());
+
+        // the starting position with other move counters is kept
+        let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 4 3";
+        let game = Game::from_fen(fen).unwrap();
+        let restored = Game::from_saved_string(&game.to_saved_string()).unwrap();
+        assert_eq!(restored.get_position().as_fen(), fen);
+        assert!(matches!(
+            Game::load(std::env::temp_dir().join("libchess_missing_game.pgn")),
+            Err(Error::InvalidGameFile { .. })
+        ));
+    }
+
+    #[test]
+    fn pgn_castling_aliases() {
+        let pgn = "[Event \"?\"]\n\n1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. 0-0 Nf6 5. d3 Kg8 1-0";
+        let game = Game::from_pgn(pgn).unwrap();
+        assert_eq!(
+            game.get_action_history().get_moves()[6],
+            castle_king_side!()
+        );
+        assert_eq!(
+            game.get_action_history().get_moves()[9],
+            castle_king_side!()
+        );
+
+        let pgn = "[Event \"?\"]\n\n1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. Kxh1 1-0";
+        let game = Game::from_pgn(pgn).unwrap();
+        assert_eq!(
+            game.get_action_history().get_moves()[6],
+            castle_king_side!()
+        );
+
+        let mut game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        game.make_move(&Action::MakeMove(mv_str!("Ke1c1"))).unwrap();
+        assert_eq!(format!("{}", game.get_action_history()), "1.O-O-O ");
+    }
+
+    #[test]
+    fn pgn_from_black_to_move_position() {
+        let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3";
+        let mut game = Game::from_fen(fen).unwrap();
+        game.make_move(&Action::MakeMove(mv_str!("Bf1b5"))).unwrap();
+        let mut game = Game::from_board(game.get_position());
+        for m in ["a7a6", "Bb5a4", "Ng8f6"] {
+            game.make_move(&Action::MakeMove(mv_str!(m))).unwrap();
+        }
+        game.make_move(&Action::Resign(Black)).unwrap();
+
+        let history = game.get_action_history();
+        assert_eq!(format!("{history}"), "3...a6 4.Ba4 Nf6 ");
+        assert_eq!(
+            history.to_movetext(MoveNumbering::FromOne, Some("1-0")),
+            "1...a6 2.Ba4 Nf6 1-0"
+        );
+
+        let pgn = game.as_pgn();
+        assert!(pgn.contains("[SetUp \"1\"]"));
+        assert!(pgn.ends_with("3...a6 4.Ba4 Nf6 1-0"));
+
+        let read_game = Game::from_pgn(&pgn).unwrap();
+        assert_eq!(read_game.get_position(), game.get_position());
+        assert_eq!(read_game.as_pgn(), pgn);
+    }
+
+    #[test]
+    fn repetition_bookkeeping() {
+        let moves = [
+            "Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "d4", "Ng8", "Ng1", "Nf6",
+        ];
+        let game = Game::from_san_moves([], &moves).unwrap();
+        let history = game.position_history().collect::<Vec<_>>();
+        assert_eq!(history.len(), moves.len() + 1);
+        for (ply, (hash, history_ply)) in history.iter().enumerate() {
+            assert_eq!(history_ply.to_index(), ply);
+            assert_eq!(
+                *hash,
+                game.get_action_history()
+                    .get_position_on_move(ply)
+                    .unwrap()
+                    .get_hash()
+            );
+        }
+
+        // the starting position is repeated before "d4", so it is not in the snapshot
+        let snapshot = game.repetition_snapshot();
+        assert_eq!(snapshot.values().sum::<usize>(), 4);
+        assert!(!snapshot.contains_key(&history[0].0));
+        for (hash, count) in snapshot.iter() {
+            assert_eq!(
+                game.get_position_counters().get_count(*hash).unwrap(),
+                *count
+            );
+        }
+
+        // the counter of the starting position may exceed the number of the positions
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 40 60").unwrap();
+        assert_eq!(game.repetition_snapshot().len(), 1);
+    }
+
+    #[test]
+    fn pgn_stalemate_comments() {
+        let fen = "7k/8/6K1/8/8/8/8/5Q2 w - - 0 1";
+        let mut game = Game::from_fen(fen).unwrap();
+        game.make_move(&Action::MakeMove(mv!(Queen, F1, F8)))
+            .unwrap();
+        game.set_analysis(
+            0,
+            MultiPv::new(vec![AnalysisLine::new(
+                Evaluation::Centipawns(0),
+                vec![mv!(Queen, F1, F7)],
+            )]),
+        )
+        .unwrap();
+        let movetext = game
+            .get_action_history()
+            .to_movetext(MoveNumbering::FromPosition, None);
+        assert!(
+            movetext.starts_with("1.Qf8# (1.Qf7 { Stalemate } {"),
+            "{movetext}"
+        );
+
+        let mut game = Game::from_fen(fen).unwrap();
+        game.make_move(&Action::MakeMove(mv!(Queen, F1, F7)))
+            .unwrap();
+        assert_eq!(game.get_game_status(), GameStatus::Stalemate);
+        let pgn = game.as_pgn();
+        assert!(pgn.ends_with("1.Qf7 { Stalemate } 1/2-1/2"), "{pgn}");
+        assert_eq!(
+            Game::from_pgn(&pgn).unwrap().get_position(),
+            game.get_position()
+        );
+    }
+
+    #[test]
+    fn pgn_with_analysis() {
+        let mut game = Game::default();
+        game.make_move(&Action::MakeMove(mv!(Pawn, E2, E4)))
+            .unwrap()
+            .make_move(&Action::MakeMove(mv!(Pawn, E7, E5)))
+            .unwrap()
+            .make_move(&Action::MakeMove(mv!(Knight, G1, F3)))
+            .unwrap();
+
+        assert!(game
+            .set_analysis(
+                3,
+                MultiPv::new(vec![AnalysisLine::new(
+                    Evaluation::Centipawns(0),
+                    vec![mv!(Knight, B8, C6)]
+                )])
+            )
+            .is_err());
+        assert!(game
+            .set_analysis(
+                1,
+                MultiPv::new(vec![AnalysisLine::new(
+                    Evaluation::Centipawns(0),
+                    vec![mv!(Pawn, E2, E4)]
+                )])
+            )
+            .is_err());
+
+        game.set_analysis(
+            0,
+            MultiPv::new(vec![
+                AnalysisLine::new(Evaluation::Centipawns(35), vec![mv!(Pawn, D2, D4)]),
+                AnalysisLine::new(Evaluation::Centipawns(30), vec![mv!(Pawn, E2, E4)]),
+            ]),
+        )
+        .unwrap()
+        .set_analysis(
+            1,
+            MultiPv::new(vec![
+                AnalysisLine::new(Evaluation::Centipawns(20), vec![mv!(Pawn, E7, E5)]),
+                AnalysisLine::new(
+                    Evaluation::Centipawns(40),
+                    vec![mv!(Pawn, C7, C5), mv!(Knight, G1, F3), mv!(Pawn, D7, D6)],
+                ),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            format!("{}", game.get_action_history()),
+            "1.e4 { +0.30 } (1.d4 { +0.35 }) 1...e5 { +0.20 } (1...c5 2.Nf3 d6 { +0.40 }) 2.Nf3 "
+        );
+
+        game.make_move(&Action::Resign(Black)).unwrap();
+        let read_game = Game::from_pgn(&game.as_pgn()).unwrap();
+        assert_eq!(read_game.get_position(), game.get_position());
+    }
+
+    #[test]
+    fn mapping_positions() {
+        let game =
+            Game::from_san_moves([], &["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6", "Qxf7#"]).unwrap();
+        let positions = game.get_action_history().get_positions();
+        let results = game.map_positions_parallel(|ply, board| {
+            (ply.to_index(), board.get_legal_moves(), board.get_status())
+        });
+        assert_eq!(results.len(), positions.len());
+        for (i, (ply, moves, status)) in results.into_iter().enumerate() {
+            assert_eq!(ply, i);
+            assert_eq!(moves, positions[i].get_legal_moves());
+            assert_eq!(status, positions[i].get_status());
+        }
+        assert_eq!(
+            Game::default().map_positions_parallel(|_, board| board.as_fen()),
+            vec![ChessBoard::default().as_fen()]
+        );
+    }
+
     #[test]
     fn readme_examples() {
         // Initializing a ChessBoard:
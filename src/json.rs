@@ -0,0 +1,460 @@
+//! JSON export and import of the game (requires ``serde`` feature)
+//!
+//! Unlike PGN, JSON keeps the data which is useful for web frontends: every ply contains the
+//! move, its SAN representation and the FEN-string of the position after the move, so clients
+//! don't need to re-derive them. Import replays the moves and checks that the stored SAN and FEN
+//! are consistent with them
+
+use crate::errors::LibChessError as Error;
+use crate::game_history::GameHistory;
+use crate::{
+    Action, AnalysisLine, Annotations, BoardMove, ChessBoard, Evaluation, Game, GameStatus,
+    MovePropertiesOnBoard, MultiPv, Ply, Square,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AnalysisLineJson {
+    evaluation: Evaluation,
+    moves:      Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TextLabelJson {
+    square: String,
+    text:   String,
+}
+
+/// The arrows and highlights are written as the arguments of PGN commands ("Ge2e4", "Rd5")
+#[derive(Debug, Serialize, Deserialize)]
+struct AnnotationsJson {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    arrows:     Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    highlights: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    labels:     Vec<TextLabelJson>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PlyJson {
+    #[serde(rename = "move")]
+    board_move:  String,
+    san:         String,
+    fen:         String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    analysis:    Option<Vec<AnalysisLineJson>>,
+    /// The time spent on the move
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time:        Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    annotations: Option<AnnotationsJson>,
+}
+
+/// The ply restored from JSON: the move and the data attached to it
+struct ParsedPly {
+    board_move:  BoardMove,
+    analysis:    Option<MultiPv>,
+    time:        Option<Duration>,
+    annotations: Option<Annotations>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GameHistoryJson {
+    initial_fen: String,
+    plies:       Vec<PlyJson>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GameJson {
+    metadata: BTreeMap<String, String>,
+    status: GameStatus,
+    draw_claims_required: bool,
+    history: GameHistoryJson,
+}
+
+fn invalid_json(s: impl ToString) -> Error { Error::InvalidJSONString { s: s.to_string() } }
+
+fn parse_move(s: &str) -> Result<BoardMove, Error> {
+    BoardMove::from_str(s).map_err(|_| invalid_json(format!("invalid move \"{s}\"")))
+}
+
+impl From<&Annotations> for AnnotationsJson {
+    fn from(annotations: &Annotations) -> Self {
+        Self {
+            arrows:     annotations
+                .get_arrows()
+                .iter()
+                .map(|a| format!("{}{}{}", a.color.to_pgn_letter(), a.source, a.destination))
+                .collect(),
+            highlights: annotations
+                .get_highlights()
+                .iter()
+                .map(|h| format!("{}{}", h.color.to_pgn_letter(), h.square))
+                .collect(),
+            labels:     annotations
+                .get_labels()
+                .iter()
+                .map(|label| TextLabelJson {
+                    square: format!("{}", label.square),
+                    text:   label.text.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl AnnotationsJson {
+    fn parse(&self) -> Result<Annotations, Error> {
+        let mut annotations = Annotations::new();
+        if !self.arrows.is_empty() {
+            annotations.add_pgn_command("cal", &self.arrows.join(","))?;
+        }
+        if !self.highlights.is_empty() {
+            annotations.add_pgn_command("csl", &self.highlights.join(","))?;
+        }
+        for label in self.labels.iter() {
+            let square = Square::from_str(&label.square)
+                .map_err(|_| invalid_json(format!("invalid square \"{}\"", label.square)))?;
+            annotations.add_label(square, label.text.clone());
+        }
+        Ok(annotations)
+    }
+}
+
+impl From<&GameHistory> for GameHistoryJson {
+    fn from(history: &GameHistory) -> Self {
+        let plies = (0..history.get_moves().len())
+            .map(|ply| {
+                let board_move = history.get_moves()[ply];
+                PlyJson {
+                    board_move:  format!("{board_move}"),
+                    san:         board_move.to_string(history.get_metadata()[ply]),
+                    fen:         history.get_positions()[ply + 1].as_fen(),
+                    analysis:    history.get_analysis(ply).map(|analysis| {
+                        analysis
+                            .get_lines()
+                            .iter()
+                            .map(|line| AnalysisLineJson {
+                                evaluation: line.get_evaluation(),
+                                moves:      line
+                                    .get_moves()
+                                    .iter()
+                                    .map(|m| format!("{m}"))
+                                    .collect(),
+                            })
+                            .collect()
+                    }),
+                    time:        history.get_move_time(ply),
+                    annotations: history.get_annotations(ply).map(AnnotationsJson::from),
+                }
+            })
+            .collect();
+
+        Self {
+            initial_fen: history.get_starting_position().as_fen(),
+            plies,
+        }
+    }
+}
+
+impl GameHistoryJson {
+    /// Parses and validates the initial position and all moves with attached data
+    fn parse(&self) -> Result<(ChessBoard, Vec<ParsedPly>), Error> {
+        let initial_position = ChessBoard::from_str(&self.initial_fen)?;
+        let mut board = initial_position;
+        let mut moves = Vec::with_capacity(self.plies.len());
+
+        for (ply, record) in self.plies.iter().enumerate() {
+            let board_move = parse_move(&record.board_move)?;
+            let next_board = board
+                .make_move(&board_move)
+                .map_err(|_| invalid_json(format!("illegal move on ply {ply}")))?;
+            let properties = MovePropertiesOnBoard::new(&board_move, &board)?;
+            if record.san != board_move.to_string(properties) {
+                return Err(invalid_json(format!("inconsistent SAN on ply {ply}")));
+            }
+            if record.fen != next_board.as_fen() {
+                return Err(invalid_json(format!("inconsistent FEN on ply {ply}")));
+            }
+
+            let analysis = match &record.analysis {
+                None => None,
+                Some(lines) => Some(MultiPv::new(
+                    lines
+                        .iter()
+                        .map(|line| {
+                            let moves: Result<Vec<_>, _> =
+                                line.moves.iter().map(|m| parse_move(m)).collect();
+                            Ok(AnalysisLine::new(line.evaluation, moves?))
+                        })
+                        .collect::<Result<_, Error>>()?,
+                )),
+            };
+            moves.push(ParsedPly {
+                board_move,
+                analysis,
+                time: record.time,
+                annotations: record.annotations.as_ref().map(|a| a.parse()).transpose()?,
+            });
+            board = next_board;
+        }
+        Ok((initial_position, moves))
+    }
+}
+
+impl GameHistory {
+    /// Exports the history to JSON-string: the initial position and every ply with its move,
+    /// SAN, FEN-string of the resulting position, attached engine analysis, the time spent on the
+    /// move and the graphical annotations
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{mv, squares::*, Action, BoardMove, Game, PieceMove, PieceType::*};
+    /// let mut game = Game::default();
+    /// game.make_move(&Action::MakeMove(mv!(Pawn, E2, E4)))
+    ///     .unwrap();
+    /// let json = game.get_action_history().to_json();
+    /// assert!(json.contains("\"san\":\"e4\""));
+    /// ```
+    pub fn to_json(&self) -> String { serde_json::to_string(&GameHistoryJson::from(self)).unwrap() }
+
+    /// Restores the history from JSON-string created by ``GameHistory::to_json``
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidJSONString`` if the string can't be parsed, contains illegal moves
+    /// or SAN and FEN-strings which are inconsistent with the moves
+    ///
+    /// ``LibChessError::InvalidAnalysisLine`` if any of analysis lines is illegal
+    ///
+    /// ``LibChessError::InvalidAnnotation`` if any of arrows or highlights is invalid
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let record: GameHistoryJson = serde_json::from_str(json).map_err(invalid_json)?;
+        let (initial_position, moves) = record.parse()?;
+
+        let mut history = Self::from_position(initial_position);
+        let mut board = initial_position;
+        for (ply, record) in moves.into_iter().enumerate() {
+            board.make_move_mut(&record.board_move)?;
+            history.push(record.board_move, board);
+            if let Some(analysis) = record.analysis {
+                history.set_analysis(ply, analysis)?;
+            }
+            if let Some(time) = record.time {
+                history.set_move_time(ply, time)?;
+            }
+            if let Some(annotations) = record.annotations {
+                history.set_annotations(ply, annotations)?;
+            }
+        }
+        Ok(history)
+    }
+}
+
+impl Game {
+    /// Exports the game to JSON-string: the metadata, the status, the draw claims mode and the
+    /// history (see ``GameHistory::to_json``)
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{mv, squares::*, Action, BoardMove, Color, Game, PieceMove, PieceType::*};
+    /// let mut game = Game::default();
+    /// game.make_move(&Action::MakeMove(mv!(Pawn, E2, E4)))
+    ///     .unwrap();
+    /// game.make_move(&Action::Resign(Color::Black)).unwrap();
+    ///
+    /// let restored = Game::from_json(&game.to_json()).unwrap();
+    /// assert_eq!(restored.get_game_status(), game.get_game_status());
+    /// assert_eq!(restored.as_pgn(), game.as_pgn());
+    /// ```
+    pub fn to_json(&self) -> String {
+        let record = GameJson {
+            metadata: self
+                .get_metadata()
+                .iter()
+                .map(|(tag, value)| (tag.clone(), value.clone()))
+                .collect(),
+            status: self.get_game_status(),
+            draw_claims_required: self.is_draw_claims_required(),
+            history: GameHistoryJson::from(self.get_action_history()),
+        };
+        serde_json::to_string(&record).unwrap()
+    }
+
+    /// Restores the game from JSON-string created by ``Game::to_json``. The moves are replayed,
+    /// so the game can be continued if it is not finished
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidJSONString`` if the string can't be parsed, contains illegal moves,
+    /// SAN and FEN-strings which are inconsistent with the moves or the status which can't be
+    /// reached
+    ///
+    /// ``LibChessError::InvalidAnalysisLine`` if any of analysis lines is illegal
+    ///
+    /// ``LibChessError::InvalidAnnotation`` if any of arrows or highlights is invalid
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let record: GameJson = serde_json::from_str(json).map_err(invalid_json)?;
+        let (initial_position, moves) = record.history.parse()?;
+
+        let mut game = Game::from_board(initial_position);
+        game.set_draw_claims_required(record.draw_claims_required);
+        for (ply, record) in moves.into_iter().enumerate() {
+            game.make_move(&Action::MakeMove(record.board_move))
+                .map_err(|_| invalid_json(format!("move on ply {ply} can't be made")))?;
+            if let Some(analysis) = record.analysis {
+                game.set_analysis(Ply::new(ply), analysis)?;
+            }
+            if let Some(time) = record.time {
+                game.set_move_time(Ply::new(ply), time)?;
+            }
+            if let Some(annotations) = record.annotations {
+                game.set_annotations(Ply::new(ply), annotations)?;
+            }
+        }
+
+        // the statuses which are not inferred from the moves are restored by the final actions
+        let side_to_move = game.get_side_to_move();
+        let actions = match record.status {
+            s if s == game.get_game_status() => vec![],
+            GameStatus::DrawOffered(color) => vec![Action::OfferDraw(color)],
+            GameStatus::Resigned(color) => vec![Action::Resign(color)],
+            GameStatus::Forfeited(color) => {
+                game.forfeit(color);
+                vec![]
+            }
+            GameStatus::DrawAccepted => vec![Action::OfferDraw(side_to_move), Action::AcceptDraw],
+            GameStatus::FiftyMovesDrawDeclared => vec![Action::ClaimFiftyMoveDraw],
+            GameStatus::RepetitionDrawDeclared => vec![Action::ClaimThreefoldDraw],
+            _ => vec![],
+        };
+        for action in actions.iter() {
+            game.make_move(action)
+                .map_err(|_| invalid_json("status can't be reached"))?;
+        }
+        if game.get_game_status() != record.status {
+            return Err(invalid_json("status can't be reached"));
+        }
+
+        for (tag, value) in record.metadata.into_iter() {
+            game.get_metadata_mut().set_value(tag, value);
+        }
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        castle_king_side, mv, squares::*, AnnotationColor::*, Color::*, IllegalMovePolicy,
+        PieceMove, PieceType::*,
+    };
+
+    #[test]
+    fn history_round_trip() {
+        let mut game =
+            Game::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3")
+                .unwrap();
+        for m in [
+            mv!(Bishop, F1, B5),
+            mv!(Pawn, A7, A6),
+            mv!(Bishop, B5, C6),
+            mv!(Pawn, D7, C6),
+            castle_king_side!(),
+        ] {
+            game.make_move(&Action::MakeMove(m)).unwrap();
+        }
+        game.set_analysis(
+            1,
+            MultiPv::new(vec![
+                AnalysisLine::new(Evaluation::Centipawns(-20), vec![mv!(Pawn, A7, A6)]),
+                AnalysisLine::new(Evaluation::Mate(-7), vec![mv!(Knight, G8, F6)]),
+            ]),
+        )
+        .unwrap();
+        let mut annotations = Annotations::new();
+        annotations
+            .add_arrow(E1, G1, Green)
+            .add_highlight(C6, Red)
+            .add_label(F7, "weak");
+        game.set_annotations(3, annotations).unwrap();
+        game.set_move_time(0, Duration::from_secs(75))
+            .unwrap()
+            .set_move_time(4, Duration::from_nanos(1_500_000_001))
+            .unwrap();
+
+        let history = game.get_action_history();
+        let restored = GameHistory::from_json(&history.to_json()).unwrap();
+        assert_eq!(restored.get_positions(), history.get_positions());
+        assert_eq!(restored.get_moves(), history.get_moves());
+        assert_eq!(restored.get_analysis(1), history.get_analysis(1));
+        for ply in 0..5 {
+            assert_eq!(restored.get_move_time(ply), history.get_move_time(ply));
+        }
+        assert_eq!(restored.get_move_time(1), None);
+        assert_eq!(restored.get_annotations(3), history.get_annotations(3));
+        assert_eq!(restored.get_annotations(2), None);
+        assert_eq!(restored.to_string(), history.to_string());
+    }
+
+    #[test]
+    fn game_round_trip() {
+        let mut game = Game::default();
+        game.get_metadata_mut()
+            .set_value("Event".to_string(), "Casual game".to_string());
+        game.set_draw_claims_required(true);
+        game.make_move(&Action::MakeMove(mv!(Pawn, E2, E4)))
+            .unwrap()
+            .make_move(&Action::OfferDraw(Black))
+            .unwrap();
+
+        game.set_move_time(0, Duration::from_millis(2500)).unwrap();
+
+        let restored = Game::from_json(&game.to_json()).unwrap();
+        assert_eq!(
+            restored.get_action_history().get_move_time(0),
+            Some(Duration::from_millis(2500))
+        );
+        assert_eq!(restored.get_game_status(), GameStatus::DrawOffered(Black));
+        assert!(restored.is_draw_claims_required());
+        assert_eq!(restored.as_pgn(), game.as_pgn());
+        assert_eq!(restored.get_position(), game.get_position());
+
+        game.make_move(&Action::AcceptDraw).unwrap();
+        let restored = Game::from_json(&game.to_json()).unwrap();
+        assert_eq!(restored.get_game_status(), GameStatus::DrawAccepted);
+        assert_eq!(restored.as_pgn(), game.as_pgn());
+
+        let mut game = Game::default();
+        game.set_illegal_move_policy(IllegalMovePolicy::ForfeitAfter(1));
+        assert!(game
+            .make_move(&Action::MakeMove(mv!(Pawn, E2, E5)))
+            .is_err());
+        let restored = Game::from_json(&game.to_json()).unwrap();
+        assert_eq!(restored.get_game_status(), GameStatus::Forfeited(White));
+    }
+
+    #[test]
+    fn inconsistent_json() {
+        let mut game = Game::default();
+        game.make_move(&Action::MakeMove(mv!(Pawn, E2, E4)))
+            .unwrap();
+        let json = game.to_json();
+
+        assert!(Game::from_json("{}").is_err());
+        assert!(Game::from_json(&json.replace("\"san\":\"e4\"", "\"san\":\"d4\"")).is_err());
+        assert!(Game::from_json(&json.replace("e2e4", "e2e5")).is_err());
+        let annotated = json.replace(
+            "\"san\":\"e4\"",
+            "\"san\":\"e4\",\"annotations\":{\"arrows\":[\"Xe2e4\"]}",
+        );
+        assert!(Game::from_json(&annotated).is_err());
+        assert!(Game::from_json(&annotated.replace("Xe2e4", "Ge2e4")).is_ok());
+        assert!(
+            Game::from_json(&json.replace("\"Ongoing\"", "{\"CheckMated\":\"White\"}")).is_err()
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! KPK (king and pawn vs king) bitbase
+//!
+//! This module implements the exact evaluation of the king and pawn vs king endgame. The bitbase
+//! is generated by retrograde analysis on the first use. Positions are normalized so that the
+//! strong side is white and the pawn is on the files A-D
+
+use crate::move_masks::{KING_TABLE as KING, PAWN_TABLE as PAWN};
+use crate::{BitBoard, Color, Square, SQUARES_NUMBER};
+use lazy_static::lazy_static;
+use std::fmt;
+
+/// Exact result of the position from the side to move perspective
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WinDrawLoss {
+    Win,
+    Draw,
+    Loss,
+}
+
+impl fmt::Display for WinDrawLoss {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match *self {
+            WinDrawLoss::Win => "win",
+            WinDrawLoss::Draw => "draw",
+            WinDrawLoss::Loss => "loss",
+        };
+        write!(f, "{s}")
+    }
+}
+
+const PAWN_SQUARES_NUMBER: usize = 24; // files A-D, ranks 2-7
+const POSITIONS_NUMBER: usize = 2 * PAWN_SQUARES_NUMBER * SQUARES_NUMBER * SQUARES_NUMBER;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KpkResult {
+    Invalid,
+    Unknown,
+    Draw,
+    Win,
+}
+
+pub struct KpkBitbase {
+    results: Vec<KpkResult>,
+}
+
+impl KpkBitbase {
+    fn new() -> Self {
+        let mut bitbase = Self {
+            results: vec![KpkResult::Unknown; POSITIONS_NUMBER],
+        };
+
+        for index in 0..POSITIONS_NUMBER {
+            bitbase.results[index] = Self::classify_initially(Self::decode(index));
+        }
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for index in 0..POSITIONS_NUMBER {
+                if bitbase.results[index] == KpkResult::Unknown {
+                    let result = bitbase.classify(Self::decode(index));
+                    if result != KpkResult::Unknown {
+                        bitbase.results[index] = result;
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        for result in bitbase.results.iter_mut() {
+            if *result == KpkResult::Unknown {
+                *result = KpkResult::Draw;
+            }
+        }
+        bitbase
+    }
+
+    /// Returns the result of normalized position (white has the pawn on files A-D) from the
+    /// white's perspective
+    pub(crate) fn probe(
+        &self,
+        white_to_move: bool,
+        white_king: Square,
+        black_king: Square,
+        pawn: Square,
+    ) -> WinDrawLoss {
+        let result = self.results[Self::encode(white_to_move, white_king, black_king, pawn)];
+        match result {
+            KpkResult::Win => WinDrawLoss::Win,
+            _ => WinDrawLoss::Draw,
+        }
+    }
+
+    fn encode(white_to_move: bool, white_king: Square, black_king: Square, pawn: Square) -> usize {
+        let pawn_index = (pawn.get_rank().to_index() - 1) * 4 + pawn.get_file().to_index();
+        let side_index = if white_to_move { 0 } else { 1 };
+        ((side_index * PAWN_SQUARES_NUMBER + pawn_index) * SQUARES_NUMBER + white_king.to_index())
+            * SQUARES_NUMBER
+            + black_king.to_index()
+    }
+
+    fn decode(index: usize) -> (bool, Square, Square, Square) {
+        let black_king = index % SQUARES_NUMBER;
+        let white_king = (index / SQUARES_NUMBER) % SQUARES_NUMBER;
+        let pawn_index = (index / SQUARES_NUMBER / SQUARES_NUMBER) % PAWN_SQUARES_NUMBER;
+        let white_to_move = index / SQUARES_NUMBER / SQUARES_NUMBER / PAWN_SQUARES_NUMBER == 0;
+        let pawn = 8 * (pawn_index / 4 + 1) + pawn_index % 4;
+        (
+            white_to_move,
+            Square::new(white_king as u8).unwrap(),
+            Square::new(black_king as u8).unwrap(),
+            Square::new(pawn as u8).unwrap(),
+        )
+    }
+
+    fn classify_initially(
+        (white_to_move, white_king, black_king, pawn): (bool, Square, Square, Square),
+    ) -> KpkResult {
+        let black_king_mask = BitBoard::from_square(black_king);
+        let pawn_attacks = PAWN.get_captures(pawn, Color::White);
+
+        if (white_king == black_king)
+            | (white_king == pawn)
+            | (black_king == pawn)
+            | !(KING.get_moves(white_king) & black_king_mask).is_blank()
+            | (white_to_move & !(pawn_attacks & black_king_mask).is_blank())
+        {
+            return KpkResult::Invalid;
+        }
+
+        if white_to_move {
+            // the pawn is promoted and the queen can't be captured
+            if pawn.get_rank().to_index() == 6 {
+                let promotion = pawn.up().unwrap();
+                let promotion_mask = BitBoard::from_square(promotion);
+                if (promotion != white_king)
+                    & (promotion != black_king)
+                    & ((KING.get_moves(black_king) & promotion_mask).is_blank()
+                        | !(KING.get_moves(white_king) & promotion_mask).is_blank())
+                {
+                    return KpkResult::Win;
+                }
+            }
+        } else {
+            let black_king_moves =
+                KING.get_moves(black_king) & !(KING.get_moves(white_king) | pawn_attacks);
+
+            // stalemate or the pawn is captured
+            if black_king_moves.is_blank()
+                | !(black_king_moves & BitBoard::from_square(pawn)).is_blank()
+            {
+                return KpkResult::Draw;
+            }
+        }
+
+        KpkResult::Unknown
+    }
+
+    fn classify(
+        &self,
+        (white_to_move, white_king, black_king, pawn): (bool, Square, Square, Square),
+    ) -> KpkResult {
+        let pawn_mask = BitBoard::from_square(pawn);
+        let mut successors = vec![];
+
+        if white_to_move {
+            let king_moves = KING.get_moves(white_king) & !KING.get_moves(black_king) & !pawn_mask;
+            for square in king_moves {
+                successors.push(Self::encode(false, square, black_king, pawn));
+            }
+
+            let rank_index = pawn.get_rank().to_index();
+            let single_push = pawn.up().unwrap();
+            if (rank_index < 6) & (single_push != white_king) & (single_push != black_king) {
+                successors.push(Self::encode(false, white_king, black_king, single_push));
+
+                let double_push = single_push.up().unwrap();
+                if (rank_index == 1) & (double_push != white_king) & (double_push != black_king) {
+                    successors.push(Self::encode(false, white_king, black_king, double_push));
+                }
+            }
+        } else {
+            let king_moves = KING.get_moves(black_king)
+                & !KING.get_moves(white_king)
+                & !PAWN.get_captures(pawn, Color::White)
+                & !pawn_mask;
+            for square in king_moves {
+                successors.push(Self::encode(true, white_king, square, pawn));
+            }
+        }
+
+        let (good, bad) = match white_to_move {
+            true => (KpkResult::Win, KpkResult::Draw),
+            false => (KpkResult::Draw, KpkResult::Win),
+        };
+        let results = successors.into_iter().map(|i| self.results[i]);
+        let mut all_bad = true;
+        for result in results {
+            if result == good {
+                return good;
+            }
+            all_bad &= result == bad;
+        }
+
+        if all_bad {
+            bad
+        } else {
+            KpkResult::Unknown
+        }
+    }
+}
+
+lazy_static! {
+    pub static ref KPK_BITBASE: KpkBitbase = KpkBitbase::new();
+}
@@ -1,24 +1,111 @@
+mod annotations;
+pub use annotations::{AnnotationColor, Annotations, Arrow, SquareHighlight, TextLabel};
+
+mod analysis;
+pub use analysis::{AnalysisLine, Evaluation, MultiPv};
+
+mod arbitration;
+pub use arbitration::{IllegalMoveAttempt, IllegalMovePolicy};
+
+pub mod books;
+
+mod canonical_forms;
+pub use canonical_forms::{BoardSymmetry, CanonicalForm};
+
 mod castling;
 pub use castling::{CastlingRights, CASTLING_RIGHTS_NUMBER};
 
+#[cfg(feature = "chess")]
+mod chess_compat;
+
 mod colors;
 pub use colors::{Color, COLORS_NUMBER};
 
+pub mod db;
+
+mod draw_searches;
+
+mod endgames;
+pub use endgames::{EndgameClass, ENDGAME_MAX_PIECES};
+
 pub mod errors;
 
+mod evaluator_backends;
+#[cfg(not(feature = "evaluator-backend"))]
+pub(crate) use evaluator_backends::EvaluatorBackend;
+#[cfg(feature = "evaluator-backend")]
+pub use evaluator_backends::{EvaluatorBackend, MaterialAccumulator};
+
+mod game_metadata;
+pub use game_metadata::{GameMetadata, Termination, TimeControl, TimeControlPeriod};
+
+mod game_reviews;
+pub use game_reviews::{GameReview, KeyMoment, REVIEW_EVALUATION_LIMIT};
+
 mod games;
-pub use games::{Action, Game, GameStatus};
+pub use games::{Action, Game, GameStatus, LastMoveInfo};
+
+#[cfg(feature = "serde")]
+mod json;
+
+mod kpk;
+pub use kpk::WinDrawLoss;
 
 pub mod move_masks;
 
+pub mod testing;
+
+#[cfg(feature = "uci-harness")]
+pub mod uci_harness;
+
+mod move_caches;
+pub use move_caches::{CacheStatistics, LEGAL_MOVES_CACHE_DEFAULT_CAPACITY};
+
+mod notation_locales;
+pub use notation_locales::NotationLocale;
+
+mod notation_trainers;
+pub use notation_trainers::NotationQuiz;
+
+mod playouts;
+pub use playouts::PlayoutPolicy;
+
+mod perfts;
+pub use perfts::PerftStats;
+
+pub mod policy_encodings;
+pub use policy_encodings::{POLICY_PLANES_NUMBER, POLICY_SIZE};
+
+mod stipulations;
+pub use stipulations::Stipulation;
+
+mod staged_move_gens;
+pub use staged_move_gens::{MoveStage, StagedMoveGen};
+
+mod plies;
+pub use plies::{MoveNumber, Ply};
+
 mod pieces;
 pub use pieces::{Piece, PieceType, PIECE_TYPES_NUMBER};
 
+mod piece_visibilities;
+pub use piece_visibilities::PieceVisibility;
+
 mod bitboards;
-pub use bitboards::{BitBoard, BLANK};
+pub use bitboards::{
+    BitBoard, BLANK, CENTER_SQUARES, DARK_SQUARES, EXTENDED_CENTER_SQUARES, LIGHT_SQUARES,
+};
+
+mod board_orientations;
+pub use board_orientations::BoardOrientation;
 
 mod board_builders;
-pub use board_builders::BoardBuilder;
+pub use board_builders::{
+    BoardBuilder, INITIAL_BACK_RANK, INITIAL_PIECE_PLACEMENT, STARTING_POSITION_FEN,
+};
+
+mod board_editors;
+pub use board_editors::{BoardEdit, BoardEditor};
 
 mod board_files;
 pub use board_files::{File, FILES, FILES_NUMBER};
@@ -27,17 +114,49 @@ mod board_ranks;
 pub use board_ranks::{Rank, RANKS, RANKS_NUMBER};
 
 mod coordinates;
-pub use coordinates::{squares, Square, SQUARES_NUMBER};
+pub use coordinates::{squares, Square, SquareColor, SQUARES_NUMBER};
 
 mod chess_boards;
-pub use chess_boards::{BoardStatus, ChessBoard, LegalMoves};
+pub use chess_boards::{
+    BoardStatus, CenterControl, ChessBoard, DrawReport, EnPassantIllegality, EnPassantPolicy,
+    FenParseOptions, LegalMoves, MAX_LEGAL_MOVES,
+};
+
+pub mod position_indexes;
+
+pub mod puzzles;
+
+mod position_patterns;
+pub use position_patterns::PositionPattern;
+
+mod position_counters;
+pub use position_counters::{InMemoryPositionCounter, PositionCounter};
+
+mod position_cores;
+pub use position_cores::PositionCore;
 
 mod zobrist;
-pub use zobrist::{PositionHashValueType, ZOBRIST_TABLES};
+pub use zobrist::{PositionHashValueType, ZobristHasher, ZOBRIST_TABLES};
+
+mod polyglot_keys;
 
 #[macro_use]
 mod board_moves;
-pub use board_moves::{BoardMove, DisplayAmbiguityType, MovePropertiesOnBoard, PieceMove};
+pub use board_moves::{
+    BoardMove, DisplayAmbiguityType, MoveFlags, MoveIntent, MoveOutcome, MovePropertiesOnBoard,
+    PieceAnimation, PieceMove, PromotionPolicy, Takeback,
+};
 
 mod game_history;
-pub use game_history::GameHistory;
+pub use game_history::{GameHistory, GameHistoryDisplay, MoveNumbering, RenderOptions};
+
+mod game_stats;
+pub use game_stats::{GamePhase, GameStats, OPENING_MIN_BACK_RANK_PIECES, OPENING_MIN_PIECES};
+
+mod time_reports;
+pub use time_reports::{TimeReport, TimeUsage};
+
+mod valuations;
+pub use valuations::{
+    Valuation, BISHOP_VALUE, KING_VALUE, KNIGHT_VALUE, PAWN_VALUE, QUEEN_VALUE, ROOK_VALUE,
+};
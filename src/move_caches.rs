@@ -0,0 +1,158 @@
+//! Legal moves cache
+//!
+//! This module implements a small cache of legal moves keyed by the position hash. It is used by
+//! ``Game`` to avoid repeated moves generation when the moves of the same position are requested
+//! many times (e.g. by GUI between user interactions)
+
+use crate::{ChessBoard, LegalMoves, PositionHashValueType};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, MutexGuard};
+
+pub const LEGAL_MOVES_CACHE_DEFAULT_CAPACITY: usize = 64;
+
+/// Statistics of the legal moves cache usage
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStatistics {
+    pub hits:     usize,
+    pub misses:   usize,
+    pub size:     usize,
+    pub capacity: usize,
+}
+
+/// Cache of legal moves with limited capacity. When the cache is full, the oldest position is
+/// evicted. Positions are compared entirely, so hash collisions can't return wrong moves
+#[derive(Debug, Clone)]
+pub(crate) struct LegalMovesCache {
+    entries:  HashMap<PositionHashValueType, (ChessBoard, LegalMoves)>,
+    order:    VecDeque<PositionHashValueType>,
+    capacity: usize,
+    hits:     usize,
+    misses:   usize,
+}
+
+impl Default for LegalMovesCache {
+    fn default() -> Self { Self::new(LEGAL_MOVES_CACHE_DEFAULT_CAPACITY) }
+}
+
+impl LegalMovesCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::with_capacity(capacity),
+            order: VecDeque::with_capacity(capacity),
+            capacity,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Returns legal moves of the position from the cache or generates them and stores them
+    pub(crate) fn get_legal_moves(&mut self, position: &ChessBoard) -> LegalMoves {
+        let hash = position.get_hash();
+        if let Some((board, moves)) = self.entries.get(&hash) {
+            if board == position {
+                self.hits += 1;
+                return moves.clone();
+            }
+        }
+
+        self.misses += 1;
+        let moves = position.get_legal_moves();
+        if self.capacity > 0 {
+            if self.entries.contains_key(&hash) {
+                self.order.retain(|&h| h != hash);
+            } else if self.entries.len() >= self.capacity {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.entries.remove(&oldest);
+                }
+            }
+            self.entries.insert(hash, (*position, moves.clone()));
+            self.order.push_back(hash);
+        }
+        moves
+    }
+
+    /// Changes the capacity of the cache (0 disables caching) and evicts the oldest positions
+    /// which don't fit into it
+    pub(crate) fn set_capacity(&mut self, capacity: usize) -> &mut Self {
+        while self.order.len() > capacity {
+            let oldest = self.order.pop_front().unwrap();
+            self.entries.remove(&oldest);
+        }
+        self.capacity = capacity;
+        self
+    }
+
+    pub(crate) fn clear(&mut self) -> &mut Self {
+        self.entries.clear();
+        self.order.clear();
+        self
+    }
+
+    pub(crate) fn get_statistics(&self) -> CacheStatistics {
+        CacheStatistics {
+            hits:     self.hits,
+            misses:   self.misses,
+            size:     self.entries.len(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+/// The legal moves cache which can be used through the shared reference. It is guarded by the
+/// mutex (not by ``RefCell``), so the owner of the cache (``Game``) stays ``Sync``
+#[derive(Debug, Default)]
+pub(crate) struct SharedLegalMovesCache(Mutex<LegalMovesCache>);
+
+impl Clone for SharedLegalMovesCache {
+    fn clone(&self) -> Self { Self(Mutex::new(self.lock().clone())) }
+}
+
+impl SharedLegalMovesCache {
+    /// Locks the cache. The cache stays consistent if the other thread panicked while using it,
+    /// so the poisoning is ignored
+    pub(crate) fn lock(&self) -> MutexGuard<'_, LegalMovesCache> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut LegalMovesCache {
+        self.0.get_mut().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn caching() {
+        let mut cache = LegalMovesCache::new(2);
+        let boards = [
+            ChessBoard::default(),
+            ChessBoard::default().apply_san_line("e4").unwrap(),
+            ChessBoard::default().apply_san_line("d4").unwrap(),
+        ];
+
+        assert_eq!(cache.get_legal_moves(&boards[0]).len(), 20);
+        assert_eq!(cache.get_legal_moves(&boards[0]).len(), 20);
+        cache.get_legal_moves(&boards[1]);
+        cache.get_legal_moves(&boards[2]); // evicts the first board
+        cache.get_legal_moves(&boards[0]);
+        assert_eq!(
+            cache.get_statistics(),
+            CacheStatistics {
+                hits:     1,
+                misses:   4,
+                size:     2,
+                capacity: 2,
+            }
+        );
+
+        cache.set_capacity(1);
+        assert_eq!(cache.get_statistics().size, 1);
+        cache.set_capacity(0).clear();
+        let board = ChessBoard::from_str("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        cache.get_legal_moves(&board);
+        assert_eq!(cache.get_statistics().size, 0);
+    }
+}
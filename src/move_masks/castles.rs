@@ -0,0 +1,91 @@
+use crate::{BitBoard, CastlingRights, Color, BLANK, COLORS_NUMBER};
+
+/// Squares between the king and the rook which must be empty for the castling. Indexed by the
+/// color and the side: ``[king side, queen side]``
+pub const CASTLING_EMPTY_PATHS: [[BitBoard; 2]; COLORS_NUMBER] = [
+    [BitBoard::new(0x60), BitBoard::new(0x0E)],
+    [
+        BitBoard::new(0x6000_0000_0000_0000),
+        BitBoard::new(0x0E00_0000_0000_0000),
+    ],
+];
+
+/// Squares which the king passes (or lands on) while castling, so they must not be attacked.
+/// Indexed by the color and the side: ``[king side, queen side]``
+pub const CASTLING_KING_PATHS: [[BitBoard; 2]; COLORS_NUMBER] = [
+    [BitBoard::new(0x60), BitBoard::new(0x0C)],
+    [
+        BitBoard::new(0x6000_0000_0000_0000),
+        BitBoard::new(0x0C00_0000_0000_0000),
+    ],
+];
+
+fn get_side_index(side: CastlingRights) -> Option<usize> {
+    match side {
+        CastlingRights::KingSide => Some(0),
+        CastlingRights::QueenSide => Some(1),
+        _ => None,
+    }
+}
+
+/// Returns the squares which must be empty for the castling to specified side (blank mask if
+/// ``side`` is not a single side)
+///
+/// # Examples
+/// ```
+/// use libchess::move_masks::castling_empty_path;
+/// use libchess::{squares::*, BitBoard, CastlingRights::*, Color::*, BLANK};
+/// assert_eq!(
+///     castling_empty_path(Black, KingSide),
+///     BitBoard::from_square(F8) | BitBoard::from_square(G8)
+/// );
+/// assert_eq!(castling_empty_path(White, QueenSide).count_ones(), 3);
+/// assert_eq!(castling_empty_path(White, BothSides), BLANK);
+/// ```
+#[inline]
+pub fn castling_empty_path(color: Color, side: CastlingRights) -> BitBoard {
+    get_side_index(side).map_or(BLANK, |i| CASTLING_EMPTY_PATHS[color.to_index()][i])
+}
+
+/// Returns the squares which must not be attacked for the castling to specified side (blank
+/// mask if ``side`` is not a single side). The king's initial square is not included
+///
+/// # Examples
+/// ```
+/// use libchess::move_masks::castling_king_path;
+/// use libchess::{squares::*, BitBoard, CastlingRights::*, Color::*};
+/// assert_eq!(
+///     castling_king_path(White, QueenSide),
+///     BitBoard::from_square(C1) | BitBoard::from_square(D1)
+/// );
+/// ```
+#[inline]
+pub fn castling_king_path(color: Color, side: CastlingRights) -> BitBoard {
+    get_side_index(side).map_or(BLANK, |i| CASTLING_KING_PATHS[color.to_index()][i])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::move_masks::between;
+    use crate::squares::*;
+
+    #[test]
+    fn castling_paths() {
+        for (color, king, rooks, destinations) in [
+            (Color::White, E1, [H1, A1], [G1, C1]),
+            (Color::Black, E8, [H8, A8], [G8, C8]),
+        ] {
+            for i in 0..2 {
+                assert_eq!(
+                    CASTLING_EMPTY_PATHS[color.to_index()][i],
+                    between(king, rooks[i])
+                );
+                assert_eq!(
+                    CASTLING_KING_PATHS[color.to_index()][i],
+                    between(king, destinations[i]) | BitBoard::from_square(destinations[i])
+                );
+            }
+        }
+    }
+}
@@ -43,6 +43,11 @@ use pawns::{generate_pawn_captures, generate_pawn_moves, PawnMoveTable};
 mod between;
 use between::{generate_between_masks, BetweenTable};
 
+mod castles;
+pub use castles::{
+    castling_empty_path, castling_king_path, CASTLING_EMPTY_PATHS, CASTLING_KING_PATHS,
+};
+
 lazy_static! {
     pub static ref RAYS_TABLE: RaysTable = RaysTable::default();
     pub static ref BISHOP_TABLE: PieceMoveTable = {
@@ -84,3 +89,85 @@ lazy_static! {
         between_table
     };
 }
+
+/// Returns the squares strictly between two squares if they are on the same rank, file or
+/// diagonal (blank mask otherwise)
+///
+/// # Examples
+/// ```
+/// use libchess::move_masks::between;
+/// use libchess::{squares::*, BitBoard, BLANK};
+/// assert_eq!(
+///     between(A1, D4),
+///     BitBoard::from_square(B2) | BitBoard::from_square(C3)
+/// );
+/// assert_eq!(between(A1, B3), BLANK);
+/// ```
+#[inline]
+pub fn between(square_a: Square, square_b: Square) -> BitBoard {
+    BETWEEN_TABLE.get(square_a, square_b).unwrap_or(BLANK)
+}
+
+/// Returns the full line (from edge to edge of the board) which passes through both squares if
+/// they are on the same rank, file or diagonal (blank mask otherwise or if squares are equal)
+///
+/// # Examples
+/// ```
+/// use libchess::move_masks::line;
+/// use libchess::{squares::*, BitBoard, BLANK};
+/// assert_eq!(line(C3, E5), line(A1, H8));
+/// assert!(!(line(C3, E5) & BitBoard::from_square(H8)).is_blank());
+/// assert_eq!(line(A1, B3), BLANK);
+/// ```
+pub fn line(square_a: Square, square_b: Square) -> BitBoard {
+    // the rays are indexed in pairs of opposite directions: (0, 1), (2, 3), (4, 7), (5, 6)
+    const OPPOSITE: [usize; 8] = [1, 0, 3, 2, 7, 6, 5, 4];
+
+    let rays = RAYS_TABLE.get(square_a);
+    match (0..rays.len()).find(|&i| !(rays[i] & BitBoard::from_square(square_b)).is_blank()) {
+        Some(i) => rays[i] | rays[OPPOSITE[i]] | BitBoard::from_square(square_a),
+        None => BLANK,
+    }
+}
+
+/// Checks if three squares are on the same rank, file or diagonal
+///
+/// # Examples
+/// ```
+/// use libchess::move_masks::aligned;
+/// use libchess::squares::*;
+/// assert!(aligned(B2, E5, H8));
+/// assert!(aligned(E1, E8, E4));
+/// assert!(!aligned(E1, E8, D4));
+/// ```
+#[inline]
+pub fn aligned(square_a: Square, square_b: Square, square_c: Square) -> bool {
+    !(line(square_a, square_b) & BitBoard::from_square(square_c)).is_blank()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::squares::*;
+
+    #[test]
+    fn lines() {
+        for square_a in (0..SQUARES_NUMBER as u8).map(|i| Square::new(i).unwrap()) {
+            assert_eq!(line(square_a, square_a), BLANK);
+            for square_b in (0..SQUARES_NUMBER as u8).map(|i| Square::new(i).unwrap()) {
+                let line_mask = line(square_a, square_b);
+                assert_eq!(line_mask, line(square_b, square_a));
+                if !line_mask.is_blank() {
+                    assert_eq!(between(square_a, square_b) & !line_mask, BLANK);
+                    assert!(aligned(square_a, square_b, square_b));
+                }
+            }
+        }
+
+        assert_eq!(line(D4, D7).count_ones(), 8);
+        assert_eq!(line(B1, A2).count_ones(), 2);
+        assert_eq!(between(E1, E8).count_ones(), 6);
+        assert!(aligned(A8, H1, D5));
+        assert!(!aligned(A8, H1, D4));
+    }
+}
@@ -59,30 +59,20 @@ impl PawnMoveTable {
 }
 
 pub fn generate_pawn_moves(table: &mut PawnMoveTable, color: Color) {
+    let (direction, start_rank) = (color.get_forward_direction(), color.get_pawn_start_rank());
     for source_index in 0..SQUARES_NUMBER as u8 {
         let source_square = Square::new(source_index).unwrap();
-        let source_rank = source_square.get_rank().to_index();
+        let source_rank = source_square.get_rank();
 
         (0..SQUARES_NUMBER as u8).for_each(|dest_index| {
             let destination_square = Square::new(dest_index).unwrap();
             let dest_mask = BitBoard::from_square(destination_square);
             let d = source_square.offsets_from(destination_square);
 
-            match color {
-                Color::White => {
-                    if (d.0 == 1) & (d.1 == 0) {
-                        table.set_moves(source_square, color, dest_mask);
-                    } else if (d.0 == 2) & (d.1 == 0) & (source_rank == 1) {
-                        table.set_double_moves(source_square, color, dest_mask);
-                    }
-                }
-                Color::Black => {
-                    if (d.0 == -1) & (d.1 == 0) {
-                        table.set_moves(source_square, color, dest_mask);
-                    } else if (d.0 == -2) & (d.1 == 0) & (source_rank == 6) {
-                        table.set_double_moves(source_square, color, dest_mask);
-                    }
-                }
+            if (d.0 == direction) & (d.1 == 0) {
+                table.set_moves(source_square, color, dest_mask);
+            } else if (d.0 == 2 * direction) & (d.1 == 0) & (source_rank == start_rank) {
+                table.set_double_moves(source_square, color, dest_mask);
             }
         });
     }
@@ -97,17 +87,8 @@ pub fn generate_pawn_captures(table: &mut PawnMoveTable, color: Color) {
             let destination_square = Square::new(dest_index).unwrap();
             let d = source_square.offsets_from(destination_square);
 
-            match color {
-                Color::White => {
-                    if (d.0 == 1) & (d.1.abs() == 1) {
-                        dest_mask |= BitBoard::from_square(Square::new(dest_index).unwrap());
-                    }
-                }
-                Color::Black => {
-                    if (d.0 == -1) & (d.1.abs() == 1) {
-                        dest_mask |= BitBoard::from_square(Square::new(dest_index).unwrap());
-                    }
-                }
+            if (d.0 == color.get_forward_direction()) & (d.1.abs() == 1) {
+                dest_mask |= BitBoard::from_square(Square::new(dest_index).unwrap());
             }
         });
         table.set_captures(source_square, color, dest_mask);
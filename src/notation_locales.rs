@@ -0,0 +1,174 @@
+//! Notation localization
+//!
+//! This module defines the set of piece symbols which is used to render SAN-moves and boards and
+//! to parse SAN-moves from non-English sources (e.g. German "Sf3" or Russian "Кf3").
+//! Only the piece letters are localized: squares, captures and castling are written the same way
+//! in all supported notations
+
+use crate::errors::LibChessError as Error;
+use crate::{Color, Piece, PieceType, COLORS_NUMBER, PIECE_TYPES_NUMBER};
+
+/// Symbols which can't be used as piece letters since they are parts of SAN itself
+const RESERVED_SYMBOLS: &str = "abcdefgh12345678x=+#-O0";
+
+/// Piece symbols used for SAN-moves and boards rendering. White's symbols are used in SAN
+///
+/// # Examples
+/// ```
+/// use libchess::{ChessBoard, NotationLocale, PieceType::*};
+/// let locale = NotationLocale::german();
+/// assert_eq!(locale.get_piece_letter(Knight), "S");
+///
+/// let board = ChessBoard::default();
+/// let m = board.parse_localized_san("Sf3", &locale).unwrap();
+/// assert_eq!(board.parse_san("Nf3").unwrap(), m);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotationLocale {
+    symbols: [[String; PIECE_TYPES_NUMBER]; COLORS_NUMBER],
+}
+
+impl Default for NotationLocale {
+    fn default() -> Self { Self::english() }
+}
+
+impl NotationLocale {
+    /// Creates the locale from the letters of pawn, knight, bishop, rook, queen and king (as they
+    /// are written in SAN). Black pieces are rendered with the lowercase letters
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidNotationLocale`` if any letter is empty, contains SAN symbols
+    /// (files, ranks, "x", "=", "+", "#", "-", "O") or letters are not unique
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{NotationLocale, PieceType::*};
+    /// let french = NotationLocale::new(["P", "C", "F", "T", "D", "R"]).unwrap();
+    /// assert_eq!(french.get_piece_letter(Bishop), "F");
+    /// assert!(NotationLocale::new(["P", "N", "b", "R", "Q", "K"]).is_err());
+    /// ```
+    pub fn new(letters: [&str; PIECE_TYPES_NUMBER]) -> Result<Self, Error> {
+        for (i, letter) in letters.iter().enumerate() {
+            if letter.is_empty()
+                | letter.chars().any(|c| RESERVED_SYMBOLS.contains(c))
+                | letters[..i].contains(letter)
+            {
+                return Err(Error::InvalidNotationLocale {
+                    letter: letter.to_string(),
+                });
+            }
+        }
+
+        Ok(Self {
+            symbols: [
+                letters.map(String::from),
+                letters.map(|letter| letter.to_lowercase()),
+            ],
+        })
+    }
+
+    pub fn english() -> Self { Self::new(["P", "N", "B", "R", "Q", "K"]).unwrap() }
+
+    pub fn german() -> Self { Self::new(["B", "S", "L", "T", "D", "K"]).unwrap() }
+
+    pub fn russian() -> Self { Self::new(["П", "К", "С", "Л", "Ф", "Кр"]).unwrap() }
+
+    /// Figurine notation: "♘f3", "exd8=♕". Both white and black figurines are accepted by the
+    /// parser
+    pub fn figurine() -> Self {
+        Self {
+            symbols: std::array::from_fn(|color| {
+                let color = Color::from_index(color).unwrap();
+                std::array::from_fn(|piece_type| {
+                    let piece_type = PieceType::from_index(piece_type).unwrap();
+                    Piece(piece_type, color).to_unicode().to_string()
+                })
+            }),
+        }
+    }
+
+    /// Returns the letter of the piece type which is used in SAN
+    #[inline]
+    pub fn get_piece_letter(&self, piece_type: PieceType) -> &str {
+        &self.symbols[Color::White.to_index()][piece_type.to_index()]
+    }
+
+    /// Returns the symbol of the piece which is used for boards rendering
+    #[inline]
+    pub fn get_piece_symbol(&self, piece: Piece) -> &str {
+        let Piece(piece_type, color) = piece;
+        &self.symbols[color.to_index()][piece_type.to_index()]
+    }
+
+    /// Translates the localized SAN-string to the English one. Pawn letters are left unchanged
+    /// since they are not used in SAN
+    pub(crate) fn to_english(&self, san: &str) -> String {
+        let english = Self::english();
+        let mut letters: Vec<(&str, PieceType)> = PieceType::iter()
+            .filter(|&piece_type| piece_type != PieceType::Pawn)
+            .flat_map(|piece_type| {
+                let white = self.get_piece_letter(piece_type);
+                let black = self.get_piece_symbol(Piece(piece_type, Color::Black));
+                // lowercase letters are not accepted to avoid clashes with files
+                let figurine = Piece(piece_type, Color::Black).to_unicode().to_string();
+                let black = (black == figurine).then_some((black, piece_type));
+                [Some((white, piece_type)), black].into_iter().flatten()
+            })
+            .collect();
+        letters.sort_by_key(|(letter, _)| std::cmp::Reverse(letter.chars().count()));
+
+        let mut result = String::new();
+        let mut rest = san;
+        'outer: while let Some(c) = rest.chars().next() {
+            for (letter, piece_type) in letters.iter() {
+                if let Some(tail) = rest.strip_prefix(letter) {
+                    result.push_str(english.get_piece_letter(*piece_type));
+                    rest = tail;
+                    continue 'outer;
+                }
+            }
+            result.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+        result
+    }
+
+    /// Returns the regex pattern matching a single piece letter of the locale (in any case)
+    pub(crate) fn get_piece_pattern(&self) -> String {
+        let mut letters: Vec<&str> = self.symbols.iter().flatten().map(|s| s.as_str()).collect();
+        letters.sort_by_key(|letter| std::cmp::Reverse(letter.chars().count()));
+        letters.dedup();
+        let alternatives: Vec<String> = letters.iter().map(|s| regex::escape(s)).collect();
+        format!("(?:{})", alternatives.join("|"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PieceType::*;
+
+    #[test]
+    fn locales() {
+        assert_eq!(NotationLocale::default(), NotationLocale::english());
+        assert!(NotationLocale::new(["P", "N", "B", "R", "Q", "N"]).is_err());
+        assert!(NotationLocale::new(["P", "", "B", "R", "Q", "K"]).is_err());
+
+        let russian = NotationLocale::russian();
+        assert_eq!(russian.get_piece_letter(King), "Кр");
+        assert_eq!(russian.get_piece_symbol(Piece(Rook, Color::Black)), "л");
+        assert_eq!(russian.to_english("Крe2"), "Ke2");
+        assert_eq!(russian.to_english("Кf3"), "Nf3");
+        assert_eq!(russian.to_english("exd8=Ф+"), "exd8=Q+");
+
+        // translated letters are not translated again
+        let german = NotationLocale::german();
+        assert_eq!(german.to_english("Lb5"), "Bb5");
+        assert_eq!(german.to_english("Txb7"), "Rxb7");
+
+        let figurine = NotationLocale::figurine();
+        assert_eq!(figurine.get_piece_symbol(Piece(Queen, Color::Black)), "♛");
+        assert_eq!(figurine.to_english("♘f3"), "Nf3");
+        assert_eq!(figurine.to_english("♞f6"), "Nf6");
+    }
+}
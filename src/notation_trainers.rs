@@ -0,0 +1,150 @@
+//! Notation trainers
+//!
+//! This module implements the exercise of the notation trainer: the learner is shown the
+//! position and the move, and types the move in SAN. The typed answer is validated tolerantly:
+//! the check and checkmate marks, the move annotations ("!", "?!") and the promotion without "="
+//! are accepted, as well as the castling written with zeros or with the lowercase letters. The
+//! disambiguation and the capture mark are required, because writing them is the skill trained
+
+use crate::chess_boards::normalize_san_promotion;
+use crate::errors::LibChessError as Error;
+use crate::{BoardMove, ChessBoard, MovePropertiesOnBoard};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// The exercise of writing the move in SAN
+///
+/// # Examples
+/// ```
+/// use libchess::{mv, squares::*, BoardMove, ChessBoard, NotationQuiz, PieceMove, PieceType::*};
+/// let board = ChessBoard::from_fen("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+///
+/// let quiz = NotationQuiz::new(board, BoardMove::CastleKingSide).unwrap();
+/// assert_eq!(quiz.get_san(), "O-O");
+/// assert!(quiz.check_answer("0-0") & quiz.check_answer(" o-o "));
+/// assert!(!quiz.check_answer("O-O-O"));
+///
+/// let quiz = NotationQuiz::new(board, mv!(Rook, A1, A8)).unwrap();
+/// assert_eq!(quiz.get_san(), "Ra8+");
+/// assert!(quiz.check_answer("Ra8") & quiz.check_answer("Ra8+!"));
+/// assert!(!quiz.check_answer("a1a8"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct NotationQuiz {
+    position:   ChessBoard,
+    board_move: BoardMove,
+    properties: MovePropertiesOnBoard,
+    san:        String,
+}
+
+impl NotationQuiz {
+    /// Creates the exercise of writing the move made on the board
+    ///
+    /// # Errors
+    /// ``LibChessError::IllegalMoveDetected`` if the move is illegal on the board
+    pub fn new(position: ChessBoard, board_move: BoardMove) -> Result<Self, Error> {
+        let properties = MovePropertiesOnBoard::new(&board_move, &position)?;
+        Ok(Self {
+            position,
+            board_move,
+            properties,
+            san: board_move.to_string(properties),
+        })
+    }
+
+    /// Creates the exercise of writing the random legal move made on the board (None if there
+    /// are no legal moves). The exercise is reproducible with the seeded generator
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{ChessBoard, NotationQuiz};
+    /// use rand::rngs::StdRng;
+    /// use rand::SeedableRng;
+    ///
+    /// let board = ChessBoard::default();
+    /// let quiz = NotationQuiz::random(&board, &mut StdRng::seed_from_u64(0)).unwrap();
+    /// assert!(board.get_legal_moves().contains(&quiz.get_move()));
+    /// assert!(quiz.check_answer(quiz.get_san()));
+    /// ```
+    pub fn random(position: &ChessBoard, rng: &mut impl Rng) -> Option<Self> {
+        let board_move = *position.get_legal_moves().choose(rng)?;
+        Self::new(*position, board_move).ok()
+    }
+
+    #[inline]
+    pub fn get_position(&self) -> ChessBoard { self.position }
+
+    #[inline]
+    pub fn get_move(&self) -> BoardMove { self.board_move }
+
+    #[inline]
+    pub fn get_properties(&self) -> MovePropertiesOnBoard { self.properties }
+
+    /// Returns the expected answer (the SAN of the move with the check or checkmate mark)
+    #[inline]
+    pub fn get_san(&self) -> &str { &self.san }
+
+    /// Returns true if the answer is the SAN of the move (see the module documentation for the
+    /// accepted variations)
+    pub fn check_answer(&self, answer: &str) -> bool {
+        normalize_answer(answer) == normalize_answer(&self.san)
+    }
+}
+
+/// Brings the answer to the form which is compared with the expected one: without the marks
+/// and annotations, with the standard castling spelling and the promotion suffix
+fn normalize_answer(answer: &str) -> String {
+    let answer = answer
+        .trim()
+        .trim_end_matches(['!', '?'])
+        .trim_end_matches(['+', '#']);
+    if answer.contains('-') & answer.chars().all(|c| "0oO-".contains(c)) {
+        answer.replace(['0', 'o'], "O")
+    } else {
+        normalize_san_promotion(answer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mv, squares::*, PieceMove, PieceType::*};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::str::FromStr;
+
+    #[test]
+    fn notation_quizzes() {
+        let board = ChessBoard::from_str("r3k3/1P6/8/8/8/2N3N1/8/4K3 w q - 0 1").unwrap();
+        let quiz = NotationQuiz::new(board, mv!(Pawn, B7, A8, Knight)).unwrap();
+        assert_eq!(quiz.get_san(), "bxa8=N");
+        assert!(quiz.get_properties().is_capture);
+        for answer in ["bxa8=N", "bxa8N", "bxa8=n", "bxa8=N?!"] {
+            assert!(quiz.check_answer(answer), "{answer}");
+        }
+        for answer in ["ba8=N", "bxa8=Q", "bxa8", "b7a8n", ""] {
+            assert!(!quiz.check_answer(answer), "{answer}");
+        }
+
+        // the disambiguation is required
+        let quiz = NotationQuiz::new(board, mv!(Knight, C3, E4)).unwrap();
+        assert_eq!(quiz.get_san(), "Nce4");
+        assert!(quiz.check_answer("Nce4") & !quiz.check_answer("Ne4"));
+
+        let board = ChessBoard::from_str("r3k3/8/8/8/8/2N3N1/8/4K3 b q - 0 1").unwrap();
+        let quiz = NotationQuiz::new(board, BoardMove::CastleQueenSide).unwrap();
+        assert!(quiz.check_answer("0-0-0") & quiz.check_answer("O-O-O"));
+        assert!(!quiz.check_answer("0-0") & !quiz.check_answer("000"));
+        assert!(NotationQuiz::new(board, BoardMove::CastleKingSide).is_err());
+
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..20 {
+            let quiz = NotationQuiz::random(&board, &mut rng).unwrap();
+            assert_eq!(quiz.get_position(), board);
+            assert_eq!(board.parse_san(quiz.get_san()).unwrap(), quiz.get_move());
+            assert!(quiz.check_answer(quiz.get_san()));
+        }
+        let stalemate = ChessBoard::from_str("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert!(NotationQuiz::random(&stalemate, &mut rng).is_none());
+    }
+}
@@ -0,0 +1,175 @@
+//! Performance tests (perft)
+//!
+//! This module implements the counting of the leaf nodes of the legal moves tree (see
+//! ``ChessBoard::perft``) and the detailed variant which also counts the kinds of moves on each
+//! depth (see ``ChessBoard::perft_detailed``). The detailed numbers are compared with the
+//! reference tables to find which move class the bug of a generator lives in
+
+use crate::{BoardMove, BoardStatus, ChessBoard, MoveFlags, PositionCore};
+use std::collections::HashMap;
+use std::ops::AddAssign;
+
+/// Statistics of the moves made on one depth of the perft tree
+///
+/// # Examples
+/// ```
+/// use libchess::ChessBoard;
+/// let stats = ChessBoard::default().perft_detailed(3);
+/// assert_eq!(stats[2].nodes, 8902);
+/// assert_eq!(stats[2].captures, 34);
+/// assert_eq!(stats[2].checks, 12);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct PerftStats {
+    pub nodes:       u64,
+    /// Captures including en passant ones
+    pub captures:    u64,
+    pub en_passants: u64,
+    pub castles:     u64,
+    pub promotions:  u64,
+    /// Moves giving check including checkmates
+    pub checks:      u64,
+    pub checkmates:  u64,
+}
+
+impl AddAssign for PerftStats {
+    fn add_assign(&mut self, other: Self) {
+        self.nodes += other.nodes;
+        self.captures += other.captures;
+        self.en_passants += other.en_passants;
+        self.castles += other.castles;
+        self.promotions += other.promotions;
+        self.checks += other.checks;
+        self.checkmates += other.checkmates;
+    }
+}
+
+impl PerftStats {
+    /// Returns the statistics of a single move made on the board
+    fn from_move(board_move: &BoardMove, board: &ChessBoard, next: &ChessBoard) -> Self {
+        let flags = MoveFlags::new(board_move, board);
+        Self {
+            nodes:       1,
+            captures:    flags.is_capture as u64,
+            en_passants: flags.is_en_passant as u64,
+            castles:     flags.is_castling as u64,
+            promotions:  flags.is_promotion as u64,
+            checks:      !next.get_check_mask().is_blank() as u64,
+            checkmates:  (next.get_status() == BoardStatus::CheckMated(next.get_side_to_move()))
+                as u64,
+        }
+    }
+}
+
+/// Counts the leaf nodes of ``depth`` plies. The last ply is counted without making the moves
+/// (bulk counting) and the results of subtrees are stored in the table, so the transpositions
+/// are counted only once. The table is keyed by ``PositionCore`` which is much smaller than
+/// ``ChessBoard``
+pub(crate) fn count_nodes(
+    board: &ChessBoard,
+    depth: usize,
+    table: &mut HashMap<(PositionCore, usize), u64>,
+) -> u64 {
+    match depth {
+        0 => return 1,
+        1 => return board.count_legal_moves() as u64,
+        _ => {}
+    }
+    let key = (PositionCore::from(board), depth);
+    if let Some(&nodes) = table.get(&key) {
+        return nodes;
+    }
+
+    let nodes = board
+        .get_legal_moves()
+        .iter()
+        .map(|m| count_nodes(&board.make_move(m).unwrap(), depth - 1, table))
+        .sum();
+    table.insert(key, nodes);
+    nodes
+}
+
+/// Adds the statistics of the moves of the subtree to ``stats`` (the first item is for the
+/// moves made on this board)
+pub(crate) fn collect_stats(board: &ChessBoard, stats: &mut [PerftStats]) {
+    let Some((current, deeper)) = stats.split_first_mut() else {
+        return;
+    };
+    for m in board.get_legal_moves() {
+        let next = board.make_move(&m).unwrap();
+        *current += PerftStats::from_move(&m, board, &next);
+        collect_stats(&next, deeper);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn detailed_perft() {
+        // "Kiwipete" position with the reference numbers for depths 1..=3
+        let board = ChessBoard::from_str(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        )
+        .unwrap();
+        let stats = board.perft_detailed(3);
+        let expected = [
+            (48, 8, 0, 2, 0, 0, 0),
+            (2039, 351, 1, 91, 0, 3, 0),
+            (97862, 17102, 45, 3162, 0, 993, 1),
+        ];
+        for (s, e) in stats.iter().zip(expected) {
+            assert_eq!(
+                (
+                    s.nodes,
+                    s.captures,
+                    s.en_passants,
+                    s.castles,
+                    s.promotions,
+                    s.checks,
+                    s.checkmates
+                ),
+                e
+            );
+        }
+        for (depth, s) in stats.iter().enumerate() {
+            assert_eq!(board.perft(depth + 1), s.nodes);
+        }
+        assert_eq!(board.perft(0), 1);
+        assert!(board.perft_detailed(0).is_empty());
+
+        // promotions and checkmates
+        let board = ChessBoard::from_str(
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        )
+        .unwrap();
+        let stats = board.perft_detailed(3);
+        assert_eq!(
+            stats[1],
+            PerftStats {
+                nodes:       264,
+                captures:    87,
+                en_passants: 0,
+                castles:     6,
+                promotions:  48,
+                checks:      10,
+                checkmates:  0,
+            }
+        );
+        assert_eq!(
+            stats[2],
+            PerftStats {
+                nodes:       9467,
+                captures:    1021,
+                en_passants: 4,
+                castles:     0,
+                promotions:  120,
+                checks:      38,
+                checkmates:  22,
+            }
+        );
+        assert_eq!(board.perft(4), 422333);
+    }
+}
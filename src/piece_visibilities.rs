@@ -0,0 +1,67 @@
+//! Piece visibility
+//!
+//! This module defines which pieces are shown on the rendered boards (see
+//! ``ChessBoard::render_partial``). Hiding the pieces is used by the training tools: blindfold
+//! play, visualization exercises and pawn structure studies
+
+use crate::{Color, Piece, PieceType, COLORS_NUMBER, PIECE_TYPES_NUMBER};
+
+/// Set of the piece types and colors hidden on the rendered board. The piece is shown only if
+/// neither its type nor its color is hidden
+///
+/// # Examples
+/// ```
+/// use libchess::{Color::*, Piece, PieceType::*, PieceVisibility};
+/// let mut visibility = PieceVisibility::all();
+/// visibility.hide_piece_type(Queen).hide_color(Black);
+/// assert!(visibility.is_visible(Piece(Rook, White)));
+/// assert!(!visibility.is_visible(Piece(Queen, White)));
+/// assert!(!visibility.is_visible(Piece(Rook, Black)));
+///
+/// assert!(PieceVisibility::pawn_structure().is_visible(Piece(Pawn, Black)));
+/// assert!(!PieceVisibility::pawn_structure().is_visible(Piece(King, Black)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct PieceVisibility {
+    hidden_piece_types: [bool; PIECE_TYPES_NUMBER],
+    hidden_colors:      [bool; COLORS_NUMBER],
+}
+
+impl PieceVisibility {
+    /// All pieces are shown
+    #[inline]
+    pub fn all() -> Self { Self::default() }
+
+    /// All pieces are hidden, only the empty board is shown
+    #[inline]
+    pub fn blindfold() -> Self {
+        Self {
+            hidden_piece_types: [true; PIECE_TYPES_NUMBER],
+            hidden_colors:      [true; COLORS_NUMBER],
+        }
+    }
+
+    /// Only pawns of both sides are shown
+    pub fn pawn_structure() -> Self {
+        let mut result = Self::all();
+        for piece_type in PieceType::iter().filter(|&p| p != PieceType::Pawn) {
+            result.hide_piece_type(piece_type);
+        }
+        result
+    }
+
+    pub fn hide_piece_type(&mut self, piece_type: PieceType) -> &mut Self {
+        self.hidden_piece_types[piece_type.to_index()] = true;
+        self
+    }
+
+    pub fn hide_color(&mut self, color: Color) -> &mut Self {
+        self.hidden_colors[color.to_index()] = true;
+        self
+    }
+
+    #[inline]
+    pub fn is_visible(&self, piece: Piece) -> bool {
+        !self.hidden_piece_types[piece.0.to_index()] & !self.hidden_colors[piece.1.to_index()]
+    }
+}
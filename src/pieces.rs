@@ -1,5 +1,5 @@
 use crate::errors::LibChessError as Error;
-use crate::Color;
+use crate::{Color, COLORS_NUMBER};
 use std::fmt;
 use std::str::FromStr;
 
@@ -15,21 +15,18 @@ pub enum PieceType {
     King,
 }
 
+/// Letters of the piece types (in the ``PieceType`` order). White pieces are written with the
+/// uppercase letters, black ones with the lowercase letters
+const PIECE_LETTERS: [char; PIECE_TYPES_NUMBER] = ['P', 'N', 'B', 'R', 'Q', 'K'];
+
+/// Figurines of white and black pieces (in the ``PieceType`` order)
+const FIGURINES: [[char; PIECE_TYPES_NUMBER]; COLORS_NUMBER] = [
+    ['♙', '♘', '♗', '♖', '♕', '♔'],
+    ['♟', '♞', '♝', '♜', '♛', '♚'],
+];
+
 impl fmt::Display for PieceType {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "{}",
-            match *self {
-                PieceType::Pawn => "P",
-                PieceType::Knight => "N",
-                PieceType::Bishop => "B",
-                PieceType::Rook => "R",
-                PieceType::Queen => "Q",
-                PieceType::King => "K",
-            }
-        )
-    }
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{}", self.to_char()) }
 }
 
 impl FromStr for PieceType {
@@ -40,17 +37,9 @@ impl FromStr for PieceType {
             return Err(Error::InvalidPeaceRepresentation);
         }
 
-        if value.is_empty() {
-            return Ok(PieceType::Pawn);
-        }
-        match value.to_uppercase().as_str().chars().next().unwrap() {
-            'P' => Ok(PieceType::Pawn),
-            'N' => Ok(PieceType::Knight),
-            'B' => Ok(PieceType::Bishop),
-            'R' => Ok(PieceType::Rook),
-            'Q' => Ok(PieceType::Queen),
-            'K' => Ok(PieceType::King),
-            _ => Err(Error::InvalidPeaceRepresentation),
+        match value.chars().next() {
+            None => Ok(PieceType::Pawn),
+            Some(c) => PieceType::from_char(c),
         }
     }
 }
@@ -75,11 +64,102 @@ impl PieceType {
         use PieceType::*;
         [Pawn, Knight, Bishop, Rook, Queen, King].iter().copied()
     }
+
+    /// Returns the uppercase letter of the piece type (as it is written in SAN)
+    #[inline]
+    pub fn to_char(&self) -> char { PIECE_LETTERS[self.to_index()] }
+
+    /// Parses the letter of the piece type. Both uppercase and lowercase letters are accepted
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidPeaceRepresentation`` if the letter is not one of "PNBRQK" (in any
+    /// case)
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::PieceType;
+    /// assert_eq!(PieceType::from_char('n').unwrap(), PieceType::Knight);
+    /// assert_eq!(PieceType::from_char('N').unwrap(), PieceType::Knight);
+    /// assert!(PieceType::from_char('x').is_err());
+    /// ```
+    pub fn from_char(c: char) -> Result<Self, Error> {
+        PIECE_LETTERS
+            .iter()
+            .position(|&letter| letter == c.to_ascii_uppercase())
+            .map(|n| PieceType::from_index(n).unwrap())
+            .ok_or(Error::InvalidPeaceRepresentation)
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// The piece of specified color
+///
+/// The piece is displayed by the letter of its type in the case of its color (as it is written
+/// in FEN), the alternate format ("{:#}") displays the figurine
+///
+/// # Examples
+/// ```
+/// use libchess::{Color::*, Piece, PieceType::*};
+/// assert_eq!(format!("{}", Piece(Knight, White)), "N");
+/// assert_eq!(format!("{:#}", Piece(Knight, Black)), "♞");
+/// assert_eq!("q".parse::<Piece>().unwrap(), Piece(Queen, Black));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Piece(pub PieceType, pub Color);
 
+impl fmt::Display for Piece {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match f.alternate() {
+            true => write!(f, "{}", self.to_unicode()),
+            false => write!(f, "{}", self.to_char()),
+        }
+    }
+}
+
+impl FromStr for Piece {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut chars = value.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Piece::from_char(c),
+            _ => Err(Error::InvalidPeaceRepresentation),
+        }
+    }
+}
+
+impl Piece {
+    /// Returns the letter of the piece: uppercase for white and lowercase for black
+    #[inline]
+    pub fn to_char(&self) -> char {
+        let Piece(piece_type, color) = *self;
+        match color {
+            Color::White => piece_type.to_char(),
+            Color::Black => piece_type.to_char().to_ascii_lowercase(),
+        }
+    }
+
+    /// Returns the figurine of the piece (e.g. '♘' for white knight and '♞' for black one)
+    #[inline]
+    pub fn to_unicode(&self) -> char {
+        let Piece(piece_type, color) = *self;
+        FIGURINES[color.to_index()][piece_type.to_index()]
+    }
+
+    /// Parses the letter of the piece: the uppercase letters are white pieces and the lowercase
+    /// ones are black pieces
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidPeaceRepresentation`` if the letter is not one of "PNBRQK" (in any
+    /// case)
+    pub fn from_char(c: char) -> Result<Self, Error> {
+        let color = match c.is_ascii_uppercase() {
+            true => Color::White,
+            false => Color::Black,
+        };
+        Ok(Piece(PieceType::from_char(c)?, color))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -100,5 +180,30 @@ mod tests {
         assert_eq!(PieceType::from_str("R").unwrap(), PieceType::Rook);
         assert_eq!(PieceType::from_str("Q").unwrap(), PieceType::Queen);
         assert_eq!(PieceType::from_str("K").unwrap(), PieceType::King);
+        assert_eq!(PieceType::from_str("k").unwrap(), PieceType::King);
+        assert!(PieceType::from_str("KK").is_err());
+        assert!(PieceType::from_str("Ж").is_err());
+    }
+
+    #[test]
+    fn piece_chars() {
+        for piece_type in PieceType::iter() {
+            assert_eq!(
+                PieceType::from_char(piece_type.to_char()).unwrap(),
+                piece_type
+            );
+            for color in Color::iter() {
+                let piece = Piece(piece_type, color);
+                assert_eq!(Piece::from_char(piece.to_char()).unwrap(), piece);
+                assert_eq!(Piece::from_str(&piece.to_string()).unwrap(), piece);
+                assert_eq!(piece.to_char().is_ascii_uppercase(), color == Color::White);
+            }
+        }
+        assert_eq!(Piece(PieceType::Pawn, Color::Black).to_char(), 'p');
+        assert_eq!(Piece(PieceType::King, Color::White).to_unicode(), '♔');
+        assert_eq!(format!("{:#}", Piece(PieceType::Rook, Color::Black)), "♜");
+        for s in ["", "x", "1", "nn", "♘"] {
+            assert!(Piece::from_str(s).is_err(), "{s}");
+        }
     }
 }
@@ -0,0 +1,81 @@
+//! Random playouts
+//!
+//! This module defines the policies of choosing moves in the random playouts (see
+//! ``ChessBoard::random_playout``) which are the basic building block of Monte-Carlo tree search
+
+use crate::{BoardMove, ChessBoard};
+use rand::distributions::WeightedIndex;
+use rand::prelude::*;
+
+/// Policy of choosing the moves in random playouts. Every legal move gets the weight 1, which is
+/// multiplied by ``capture_weight`` for captures and by ``check_weight`` for checks, and the
+/// move is chosen randomly in proportion to the weights
+///
+/// # Examples
+/// ```
+/// use libchess::{ChessBoard, PlayoutPolicy};
+/// use rand::rngs::StdRng;
+/// use rand::SeedableRng;
+///
+/// let mut rng = StdRng::seed_from_u64(0);
+/// let policy = PlayoutPolicy::new(4.0, 2.0); // captures are 4 times more likely than quiet moves
+/// let (status, moves) = ChessBoard::default().random_playout(&policy, &mut rng, 200);
+/// println!("{status} after {} plies", moves.len());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlayoutPolicy {
+    pub capture_weight: f64,
+    pub check_weight:   f64,
+}
+
+impl Default for PlayoutPolicy {
+    /// The uniform policy: all legal moves are equally likely
+    #[inline]
+    fn default() -> Self { Self::new(1.0, 1.0) }
+}
+
+impl PlayoutPolicy {
+    #[inline]
+    pub fn new(capture_weight: f64, check_weight: f64) -> Self {
+        Self {
+            capture_weight,
+            check_weight,
+        }
+    }
+
+    /// Returns true if all legal moves are equally likely
+    #[inline]
+    pub fn is_uniform(&self) -> bool { (self.capture_weight == 1.0) & (self.check_weight == 1.0) }
+
+    /// Returns the weight of the legal move on the board (the move must be legal)
+    pub(crate) fn get_weight(&self, board: &ChessBoard, board_move: &BoardMove) -> f64 {
+        let mut weight = 1.0;
+        if board.get_move_flags(board_move).is_capture {
+            weight *= self.capture_weight;
+        }
+        if self.check_weight != 1.0 {
+            // the move is legal, so the unchecked path is safe here
+            let next_board = unsafe { board.make_move_unchecked(board_move) };
+            if !next_board.get_check_mask().is_blank() {
+                weight *= self.check_weight;
+            }
+        }
+        weight
+    }
+
+    /// Chooses one of the legal moves of the board according to the policy. Returns ``None`` if
+    /// there are no moves or all of them have zero weights
+    pub(crate) fn choose(
+        &self,
+        board: &ChessBoard,
+        moves: &[BoardMove],
+        rng: &mut impl Rng,
+    ) -> Option<BoardMove> {
+        if self.is_uniform() {
+            return moves.choose(rng).copied();
+        }
+        let weights = moves.iter().map(|m| self.get_weight(board, m));
+        let index = WeightedIndex::new(weights).ok()?;
+        Some(moves[index.sample(rng)])
+    }
+}
@@ -0,0 +1,170 @@
+//! Ply and move number types
+//!
+//! Ply is a single move of one side (counting from 0), while the move number is a full move of
+//! both sides (counting from 1, as in PGN). Separate types prevent the confusion between them
+
+use crate::Color;
+use std::fmt;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+
+/// Index of the half-move in the game (counting from 0)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ply(usize);
+
+/// Number of the full move (counting from 1). Increments every time after black makes move
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MoveNumber(usize);
+
+impl Ply {
+    #[inline]
+    pub fn new(ply: usize) -> Self { Self(ply) }
+
+    #[inline]
+    pub fn to_index(&self) -> usize { self.0 }
+
+    /// Returns the ply of the move made by specified side on specified move number (for the
+    /// game started from the initial position)
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{Color, MoveNumber, Ply};
+    /// assert_eq!(
+    ///     Ply::from_move_number(MoveNumber::new(1), Color::White),
+    ///     Ply::new(0)
+    /// );
+    /// assert_eq!(
+    ///     Ply::from_move_number(MoveNumber::new(3), Color::Black),
+    ///     Ply::new(5)
+    /// );
+    /// ```
+    pub fn from_move_number(move_number: MoveNumber, side: Color) -> Self {
+        Self((move_number.0 - 1) * 2 + side.to_index())
+    }
+
+    /// Returns the move number of this ply (for the game started from the initial position)
+    #[inline]
+    pub fn get_move_number(&self) -> MoveNumber { MoveNumber(self.0 / 2 + 1) }
+
+    /// Returns the side which makes the move on this ply (for the game started from the initial
+    /// position)
+    #[inline]
+    pub fn get_side(&self) -> Color { Color::from_index(self.0 % 2).unwrap() }
+}
+
+impl MoveNumber {
+    /// Creates the move number
+    ///
+    /// # Panics
+    /// If ``move_number`` is 0
+    #[inline]
+    pub fn new(move_number: usize) -> Self {
+        assert!(move_number > 0, "move number starts from 1");
+        Self(move_number)
+    }
+
+    /// Creates the move number from the fullmove counter of the position. The counter 0 (which
+    /// is accepted in FEN) is treated as the first move
+    #[inline]
+    pub(crate) fn from_counter(counter: usize) -> Self { Self(counter.max(1)) }
+
+    #[inline]
+    pub fn to_usize(&self) -> usize { self.0 }
+}
+
+impl Default for MoveNumber {
+    fn default() -> Self { Self(1) }
+}
+
+impl From<usize> for Ply {
+    fn from(ply: usize) -> Self { Self(ply) }
+}
+
+impl From<Ply> for usize {
+    fn from(ply: Ply) -> Self { ply.0 }
+}
+
+impl From<MoveNumber> for usize {
+    fn from(move_number: MoveNumber) -> Self { move_number.0 }
+}
+
+impl fmt::Display for Ply {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{}", self.0) }
+}
+
+impl fmt::Display for MoveNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{}", self.0) }
+}
+
+impl Add<usize> for Ply {
+    type Output = Ply;
+
+    fn add(self, other: usize) -> Ply { Ply(self.0 + other) }
+}
+
+impl AddAssign<usize> for Ply {
+    fn add_assign(&mut self, other: usize) { self.0 += other; }
+}
+
+impl Sub<usize> for Ply {
+    type Output = Ply;
+
+    fn sub(self, other: usize) -> Ply { Ply(self.0 - other) }
+}
+
+impl SubAssign<usize> for Ply {
+    fn sub_assign(&mut self, other: usize) { self.0 -= other; }
+}
+
+impl Sub for Ply {
+    type Output = usize;
+
+    fn sub(self, other: Ply) -> usize { self.0 - other.0 }
+}
+
+impl Add<usize> for MoveNumber {
+    type Output = MoveNumber;
+
+    fn add(self, other: usize) -> MoveNumber { MoveNumber(self.0 + other) }
+}
+
+impl AddAssign<usize> for MoveNumber {
+    fn add_assign(&mut self, other: usize) { self.0 += other; }
+}
+
+impl Sub for MoveNumber {
+    type Output = usize;
+
+    fn sub(self, other: MoveNumber) -> usize { self.0 - other.0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        for ply in 0..10 {
+            let ply = Ply::new(ply);
+            assert_eq!(
+                Ply::from_move_number(ply.get_move_number(), ply.get_side()),
+                ply
+            );
+        }
+        assert_eq!(Ply::new(4).get_move_number(), MoveNumber::new(3));
+        assert_eq!(Ply::new(3).get_side(), Color::Black);
+        assert_eq!(usize::from(Ply::from(7)), 7);
+    }
+
+    #[test]
+    fn arithmetic() {
+        let mut ply = Ply::new(3) + 2;
+        ply -= 1;
+        assert_eq!(ply, Ply::new(4));
+        assert_eq!(Ply::new(10) - Ply::new(4), 6);
+
+        let mut move_number = MoveNumber::default() + 1;
+        move_number += 1;
+        assert_eq!(move_number.to_usize(), 3);
+        assert_eq!(MoveNumber::new(5) - MoveNumber::new(2), 3);
+    }
+}
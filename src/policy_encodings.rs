@@ -0,0 +1,165 @@
+//! Policy encodings
+//!
+//! This module implements the AlphaZero-style encoding of the moves for the policy heads of the
+//! neural networks (see ``ChessBoard::get_policy_index``). Every move is described by its source
+//! square and by one of 73 planes: 56 planes of the queen-like moves (8 directions by 7
+//! distances), 8 planes of the knight moves and 9 planes of the underpromotions (to knight,
+//! bishop and rook by the left capture, the push and the right capture). The index of the move
+//! is ``plane * 64 + source``, so the policy is the 73x64 table
+//!
+//! The squares are taken from the point of view of the side to move: the board is flipped
+//! vertically for black, so the same move of both sides has the same index. The promotions to
+//! queen and the castling (as the move of the king by two squares) are encoded by the queen-like
+//! planes
+
+use crate::{Color, PieceType, Square, SQUARES_NUMBER};
+
+/// Number of the move planes of each source square
+pub const POLICY_PLANES_NUMBER: usize = 73;
+
+/// Size of the policy table (the number of the possible indexes)
+pub const POLICY_SIZE: usize = POLICY_PLANES_NUMBER * SQUARES_NUMBER;
+
+/// Directions of the queen-like moves as (rank, file) offsets of one step
+const QUEEN_DIRECTIONS: [(i32, i32); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+/// Maximal distance of the queen-like moves
+const QUEEN_MAX_DISTANCE: usize = 7;
+
+/// Offsets of the knight moves as (rank, file) offsets
+const KNIGHT_OFFSETS: [(i32, i32); 8] = [
+    (2, 1),
+    (1, 2),
+    (-1, 2),
+    (-2, 1),
+    (-2, -1),
+    (-1, -2),
+    (1, -2),
+    (2, -1),
+];
+
+const KNIGHT_PLANES_OFFSET: usize = QUEEN_DIRECTIONS.len() * QUEEN_MAX_DISTANCE;
+const UNDERPROMOTION_PLANES_OFFSET: usize = KNIGHT_PLANES_OFFSET + KNIGHT_OFFSETS.len();
+
+/// The pieces of the underpromotions in the order of their planes
+const UNDERPROMOTION_PIECES: [PieceType; 3] =
+    [PieceType::Knight, PieceType::Bishop, PieceType::Rook];
+
+/// Returns the square from the point of view of the side to move
+#[inline]
+fn oriented(square: Square, side_to_move: Color) -> Square {
+    match side_to_move {
+        Color::White => square,
+        Color::Black => Square::new(square.to_int() ^ 56).unwrap(),
+    }
+}
+
+/// Returns the policy index of the move of the side to move or None if the geometry of the move
+/// can't be encoded (it is neither queen-like, nor knight-like, nor the underpromotion)
+pub(crate) fn encode_move(
+    source: Square,
+    destination: Square,
+    promotion: Option<PieceType>,
+    side_to_move: Color,
+) -> Option<usize> {
+    let source = oriented(source, side_to_move);
+    let (ranks, files) = source.offsets_from(oriented(destination, side_to_move));
+
+    let plane = match promotion {
+        Some(piece_type) if piece_type != PieceType::Queen => {
+            let piece = UNDERPROMOTION_PIECES
+                .iter()
+                .position(|&p| p == piece_type)?;
+            if (ranks != 1) | (files.abs() > 1) {
+                return None;
+            }
+            UNDERPROMOTION_PLANES_OFFSET + piece * 3 + (files + 1) as usize
+        }
+        _ => match KNIGHT_OFFSETS.iter().position(|&o| o == (ranks, files)) {
+            Some(i) => KNIGHT_PLANES_OFFSET + i,
+            None => {
+                let distance = ranks.abs().max(files.abs());
+                if (distance == 0) | ((ranks != 0) & (files != 0) & (ranks.abs() != files.abs())) {
+                    return None;
+                }
+                let direction = (ranks.signum(), files.signum());
+                let d = QUEEN_DIRECTIONS.iter().position(|&o| o == direction)?;
+                d * QUEEN_MAX_DISTANCE + distance as usize - 1
+            }
+        },
+    };
+    Some(plane * SQUARES_NUMBER + source.to_index())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mv, squares::*, BoardMove, ChessBoard, PieceMove, PieceType::*};
+    use std::collections::HashSet;
+    use std::str::FromStr;
+
+    #[test]
+    fn policy_indexes() {
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1",
+            "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1",
+            "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N w - - 0 1",
+        ] {
+            let board = ChessBoard::from_str(fen).unwrap();
+            let legal_moves = board.get_legal_moves();
+            let indexes = legal_moves
+                .iter()
+                .map(|m| board.get_policy_index(m).unwrap())
+                .collect::<HashSet<_>>();
+            assert_eq!(indexes.len(), legal_moves.len(), "{fen}");
+            assert!(indexes.iter().all(|&i| i < POLICY_SIZE));
+            for m in legal_moves.iter() {
+                let index = board.get_policy_index(m).unwrap();
+                assert_eq!(board.get_policy_move(index), Some(*m));
+            }
+            let mask = board.get_policy_mask();
+            assert_eq!(
+                (0..POLICY_SIZE)
+                    .filter(|&i| mask[i])
+                    .collect::<HashSet<_>>(),
+                indexes
+            );
+        }
+
+        // the promotions by the capture to the right from B7 (square 49): the queen one is the
+        // diagonal move by one square, the underpromotions have own planes
+        let board = ChessBoard::from_str("n1n5/PPPk4/8/8/8/8/4Kppp/4RN1N w - - 0 1").unwrap();
+        let capture = |promotion| {
+            board.get_policy_index(&BoardMove::MovePiece(
+                PieceMove::new(Pawn, B7, C8, promotion).unwrap(),
+            ))
+        };
+        assert_eq!(capture(Some(Queen)), Some(7 * 64 + 49));
+        assert_eq!(capture(Some(Knight)), Some((64 + 2) * 64 + 49));
+        assert_eq!(capture(Some(Rook)), Some((64 + 2 * 3 + 2) * 64 + 49));
+
+        // the castling is the king move by two squares to the east or to the west
+        let board = ChessBoard::default();
+        assert_eq!(
+            board.get_policy_index(&BoardMove::CastleKingSide),
+            Some((2 * 7 + 1) * 64 + 4)
+        );
+        assert_eq!(
+            board.get_policy_index(&BoardMove::CastleQueenSide),
+            Some((6 * 7 + 1) * 64 + 4)
+        );
+        assert_eq!(board.get_policy_index(&mv!(Knight, A1, B4)), None);
+        assert_eq!(board.get_policy_index(&mv!(Knight, B1, B1)), None);
+        assert_eq!(board.get_policy_move(POLICY_SIZE), None);
+    }
+}
@@ -0,0 +1,92 @@
+//! Compact position representation
+//!
+//! ``PositionCore`` contains only the state which can't be derived from the pieces placement
+//! (the masks of pieces, side to move, castling rights, en-passant square, move counters and the
+//! hash). It is cheaper to copy and store than ``ChessBoard`` (e.g. in search stacks or
+//! transposition tables), while the pins, checks and terminal status are recalculated on the
+//! conversion back to ``ChessBoard``
+
+use crate::{
+    BitBoard, CastlingRights, Color, PieceType, PositionHashValueType, Square, COLORS_NUMBER,
+    PIECE_TYPES_NUMBER,
+};
+
+/// Minimal representation of the valid position. Can be created only from ``ChessBoard``, so it
+/// is always valid
+///
+/// # Examples
+/// ```
+/// use libchess::{ChessBoard, PositionCore};
+/// let board = ChessBoard::default();
+/// let core = PositionCore::from(&board);
+/// assert_eq!(core.get_hash(), board.get_hash());
+/// assert_eq!(ChessBoard::from(core), board);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PositionCore {
+    pub(crate) pieces_mask: [BitBoard; PIECE_TYPES_NUMBER],
+    pub(crate) colors_mask: [BitBoard; COLORS_NUMBER],
+    pub(crate) hash: PositionHashValueType,
+    pub(crate) moves_since_capture_or_pawn_move: u32,
+    pub(crate) move_number: u32,
+    pub(crate) side_to_move: Color,
+    pub(crate) castle_rights: [CastlingRights; COLORS_NUMBER],
+    pub(crate) en_passant: Option<Square>,
+}
+
+impl PositionCore {
+    #[inline]
+    pub fn get_piece_type_mask(&self, piece_type: PieceType) -> BitBoard {
+        self.pieces_mask[piece_type.to_index()]
+    }
+
+    #[inline]
+    pub fn get_color_mask(&self, color: Color) -> BitBoard { self.colors_mask[color.to_index()] }
+
+    #[inline]
+    pub fn get_side_to_move(&self) -> Color { self.side_to_move }
+
+    #[inline]
+    pub fn get_castle_rights(&self, color: Color) -> CastlingRights {
+        self.castle_rights[color.to_index()]
+    }
+
+    #[inline]
+    pub fn get_en_passant(&self) -> Option<Square> { self.en_passant }
+
+    #[inline]
+    pub fn get_move_number(&self) -> usize { self.move_number as usize }
+
+    #[inline]
+    pub fn get_moves_since_capture_or_pawn_move(&self) -> usize {
+        self.moves_since_capture_or_pawn_move as usize
+    }
+
+    #[inline]
+    pub fn get_hash(&self) -> PositionHashValueType { self.hash }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ChessBoard;
+    use std::mem::size_of;
+    use std::str::FromStr;
+
+    #[test]
+    fn conversions() {
+        assert!(size_of::<PositionCore>() <= 88);
+        assert!(size_of::<PositionCore>() < size_of::<ChessBoard>());
+
+        let board = ChessBoard::from_str("4k3/8/8/3pP3/8/8/8/R3K2r w Q d6 7 42").unwrap();
+        let restored = ChessBoard::from(PositionCore::from(board));
+        assert_eq!(restored, board);
+        assert_eq!(restored.as_fen(), board.as_fen());
+        assert_eq!(restored.get_check_mask(), board.get_check_mask());
+        assert_eq!(restored.get_pin_mask(), board.get_pin_mask());
+        assert_eq!(restored.get_legal_moves(), board.get_legal_moves());
+
+        let mate = ChessBoard::from_str("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+        assert!(ChessBoard::from(PositionCore::from(&mate)).is_terminal());
+    }
+}
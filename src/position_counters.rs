@@ -0,0 +1,176 @@
+//! Position counters
+//!
+//! ``Game`` counts the occurrences of every position to detect the repetitions. By default the
+//! counters are kept in memory, but long games hosted on the server may need to persist them
+//! in the external storage (e.g. key-value database). This module defines the interface of such
+//! storage
+
+use crate::errors::LibChessError as Error;
+use crate::PositionHashValueType;
+use std::collections::BTreeMap;
+
+/// The default in-memory storage of position counters
+pub type InMemoryPositionCounter = BTreeMap<PositionHashValueType, usize>;
+
+/// Storage of the numbers of times each position has arisen in the game. Positions are
+/// identified by their hash (see ``ChessBoard::get_hash``). The storage failures are reported
+/// as ``LibChessError::InvalidPositionCounter`` and passed through by ``Game``
+///
+/// # Examples
+/// ```
+/// use libchess::errors::LibChessError;
+/// use libchess::{Game, PositionCounter, PositionHashValueType};
+/// use std::collections::HashMap;
+///
+/// #[derive(Debug, Clone, Default)]
+/// struct HashMapCounter(HashMap<PositionHashValueType, usize>);
+///
+/// impl PositionCounter for HashMapCounter {
+///     fn get_count(&self, hash: PositionHashValueType) -> Result<usize, LibChessError> {
+///         Ok(self.0.get(&hash).copied().unwrap_or(0))
+///     }
+///
+///     fn increment(&mut self, hash: PositionHashValueType) -> Result<usize, LibChessError> {
+///         let counter = self.0.entry(hash).or_insert(0);
+///         *counter += 1;
+///         Ok(*counter)
+///     }
+/// }
+///
+/// let game = Game::with_position_counter(Default::default(), HashMapCounter::default()).unwrap();
+/// assert_eq!(game.get_position_counter(&game.get_position()).unwrap(), 1);
+/// ```
+pub trait PositionCounter {
+    /// Returns the number of times the position with specified hash has arisen
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidPositionCounter`` if the storage can't be read
+    fn get_count(&self, hash: PositionHashValueType) -> Result<usize, Error>;
+
+    /// Increments the counter of the position with specified hash and returns its new value
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidPositionCounter`` if the storage can't be written
+    fn increment(&mut self, hash: PositionHashValueType) -> Result<usize, Error>;
+}
+
+impl PositionCounter for InMemoryPositionCounter {
+    #[inline]
+    fn get_count(&self, hash: PositionHashValueType) -> Result<usize, Error> {
+        Ok(self.get(&hash).copied().unwrap_or(0))
+    }
+
+    #[inline]
+    fn increment(&mut self, hash: PositionHashValueType) -> Result<usize, Error> {
+        let counter = self.entry(hash).or_insert(0);
+        *counter += 1;
+        Ok(*counter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mv, squares::*, Action, BoardMove, ChessBoard, Game, GameStatus, PieceMove};
+    use crate::{Color::*, PieceType::*};
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::rc::Rc;
+
+    /// Imitates the external storage which outlives the game object
+    #[derive(Debug, Clone, Default)]
+    struct SharedCounter(Rc<RefCell<HashMap<PositionHashValueType, usize>>>);
+
+    impl PositionCounter for SharedCounter {
+        fn get_count(&self, hash: PositionHashValueType) -> Result<usize, Error> {
+            Ok(self.0.borrow().get(&hash).copied().unwrap_or(0))
+        }
+
+        fn increment(&mut self, hash: PositionHashValueType) -> Result<usize, Error> {
+            let mut storage = self.0.borrow_mut();
+            let counter = storage.entry(hash).or_insert(0);
+            *counter += 1;
+            Ok(*counter)
+        }
+    }
+
+    /// Imitates the storage which becomes unavailable after the number of writes
+    #[derive(Debug, Clone, Default)]
+    struct FailingCounter {
+        counters:    InMemoryPositionCounter,
+        writes_left: usize,
+    }
+
+    impl PositionCounter for FailingCounter {
+        fn get_count(&self, hash: PositionHashValueType) -> Result<usize, Error> {
+            self.counters.get_count(hash)
+        }
+
+        fn increment(&mut self, hash: PositionHashValueType) -> Result<usize, Error> {
+            match self.writes_left {
+                0 => Err(Error::InvalidPositionCounter {
+                    description: "storage is unavailable".to_string(),
+                }),
+                _ => {
+                    self.writes_left -= 1;
+                    self.counters.increment(hash)
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn in_memory_counter() {
+        let mut counter = InMemoryPositionCounter::new();
+        assert_eq!(counter.get_count(42).unwrap(), 0);
+        assert_eq!(counter.increment(42).unwrap(), 1);
+        assert_eq!(counter.increment(42).unwrap(), 2);
+        assert_eq!(counter.get_count(42).unwrap(), 2);
+    }
+
+    #[test]
+    fn external_counter() {
+        let storage = SharedCounter::default();
+        let mut game = Game::with_position_counter(ChessBoard::default(), storage.clone()).unwrap();
+        let moves = [
+            mv!(Knight, G1, F3),
+            mv!(Knight, G8, F6),
+            mv!(Knight, F3, G1),
+            mv!(Knight, F6, G8),
+        ];
+        for _ in 0..2 {
+            for m in moves.iter() {
+                game.make_move(&Action::MakeMove(*m)).unwrap();
+            }
+        }
+        assert_eq!(
+            storage.get_count(ChessBoard::default().get_hash()).unwrap(),
+            3
+        );
+        assert_eq!(game.get_game_status(), GameStatus::RepetitionDrawDeclared);
+        assert_eq!(game.get_side_to_move(), White);
+    }
+
+    #[test]
+    fn failing_counter() {
+        let storage = FailingCounter::default();
+        assert!(matches!(
+            Game::with_position_counter(ChessBoard::default(), storage),
+            Err(Error::InvalidPositionCounter { .. })
+        ));
+
+        let storage = FailingCounter {
+            writes_left: 1,
+            ..Default::default()
+        };
+        let mut game = Game::with_position_counter(ChessBoard::default(), storage).unwrap();
+        let m = Action::MakeMove(mv!(Knight, G1, F3));
+        assert!(matches!(
+            game.make_move(&m),
+            Err(Error::InvalidPositionCounter { .. })
+        ));
+        // the move is not made, so it may be repeated when the storage is available again
+        assert_eq!(game.get_position(), ChessBoard::default());
+        assert!(game.get_action_history().get_moves().is_empty());
+    }
+}
@@ -0,0 +1,404 @@
+//! Position indexes
+//!
+//! This module implements the index of PGN databases which allows to find all games containing
+//! the position: every position of every game is mapped to the list of postings (the game and
+//! the ply where the position occurred). The files are indexed in parallel if the ``rayon``
+//! feature is enabled. The index can be saved to the disk and loaded back
+
+use crate::db::PgnReader;
+use crate::errors::LibChessError as Error;
+use crate::{ChessBoard, Game, PositionHashValueType, PositionPattern};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+
+/// The signature of the index files
+const INDEX_MAGIC: &[u8; 4] = b"LCPI";
+const INDEX_VERSION: u32 = 1;
+
+/// The occurrence of the position in the database
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Posting {
+    /// The index of the file (or stream) in the list of indexed files
+    pub file: u32,
+    /// The index of the game in the file (the invalid games are counted too)
+    pub game: u32,
+    /// The first ply when the position occurred in the game
+    pub ply:  u32,
+}
+
+/// Index of the positions of PGN databases
+///
+/// # Examples
+/// ```
+/// use libchess::position_indexes::{PositionIndex, Posting};
+/// use libchess::ChessBoard;
+/// let first = "[Event \"1\"]\n\n1. e4 e5 2. Nf3 Nc6 1-0\n\n[Event \"2\"]\n\n1. d4 d5 0-1\n";
+/// let second = "[Event \"3\"]\n\n1. Nf3 Nc6 2. e4 e5 1/2-1/2\n";
+/// let index = PositionIndex::from_streams(vec![first.as_bytes(), second.as_bytes()]).unwrap();
+///
+/// let board = ChessBoard::default()
+///     .apply_san_line("e4 e5 Nf3 Nc6")
+///     .unwrap();
+/// assert_eq!(
+///     index.find(&board),
+///     &[
+///         Posting {
+///             file: 0,
+///             game: 0,
+///             ply:  4,
+///         },
+///         Posting {
+///             file: 1,
+///             game: 0,
+///             ply:  4,
+///         },
+///     ]
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PositionIndex {
+    postings:      HashMap<PositionHashValueType, Vec<Posting>>,
+    games:         usize,
+    invalid_games: usize,
+}
+
+impl PositionIndex {
+    /// Builds the index of the PGN files. The postings refer to the files by their indices in
+    /// the list
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidPGNStream`` if any of the files can't be read. The games which
+    /// can't be parsed are skipped (see ``PositionIndex::get_invalid_games``)
+    pub fn build(paths: &[impl AsRef<Path>]) -> Result<Self, Error> {
+        Self::from_streams(open_files(paths)?)
+    }
+
+    /// Builds the index of the PGN streams (see ``PositionIndex::build``)
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidPGNStream`` if any of the streams can't be read
+    pub fn from_streams<R: BufRead + Send>(readers: Vec<R>) -> Result<Self, Error> {
+        let parts = map_streams(readers, Self::from_stream)?;
+
+        let mut result = Self::default();
+        for part in parts {
+            result.games += part.games;
+            result.invalid_games += part.invalid_games;
+            for (hash, postings) in part.postings {
+                result.postings.entry(hash).or_default().extend(postings);
+            }
+        }
+        Ok(result)
+    }
+
+    fn from_stream<R: BufRead>(file: u32, reader: R) -> Result<Self, Error> {
+        let mut result = Self::default();
+        for (game, pgn) in PgnReader::new(reader).enumerate() {
+            let game_record = match Game::from_pgn(&pgn?) {
+                Ok(game_record) => game_record,
+                Err(_) => {
+                    result.invalid_games += 1;
+                    continue;
+                }
+            };
+            result.games += 1;
+
+            let mut seen = HashSet::new();
+            let positions = game_record.get_action_history().get_positions();
+            for (ply, board) in positions.iter().enumerate() {
+                if seen.insert(board.get_hash()) {
+                    result
+                        .postings
+                        .entry(board.get_hash())
+                        .or_default()
+                        .push(Posting {
+                            file,
+                            game: game as u32,
+                            ply: ply as u32,
+                        });
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Returns the occurrences of the position sorted by the files and the games
+    #[inline]
+    pub fn find(&self, board: &ChessBoard) -> &[Posting] { self.find_hash(board.get_hash()) }
+
+    /// Returns the occurrences of the position with specified hash (see ``PositionIndex::find``)
+    pub fn find_hash(&self, hash: PositionHashValueType) -> &[Posting] {
+        self.postings.get(&hash).map_or(&[], |postings| postings)
+    }
+
+    /// Returns the number of the unique positions in the index
+    #[inline]
+    pub fn len(&self) -> usize { self.postings.len() }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.postings.is_empty() }
+
+    /// Returns the number of the indexed games
+    #[inline]
+    pub fn get_games(&self) -> usize { self.games }
+
+    /// Returns the number of the games which can't be parsed
+    #[inline]
+    pub fn get_invalid_games(&self) -> usize { self.invalid_games }
+
+    /// Writes the index in the binary format: the signature and the version of the format, the
+    /// numbers of games, then the positions sorted by their hashes with their postings. All
+    /// numbers are big-endian
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidIndexStream`` if the writer fails
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::position_indexes::PositionIndex;
+    /// let pgn = "[Event \"1\"]\n\n1. e4 e5 1-0\n";
+    /// let index = PositionIndex::from_streams(vec![pgn.as_bytes()]).unwrap();
+    ///
+    /// let mut buffer = Vec::new();
+    /// index.write(&mut buffer).unwrap();
+    /// assert_eq!(PositionIndex::read(&mut buffer.as_slice()).unwrap(), index);
+    /// ```
+    pub fn write(&self, writer: &mut impl Write) -> Result<(), Error> {
+        let mut hashes: Vec<&PositionHashValueType> = self.postings.keys().collect();
+        hashes.sort();
+
+        let mut buffer = Vec::with_capacity(32 + self.postings.len() * 24);
+        buffer.extend_from_slice(INDEX_MAGIC);
+        buffer.extend_from_slice(&INDEX_VERSION.to_be_bytes());
+        buffer.extend_from_slice(&(self.games as u64).to_be_bytes());
+        buffer.extend_from_slice(&(self.invalid_games as u64).to_be_bytes());
+        buffer.extend_from_slice(&(hashes.len() as u64).to_be_bytes());
+        for hash in hashes {
+            let postings = &self.postings[hash];
+            buffer.extend_from_slice(&hash.to_be_bytes());
+            buffer.extend_from_slice(&(postings.len() as u32).to_be_bytes());
+            for posting in postings {
+                buffer.extend_from_slice(&posting.file.to_be_bytes());
+                buffer.extend_from_slice(&posting.game.to_be_bytes());
+                buffer.extend_from_slice(&posting.ply.to_be_bytes());
+            }
+        }
+        writer
+            .write_all(&buffer)
+            .map_err(|e| Error::InvalidIndexStream {
+                description: e.to_string(),
+            })
+    }
+
+    /// Reads the index written by ``PositionIndex::write``
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidIndexStream`` if the reader fails or the data is not a valid index
+    pub fn read(reader: &mut impl Read) -> Result<Self, Error> {
+        let invalid = |description: &str| Error::InvalidIndexStream {
+            description: description.to_string(),
+        };
+        let mut data = Vec::new();
+        reader
+            .read_to_end(&mut data)
+            .map_err(|e| invalid(&e.to_string()))?;
+
+        let mut data = data.as_slice();
+        let mut take = |n: usize| -> Result<&[u8], Error> {
+            if data.len() < n {
+                return Err(invalid("unexpected end of the index"));
+            }
+            let (head, tail) = data.split_at(n);
+            data = tail;
+            Ok(head)
+        };
+        let read_u32 = |bytes: &[u8]| u32::from_be_bytes(bytes.try_into().unwrap());
+        let read_u64 = |bytes: &[u8]| u64::from_be_bytes(bytes.try_into().unwrap());
+
+        if take(4)? != INDEX_MAGIC {
+            return Err(invalid("wrong signature"));
+        }
+        if read_u32(take(4)?) != INDEX_VERSION {
+            return Err(invalid("unsupported version"));
+        }
+        let mut result = Self {
+            games: read_u64(take(8)?) as usize,
+            invalid_games: read_u64(take(8)?) as usize,
+            ..Default::default()
+        };
+        for _ in 0..read_u64(take(8)?) {
+            let hash = read_u64(take(8)?);
+            let postings = (0..read_u32(take(4)?))
+                .map(|_| {
+                    let posting = take(12)?;
+                    Ok(Posting {
+                        file: read_u32(&posting[..4]),
+                        game: read_u32(&posting[4..8]),
+                        ply:  read_u32(&posting[8..]),
+                    })
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            result.postings.insert(hash, postings);
+        }
+        if !data.is_empty() {
+            return Err(invalid("unexpected data after the index"));
+        }
+        Ok(result)
+    }
+}
+
+/// Finds the games of the PGN files where the position matching the pattern occurred (see
+/// ``search_streams``)
+///
+/// # Errors
+/// ``LibChessError::InvalidPGNStream`` if any of the files can't be read
+pub fn search_files(
+    paths: &[impl AsRef<Path>],
+    pattern: &PositionPattern,
+) -> Result<Vec<Posting>, Error> {
+    search_streams(open_files(paths)?, pattern)
+}
+
+/// Finds the games of the PGN streams where the position matching the pattern occurred. Every
+/// game is posted once with the first ply of the matching position. The games which can't be
+/// parsed are skipped
+///
+/// # Errors
+/// ``LibChessError::InvalidPGNStream`` if any of the streams can't be read
+///
+/// # Examples
+/// ```
+/// use libchess::position_indexes::search_streams;
+/// use libchess::PositionPattern;
+/// let database = "[Event \"1\"]\n\n1. e4 e5 2. Nf3 Nc6 3. Bb5 1-0\n\n\
+///                 [Event \"2\"]\n\n1. d4 d5 2. Nf3 Nc6 3. Bg5 0-1\n";
+/// let pattern = PositionPattern::parse("white: Bb5; black: Nc6").unwrap();
+/// let postings = search_streams(vec![database.as_bytes()], &pattern).unwrap();
+/// assert_eq!(postings.len(), 1);
+/// assert_eq!((postings[0].game, postings[0].ply), (0, 5));
+/// ```
+pub fn search_streams<R: BufRead + Send>(
+    readers: Vec<R>,
+    pattern: &PositionPattern,
+) -> Result<Vec<Posting>, Error> {
+    let parts = map_streams(readers, |file, reader| {
+        let mut postings = Vec::new();
+        for (game, pgn) in PgnReader::new(reader).enumerate() {
+            let Ok(game_record) = Game::from_pgn(&pgn?) else {
+                continue;
+            };
+            let positions = game_record.get_action_history().get_positions();
+            if let Some(ply) = positions.iter().position(|board| pattern.matches(board)) {
+                postings.push(Posting {
+                    file,
+                    game: game as u32,
+                    ply: ply as u32,
+                });
+            }
+        }
+        Ok(postings)
+    })?;
+    Ok(parts.into_iter().flatten().collect())
+}
+
+fn open_files(paths: &[impl AsRef<Path>]) -> Result<Vec<BufReader<File>>, Error> {
+    paths
+        .iter()
+        .map(|path| {
+            File::open(path)
+                .map(BufReader::new)
+                .map_err(|e| Error::InvalidPGNStream {
+                    description: e.to_string(),
+                })
+        })
+        .collect()
+}
+
+/// Applies the function to every stream and its index (in parallel if the ``rayon`` feature is
+/// enabled). The results are returned in the order of the streams
+fn map_streams<R, T, F>(readers: Vec<R>, f: F) -> Result<Vec<T>, Error>
+where
+    R: BufRead + Send,
+    T: Send,
+    F: Fn(u32, R) -> Result<T, Error> + Send + Sync,
+{
+    #[cfg(feature = "rayon")]
+    let readers = readers.into_par_iter();
+    #[cfg(not(feature = "rayon"))]
+    let readers = readers.into_iter();
+
+    readers
+        .enumerate()
+        .map(|(file, reader)| f(file as u32, reader))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indexing_files() {
+        let paths = ["examples/pgn_data/game1.pgn", "examples/pgn_data/game2.pgn"];
+        let index = PositionIndex::build(&paths).unwrap();
+        assert_eq!(index.get_games(), 2);
+        assert_eq!(index.get_invalid_games(), 0);
+        assert_eq!(
+            index.find(&ChessBoard::default()),
+            &[
+                Posting {
+                    file: 0,
+                    game: 0,
+                    ply:  0,
+                },
+                Posting {
+                    file: 1,
+                    game: 0,
+                    ply:  0,
+                },
+            ]
+        );
+        assert!(PositionIndex::build(&["examples/pgn_data/missing.pgn"]).is_err());
+    }
+
+    #[test]
+    fn indexing_streams() {
+        let database = "[Event \"1\"]\n\n1. Nf3 Nf6 2. Ng1 Ng8 3. Nf3 1-0\n\n\
+                        [Event \"broken\"]\n\n1. e4 e4 1-0\n\n\
+                        [Event \"3\"]\n\n1. Nf3 d5 0-1\n";
+        let index = PositionIndex::from_streams(vec![database.as_bytes()]).unwrap();
+        assert_eq!((index.get_games(), index.get_invalid_games()), (2, 1));
+
+        // the repeated position is posted once with its first ply
+        let board = ChessBoard::default().apply_san_line("Nf3").unwrap();
+        let postings: Vec<(u32, u32)> =
+            index.find(&board).iter().map(|p| (p.game, p.ply)).collect();
+        assert_eq!(postings, vec![(0, 1), (2, 1)]);
+        assert!(index.find(&board.apply_san_line("e5").unwrap()).is_empty());
+
+        let mut buffer = Vec::new();
+        index.write(&mut buffer).unwrap();
+        assert_eq!(PositionIndex::read(&mut buffer.as_slice()).unwrap(), index);
+        assert!(PositionIndex::read(&mut &buffer[..buffer.len() - 1]).is_err());
+        assert!(PositionIndex::read(&mut &buffer[1..]).is_err());
+
+        let pattern = PositionPattern::parse("white: Nf3; black: Ng8").unwrap();
+        let postings = search_streams(vec![database.as_bytes()], &pattern).unwrap();
+        let postings: Vec<(u32, u32)> = postings.iter().map(|p| (p.game, p.ply)).collect();
+        assert_eq!(postings, vec![(0, 1), (2, 1)]);
+        let pattern = PositionPattern::parse("white: !N??").unwrap();
+        assert_eq!(
+            search_files(&["examples/pgn_data/game1.pgn"], &pattern)
+                .unwrap()
+                .len(),
+            0
+        );
+
+        let empty = PositionIndex::from_streams(Vec::<&[u8]>::new()).unwrap();
+        assert!(empty.is_empty());
+    }
+}
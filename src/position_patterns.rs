@@ -0,0 +1,246 @@
+//! Position patterns
+//!
+//! This module implements the partial piece placement patterns which are used to search for
+//! thematic positions (e.g. "a white knight on e5 and a black king on g8") on single boards or
+//! in PGN databases (see ``position_indexes::search_streams``)
+
+use crate::errors::LibChessError as Error;
+use crate::{BitBoard, ChessBoard, Color, File, PieceType, Rank, BLANK};
+use std::str::FromStr;
+
+/// One condition of the pattern: the piece of specified color and type (any type if ``None``)
+/// on any of specified squares. Negated conditions require that there are no such pieces
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PatternItem {
+    color:      Color,
+    piece_type: Option<PieceType>,
+    squares:    BitBoard,
+    negated:    bool,
+}
+
+impl PatternItem {
+    fn matches(&self, board: &ChessBoard) -> bool {
+        let mut mask = board.get_color_mask(self.color) & self.squares;
+        if let Some(piece_type) = self.piece_type {
+            mask &= board.get_piece_type_mask(piece_type);
+        }
+        mask.is_blank() == self.negated
+    }
+}
+
+/// Partial piece placement pattern. The board matches the pattern if it matches all of its
+/// conditions
+///
+/// The text representation consists of sections for each color separated by ";". A section
+/// starts with the color ("white:" or "black:", or "w:" and "b:") and lists the pieces
+/// separated by ",". A piece is written as in SAN ("Ne5", "d4" for a pawn) or by its name
+/// ("knight e5", "pawn d4"). The wildcards are "*" for any piece type and "?" for any file or
+/// rank. The piece prefixed with "!" must be absent
+///
+/// # Examples
+/// ```
+/// use libchess::{ChessBoard, PositionPattern};
+/// let pattern = PositionPattern::parse("white: Ne5, pawn d4; black: king g8, !*f?").unwrap();
+/// let board = ChessBoard::from_fen("6k1/5p1p/8/4N3/3P4/8/8/6K1 w - - 0 1").unwrap();
+/// assert!(!pattern.matches(&board)); // a black piece is on the f-file
+///
+/// let board = ChessBoard::from_fen("6k1/6pp/8/4N3/3P4/8/8/6K1 w - - 0 1").unwrap();
+/// assert!(pattern.matches(&board));
+/// ```
+///
+/// The same pattern created with the builder methods:
+/// ```
+/// use libchess::{squares::*, BitBoard, Color::*, File, PieceType::*, PositionPattern};
+/// let mut pattern = PositionPattern::default();
+/// pattern
+///     .require(White, Some(Knight), BitBoard::from_square(E5))
+///     .require(White, Some(Pawn), BitBoard::from_square(D4))
+///     .require(Black, Some(King), BitBoard::from_square(G8))
+///     .forbid(Black, None, BitBoard::from_file(File::F));
+/// assert_eq!(
+///     pattern,
+///     PositionPattern::parse("white: Ne5, pawn d4; black: king g8, !*f?").unwrap()
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PositionPattern {
+    items: Vec<PatternItem>,
+}
+
+impl FromStr for PositionPattern {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidPositionPattern { s: s.to_string() };
+        let mut result = Self::default();
+
+        for section in s.split(';').filter(|section| !section.trim().is_empty()) {
+            let (color, pieces) = section.split_once(':').ok_or_else(invalid)?;
+            let color = Color::from_str(color.trim()).map_err(|_| invalid())?;
+            for piece in pieces.split(',') {
+                let mut piece = piece.trim();
+                let negated = piece.starts_with('!');
+                if negated {
+                    piece = piece[1..].trim_start();
+                }
+                if !piece.is_ascii() | (piece.len() < 2) {
+                    return Err(invalid());
+                }
+                let (piece_type, square) = piece.split_at(piece.len() - 2);
+                let piece_type = parse_piece_type(piece_type.trim()).ok_or_else(invalid)?;
+                let squares = parse_squares(square).ok_or_else(invalid)?;
+                result.items.push(PatternItem {
+                    color,
+                    piece_type,
+                    squares,
+                    negated,
+                });
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl PositionPattern {
+    /// Parses the text representation of the pattern (see ``PositionPattern``)
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidPositionPattern`` if the string can't be parsed
+    #[inline]
+    pub fn parse(s: &str) -> Result<Self, Error> { Self::from_str(s) }
+
+    /// Adds the condition: there is a piece of specified color and type (any type if ``None``)
+    /// on any of specified squares
+    pub fn require(
+        &mut self,
+        color: Color,
+        piece_type: Option<PieceType>,
+        squares: BitBoard,
+    ) -> &mut Self {
+        self.items.push(PatternItem {
+            color,
+            piece_type,
+            squares,
+            negated: false,
+        });
+        self
+    }
+
+    /// Adds the condition: there are no pieces of specified color and type (any type if
+    /// ``None``) on all of specified squares
+    pub fn forbid(
+        &mut self,
+        color: Color,
+        piece_type: Option<PieceType>,
+        squares: BitBoard,
+    ) -> &mut Self {
+        self.items.push(PatternItem {
+            color,
+            piece_type,
+            squares,
+            negated: true,
+        });
+        self
+    }
+
+    /// Returns true if the board matches all conditions of the pattern (the empty pattern
+    /// matches any board)
+    pub fn matches(&self, board: &ChessBoard) -> bool {
+        self.items.iter().all(|item| item.matches(board))
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.items.is_empty() }
+}
+
+/// Parses the piece type of the pattern: SAN letter, name or "*" for any piece type. The empty
+/// string means a pawn
+fn parse_piece_type(s: &str) -> Option<Option<PieceType>> {
+    match s.to_lowercase().as_str() {
+        "*" => Some(None),
+        "pawn" => Some(Some(PieceType::Pawn)),
+        "knight" => Some(Some(PieceType::Knight)),
+        "bishop" => Some(Some(PieceType::Bishop)),
+        "rook" => Some(Some(PieceType::Rook)),
+        "queen" => Some(Some(PieceType::Queen)),
+        "king" => Some(Some(PieceType::King)),
+        _ => PieceType::from_str(s).ok().map(Some),
+    }
+}
+
+/// Parses the square of the pattern where the file and the rank may be replaced by "?"
+fn parse_squares(s: &str) -> Option<BitBoard> {
+    let (file, rank) = s.split_at(1);
+    let files = match file {
+        "?" => !BLANK,
+        file => BitBoard::from_file(File::from_str(file).ok()?),
+    };
+    let ranks = match rank {
+        "?" => !BLANK,
+        rank => BitBoard::from_rank(Rank::from_str(rank).ok()?),
+    };
+    Some(files & ranks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::squares::*;
+
+    #[test]
+    fn patterns_parsing() {
+        let pattern = PositionPattern::parse("white: Ke1, e4, bishop ?3; black:!Q??").unwrap();
+        let mut expected = PositionPattern::default();
+        expected
+            .require(
+                Color::White,
+                Some(PieceType::King),
+                BitBoard::from_square(E1),
+            )
+            .require(
+                Color::White,
+                Some(PieceType::Pawn),
+                BitBoard::from_square(E4),
+            )
+            .require(
+                Color::White,
+                Some(PieceType::Bishop),
+                BitBoard::from_rank(Rank::Third),
+            )
+            .forbid(Color::Black, Some(PieceType::Queen), !BLANK);
+        assert_eq!(pattern, expected);
+
+        assert!(PositionPattern::parse("").unwrap().is_empty());
+        assert!(PositionPattern::parse("white: Ne5;").is_ok());
+        for invalid in [
+            "Ne5",
+            "red: Ne5",
+            "white: Ne9",
+            "white: Xe5",
+            "white: Ne5,",
+            "white: e",
+            "white: Nеe5",
+        ] {
+            assert!(
+                matches!(
+                    PositionPattern::parse(invalid),
+                    Err(Error::InvalidPositionPattern { .. })
+                ),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn patterns_matching() {
+        let board = ChessBoard::default();
+        let matches = |s: &str| PositionPattern::parse(s).unwrap().matches(&board);
+        assert!(matches(""));
+        assert!(matches("white: Ke1, Ng1; black: Ke8"));
+        assert!(matches("white: *a1, ?2"));
+        assert!(!matches("white: *a1, ?3"));
+        assert!(!matches("white: Ke1; black: Ke1"));
+        assert!(matches("white: !*?4; black: !Q?1"));
+        assert!(!matches("black: !pawn ??"));
+    }
+}
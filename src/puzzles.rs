@@ -0,0 +1,287 @@
+//! Puzzle databases
+//!
+//! This module implements reading of the lichess puzzle database in CSV format (record by
+//! record, without loading the whole file into memory). Every record has the columns
+//! "PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,NbPlays,Themes,GameUrl,OpeningTags".
+//! The FEN is the position before the opponent's move which creates the puzzle, so the first of
+//! the UCI moves is made by the opponent and the rest are the solution
+
+use crate::errors::LibChessError as Error;
+use crate::{BoardMove, ChessBoard};
+use std::io::BufRead;
+use std::str::FromStr;
+
+/// The header of the lichess puzzle CSV file
+const CSV_HEADER_PREFIX: &str = "PuzzleId,";
+
+/// The puzzle of lichess database. The moves are validated against the position when the
+/// puzzle is parsed
+///
+/// # Examples
+/// ```
+/// use libchess::puzzles::Puzzle;
+/// use libchess::Color::*;
+/// let puzzle = Puzzle::from_csv_record(
+///     "00008,r6k/pp2r2p/4Rp1Q/3p4/8/1N1P2R1/PqP2bPP/7K b - - 0 24,f2g3 e6e7 b2b1 b3c1 b1c1 h6c1,\
+///      1913,75,94,6230,crushing hangingPiece long middlegame,https://lichess.org/787zsVup/black#48,",
+/// )
+/// .unwrap();
+/// assert_eq!(puzzle.get_id(), "00008");
+/// assert_eq!(puzzle.get_rating(), 1913);
+/// assert_eq!(puzzle.get_solution().len(), 5);
+/// assert!(puzzle.has_theme("hangingPiece"));
+/// assert_eq!(puzzle.get_start_position().get_side_to_move(), White);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Puzzle {
+    id: String,
+    position: ChessBoard,
+    start_position: ChessBoard,
+    moves: Vec<BoardMove>,
+    rating: u32,
+    rating_deviation: u32,
+    popularity: i32,
+    plays: u32,
+    themes: Vec<String>,
+    game_url: String,
+    opening_tags: Vec<String>,
+}
+
+impl FromStr for Puzzle {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> { Self::from_csv_record(s) }
+}
+
+impl Puzzle {
+    /// Parses the record (line) of the lichess puzzle CSV file. The columns "GameUrl" and
+    /// "OpeningTags" are optional
+    ///
+    /// # Errors
+    /// ``LibChessError::InvalidPuzzle`` if the record can't be parsed, there is no solution or
+    /// any move is illegal
+    pub fn from_csv_record(record: &str) -> Result<Self, Error> {
+        let invalid = || Error::InvalidPuzzle {
+            s: record.to_string(),
+        };
+        let columns = record.trim_end().split(',').collect::<Vec<_>>();
+        if !(8..=10).contains(&columns.len()) {
+            return Err(invalid());
+        }
+        fn number<T: FromStr>(column: &str) -> Option<T> { column.trim().parse().ok() }
+        let words = |i: usize| {
+            columns
+                .get(i)
+                .map(|column| column.split_whitespace().map(String::from).collect())
+                .unwrap_or_default()
+        };
+
+        let position = ChessBoard::from_fen(columns[1].trim()).map_err(|_| invalid())?;
+        let mut board = position;
+        let mut start_position = position;
+        let mut moves = Vec::new();
+        for uci in columns[2].split_whitespace() {
+            let m = board.parse_uci(uci).map_err(|_| invalid())?;
+            board.make_move_mut(&m).map_err(|_| invalid())?;
+            if moves.is_empty() {
+                start_position = board;
+            }
+            moves.push(m);
+        }
+        if moves.len() < 2 {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            id: columns[0].trim().to_string(),
+            position,
+            start_position,
+            moves,
+            rating: number(columns[3]).ok_or_else(invalid)?,
+            rating_deviation: number(columns[4]).ok_or_else(invalid)?,
+            popularity: number(columns[5]).ok_or_else(invalid)?,
+            plays: number(columns[6]).ok_or_else(invalid)?,
+            themes: words(7),
+            game_url: columns.get(8).map_or("", |url| url.trim()).to_string(),
+            opening_tags: words(9),
+        })
+    }
+
+    #[inline]
+    pub fn get_id(&self) -> &str { &self.id }
+
+    /// Returns the position before the opponent's move
+    #[inline]
+    pub fn get_position(&self) -> ChessBoard { self.position }
+
+    /// Returns the position which the solver starts from (after the opponent's move)
+    #[inline]
+    pub fn get_start_position(&self) -> ChessBoard { self.start_position }
+
+    /// Returns the opponent's move followed by the solution
+    #[inline]
+    pub fn get_moves(&self) -> &[BoardMove] { &self.moves }
+
+    /// Returns the moves of the solution (the moves of both sides after the opponent's move).
+    /// The puzzle always has at least one move of the solution
+    #[inline]
+    pub fn get_solution(&self) -> &[BoardMove] { &self.moves[1..] }
+
+    #[inline]
+    pub fn get_rating(&self) -> u32 { self.rating }
+
+    #[inline]
+    pub fn get_rating_deviation(&self) -> u32 { self.rating_deviation }
+
+    /// Returns the difference of upvotes and downvotes (in percents, may be negative)
+    #[inline]
+    pub fn get_popularity(&self) -> i32 { self.popularity }
+
+    #[inline]
+    pub fn get_plays(&self) -> u32 { self.plays }
+
+    #[inline]
+    pub fn get_themes(&self) -> &[String] { &self.themes }
+
+    /// Returns the URL of the game the puzzle is taken from (empty if the record doesn't
+    /// contain it)
+    #[inline]
+    pub fn get_game_url(&self) -> &str { &self.game_url }
+
+    #[inline]
+    pub fn get_opening_tags(&self) -> &[String] { &self.opening_tags }
+
+    #[inline]
+    pub fn has_theme(&self, theme: &str) -> bool { self.themes.iter().any(|t| t == theme) }
+}
+
+/// Iterator over the puzzles read from the lichess puzzle CSV stream. The header line and the
+/// empty lines are skipped
+///
+/// # Examples
+/// ```
+/// use libchess::puzzles::PuzzleReader;
+/// let database = "PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,NbPlays,Themes,GameUrl\n\
+///                 0000D,5rk1/1p3ppp/pq3b2/8/8/1P1Q1N2/P4PPP/3R2K1 w - - 2 27,d3d6 f8d8 d6d8 f6d8,\
+///                 1485,76,94,7257,advantage endgame short,https://lichess.org/F8M8OS71#53\n";
+/// let puzzles = PuzzleReader::new(database.as_bytes())
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(puzzles.len(), 1);
+/// assert_eq!(puzzles[0].get_solution().len(), 3);
+/// ```
+pub struct PuzzleReader<R: BufRead> {
+    lines: std::io::Lines<R>,
+}
+
+impl<R: BufRead> PuzzleReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            lines: reader.lines(),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for PuzzleReader<R> {
+    type Item = Result<Puzzle, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for line in self.lines.by_ref() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    return Some(Err(Error::InvalidPuzzleStream {
+                        description: e.to_string(),
+                    }))
+                }
+            };
+            let trimmed = line.trim();
+            if !trimmed.is_empty() & !trimmed.starts_with(CSV_HEADER_PREFIX) {
+                return Some(Puzzle::from_csv_record(trimmed));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mv, squares::*, BoardStatus, PieceMove, PieceType::*};
+
+    #[test]
+    fn reading_puzzles() {
+        let database = "\
+PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,NbPlays,Themes,GameUrl,OpeningTags
+000hf,r1bqk2r/pp1nbNp1/2p1p2p/8/2BP4/1PN3P1/P3QP1P/3R1RK1 b kq - 0 19,e8f7 e2e6 f7f8 e6f7,1575,74,88,454,mate mateIn2 middlegame short,https://lichess.org/71ygsFeE/black#38,Horwitz_Defense Horwitz_Defense_Other_variations
+
+000hf-broken,r1bqk2r/pp1nbNp1/2p1p2p/8/2BP4/1PN3P1/P3QP1P/3R1RK1 b kq - 0 19,e8f7 e2e7,1575,74,88,454,mate,,
+00143,r2q1rk1/5ppp/1np5/p1b5/2p1B3/P7/1P3PPP/R1BQ1RK1 b - - 1 17,d8f6 d1h5 h7h6 h5c5,1896,77,95,633,advantage middlegame short,https://lichess.org/jcuxlI63/black#34
+";
+        let puzzles = PuzzleReader::new(database.as_bytes()).collect::<Vec<_>>();
+        assert_eq!(puzzles.len(), 3);
+        assert!(matches!(puzzles[1], Err(Error::InvalidPuzzle { .. })));
+
+        let puzzle = puzzles[0].as_ref().unwrap();
+        assert_eq!(puzzle.get_id(), "000hf");
+        assert_eq!(
+            (
+                puzzle.get_rating(),
+                puzzle.get_rating_deviation(),
+                puzzle.get_popularity(),
+                puzzle.get_plays()
+            ),
+            (1575, 74, 88, 454)
+        );
+        assert_eq!(puzzle.get_themes().len(), 4);
+        assert!(puzzle.has_theme("mateIn2") & !puzzle.has_theme("mate In2"));
+        assert_eq!(
+            puzzle.get_opening_tags(),
+            ["Horwitz_Defense", "Horwitz_Defense_Other_variations"]
+        );
+        assert_eq!(puzzle.get_moves()[0], mv!(King, E8, F7));
+        assert_eq!(
+            puzzle.get_start_position(),
+            puzzle
+                .get_position()
+                .make_move(&puzzle.get_moves()[0])
+                .unwrap()
+        );
+        assert_eq!(
+            puzzle.get_start_position().get_side_to_move(),
+            crate::Color::White
+        );
+        let mut board = puzzle.get_start_position();
+        for m in puzzle.get_solution() {
+            board.make_move_mut(m).unwrap();
+        }
+        assert_eq!(
+            board.get_status(),
+            BoardStatus::CheckMated(crate::Color::Black)
+        );
+
+        let puzzle = puzzles[2].as_ref().unwrap();
+        assert!(puzzle.get_opening_tags().is_empty());
+        assert!(puzzle.get_game_url().ends_with("#34"));
+
+        for invalid in [
+            "",
+            "00001,4k3/8/8/8/8/8/8/4K3 x - - 0 1,e1e2 e8e7,1500,75,90,100,short,",
+            "00002,4k3/8/8/8/8/8/8/4K3 w - - 0 1,e1e2,1500,75,90,100,short,",
+            "00003,4k3/8/8/8/8/8/8/4K3 w - - 0 1,e1e2 e8e7,rated,75,90,100,short,",
+            "00004,4k3/8/8/8/8/8/8/4K3 w - - 0 1,e1e2 e8e7",
+        ] {
+            assert!(
+                matches!(
+                    Puzzle::from_csv_record(invalid),
+                    Err(Error::InvalidPuzzle { .. })
+                ),
+                "{invalid}"
+            );
+        }
+        assert!(
+            Puzzle::from_str("00005,4k3/8/8/8/8/8/8/4K3 w - - 0 1,e1e2 e8e7,1500,75,-3,100,,")
+                .is_ok_and(|puzzle| puzzle.get_popularity() == -3)
+        );
+    }
+}
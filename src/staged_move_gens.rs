@@ -0,0 +1,245 @@
+//! Staged move generation
+//!
+//! This module implements the move ordering which is used by the most of alpha-beta searches:
+//! the hash move (from the transposition table) is tried first without generating any moves,
+//! then the captures are generated and ordered by MVV-LVA, then the killer moves are tried
+//! without generating the quiet moves, and the rest of quiet moves are generated last. The
+//! moves supplied by the caller are validated, so the stale hash moves and killers of other
+//! positions are skipped, and no move is yielded twice
+
+use crate::{BoardMove, ChessBoard, LegalMoves, Valuation};
+use std::cmp::Reverse;
+
+/// The stage of the staged move generation (see ``StagedMoveGen::get_stage``)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MoveStage {
+    HashMove,
+    Captures,
+    Killers,
+    Quiets,
+    Done,
+}
+
+/// Iterator over the legal moves of the board in the order of stages (see ``MoveStage``). The
+/// captures (including en passant ones) are ordered by ``Valuation::get_mvv_lva_score``, the
+/// killers are yielded in the order they are supplied (only the legal quiet ones), the rest of
+/// quiet moves are in the order of ``ChessBoard::get_legal_moves``
+///
+/// The killers are tried after the captures (not before them), because a killer is a quiet move
+/// by definition and the captures are cheap to refute or to accept
+///
+/// # Examples
+/// ```
+/// use libchess::StagedMoveGen;
+/// use libchess::{mv, squares::*, BoardMove, ChessBoard, MoveStage, PieceMove, PieceType::*};
+/// use std::str::FromStr;
+///
+/// let board = ChessBoard::from_str("4k3/8/3q4/2p5/3P4/8/8/3QK3 w - - 0 1").unwrap();
+/// let mut moves = StagedMoveGen::new(&board);
+/// moves
+///     .set_hash_move(Some(mv!(Queen, D1, D3)))
+///     .set_killers(&[mv!(King, E1, F1), mv!(Pawn, D4, C5)]);
+///
+/// assert_eq!(moves.next(), Some(mv!(Queen, D1, D3)));
+/// assert_eq!(moves.get_stage(), Some(MoveStage::HashMove));
+/// assert_eq!(moves.next(), Some(mv!(Pawn, D4, C5)));
+/// assert_eq!(moves.get_stage(), Some(MoveStage::Captures));
+/// assert_eq!(moves.next(), Some(mv!(King, E1, F1)));
+/// assert_eq!(moves.get_stage(), Some(MoveStage::Killers));
+/// assert_eq!(moves.count(), board.get_legal_moves().len() - 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct StagedMoveGen<'a> {
+    board:     &'a ChessBoard,
+    valuation: Valuation,
+    hash_move: Option<BoardMove>,
+    killers:   Vec<BoardMove>,
+    stage:     Option<MoveStage>,
+    /// The moves of the current stage which are not yielded yet (in the reversed order)
+    pending:   LegalMoves,
+}
+
+impl<'a> StagedMoveGen<'a> {
+    pub fn new(board: &'a ChessBoard) -> Self {
+        Self {
+            board,
+            valuation: Valuation::default(),
+            hash_move: None,
+            killers: Vec::new(),
+            stage: None,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Sets the move which is tried first (is skipped if it is illegal on the board)
+    pub fn set_hash_move(&mut self, hash_move: Option<BoardMove>) -> &mut Self {
+        self.hash_move = hash_move;
+        self
+    }
+
+    /// Sets the killer moves (they are skipped if they are illegal or captures)
+    pub fn set_killers(&mut self, killers: &[BoardMove]) -> &mut Self {
+        self.killers = killers.to_vec();
+        self
+    }
+
+    /// Sets the values of the pieces used for MVV-LVA ordering of the captures
+    pub fn set_valuation(&mut self, valuation: Valuation) -> &mut Self {
+        self.valuation = valuation;
+        self
+    }
+
+    /// Returns the stage of the last yielded move (None before the first move is requested,
+    /// ``MoveStage::Done`` after all moves are yielded)
+    #[inline]
+    pub fn get_stage(&self) -> Option<MoveStage> { self.stage }
+
+    /// Generates the captures except the hash move (the quiet moves are not generated)
+    fn generate_captures(&mut self) {
+        let mut captures = Vec::new();
+        for m in self.board.get_legal_captures() {
+            if Some(m) == self.hash_move {
+                continue;
+            }
+            if let Some(score) = self.valuation.get_mvv_lva_score(self.board, &m) {
+                captures.push((score, m));
+            }
+        }
+        captures.sort_by_key(|(score, _)| Reverse(*score));
+        self.pending = captures.into_iter().rev().map(|(_, m)| m).collect();
+    }
+
+    /// Keeps only the killers which are legal quiet moves other than the hash move (without
+    /// repetitions) and makes them pending
+    fn take_killers(&mut self) {
+        let board = self.board;
+        let mut killers: Vec<BoardMove> = Vec::new();
+        for killer in self.killers.iter() {
+            if board.is_legal_move(killer)
+                & self.valuation.get_mvv_lva_score(board, killer).is_none()
+                & (Some(*killer) != self.hash_move)
+                & !killers.contains(killer)
+            {
+                killers.push(*killer);
+            }
+        }
+        self.pending = killers.iter().rev().copied().collect();
+        self.killers = killers;
+    }
+
+    /// Generates the quiet moves except the hash move and the killers
+    fn generate_quiets(&mut self) {
+        let mut quiets = self.board.get_legal_quiets();
+        quiets.retain(|m| (Some(*m) != self.hash_move) & !self.killers.contains(m));
+        self.pending = quiets.into_iter().rev().collect();
+    }
+}
+
+impl Iterator for StagedMoveGen<'_> {
+    type Item = BoardMove;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(m) = self.pending.pop() {
+                return Some(m);
+            }
+            self.stage = Some(match self.stage {
+                None => {
+                    let board = self.board;
+                    self.pending
+                        .extend(self.hash_move.filter(|m| board.is_legal_move(m)));
+                    MoveStage::HashMove
+                }
+                Some(MoveStage::HashMove) => {
+                    self.generate_captures();
+                    MoveStage::Captures
+                }
+                Some(MoveStage::Captures) => {
+                    self.take_killers();
+                    MoveStage::Killers
+                }
+                Some(MoveStage::Killers) => {
+                    self.generate_quiets();
+                    MoveStage::Quiets
+                }
+                Some(MoveStage::Quiets | MoveStage::Done) => {
+                    self.stage = Some(MoveStage::Done);
+                    return None;
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mv, squares::*, PieceMove, PieceType::*};
+    use std::str::FromStr;
+
+    #[test]
+    fn staged_generation() {
+        let board = ChessBoard::from_str(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        )
+        .unwrap();
+        let valuation = Valuation::default();
+        let legal_moves = board.get_legal_moves();
+        let is_capture = |m: &BoardMove| valuation.get_mvv_lva_score(&board, m).is_some();
+
+        // without the hints the captures are ordered by MVV-LVA and followed by the quiet moves
+        let mut generator = StagedMoveGen::new(&board);
+        assert_eq!(generator.get_stage(), None);
+        let moves = generator.by_ref().collect::<Vec<_>>();
+        assert_eq!(generator.get_stage(), Some(MoveStage::Done));
+        assert_eq!(generator.next(), None);
+        assert_eq!(moves.len(), legal_moves.len());
+        let captures_number = legal_moves.iter().filter(|m| is_capture(m)).count();
+        assert!(moves[..captures_number].iter().all(is_capture));
+        let scores = moves[..captures_number]
+            .iter()
+            .map(|m| valuation.get_mvv_lva_score(&board, m).unwrap())
+            .collect::<Vec<_>>();
+        assert!(scores.windows(2).all(|w| w[0] >= w[1]));
+        assert_eq!(
+            moves[captures_number..],
+            legal_moves
+                .iter()
+                .copied()
+                .filter(|m| !is_capture(m))
+                .collect::<Vec<_>>()
+        );
+
+        // the illegal hints and the captures among the killers are skipped, nothing is repeated
+        let hash_move = mv!(Knight, E5, F7);
+        let killers = [mv!(Pawn, A2, A4), mv!(Knight, A1, B3), mv!(Knight, E5, F7)];
+        let mut generator = StagedMoveGen::new(&board);
+        generator
+            .set_hash_move(Some(hash_move))
+            .set_killers(&killers);
+        assert_eq!(generator.next(), Some(hash_move));
+        assert_eq!(generator.get_stage(), Some(MoveStage::HashMove));
+        let mut stages = vec![];
+        let mut moves = vec![hash_move];
+        while let Some(m) = generator.next() {
+            stages.push(generator.get_stage().unwrap());
+            moves.push(m);
+        }
+        assert!(stages.windows(2).all(|w| w[0] <= w[1]));
+        let killer_index = stages
+            .iter()
+            .position(|&stage| stage == MoveStage::Killers)
+            .unwrap();
+        assert_eq!(moves[killer_index + 1], mv!(Pawn, A2, A4));
+        assert_eq!(stages[killer_index + 1], MoveStage::Quiets);
+        moves.sort_by_key(BoardMove::sort_key);
+        assert_eq!(moves, legal_moves);
+
+        let mut generator = StagedMoveGen::new(&board);
+        generator
+            .set_hash_move(Some(mv!(Pawn, A2, A5)))
+            .set_valuation(Valuation::new(100, 320, 330, 500, 900));
+        assert!(generator.next().is_some_and(|m| is_capture(&m)));
+        assert_eq!(generator.get_stage(), Some(MoveStage::Captures));
+    }
+}
@@ -0,0 +1,198 @@
+//! Stipulations of chess compositions
+//!
+//! This module implements the bounded solver of chess problems (see ``ChessBoard::solve``) with
+//! the classical stipulations: the direct mate, the helpmate and the selfmate. The solver is
+//! the plain exhaustive search over the legal moves, so it is intended for the small numbers of
+//! moves only
+
+use crate::errors::LibChessError as Error;
+use crate::{BoardMove, BoardStatus, ChessBoard};
+use std::fmt;
+use std::str::FromStr;
+
+/// Stipulation of the chess problem. The number is the number of moves of the side to move
+///
+/// # Examples
+/// ```
+/// use libchess::Stipulation;
+/// use std::str::FromStr;
+/// assert_eq!(
+///     Stipulation::from_str("h#2").unwrap(),
+///     Stipulation::Helpmate(2)
+/// );
+/// assert_eq!(Stipulation::Selfmate(3).to_string(), "s#3");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stipulation {
+    /// "#n": the side to move mates in ``n`` moves against any defence
+    Mate(usize),
+    /// "h#n": the side to move starts and both sides cooperate, so the side to move is mated
+    /// by the ``n``-th move of the opponent
+    Helpmate(usize),
+    /// "s#n": the side to move forces the opponent to give mate on the opponent's ``n``-th move
+    /// against any defence
+    Selfmate(usize),
+}
+
+impl fmt::Display for Stipulation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stipulation::Mate(n) => write!(f, "#{n}"),
+            Stipulation::Helpmate(n) => write!(f, "h#{n}"),
+            Stipulation::Selfmate(n) => write!(f, "s#{n}"),
+        }
+    }
+}
+
+impl FromStr for Stipulation {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidStipulation { s: s.to_string() };
+        let lowercase = s.trim().to_lowercase();
+        let (kind, number) = lowercase.split_once('#').ok_or_else(invalid)?;
+        let number = number.parse::<usize>().map_err(|_| invalid())?;
+        if number == 0 {
+            return Err(invalid());
+        }
+        match kind {
+            "" => Ok(Stipulation::Mate(number)),
+            "h" => Ok(Stipulation::Helpmate(number)),
+            "s" => Ok(Stipulation::Selfmate(number)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl Stipulation {
+    /// Returns the number of moves of the side to move
+    #[inline]
+    pub fn get_moves_number(&self) -> usize {
+        match *self {
+            Stipulation::Mate(n) | Stipulation::Helpmate(n) | Stipulation::Selfmate(n) => n,
+        }
+    }
+}
+
+fn is_mated(board: &ChessBoard) -> bool {
+    board.get_status() == BoardStatus::CheckMated(board.get_side_to_move())
+}
+
+/// Returns the positions after all legal moves of the side to move
+fn get_children(board: &ChessBoard) -> Vec<(BoardMove, ChessBoard)> {
+    board
+        .get_legal_moves()
+        .into_iter()
+        .map(|m| (m, board.make_move(&m).unwrap()))
+        .collect()
+}
+
+/// Returns true if the side to move mates in at most ``n`` moves against any defence
+fn forces_mate(board: &ChessBoard, n: usize) -> bool {
+    (n > 0)
+        && get_children(board)
+            .iter()
+            .any(|(_, next)| is_mate_after_key(next, n))
+}
+
+/// Returns true if the opponent is mated or can't escape the mate in ``n - 1`` more moves
+pub(crate) fn is_mate_after_key(board: &ChessBoard, n: usize) -> bool {
+    if is_mated(board) {
+        return true;
+    }
+    let replies = get_children(board);
+    (n > 1) && !replies.is_empty() && replies.iter().all(|(_, next)| forces_mate(next, n - 1))
+}
+
+/// Returns true if the side to move forces the opponent to give mate in at most ``n`` moves
+fn forces_selfmate(board: &ChessBoard, n: usize) -> bool {
+    (n > 0)
+        && get_children(board)
+            .iter()
+            .any(|(_, next)| is_selfmate_after_key(next, n))
+}
+
+/// Returns true if every reply of the opponent either mates or leads to the selfmate in
+/// ``n - 1`` more moves
+pub(crate) fn is_selfmate_after_key(board: &ChessBoard, n: usize) -> bool {
+    let replies = get_children(board);
+    !replies.is_empty()
+        && replies
+            .iter()
+            .all(|(_, next)| is_mated(next) || forces_selfmate(next, n - 1))
+}
+
+/// Collects all lines of ``plies`` cooperative moves which finish with the mate
+pub(crate) fn collect_helpmates(
+    board: &ChessBoard,
+    plies: usize,
+    line: &mut Vec<BoardMove>,
+    solutions: &mut Vec<Vec<BoardMove>>,
+) {
+    for (m, next) in get_children(board) {
+        line.push(m);
+        match plies {
+            1 if is_mated(&next) => solutions.push(line.clone()),
+            1 => {}
+            _ => collect_helpmates(&next, plies - 1, line, solutions),
+        }
+        line.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Color;
+
+    #[test]
+    fn stipulations_parsing() {
+        for (s, stipulation) in [
+            ("#2", Stipulation::Mate(2)),
+            ("h#3", Stipulation::Helpmate(3)),
+            ("S#12", Stipulation::Selfmate(12)),
+        ] {
+            assert_eq!(Stipulation::from_str(s).unwrap(), stipulation);
+            assert_eq!(stipulation.to_string(), s.to_lowercase());
+        }
+        for invalid in ["", "#", "#0", "h2", "x#2", "h#-1"] {
+            assert!(
+                matches!(
+                    Stipulation::from_str(invalid),
+                    Err(Error::InvalidStipulation { .. })
+                ),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn problems_solving() {
+        let board = ChessBoard::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let mate = vec![board.parse_san("Ra8").unwrap()];
+        assert_eq!(board.solve(Stipulation::Mate(1)), vec![mate.clone()]);
+        assert!(board.solve(Stipulation::Mate(2)).contains(&mate));
+        assert!(board.solve(Stipulation::Mate(0)).is_empty());
+
+        // the black king is boxed, so after any waiting move "g2" is the only reply
+        let board = ChessBoard::from_fen("4R3/8/8/7B/R5n1/5kp1/8/6BK w - - 0 1").unwrap();
+        let keys = board.solve(Stipulation::Selfmate(1));
+        assert_eq!(keys.len(), 9);
+        assert!(keys.contains(&vec![board.parse_san("Re7").unwrap()]));
+        assert!(!keys.contains(&vec![board.parse_san("Ra5").unwrap()]));
+
+        let board = ChessBoard::from_fen("7k/8/6K1/8/8/8/8/R7 b - - 0 1").unwrap();
+        assert_eq!(board.solve(Stipulation::Helpmate(1)).len(), 1);
+        let solutions = board.solve(Stipulation::Helpmate(2));
+        assert!(!solutions.is_empty());
+        for line in solutions {
+            let mut position = board;
+            for m in line.iter() {
+                assert_eq!(position.get_status(), BoardStatus::Ongoing);
+                position.make_move_mut(m).unwrap();
+            }
+            assert_eq!(line.len(), 4);
+            assert_eq!(position.get_status(), BoardStatus::CheckMated(Color::Black));
+        }
+    }
+}
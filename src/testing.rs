@@ -0,0 +1,335 @@
+//! Testing utilities
+//!
+//! This module exposes the invariant checks which are used to test the crate itself, so engines
+//! built on top of it can run them against their own integrations: random legal positions
+//! generation, perft with consistency checks of incremental board updates and SAN round-trip
+//! checks, hash, FEN and copy-make consistency checks for moves sequences. With ``proptest``
+//! feature enabled, it also provides ``proptest`` strategies generating random legal positions
+
+use crate::errors::LibChessError as Error;
+use crate::{BoardMove, ChessBoard, MovePropertiesOnBoard, PositionCore, ZOBRIST_TABLES};
+#[cfg(feature = "proptest")]
+use proptest::prelude::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::str::FromStr;
+
+/// Returns the position reached by playing random legal moves from specified board. Stops
+/// earlier if the game is finished (checkmate or stalemate)
+///
+/// The position is reproducible with the seeded generator, but the position reached for a given
+/// seed is not guaranteed to be the same between releases
+///
+/// # Examples
+/// ```
+/// use libchess::testing::random_board;
+/// use libchess::ChessBoard;
+/// use rand::rngs::StdRng;
+/// use rand::SeedableRng;
+///
+/// let mut rng = StdRng::seed_from_u64(42);
+/// let board = random_board(&ChessBoard::default(), 20, &mut rng);
+/// println!("{}", board);
+/// ```
+pub fn random_board(board: &ChessBoard, plies: usize, rng: &mut impl Rng) -> ChessBoard {
+    let mut board = *board;
+    for _ in 0..plies {
+        match board.get_legal_moves().choose(rng) {
+            Some(m) => {
+                board.make_move_mut(m).unwrap();
+            }
+            None => break,
+        }
+    }
+    board
+}
+
+/// Counts leaf nodes of the moves tree with specified depth (perft) and checks on every node
+/// that:
+/// 1. ``ChessBoard::count_legal_moves`` is consistent with ``ChessBoard::get_legal_moves``
+/// 2. the board updated incrementally by the move is the same as the board created from scratch
+///    from its FEN-string (including the hash, pins, checks and terminal status)
+///
+/// # Errors
+/// ``LibChessError::InvariantViolation`` with the description of the first failed check
+///
+/// # Examples
+/// ```
+/// use libchess::testing::check_perft;
+/// use libchess::ChessBoard;
+/// assert_eq!(check_perft(&ChessBoard::default(), 3).unwrap(), 8902);
+/// ```
+pub fn check_perft(board: &ChessBoard, depth: usize) -> Result<usize, Error> {
+    if depth == 0 {
+        return Ok(1);
+    }
+
+    let moves = board.get_legal_moves();
+    if moves.len() != board.count_legal_moves() {
+        return Err(Error::InvariantViolation {
+            description: format!(
+                "{} legal moves were generated, but {} were counted in \"{}\"",
+                moves.len(),
+                board.count_legal_moves(),
+                board.as_fen()
+            ),
+        });
+    }
+
+    let mut nodes = 0;
+    for m in moves.iter() {
+        let next_board = board.make_move(m)?;
+        let expected = ChessBoard::from_str(&next_board.as_fen())?;
+        if (next_board != expected)
+            | (next_board.get_pin_mask() != expected.get_pin_mask())
+            | (next_board.get_check_mask() != expected.get_check_mask())
+            | (next_board.is_terminal() != expected.is_terminal())
+        {
+            return Err(Error::InvariantViolation {
+                description: format!(
+                    "position after {} in \"{}\" differs from the one created from FEN",
+                    m,
+                    board.as_fen()
+                ),
+            });
+        }
+        nodes += check_perft(&next_board, depth - 1)?;
+    }
+    Ok(nodes)
+}
+
+/// Checks that every legal move of the board is rendered to SAN-string which is parsed back to
+/// the same move
+///
+/// # Errors
+/// ``LibChessError::InvariantViolation`` with the description of the first failed move
+///
+/// # Examples
+/// ```
+/// use libchess::testing::check_san_round_trip;
+/// use libchess::ChessBoard;
+/// assert!(check_san_round_trip(&ChessBoard::default()).is_ok());
+/// ```
+pub fn check_san_round_trip(board: &ChessBoard) -> Result<(), Error> {
+    for m in board.get_legal_moves() {
+        let san = m.to_string(MovePropertiesOnBoard::new(&m, board)?);
+        match board.parse_san(&san) {
+            Ok(parsed) if parsed == m => {}
+            _ => {
+                return Err(Error::InvariantViolation {
+                    description: format!(
+                        "SAN \"{}\" is not parsed back in \"{}\"",
+                        san,
+                        board.as_fen()
+                    ),
+                })
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Applies the moves to the board one by one and checks that the incrementally updated hash is
+/// equal to the hash calculated from scratch after every move
+///
+/// # Errors
+/// ``LibChessError::InvariantViolation`` with the description of the first failed check
+///
+/// ``LibChessError::IllegalMoveDetected`` if any move is illegal
+///
+/// # Examples
+/// ```
+/// use libchess::testing::check_hash_consistency;
+/// use libchess::{mv, squares::*, BoardMove, ChessBoard, PieceMove, PieceType::*};
+/// let moves = [mv!(Pawn, E2, E4), mv!(Pawn, D7, D5), mv!(Pawn, E4, D5)];
+/// assert!(check_hash_consistency(&ChessBoard::default(), &moves).is_ok());
+/// ```
+pub fn check_hash_consistency(board: &ChessBoard, moves: &[BoardMove]) -> Result<(), Error> {
+    let mut board = *board;
+    for m in moves.iter() {
+        board.make_move_mut(m)?;
+        if board.get_hash() != ZOBRIST_TABLES.calculate_position_hash(&board) {
+            return Err(Error::InvariantViolation {
+                description: format!(
+                    "incremental hash differs from the calculated one after {} in \"{}\"",
+                    m,
+                    board.as_fen()
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the board created from the FEN-string of specified board is the same board
+///
+/// # Errors
+/// ``LibChessError::InvariantViolation`` if the boards differ
+///
+/// # Examples
+/// ```
+/// use libchess::testing::check_fen_round_trip;
+/// use libchess::ChessBoard;
+/// assert!(check_fen_round_trip(&ChessBoard::default()).is_ok());
+/// ```
+pub fn check_fen_round_trip(board: &ChessBoard) -> Result<(), Error> {
+    let fen = board.as_fen();
+    let restored = ChessBoard::from_str(&fen)?;
+    if (restored != *board) | (restored.as_fen() != fen) {
+        return Err(Error::InvariantViolation {
+            description: format!("FEN round trip changes the position \"{fen}\""),
+        });
+    }
+    Ok(())
+}
+
+/// Checks for every legal move of the board that copy-make (``ChessBoard::make_move``) and
+/// make/unmake (``ChessBoard::make_move_mut`` with restoring from ``PositionCore``) produce the
+/// same positions with the same legal moves
+///
+/// # Errors
+/// ``LibChessError::InvariantViolation`` with the description of the first failed move
+///
+/// # Examples
+/// ```
+/// use libchess::testing::check_copy_make_consistency;
+/// use libchess::ChessBoard;
+/// assert!(check_copy_make_consistency(&ChessBoard::default()).is_ok());
+/// ```
+pub fn check_copy_make_consistency(board: &ChessBoard) -> Result<(), Error> {
+    let legal_moves = board.get_legal_moves();
+    let saved = PositionCore::from(board);
+    let mut current = *board;
+
+    for m in legal_moves.iter() {
+        let copied = board.make_move(m)?;
+        current.make_move_mut(m)?;
+        let made_equal =
+            (copied == current) & (copied.get_legal_moves() == current.get_legal_moves());
+
+        current = ChessBoard::from(saved);
+        let unmade_equal = (current == *board) & (current.get_legal_moves() == legal_moves);
+        if !(made_equal & unmade_equal) {
+            return Err(Error::InvariantViolation {
+                description: format!(
+                    "copy-make and make/unmake of {} differ in \"{}\"",
+                    m,
+                    board.as_fen()
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Returns the board reached by playing the moves chosen by indices (taken modulo the number of
+/// legal moves) from specified board. Stops earlier if the game is finished
+#[cfg(any(test, feature = "proptest"))]
+fn board_from_choices(board: &ChessBoard, choices: &[usize]) -> (ChessBoard, Vec<BoardMove>) {
+    let mut board = *board;
+    let mut moves = Vec::with_capacity(choices.len());
+    for choice in choices.iter() {
+        let legal_moves = board.get_legal_moves();
+        if legal_moves.is_empty() {
+            break;
+        }
+        let m = legal_moves[choice % legal_moves.len()];
+        board.make_move_mut(&m).unwrap();
+        moves.push(m);
+    }
+    (board, moves)
+}
+
+/// ``proptest`` strategy generating sequences of up to ``max_plies`` random legal moves from
+/// specified board. Shrinks towards shorter sequences and the first legal moves
+///
+/// # Examples
+/// ```
+/// use libchess::testing::{arb_line, check_hash_consistency};
+/// use libchess::ChessBoard;
+/// use proptest::prelude::*;
+///
+/// proptest!(|((board, moves) in arb_line(ChessBoard::default(), 20))| {
+///     prop_assert!(check_hash_consistency(&board, &moves).is_ok());
+/// });
+/// ```
+#[cfg(feature = "proptest")]
+pub fn arb_line(
+    board: ChessBoard,
+    max_plies: usize,
+) -> impl Strategy<Value = (ChessBoard, Vec<BoardMove>)> {
+    prop::collection::vec(any::<usize>(), 0..=max_plies).prop_map(move |choices| {
+        let (_, moves) = board_from_choices(&board, &choices);
+        (board, moves)
+    })
+}
+
+/// ``proptest`` strategy generating the positions reached by up to ``max_plies`` random legal
+/// moves from specified board
+///
+/// # Examples
+/// ```
+/// use libchess::testing::{arb_board, check_fen_round_trip};
+/// use libchess::ChessBoard;
+/// use proptest::prelude::*;
+///
+/// proptest!(|(board in arb_board(ChessBoard::default(), 40))| {
+///     prop_assert!(check_fen_round_trip(&board).is_ok());
+/// });
+/// ```
+#[cfg(feature = "proptest")]
+pub fn arb_board(board: ChessBoard, max_plies: usize) -> impl Strategy<Value = ChessBoard> {
+    prop::collection::vec(any::<usize>(), 0..=max_plies)
+        .prop_map(move |choices| board_from_choices(&board, &choices).0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn random_positions_invariants() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for plies in [5, 20, 40, 80] {
+            let board = random_board(&ChessBoard::default(), plies, &mut rng);
+            check_perft(&board, 2).unwrap();
+            check_san_round_trip(&board).unwrap();
+            check_fen_round_trip(&board).unwrap();
+            check_copy_make_consistency(&board).unwrap();
+        }
+
+        let (board, moves) = board_from_choices(&ChessBoard::default(), &[7, 3, 11, 0, 25, 9]);
+        assert_eq!(moves.len(), 6);
+        check_hash_consistency(&ChessBoard::default(), &moves).unwrap();
+        let replayed = moves.iter().fold(ChessBoard::default(), |board, m| {
+            board.make_move(m).unwrap()
+        });
+        assert_eq!(replayed, board);
+    }
+}
+
+#[cfg(all(test, feature = "proptest"))]
+mod properties {
+    use super::*;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn hash_consistency((board, moves) in arb_line(ChessBoard::default(), 60)) {
+            prop_assert!(check_hash_consistency(&board, &moves).is_ok());
+        }
+
+        #[test]
+        fn fen_round_trip(board in arb_board(ChessBoard::default(), 60)) {
+            prop_assert!(check_fen_round_trip(&board).is_ok());
+        }
+
+        #[test]
+        fn copy_make_consistency(board in arb_board(ChessBoard::default(), 60)) {
+            prop_assert!(check_copy_make_consistency(&board).is_ok());
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Move times
+//!
+//! This module implements the time usage statistics of the game and the conversions of move
+//! durations to and from the ``[%emt H:MM:SS]`` (elapsed move time) PGN-comments
+
+use crate::errors::LibChessError as Error;
+use crate::{Color, Ply, COLORS_NUMBER};
+use std::fmt;
+use std::time::Duration;
+
+/// Time usage of one player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TimeUsage {
+    /// Total time spent on the timed moves
+    pub total:       Duration,
+    /// Number of moves with the recorded time
+    pub timed_moves: usize,
+    /// The longest move and its ply
+    pub longest:     Option<(Ply, Duration)>,
+}
+
+impl TimeUsage {
+    /// Returns the average time of the timed moves
+    pub fn get_average(&self) -> Option<Duration> {
+        match self.timed_moves {
+            0 => None,
+            n => Some(self.total / n as u32),
+        }
+    }
+
+    pub(crate) fn add(&mut self, ply: Ply, duration: Duration) -> &mut Self {
+        self.total += duration;
+        self.timed_moves += 1;
+        if self.longest.is_none_or(|(_, longest)| duration > longest) {
+            self.longest = Some((ply, duration));
+        }
+        self
+    }
+}
+
+/// Time usage statistics of both players (see ``Game::time_report``)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TimeReport {
+    usage: [TimeUsage; COLORS_NUMBER],
+}
+
+impl TimeReport {
+    #[inline]
+    pub fn get_usage(&self, color: Color) -> TimeUsage { self.usage[color.to_index()] }
+
+    pub(crate) fn add(&mut self, color: Color, ply: Ply, duration: Duration) -> &mut Self {
+        self.usage[color.to_index()].add(ply, duration);
+        self
+    }
+}
+
+impl fmt::Display for TimeReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lines: Vec<String> = Color::iter()
+            .map(|color| {
+                let usage = self.get_usage(color);
+                match usage.get_average() {
+                    None => format!("{color}: no timed moves"),
+                    Some(average) => format!(
+                        "{color}: {} for {} moves (average {})",
+                        format_emt(usage.total),
+                        usage.timed_moves,
+                        format_emt(average),
+                    ),
+                }
+            })
+            .collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// Formats the duration as ``H:MM:SS`` (with milliseconds if they are not zero)
+pub(crate) fn format_emt(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let result = format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+    match duration.subsec_millis() {
+        0 => result,
+        millis => format!("{result}.{millis:03}"),
+    }
+}
+
+/// Parses the duration in ``H:MM:SS``, ``M:SS`` or ``S`` formats (seconds can be fractional)
+pub(crate) fn parse_emt(s: &str) -> Result<Duration, Error> {
+    let invalid = || Error::InvalidMoveTime { s: s.to_string() };
+    let parts: Vec<&str> = s.split(':').collect();
+    if parts.len() > 3 {
+        return Err(invalid());
+    }
+
+    let (seconds, minutes_and_hours) = parts.split_last().ok_or_else(invalid)?;
+    let (seconds, fraction) = seconds.split_once('.').unwrap_or((seconds, ""));
+    let mut total = Duration::from_secs(seconds.parse::<u64>().map_err(|_| invalid())?);
+    if !fraction.is_empty() {
+        if (fraction.len() > 9) | !fraction.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        total += Duration::from_nanos(format!("{fraction:0<9}").parse::<u64>().unwrap());
+    }
+    for (i, part) in minutes_and_hours.iter().rev().enumerate() {
+        let units = part.parse::<u64>().map_err(|_| invalid())?;
+        total += Duration::from_secs(units * 60u64.pow(i as u32 + 1));
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emt_conversions() {
+        assert_eq!(format_emt(Duration::from_secs(5)), "0:00:05");
+        assert_eq!(format_emt(Duration::from_secs(3725)), "1:02:05");
+        assert_eq!(format_emt(Duration::from_millis(1500)), "0:00:01.500");
+
+        assert_eq!(parse_emt("0:00:05").unwrap(), Duration::from_secs(5));
+        assert_eq!(parse_emt("1:02:05").unwrap(), Duration::from_secs(3725));
+        assert_eq!(parse_emt("2:05").unwrap(), Duration::from_secs(125));
+        assert_eq!(parse_emt("0:00:01.5").unwrap(), Duration::from_millis(1500));
+        assert!(parse_emt("1:2:3:4").is_err());
+        assert!(parse_emt("0:-1:00").is_err());
+        assert!(parse_emt("").is_err());
+        assert!(parse_emt("0:00:1.x").is_err());
+
+        let time = Duration::from_millis(16_666);
+        assert_eq!(parse_emt(&format_emt(time)).unwrap(), time);
+    }
+
+    #[test]
+    fn time_usage() {
+        let mut report = TimeReport::default();
+        report
+            .add(Color::White, Ply::new(0), Duration::from_secs(10))
+            .add(Color::White, Ply::new(2), Duration::from_secs(20))
+            .add(Color::White, Ply::new(4), Duration::from_secs(20));
+
+        let usage = report.get_usage(Color::White);
+        assert_eq!(usage.total, Duration::from_secs(50));
+        assert_eq!(usage.longest, Some((Ply::new(2), Duration::from_secs(20))));
+        assert_eq!(report.get_usage(Color::Black).get_average(), None);
+        assert_eq!(
+            report.to_string(),
+            "white: 0:00:50 for 3 moves (average 0:00:16.666)\nblack: no timed moves"
+        );
+    }
+}
@@ -0,0 +1,285 @@
+//! Move generation harness (requires ``uci-harness`` feature)
+//!
+//! This module cross-checks the moves generation against the external UCI engine which supports
+//! the "go perft" command (e.g. Stockfish). The numbers of leaf nodes after every legal move are
+//! compared with the engine's ones, and the search descends into the first subtree with
+//! different number, so the first divergent position and the moves which differ are reported.
+//! It is intended for the contributors changing the moves generation, not for the release builds
+//!
+//! # Examples
+//! ```no_run
+//! use libchess::uci_harness::{find_perft_divergence, UciEngine};
+//! use libchess::ChessBoard;
+//!
+//! let mut engine = UciEngine::spawn("stockfish").unwrap();
+//! match find_perft_divergence(&mut engine, &ChessBoard::default(), 5).unwrap() {
+//!     None => println!("no divergence"),
+//!     Some(divergence) => println!("{}", divergence),
+//! }
+//! ```
+
+use crate::errors::LibChessError as Error;
+use crate::{BoardMove, ChessBoard};
+use std::fmt;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+/// The prefix of the line with the total number of nodes finishing the "go perft" output
+const PERFT_TOTAL_PREFIX: &str = "Nodes searched";
+
+fn failure(description: impl ToString) -> Error {
+    Error::UciEngineFailure {
+        description: description.to_string(),
+    }
+}
+
+/// Connection to the UCI engine. The engine process (if it was spawned) is stopped when the
+/// connection is dropped
+pub struct UciEngine<R: BufRead = BufReader<ChildStdout>, W: Write = ChildStdin> {
+    reader: R,
+    writer: W,
+    child:  Option<Child>,
+}
+
+impl UciEngine {
+    /// Starts the engine from the executable and initializes it
+    ///
+    /// # Errors
+    /// ``LibChessError::UciEngineFailure`` if the engine can't be started or doesn't answer
+    /// "uciok"
+    pub fn spawn(path: &str) -> Result<Self, Error> {
+        let mut child = Command::new(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(failure)?;
+        let reader = BufReader::new(child.stdout.take().unwrap());
+        let writer = child.stdin.take().unwrap();
+        let mut engine = Self {
+            reader,
+            writer,
+            child: Some(child),
+        };
+        engine.initialize()?;
+        Ok(engine)
+    }
+}
+
+impl<R: BufRead, W: Write> UciEngine<R, W> {
+    /// Connects to the engine through the streams (e.g. the network connection) and
+    /// initializes it
+    ///
+    /// # Errors
+    /// ``LibChessError::UciEngineFailure`` if the engine doesn't answer "uciok"
+    pub fn new(reader: R, writer: W) -> Result<Self, Error> {
+        let mut engine = Self {
+            reader,
+            writer,
+            child: None,
+        };
+        engine.initialize()?;
+        Ok(engine)
+    }
+
+    fn initialize(&mut self) -> Result<(), Error> {
+        self.send("uci")?;
+        while self.read_line()? != "uciok" {}
+        Ok(())
+    }
+
+    fn send(&mut self, command: &str) -> Result<(), Error> {
+        writeln!(self.writer, "{command}").map_err(failure)?;
+        self.writer.flush().map_err(failure)
+    }
+
+    fn read_line(&mut self) -> Result<String, Error> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line).map_err(failure)? {
+            0 => Err(failure("the engine closed the output")),
+            _ => Ok(line.trim().to_string()),
+        }
+    }
+
+    /// Returns the numbers of leaf nodes after every move (in UCI notation) reported by the
+    /// engine for the "go perft" command
+    ///
+    /// # Errors
+    /// ``LibChessError::UciEngineFailure`` if the engine's output can't be read
+    pub fn perft_divide(
+        &mut self,
+        board: &ChessBoard,
+        depth: usize,
+    ) -> Result<Vec<(String, u64)>, Error> {
+        self.send(&format!("position fen {}", board.as_fen()))?;
+        self.send(&format!("go perft {depth}"))?;
+        let mut result = Vec::new();
+        loop {
+            let line = self.read_line()?;
+            if line.starts_with(PERFT_TOTAL_PREFIX) {
+                return Ok(result);
+            }
+            // the info strings and the empty lines are skipped
+            if let Some((m, nodes)) = line.split_once(':') {
+                if let Ok(nodes) = nodes.trim().parse() {
+                    result.push((m.trim().to_string(), nodes));
+                }
+            }
+        }
+    }
+}
+
+impl<R: BufRead, W: Write> Drop for UciEngine<R, W> {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        if let Some(child) = self.child.as_mut() {
+            let _ = child.wait();
+        }
+    }
+}
+
+/// The position where the moves generation diverges from the engine
+#[derive(Debug, Clone, PartialEq)]
+pub struct PerftDivergence {
+    pub position:   ChessBoard,
+    /// The depth of the perft which is compared in this position
+    pub depth:      usize,
+    /// The moves reported by the engine which are not generated (in UCI notation)
+    pub missing:    Vec<String>,
+    /// The generated moves which are not reported by the engine
+    pub extra:      Vec<BoardMove>,
+    /// The moves with different numbers of leaf nodes (the generated and the engine's ones).
+    /// It is not empty only if the divergence is not found deeper
+    pub mismatched: Vec<(BoardMove, u64, u64)>,
+}
+
+impl fmt::Display for PerftDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} (depth {})", self.position.as_fen(), self.depth)?;
+        for m in self.missing.iter() {
+            writeln!(f, "missing move: {m}")?;
+        }
+        for m in self.extra.iter() {
+            writeln!(f, "extra move: {m}")?;
+        }
+        for (m, nodes, expected) in self.mismatched.iter() {
+            writeln!(f, "{m}: {nodes} nodes instead of {expected}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares the perft of specified depth with the engine's one and returns the first (the
+/// deepest) divergent position (None if the numbers are equal)
+///
+/// # Errors
+/// ``LibChessError::UciEngineFailure`` if the engine's output can't be read
+pub fn find_perft_divergence<R: BufRead, W: Write>(
+    engine: &mut UciEngine<R, W>,
+    board: &ChessBoard,
+    depth: usize,
+) -> Result<Option<PerftDivergence>, Error> {
+    if depth == 0 {
+        return Ok(None);
+    }
+    let mut reported = Vec::new();
+    let mut missing = Vec::new();
+    for (uci, nodes) in engine.perft_divide(board, depth)? {
+        match board.parse_uci(&uci) {
+            Ok(m) if board.is_legal_move(&m) => reported.push((m, nodes)),
+            _ => missing.push(uci),
+        }
+    }
+
+    let mut extra = Vec::new();
+    let mut mismatched = Vec::new();
+    for m in board.get_legal_moves() {
+        match reported
+            .iter()
+            .find(|(reported_move, _)| *reported_move == m)
+        {
+            None => extra.push(m),
+            Some(&(_, expected)) => {
+                let nodes = board.make_move(&m).unwrap().perft(depth - 1);
+                if nodes != expected {
+                    mismatched.push((m, nodes, expected));
+                }
+            }
+        }
+    }
+    if missing.is_empty() & extra.is_empty() {
+        if let Some(&(m, _, _)) = mismatched.first() {
+            let next = board.make_move(&m).unwrap();
+            if let Some(divergence) = find_perft_divergence(engine, &next, depth - 1)? {
+                return Ok(Some(divergence));
+            }
+        }
+    }
+
+    match missing.is_empty() & extra.is_empty() & mismatched.is_empty() {
+        true => Ok(None),
+        false => Ok(Some(PerftDivergence {
+            position: *board,
+            depth,
+            missing,
+            extra,
+            mismatched,
+        })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mv, squares::*, PieceMove, PieceType::*};
+
+    /// Returns the engine's output for the perft of the board (the moves are written by the
+    /// square names, so the castling is the king move)
+    fn perft_output(board: &ChessBoard, depth: usize, skip: usize) -> String {
+        let mut output = String::from("info string NNUE evaluation enabled\n");
+        for (i, m) in board.get_legal_moves().iter().enumerate() {
+            let next = board.make_move(m).unwrap();
+            let m = match m {
+                BoardMove::MovePiece(m) => {
+                    format!("{}{}", m.get_source_square(), m.get_destination_square())
+                        .to_lowercase()
+                }
+                _ => unreachable!(),
+            };
+            if i != skip {
+                output += &format!("{m}: {}\n", next.perft(depth - 1));
+            }
+        }
+        output + "\nNodes searched: 0\n\n"
+    }
+
+    #[test]
+    fn perft_divergence() {
+        let board = ChessBoard::default();
+        let child = board.make_move(&mv!(Pawn, A2, A3)).unwrap();
+
+        // the engine doesn't report the first move of black after "a2a3" and adds the own one
+        let output = "id name Fake\nuciok\n".to_string()
+            + &perft_output(&board, 2, usize::MAX).replace("a2a3: 20", "a2a3: 21")
+            + &perft_output(&child, 1, 0).replacen("\n\n", "\nh7h4: 1\n\n", 1)
+            + &perft_output(&board, 2, usize::MAX);
+        let mut commands = Vec::new();
+        let mut engine = UciEngine::new(output.as_bytes(), &mut commands).unwrap();
+
+        let divergence = find_perft_divergence(&mut engine, &board, 2)
+            .unwrap()
+            .unwrap();
+        assert_eq!(divergence.position, child);
+        assert_eq!(divergence.depth, 1);
+        assert_eq!(divergence.missing, ["h7h4"]);
+        assert_eq!(divergence.extra, [child.get_legal_moves()[0]]);
+        assert!(divergence.mismatched.is_empty());
+
+        assert_eq!(find_perft_divergence(&mut engine, &board, 2).unwrap(), None);
+        assert!(engine.perft_divide(&board, 1).is_err());
+        drop(engine);
+        let commands = String::from_utf8(commands).unwrap();
+        assert!(commands.starts_with("uci\nposition fen "));
+        assert!(commands.contains("go perft 1\n"));
+        assert!(commands.ends_with("quit\n"));
+    }
+}
@@ -0,0 +1,244 @@
+//! Piece valuation
+//!
+//! This module defines the standard values of pieces (in centipawns) and the ``Valuation``
+//! structure which allows to tweak them. Valuation is used by material counting, MVV-LVA
+//! (Most Valuable Victim - Least Valuable Attacker) move ordering and SEE (Static Exchange
+//! Evaluation)
+
+use crate::{BoardMove, ChessBoard, Color, PieceMove, PieceType, PIECE_TYPES_NUMBER};
+use std::cmp::Ordering;
+
+pub const PAWN_VALUE: i32 = 100;
+pub const KNIGHT_VALUE: i32 = 320;
+pub const BISHOP_VALUE: i32 = 330;
+pub const ROOK_VALUE: i32 = 500;
+pub const QUEEN_VALUE: i32 = 900;
+/// The king is never counted as material, this value is used only to order the attackers
+pub const KING_VALUE: i32 = 20000;
+
+/// Values of the pieces in centipawns
+///
+/// # Examples
+/// ```
+/// use libchess::{ChessBoard, PieceType::*, Valuation};
+/// let mut valuation = Valuation::default();
+/// assert_eq!(valuation.get_value(Knight), 320);
+///
+/// valuation.set_value(Bishop, 350);
+/// assert_eq!(valuation.get_material_balance(&ChessBoard::default()), 0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Valuation {
+    values: [i32; PIECE_TYPES_NUMBER],
+}
+
+impl Default for Valuation {
+    fn default() -> Self {
+        Self::new(
+            PAWN_VALUE,
+            KNIGHT_VALUE,
+            BISHOP_VALUE,
+            ROOK_VALUE,
+            QUEEN_VALUE,
+        )
+    }
+}
+
+impl Valuation {
+    pub fn new(pawn: i32, knight: i32, bishop: i32, rook: i32, queen: i32) -> Self {
+        Self {
+            values: [pawn, knight, bishop, rook, queen, KING_VALUE],
+        }
+    }
+
+    #[inline]
+    pub fn get_value(&self, piece_type: PieceType) -> i32 { self.values[piece_type.to_index()] }
+
+    /// Allows to set the value of specified piece type manually
+    #[inline]
+    pub fn set_value(&mut self, piece_type: PieceType, value: i32) -> &mut Self {
+        self.values[piece_type.to_index()] = value;
+        self
+    }
+
+    /// Returns the sum of values of all pieces (except the king) of specified color
+    pub fn get_material(&self, board: &ChessBoard, color: Color) -> i32 {
+        PieceType::iter()
+            .filter(|&piece_type| piece_type != PieceType::King)
+            .map(|piece_type| {
+                let mask = board.get_piece_type_mask(piece_type) & board.get_color_mask(color);
+                mask.count_ones() as i32 * self.get_value(piece_type)
+            })
+            .sum()
+    }
+
+    /// Returns the material difference from white's perspective
+    pub fn get_material_balance(&self, board: &ChessBoard) -> i32 {
+        self.get_material(board, Color::White) - self.get_material(board, Color::Black)
+    }
+
+    /// Returns the MVV-LVA score of the capture: captures of more valuable pieces by less
+    /// valuable ones get higher scores. Returns None if the move is not a capture
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{mv, squares::*, BoardMove, ChessBoard, PieceMove, PieceType::*, Valuation};
+    /// use std::str::FromStr;
+    /// let board = ChessBoard::from_str("4k3/8/8/3q4/4P3/8/8/3QK3 w - - 0 1").unwrap();
+    /// let valuation = Valuation::default();
+    /// assert!(
+    ///     valuation
+    ///         .get_mvv_lva_score(&board, &mv!(Pawn, E4, D5))
+    ///         .unwrap()
+    ///         > valuation
+    ///             .get_mvv_lva_score(&board, &mv!(Queen, D1, D5))
+    ///             .unwrap()
+    /// );
+    /// assert_eq!(
+    ///     valuation.get_mvv_lva_score(&board, &mv!(Pawn, E4, E5)),
+    ///     None
+    /// );
+    /// ```
+    pub fn get_mvv_lva_score(&self, board: &ChessBoard, board_move: &BoardMove) -> Option<i32> {
+        let m = board_move.piece_move().ok()?;
+        let victim = self.get_captured_value(board, &m)?;
+        Some(victim * PIECE_TYPES_NUMBER as i32 - self.get_attacker_rank(m.get_piece_type()))
+    }
+
+    /// Returns the SEE value of the capture: the material gain of the side to move after the
+    /// sequence of the cheapest recaptures on the destination square (each side may stop
+    /// capturing when it's not profitable). Returns None if the move is not a legal capture
+    ///
+    /// # Examples
+    /// ```
+    /// use libchess::{mv, squares::*, BoardMove, ChessBoard, PieceMove, PieceType::*, Valuation};
+    /// use std::str::FromStr;
+    /// let board = ChessBoard::from_str("4k3/2p5/3p4/8/8/8/3R4/3RK3 w - - 0 1").unwrap();
+    /// let valuation = Valuation::default();
+    /// assert_eq!(
+    ///     valuation.get_static_exchange(&board, &mv!(Rook, D2, D6)),
+    ///     Some(100 - 500 + 100)
+    /// );
+    /// ```
+    pub fn get_static_exchange(&self, board: &ChessBoard, board_move: &BoardMove) -> Option<i32> {
+        let m = board_move.piece_move().ok()?;
+        let gain = self.get_capture_gain(board, &m)?;
+        let next_board = board.make_move(board_move).ok()?;
+        Some(gain - self.get_exchange_on(&next_board, m))
+    }
+
+    /// Returns the best material gain of the side to move by the exchange on the destination
+    /// square of the last move (never negative because the side may not capture at all)
+    fn get_exchange_on(&self, board: &ChessBoard, last_move: PieceMove) -> i32 {
+        let square = last_move.get_destination_square();
+        let recapture = board
+            .get_legal_moves()
+            .into_iter()
+            .filter_map(|m| m.piece_move().ok())
+            .filter(|m| m.get_destination_square() == square)
+            .min_by(|a, b| match self.compare_attackers(a, b) {
+                Ordering::Equal => self
+                    .get_capture_gain(board, b)
+                    .cmp(&self.get_capture_gain(board, a)),
+                ordering => ordering,
+            });
+
+        match recapture {
+            None => 0,
+            Some(m) => {
+                let gain = self.get_capture_gain(board, &m).unwrap();
+                let next_board = board.make_move(&BoardMove::MovePiece(m)).unwrap();
+                0.max(gain - self.get_exchange_on(&next_board, m))
+            }
+        }
+    }
+
+    fn compare_attackers(&self, a: &PieceMove, b: &PieceMove) -> Ordering {
+        self.get_value(a.get_piece_type())
+            .cmp(&self.get_value(b.get_piece_type()))
+    }
+
+    /// Returns the value of captured piece (None if the move is not a capture)
+    fn get_captured_value(&self, board: &ChessBoard, m: &PieceMove) -> Option<i32> {
+        if m.is_en_passant_move(board) {
+            return Some(self.get_value(PieceType::Pawn));
+        }
+        let destination = m.get_destination_square();
+        match board.get_piece_color_on(destination) {
+            Some(color) if color != board.get_side_to_move() => board
+                .get_piece_type_on(destination)
+                .map(|piece_type| self.get_value(piece_type)),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of captured piece including the gain of promotion
+    fn get_capture_gain(&self, board: &ChessBoard, m: &PieceMove) -> Option<i32> {
+        let promotion_gain = m.get_promotion().map_or(0, |piece_type| {
+            self.get_value(piece_type) - self.get_value(PieceType::Pawn)
+        });
+        Some(self.get_captured_value(board, m)? + promotion_gain)
+    }
+
+    /// Returns the rank of the attacker (0 for the least valuable piece type)
+    fn get_attacker_rank(&self, piece_type: PieceType) -> i32 {
+        PieceType::iter()
+            .filter(|&other| self.get_value(other) < self.get_value(piece_type))
+            .count() as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{squares::*, PieceType::*};
+    use std::str::FromStr;
+
+    #[test]
+    fn material() {
+        let valuation = Valuation::default();
+        let board = ChessBoard::default();
+        assert_eq!(valuation.get_material(&board, Color::White), 4000);
+        assert_eq!(valuation.get_material_balance(&board), 0);
+
+        let board = ChessBoard::from_str("4k3/8/8/3q4/8/8/PP6/R3K3 w - - 0 1").unwrap();
+        assert_eq!(valuation.get_material_balance(&board), -200);
+
+        let mut valuation = Valuation::default();
+        valuation.set_value(Queen, 1000);
+        assert_eq!(valuation.get_material_balance(&board), -300);
+    }
+
+    #[test]
+    fn static_exchange() {
+        let valuation = Valuation::default();
+
+        // undefended pawn
+        let board = ChessBoard::from_str("4k3/8/3p4/8/8/8/8/3RK3 w - - 0 1").unwrap();
+        assert_eq!(
+            valuation.get_static_exchange(&board, &mv!(Rook, D1, D6)),
+            Some(100)
+        );
+
+        // the queen takes a defended knight and is recaptured
+        let board = ChessBoard::from_str("4k3/2p5/3n4/8/8/8/8/3QK3 w - - 0 1").unwrap();
+        assert_eq!(
+            valuation.get_static_exchange(&board, &mv!(Queen, D1, D6)),
+            Some(320 - 900)
+        );
+
+        // the defender doesn't recapture when it's not profitable
+        let board = ChessBoard::from_str("3qk3/8/3p4/4P3/8/8/8/3RK3 w - - 0 1").unwrap();
+        assert_eq!(
+            valuation.get_static_exchange(&board, &mv!(Rook, D1, D6)),
+            Some(100)
+        );
+
+        // not a capture
+        let board = ChessBoard::default();
+        assert_eq!(
+            valuation.get_static_exchange(&board, &mv!(Pawn, E2, E4)),
+            None
+        );
+    }
+}
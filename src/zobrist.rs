@@ -3,20 +3,75 @@
 //! Allows to calculate and to fast update the "unique" hash value for each position
 //! Number of hash collisions grows like the square root of the number of positions
 //! under consideration
+//!
+//! The global tables (``ZOBRIST_TABLES``) are generated by the SplitMix64 generator (implemented
+//! here, so it doesn't depend on the version of the ``rand`` crate) seeded with the fixed seed,
+//! so the hashes of the positions are the same on every run and platform. The tables may change
+//! only with a breaking release since the hashes may be stored (e.g. in the opening books and the
+//! position indexes). They were changed in 0.9.0 (earlier versions used ``StdRng``), so the
+//! hashes stored by 0.8 don't match the hashes of the same positions anymore. The tables with
+//! other values (e.g. for testing the collisions) are generated by ``ZobristHasher::with_seed``
+//! or by any caller's generator (see ``ZobristHasher::from_rng``)
 
 use crate::{
-    CastlingRights, ChessBoard, Color, Piece, Square, CASTLING_RIGHTS_NUMBER, COLORS_NUMBER,
-    FILES_NUMBER, PIECE_TYPES_NUMBER, SQUARES_NUMBER,
+    BoardSymmetry, CastlingRights, ChessBoard, Color, Piece, Square, CASTLING_RIGHTS_NUMBER,
+    COLORS_NUMBER, FILES_NUMBER, PIECE_TYPES_NUMBER, SQUARES_NUMBER,
 };
 use lazy_static::lazy_static;
-use rand::rngs::StdRng;
-use rand::{Rng, SeedableRng};
+use rand::{Rng, RngCore};
 
 const SEED: u64 = 1370359990842121; // The most meaningful constant in my code.
                                     // And in any other's code too, actually
 
 pub type PositionHashValueType = u64;
 
+/// SplitMix64 generator (Steele, Lea, Flood; 2014) which produces the tables. Its output is
+/// fixed by the algorithm unlike ``StdRng`` which may change with the ``rand`` crate
+struct SplitMix64(u64);
+
+impl RngCore for SplitMix64 {
+    fn next_u32(&mut self) -> u32 { (self.next_u64() >> 32) as u32 }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// The tables of random values for the position hashing
+///
+/// # Examples
+/// ```
+/// use libchess::{ChessBoard, ZobristHasher, ZOBRIST_TABLES};
+/// use rand::rngs::StdRng;
+/// use rand::SeedableRng;
+///
+/// let board = ChessBoard::default();
+/// let hasher = ZobristHasher::from_rng(&mut StdRng::seed_from_u64(42));
+/// assert_ne!(
+///     hasher.calculate_position_hash(&board),
+///     ZOBRIST_TABLES.calculate_position_hash(&board)
+/// );
+/// assert_eq!(
+///     ZobristHasher::with_seed(42).calculate_position_hash(&board),
+///     ZobristHasher::with_seed(42).calculate_position_hash(&board)
+/// );
+/// ```
 #[derive(Debug, Clone)]
 pub struct ZobristHasher {
     piece_square_table:
@@ -31,7 +86,15 @@ impl Default for ZobristHasher {
 }
 
 impl ZobristHasher {
-    pub fn new() -> Self {
+    /// Generates the tables equal to the global ones (``ZOBRIST_TABLES``)
+    pub fn new() -> Self { Self::with_seed(SEED) }
+
+    /// Generates the tables by the SplitMix64 generator seeded with specified seed. The tables
+    /// are the same for the seed in all versions of the crate
+    pub fn with_seed(seed: u64) -> Self { Self::from_rng(&mut SplitMix64(seed)) }
+
+    /// Generates the tables by the caller's generator
+    pub fn from_rng(rng: &mut impl Rng) -> Self {
         let mut result = Self {
             piece_square_table:  [[[0; SQUARES_NUMBER]; PIECE_TYPES_NUMBER]; COLORS_NUMBER],
             castling_table:      [[0; CASTLING_RIGHTS_NUMBER]; COLORS_NUMBER],
@@ -39,13 +102,11 @@ impl ZobristHasher {
             black_to_move_value: 0,
         };
 
-        result.generate_tables();
+        result.generate_tables(rng);
         result
     }
 
-    fn generate_tables(&mut self) -> &mut Self {
-        let mut rng = StdRng::seed_from_u64(SEED);
-
+    fn generate_tables(&mut self, rng: &mut impl Rng) -> &mut Self {
         // side to move
         self.black_to_move_value = rng.gen();
 
@@ -74,29 +135,60 @@ impl ZobristHasher {
     }
 
     pub fn calculate_position_hash(&self, position: &ChessBoard) -> PositionHashValueType {
+        self.calculate_transformed_hash(position, |color| color, |square| square)
+    }
+
+    /// Calculates the hash of the position transformed by the symmetry without building it.
+    /// Returns None if the symmetry is not valid for the position (see ``BoardSymmetry``)
+    pub fn calculate_symmetric_hash(
+        &self,
+        position: &ChessBoard,
+        symmetry: BoardSymmetry,
+    ) -> Option<PositionHashValueType> {
+        let has_castling_rights =
+            Color::iter().any(|color| position.get_castle_rights(color) != CastlingRights::Neither);
+        if symmetry.flips_files() & has_castling_rights {
+            return None;
+        }
+        let color = |color: Color| match symmetry.swaps_colors() {
+            true => !color,
+            false => color,
+        };
+        Some(self.calculate_transformed_hash(position, color, |square| {
+            symmetry.transform_square(square)
+        }))
+    }
+
+    /// Calculates the hash of the position in which the colors and the squares are replaced by
+    /// specified functions
+    fn calculate_transformed_hash(
+        &self,
+        position: &ChessBoard,
+        color: impl Fn(Color) -> Color,
+        square: impl Fn(Square) -> Square,
+    ) -> PositionHashValueType {
         let mut hash = 0;
 
         // side to move
-        if Color::Black == position.get_side_to_move() {
+        if Color::Black == color(position.get_side_to_move()) {
             hash ^= self.black_to_move_value;
         }
 
         // pieces positions
         for sq in position.get_combined_mask() {
             let piece_type = position.get_piece_type_on(sq).unwrap();
-            let color = position.get_piece_color_on(sq).unwrap();
-            hash ^= self.piece_square_table[color.to_index()][piece_type.to_index()][sq.to_index()];
+            let piece = Piece(piece_type, color(position.get_piece_color_on(sq).unwrap()));
+            hash ^= self.get_piece_square_value(piece, square(sq));
         }
 
         // castling
-        for color in [Color::White, Color::Black] {
-            hash ^=
-                self.castling_table[color.to_index()][position.get_castle_rights(color).to_index()];
+        for c in [Color::White, Color::Black] {
+            hash ^= self.get_castling_rights_value(position.get_castle_rights(c), color(c));
         }
 
-        // en passant
-        if let Some(sq) = position.get_en_passant() {
-            hash ^= self.en_passant_table[sq.get_file().to_index()];
+        // en passant (only if it can be captured)
+        if position.is_en_passant_capturable() {
+            hash ^= self.get_en_passant_value(square(position.get_en_passant().unwrap()));
         }
 
         hash
@@ -148,4 +240,77 @@ mod tests {
         let live_updating_hash = new_board.get_hash();
         assert_eq!(direct_calculated_hash, live_updating_hash);
     }
+
+    #[test]
+    fn stable_tables() {
+        // the global tables must not change between releases
+        let mut rng = SplitMix64(1234567); // the reference output of the generator
+        assert_eq!(
+            [rng.next_u64(), rng.next_u64(), rng.next_u64()],
+            [
+                6457827717110365317,
+                3203168211198807973,
+                9817491932198370423
+            ]
+        );
+
+        const STARTING_POSITION_HASH: PositionHashValueType = 8169280725913156398;
+        assert_eq!(ChessBoard::default().get_hash(), STARTING_POSITION_HASH);
+        let board = ChessBoard::default();
+        assert_eq!(
+            ZobristHasher::new().calculate_position_hash(&board),
+            STARTING_POSITION_HASH
+        );
+        assert_ne!(
+            ZobristHasher::with_seed(SEED + 1).calculate_position_hash(&board),
+            STARTING_POSITION_HASH
+        );
+    }
+
+    #[test]
+    fn symmetric_hashes() {
+        use crate::BoardSymmetry::*;
+        let hash = |fen: &str| ChessBoard::from_fen(fen).unwrap().get_hash();
+
+        // (position, horizontally flipped, rotated), the en passant capture is possible
+        let positions = [
+            (
+                "4k3/8/8/3pP3/8/8/1Q6/4K3 w - d6 0 2",
+                "3k4/8/8/3Pp3/8/8/6Q1/3K4 w - e6 0 2",
+                "3k4/6q1/8/8/3pP3/8/8/3K4 b - e3 0 2",
+            ),
+            (
+                "r3k3/1b6/8/8/8/8/8/4K2R b - - 3 40",
+                "3k3r/6b1/8/8/8/8/8/R2K4 b - - 3 40",
+                "r2k4/8/8/8/8/8/6B1/3K3R w - - 3 40",
+            ),
+        ];
+        for (fen, flipped, rotated) in positions {
+            let board = ChessBoard::from_fen(fen).unwrap();
+            assert_eq!(
+                ZOBRIST.calculate_symmetric_hash(&board, ColorMirror),
+                Some(board.get_mirrored().get_hash())
+            );
+            assert_eq!(
+                ZOBRIST.calculate_symmetric_hash(&board, HorizontalFlip),
+                Some(hash(flipped))
+            );
+            assert_eq!(
+                ZOBRIST.calculate_symmetric_hash(&board, Rotation),
+                Some(hash(rotated))
+            );
+        }
+
+        // the mirrored starting position differs only by the side to move
+        let board = ChessBoard::default();
+        assert_eq!(
+            ZOBRIST.calculate_symmetric_hash(&board, ColorMirror),
+            Some(hash(
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1"
+            ))
+        );
+        assert_eq!(ZOBRIST.calculate_symmetric_hash(&board, Rotation), None);
+        assert_eq!(C2, HorizontalFlip.transform_square(F2));
+        assert_eq!(D8, Rotation.transform_square(E1));
+    }
 }